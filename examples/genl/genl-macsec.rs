@@ -0,0 +1,206 @@
+use std::{env, ffi::CString, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    genetlink::Family,
+    if_macsec::{self, MacsecDevice, MacsecSa, MacsecSaConfig},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show [ifname]", prog);
+    println!("       {} add-rxsc <ifname> <sci>", prog);
+    println!("       {} del-rxsc <ifname> <sci>", prog);
+    println!("       {} add-txsa <ifname> <an> <pn> <keyid> <key>", prog);
+    println!("       {} del-txsa <ifname> <an>", prog);
+    println!(
+        "       {} add-rxsa <ifname> <sci> <an> <pn> <keyid> <key>",
+        prog
+    );
+    println!("       {} del-rxsa <ifname> <sci> <an>", prog);
+    println!("sci, keyid and key are in hex");
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn parse_hex(v: &str) -> Result<Vec<u8>, String> {
+    if v.len() & 1 != 0 {
+        return Err(format!("odd length hex: {}", v));
+    }
+    (0..v.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&v[i..i + 2], 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid hex: {}", v))
+}
+
+// as printed, the MAC address followed by the port
+fn parse_sci(v: &str) -> Result<u64, String> {
+    let b = parse_hex(v)?;
+    if b.len() != 8 {
+        return Err(format!("invalid sci: {}", v));
+    }
+    Ok(if_macsec::macsec_sci(
+        &[b[0], b[1], b[2], b[3], b[4], b[5]],
+        u16::from_be_bytes([b[6], b[7]]),
+    ))
+}
+
+fn sci_str(sci: u64) -> String {
+    sci.to_ne_bytes()
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect()
+}
+
+fn print_sa(indent: &str, sa: &MacsecSa) {
+    println!(
+        "{}{}: PN {} state {} key {}",
+        indent,
+        sa.an,
+        sa.pn,
+        if sa.active { "on" } else { "off" },
+        sa.keyid
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect::<String>()
+    );
+    println!(
+        "{}    OutPktsProtected {} OutPktsEncrypted {} InPktsOK {} InPktsInvalid {} InPktsNotValid {}",
+        indent,
+        sa.stats.out_pkts_protected,
+        sa.stats.out_pkts_encrypted,
+        sa.stats.in_pkts_ok,
+        sa.stats.in_pkts_invalid,
+        sa.stats.in_pkts_not_valid,
+    );
+}
+
+fn print_device(dev: &MacsecDevice) {
+    println!(
+        "{}: sci {} cipher {:016x} icvlen {} encodingsa {}",
+        dev.ifindex,
+        sci_str(dev.secy.sci),
+        dev.secy.cipher_suite,
+        dev.secy.icv_len,
+        dev.secy.encoding_sa
+    );
+    for sa in &dev.txsas {
+        print_sa("    TXSA ", sa);
+    }
+    for rxsc in &dev.rxscs {
+        println!(
+            "    RXSC {} state {}",
+            sci_str(rxsc.sci),
+            if rxsc.active { "on" } else { "off" }
+        );
+        for sa in &rxsc.sas {
+            print_sa("        RXSA ", sa);
+        }
+    }
+}
+
+fn parse_an(v: &str) -> Result<u8, String> {
+    v.parse().map_err(|_| format!("invalid an: {}", v))
+}
+
+fn sa_config<'a>(
+    args: &[String],
+    keyid: &'a [u8; 16],
+    key: &'a [u8],
+) -> Result<MacsecSaConfig<'a>, String> {
+    Ok(MacsecSaConfig {
+        an: parse_an(&args[0])?,
+        pn: Some(
+            args[1]
+                .parse()
+                .map_err(|_| format!("invalid pn: {}", args[1]))?,
+        ),
+        active: Some(true),
+        key: Some(key),
+        keyid: Some(keyid),
+        ..Default::default()
+    })
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+    match (args[1].as_ref(), args.len()) {
+        ("show", 2) | ("show", 3) => {}
+        ("add-rxsc", 4) | ("del-rxsc", 4) | ("del-txsa", 4) => {}
+        ("add-txsa", 7) | ("add-rxsa", 8) | ("del-rxsa", 5) => {}
+        _ => usage(&args[0]),
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_GENERIC, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let family = Family::resolve(&mut nl, if_macsec::MACSEC_GENL_NAME)
+        .map_err(|errno| format!("failed to resolve macsec family: {}", errno))?;
+
+    if args[1] == "show" {
+        let filter = match args.get(2) {
+            Some(name) => Some(ifindex(name)?),
+            None => None,
+        };
+        let devices = if_macsec::dump(&mut nl, &family)
+            .map_err(|errno| format!("failed to dump: {}", errno))?;
+        for dev in devices
+            .iter()
+            .filter(|dev| filter.is_none() || filter == Some(dev.ifindex))
+        {
+            print_device(dev);
+        }
+        return Ok(());
+    }
+
+    let index = ifindex(&args[2])?;
+    let ret = match args[1].as_ref() {
+        "add-rxsc" => if_macsec::add_rxsc(&mut nl, &family, index, parse_sci(&args[3])?, None),
+        "del-rxsc" => if_macsec::del_rxsc(&mut nl, &family, index, parse_sci(&args[3])?),
+        "del-txsa" => if_macsec::del_txsa(&mut nl, &family, index, parse_an(&args[3])?),
+        "del-rxsa" => if_macsec::del_rxsa(
+            &mut nl,
+            &family,
+            index,
+            parse_sci(&args[3])?,
+            parse_an(&args[4])?,
+        ),
+        _ => {
+            let rest = if args[1] == "add-rxsa" {
+                &args[4..]
+            } else {
+                &args[3..]
+            };
+            let keyid = parse_hex(&rest[2])?;
+            if keyid.len() > if_macsec::MACSEC_KEYID_LEN {
+                return Err(format!("too long keyid: {}", rest[2]));
+            }
+            let mut id = [0u8; if_macsec::MACSEC_KEYID_LEN];
+            id[..keyid.len()].copy_from_slice(&keyid);
+            let key = parse_hex(&rest[3])?;
+            let sa = sa_config(rest, &id, &key)?;
+            if args[1] == "add-rxsa" {
+                if_macsec::add_rxsa(&mut nl, &family, index, parse_sci(&args[3])?, &sa)
+            } else {
+                if_macsec::add_txsa(&mut nl, &family, index, &sa)
+            }
+        }
+    };
+    ret.map_err(|errno| format!("failed to {}: {}", args[1], errno))
+}
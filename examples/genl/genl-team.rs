@@ -0,0 +1,190 @@
+use std::{env, ffi::CString, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{CbStatus, Msghdr, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    genetlink::Family,
+    if_team::{self, TeamEvent, TeamOption, TeamOptionValue, TeamPort},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} options <ifname>", prog);
+    println!("       {} ports <ifname>", prog);
+    println!(
+        "       {} set <ifname> <name> <u32|s32|bool|string|binary> <value> [port <ifname>] [index <n>]",
+        prog
+    );
+    println!("       {} monitor", prog);
+    println!("binary value is in hex");
+    println!("Example: {} set team0 mode string activebackup", prog);
+    println!("         {} set team0 enabled bool true port eth0", prog);
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    if unsafe { libc::if_indextoname(index, buf.as_mut_ptr()).is_null() } {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+fn parse_value(otype: &str, v: &str) -> Result<TeamOptionValue, String> {
+    match otype {
+        "u32" => Ok(TeamOptionValue::U32(parse(v)?)),
+        "s32" => Ok(TeamOptionValue::S32(parse(v)?)),
+        "bool" => Ok(TeamOptionValue::Bool(parse(v)?)),
+        "string" => Ok(TeamOptionValue::String(v.to_string())),
+        "binary" if v.len() & 1 == 0 => (0..v.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&v[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map(TeamOptionValue::Binary)
+            .map_err(|_| format!("invalid hex: {}", v)),
+        _ => Err(format!("invalid type or value: {} {}", otype, v)),
+    }
+}
+
+fn print_option(option: &TeamOption) {
+    print!("{}", option.name);
+    if let Some(port) = option.port_ifindex {
+        print!(" (port:{})", ifname(port));
+    }
+    if let Some(index) = option.array_index {
+        print!(" (array index:{})", index);
+    }
+    match &option.value {
+        TeamOptionValue::U32(v) => print!(" {}", v),
+        TeamOptionValue::S32(v) => print!(" {}", v),
+        TeamOptionValue::Bool(v) => print!(" {}", v),
+        TeamOptionValue::String(v) => print!(" \"{}\"", v),
+        TeamOptionValue::Binary(v) => {
+            print!(" ");
+            for b in v {
+                print!("{:02x}", b);
+            }
+        }
+    }
+    if option.changed {
+        print!(" changed");
+    }
+    if option.removed {
+        print!(" removed");
+    }
+    println!();
+}
+
+fn print_port(port: &TeamPort) {
+    print!(
+        "{}: {}",
+        ifname(port.ifindex),
+        if port.linkup { "up" } else { "down" }
+    );
+    if port.linkup {
+        print!(
+            " {}Mbit {}duplex",
+            port.speed,
+            if port.duplex == 1 { "full" } else { "half" }
+        );
+    }
+    if port.changed {
+        print!(" changed");
+    }
+    if port.removed {
+        print!(" removed");
+    }
+    println!();
+}
+
+fn monitor(nl: &mut Socket, family: &Family) -> Result<(), String> {
+    if_team::subscribe(nl, family).map_err(|errno| format!("failed to subscribe: {}", errno))?;
+    let mut buf = mnl::default_buffer();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+        mnl::cb_run(
+            &buf[..nrecv],
+            0,
+            0,
+            Some(|nlh: &Msghdr| {
+                match TeamEvent::from_nlmsg(nlh)? {
+                    TeamEvent::Options { ifindex, options } => {
+                        for option in &options {
+                            print!("{}: ", ifname(ifindex));
+                            print_option(option);
+                        }
+                    }
+                    TeamEvent::Ports { ifindex, ports } => {
+                        for port in &ports {
+                            print!("{} port ", ifname(ifindex));
+                            print_port(port);
+                        }
+                    }
+                }
+                Ok(CbStatus::Ok)
+            }),
+        )
+        .map_err(|errno| format!("mnl_cb_run: {}", errno))?;
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_GENERIC, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let family = Family::resolve(&mut nl, if_team::TEAM_GENL_NAME)
+        .map_err(|errno| format!("failed to resolve team family: {}", errno))?;
+
+    match (args[1].as_ref(), args.len()) {
+        ("options", 3) => {
+            let options = if_team::options_get(&mut nl, &family, ifindex(&args[2])?)
+                .map_err(|errno| format!("failed to get options: {}", errno))?;
+            options.iter().for_each(print_option);
+        }
+        ("ports", 3) => {
+            let ports = if_team::port_list_get(&mut nl, &family, ifindex(&args[2])?)
+                .map_err(|errno| format!("failed to get ports: {}", errno))?;
+            ports.iter().for_each(print_port);
+        }
+        ("set", n) if n >= 6 && n & 1 == 0 => {
+            let mut option = TeamOption::new(&args[3], parse_value(&args[4], &args[5])?);
+            for kv in args[6..].chunks(2) {
+                match kv[0].as_ref() {
+                    "port" => option.port_ifindex = Some(ifindex(&kv[1])?),
+                    "index" => option.array_index = Some(parse(&kv[1])?),
+                    _ => usage(&args[0]),
+                }
+            }
+            if_team::options_set(&mut nl, &family, ifindex(&args[2])?, &[option])
+                .map_err(|errno| format!("failed to set option: {}", errno))?;
+        }
+        ("monitor", 2) => monitor(&mut nl, &family)?,
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
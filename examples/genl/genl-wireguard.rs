@@ -0,0 +1,200 @@
+use std::{env, net::IpAddr, process, time::SystemTime};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    genetlink::Family,
+    wireguard::{self, AllowedIp, DeviceConfig, PeerConfig, WG_KEY_LEN},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show <ifname>", prog);
+    println!(
+        "       {} set <ifname> [private-key <key>] [listen-port <port>] [fwmark <mark>]",
+        prog
+    );
+    println!("           [peer <key> [remove] [endpoint <addr:port>]");
+    println!("            [persistent-keepalive <secs>] [allowed-ips <ip/cidr>[,...]]]...");
+    println!("keys are in base64");
+    process::exit(libc::EXIT_FAILURE);
+}
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn key_to_base64(key: &[u8; WG_KEY_LEN]) -> String {
+    let mut s = String::new();
+    for chunk in key.chunks(3) {
+        let mut b = [0u8; 3];
+        b[..chunk.len()].copy_from_slice(chunk);
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i > chunk.len() {
+                s.push('=');
+            } else {
+                s.push(BASE64[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            }
+        }
+    }
+    s
+}
+
+fn key_from_base64(s: &str) -> Result<[u8; WG_KEY_LEN], String> {
+    let err = || format!("invalid key: {}", s);
+    // 32 bytes is 43 chars and a padding
+    if s.len() != 44 || !s.ends_with('=') {
+        return Err(err());
+    }
+    let mut n = 0u32;
+    let mut bits = 0;
+    let mut key = Vec::with_capacity(WG_KEY_LEN);
+    for c in s.trim_end_matches('=').bytes() {
+        let v = BASE64.iter().position(|&b| b == c).ok_or_else(err)?;
+        n = n << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            key.push((n >> bits) as u8);
+        }
+    }
+    let mut ret = [0u8; WG_KEY_LEN];
+    ret.copy_from_slice(&key);
+    Ok(ret)
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+fn parse_allowed_ip(v: &str) -> Result<AllowedIp, String> {
+    let mut it = v.splitn(2, '/');
+    let addr: IpAddr = parse(it.next().unwrap())?;
+    let cidr = match (it.next(), addr) {
+        (Some(cidr), _) => parse(cidr)?,
+        (None, IpAddr::V4(_)) => 32,
+        (None, IpAddr::V6(_)) => 128,
+    };
+    Ok(AllowedIp::new(addr, cidr))
+}
+
+fn parse_config(prog: &str, args: &[String]) -> Result<DeviceConfig, String> {
+    let mut config = DeviceConfig::default();
+    let mut it = args.iter();
+    while let Some(key) = it.next() {
+        if key == "remove" {
+            match config.peers.last_mut() {
+                Some(peer) => peer.remove = true,
+                None => usage(prog),
+            }
+            continue;
+        }
+        let value = it.next().unwrap_or_else(|| usage(prog));
+        match (key.as_ref(), config.peers.last_mut()) {
+            ("peer", _) => config.peers.push(PeerConfig::new(key_from_base64(value)?)),
+            ("private-key", None) => config.private_key = Some(key_from_base64(value)?),
+            ("listen-port", None) => config.listen_port = Some(parse(value)?),
+            ("fwmark", None) => config.fwmark = Some(parse(value)?),
+            ("endpoint", Some(peer)) => peer.endpoint = Some(parse(value)?),
+            ("persistent-keepalive", Some(peer)) => {
+                peer.persistent_keepalive_interval = Some(parse(value)?)
+            }
+            ("allowed-ips", Some(peer)) => {
+                peer.replace_allowed_ips = true;
+                for v in value.split(',') {
+                    peer.allowed_ips.push(parse_allowed_ip(v)?);
+                }
+            }
+            _ => usage(prog),
+        }
+    }
+    Ok(config)
+}
+
+fn show(device: &wireguard::Device) {
+    println!("interface: {} ({})", device.ifname, device.ifindex);
+    if let Some(key) = &device.public_key {
+        println!("  public key: {}", key_to_base64(key));
+    }
+    if device.private_key.is_some() {
+        println!("  private key: (hidden)");
+    }
+    if device.listen_port != 0 {
+        println!("  listening port: {}", device.listen_port);
+    }
+    if device.fwmark != 0 {
+        println!("  fwmark: {:#x}", device.fwmark);
+    }
+    for peer in &device.peers {
+        println!();
+        println!("peer: {}", key_to_base64(&peer.public_key));
+        if peer.preshared_key.is_some() {
+            println!("  preshared key: (hidden)");
+        }
+        if let Some(endpoint) = &peer.endpoint {
+            println!("  endpoint: {}", endpoint);
+        }
+        let ips: Vec<_> = peer
+            .allowed_ips
+            .iter()
+            .map(|ip| format!("{}/{}", ip.addr, ip.cidr))
+            .collect();
+        println!(
+            "  allowed ips: {}",
+            if ips.is_empty() {
+                "(none)".to_string()
+            } else {
+                ips.join(", ")
+            }
+        );
+        if let Some(t) = peer.last_handshake_time {
+            match SystemTime::now().duration_since(t) {
+                Ok(d) => println!("  latest handshake: {} seconds ago", d.as_secs()),
+                Err(_) => println!("  latest handshake: in the future"),
+            }
+        }
+        if peer.rx_bytes != 0 || peer.tx_bytes != 0 {
+            println!(
+                "  transfer: {} B received, {} B sent",
+                peer.rx_bytes, peer.tx_bytes
+            );
+        }
+        if peer.persistent_keepalive_interval != 0 {
+            println!(
+                "  persistent keepalive: every {} seconds",
+                peer.persistent_keepalive_interval
+            );
+        }
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_GENERIC, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let family = Family::resolve(&mut nl, wireguard::WG_GENL_NAME)
+        .map_err(|errno| format!("failed to resolve wireguard family: {}", errno))?;
+
+    match (args[1].as_ref(), args.len()) {
+        ("show", 3) => {
+            let device = wireguard::get_device(&mut nl, &family, &args[2])
+                .map_err(|errno| format!("failed to get device: {}", errno))?;
+            show(&device);
+        }
+        ("set", _) => {
+            let config = parse_config(&args[0], &args[3..])?;
+            wireguard::set_device(&mut nl, &family, &args[2], &config)
+                .map_err(|errno| format!("failed to set device: {}", errno))?;
+        }
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use std::{env, ffi::CString, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    pkt_cls::{self, Act, ActTbl},
+    rtnetlink,
+    tc_act::{
+        self,
+        tc_gact::{Gact, TcGact},
+        tc_mirred::{self, Mirred, TcMirred},
+        Action, ActionOptions,
+    },
+};
+
+fn usage(prog: &str) -> ! {
+    println!(
+        "Usage: {} show <kind> [terse] [large] [since <msecs>]",
+        prog
+    );
+    println!("       {} add gact <verdict> [index <n>]", prog);
+    println!(
+        "       {} add mirred <egress|ingress> <redirect|mirror> <ifname> [index <n>]",
+        prog
+    );
+    println!("       {} del <kind> <index>", prog);
+    println!("       {} flush <kind>", prog);
+    println!("verdict: pass, drop, reclassify, pipe, continue");
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    let p = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if p.is_null() {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(p) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+fn verdict_str(v: i32) -> String {
+    match v {
+        pkt_cls::TC_ACT_UNSPEC => "continue".to_string(),
+        pkt_cls::TC_ACT_OK => "pass".to_string(),
+        pkt_cls::TC_ACT_RECLASSIFY => "reclassify".to_string(),
+        pkt_cls::TC_ACT_SHOT => "drop".to_string(),
+        pkt_cls::TC_ACT_PIPE => "pipe".to_string(),
+        pkt_cls::TC_ACT_STOLEN => "stolen".to_string(),
+        pkt_cls::TC_ACT_REDIRECT => "redirect".to_string(),
+        _ => format!("{}", v),
+    }
+}
+
+fn parse_verdict(s: &str) -> Result<i32, String> {
+    Ok(match s {
+        "pass" | "ok" => pkt_cls::TC_ACT_OK,
+        "drop" | "shot" => pkt_cls::TC_ACT_SHOT,
+        "reclassify" => pkt_cls::TC_ACT_RECLASSIFY,
+        "pipe" => pkt_cls::TC_ACT_PIPE,
+        "continue" => pkt_cls::TC_ACT_UNSPEC,
+        _ => return Err(format!("invalid verdict: {}", s)),
+    })
+}
+
+fn show_options(tb: &ActTbl) -> mnl::Result<String> {
+    Ok(match tb.options()? {
+        None => String::new(),
+        Some(ActionOptions::Gact(tb)) => {
+            let mut s = String::new();
+            if let Some(parms) = tb.parms()? {
+                s.push_str(&format!(" {}", verdict_str(parms.action)));
+            }
+            if let Some(prob) = tb.prob()? {
+                s.push_str(&format!(
+                    " random type {} {} val {}",
+                    prob.ptype,
+                    verdict_str(prob.paction),
+                    prob.pval
+                ));
+            }
+            s
+        }
+        Some(ActionOptions::Mirred(tb)) => match tb.parms()? {
+            Some(parms) => format!(
+                " {} to {} {}",
+                match parms.eaction {
+                    tc_mirred::TCA_EGRESS_REDIR => "egress redirect",
+                    tc_mirred::TCA_EGRESS_MIRROR => "egress mirror",
+                    tc_mirred::TCA_INGRESS_REDIR => "ingress redirect",
+                    tc_mirred::TCA_INGRESS_MIRROR => "ingress mirror",
+                    _ => "unknown",
+                },
+                ifname(parms.ifindex),
+                verdict_str(parms.action)
+            ),
+            None => String::new(),
+        },
+        Some(ActionOptions::Police(tb)) => match tb.tbf()? {
+            Some(tbf) => format!(
+                " rate {}bit burst {} mtu {} {}",
+                tbf.rate.rate as u64 * 8,
+                tbf.burst,
+                tbf.mtu,
+                verdict_str(tbf.action)
+            ),
+            None => String::new(),
+        },
+        Some(ActionOptions::Skbedit(tb)) => {
+            let mut s = String::new();
+            if let Some(mark) = tb.mark()? {
+                s.push_str(&format!(" mark {:#x}", mark));
+            }
+            if let Some(priority) = tb.priority()? {
+                s.push_str(&format!(" priority {:#x}", priority));
+            }
+            if let Some(queue) = tb.queue_mapping()? {
+                s.push_str(&format!(" queue_mapping {}", queue));
+            }
+            s
+        }
+        Some(ActionOptions::Vlan(tb)) => {
+            let mut s = String::new();
+            if let Some(parms) = tb.parms()? {
+                s.push_str(&format!(" v_action {}", parms.v_action));
+            }
+            if let Some(id) = tb.push_vlan_id()? {
+                s.push_str(&format!(" id {}", id));
+            }
+            s
+        }
+        Some(_) => String::new(),
+    })
+}
+
+fn show(nl: &mut Socket, kind: &str, args: &[String]) -> Result<(), String> {
+    let mut flags = 0;
+    let mut time_delta = None;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_ref() {
+            "terse" => flags |= rtnetlink::TCA_ACT_FLAG_TERSE_DUMP,
+            "large" => flags |= rtnetlink::TCA_ACT_FLAG_LARGE_DUMP_ON,
+            "since" => {
+                time_delta = Some(parse(it.next().ok_or("msecs required")?)?);
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    let count = tc_act::dump_with(nl, kind, flags, time_delta, |tb| {
+        let act = Action::from_tbl(tb)?;
+        print!("action {} index {}", act.kind, act.index);
+        if let Some(cookie) = act.cookie {
+            print!(" cookie ");
+            for b in cookie {
+                print!("{:02x}", b);
+            }
+        }
+        println!("{}", show_options(tb)?);
+        Ok(())
+    })
+    .map_err(|errno| format!("failed to dump: {}", errno))?;
+    println!("total {}", count);
+    Ok(())
+}
+
+fn add(nl: &mut Socket, prog: &str, args: &[String]) -> Result<(), String> {
+    let mut act = Action::new(&args[0]);
+    let mut rest: Vec<&str> = args[1..].iter().map(|s| s.as_ref()).collect();
+    if rest.len() >= 2 && rest[rest.len() - 2] == "index" {
+        act.index = parse(rest[rest.len() - 1])?;
+        rest.truncate(rest.len() - 2);
+    }
+
+    let ret = match (act.kind.as_ref(), &rest[..]) {
+        ("gact", [verdict]) => {
+            let parms = TcGact {
+                index: act.index,
+                action: parse_verdict(verdict)?,
+                ..Default::default()
+            };
+            tc_act::add(nl, |nlv| {
+                act.put(nlv, 1, |nlv| {
+                    Act::options_start(nlv)?;
+                    nlv.put(Gact::Parms, &parms)?;
+                    nlv.nest_end()?;
+                    Ok(())
+                })?;
+                Ok(())
+            })
+        }
+        ("mirred", [direction, how, dev]) => {
+            let eaction = match (*direction, *how) {
+                ("egress", "redirect") => tc_mirred::TCA_EGRESS_REDIR,
+                ("egress", "mirror") => tc_mirred::TCA_EGRESS_MIRROR,
+                ("ingress", "redirect") => tc_mirred::TCA_INGRESS_REDIR,
+                ("ingress", "mirror") => tc_mirred::TCA_INGRESS_MIRROR,
+                _ => usage(prog),
+            };
+            let parms = TcMirred {
+                index: act.index,
+                action: if *how == "redirect" {
+                    pkt_cls::TC_ACT_STOLEN
+                } else {
+                    pkt_cls::TC_ACT_PIPE
+                },
+                eaction,
+                ifindex: ifindex(dev)?,
+                ..Default::default()
+            };
+            tc_act::add(nl, |nlv| {
+                act.put(nlv, 1, |nlv| {
+                    Act::options_start(nlv)?;
+                    nlv.put(Mirred::Parms, &parms)?;
+                    nlv.nest_end()?;
+                    Ok(())
+                })?;
+                Ok(())
+            })
+        }
+        _ => usage(prog),
+    };
+    ret.map_err(|errno| format!("failed to add: {}", errno))
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match args[1].as_ref() {
+        "show" => show(&mut nl, &args[2], &args[3..])?,
+        "add" if args.len() >= 4 => add(&mut nl, &args[0], &args[2..])?,
+        "del" if args.len() == 4 => tc_act::delete(&mut nl, &args[2], parse(&args[3])?)
+            .map_err(|errno| format!("failed to delete: {}", errno))?,
+        "flush" if args.len() == 3 => tc_act::flush(&mut nl, &args[2])
+            .map_err(|errno| format!("failed to flush: {}", errno))?,
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
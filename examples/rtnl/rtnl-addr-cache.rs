@@ -0,0 +1,77 @@
+use std::{env, ffi::CString, net::Ipv6Addr, process};
+
+extern crate libc;
+
+extern crate rsmnl_linux as linux;
+use linux::addr_cache::{AddrCache, AddrEvent};
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    let mut cache = AddrCache::new().map_err(|errno| format!("AddrCache::new: {}", errno))?;
+
+    match args.len() {
+        1 => {}
+        // waits for DAD, e.g. after `ip addr add 2001:db8::1/64 dev eth0`
+        3 => {
+            let index = ifindex(&args[1])?;
+            let addr: Ipv6Addr = args[2]
+                .parse()
+                .map_err(|_| format!("invalid address: {}", args[2]))?;
+            return match cache.wait_dad(index, addr) {
+                Ok(_) => {
+                    println!("{} is usable", addr);
+                    Ok(())
+                }
+                Err(errno) => Err(format!("{}: {}", addr, errno)),
+            };
+        }
+        _ => {
+            println!("Usage: {} [<ifname> <ipv6 addr>]", args[0]);
+            process::exit(libc::EXIT_FAILURE);
+        }
+    }
+
+    let mut addrs: Vec<_> = cache.addrs().collect();
+    addrs.sort_by_key(|addr| (addr.index, addr.local));
+    for addr in addrs {
+        print!("{}: {}/{}", addr.index, addr.local, addr.prefixlen);
+        if let Some(state) = addr.dad_state() {
+            print!(" {:?}", state);
+        }
+        println!();
+    }
+
+    loop {
+        let events = cache
+            .process()
+            .map_err(|errno| format!("AddrCache::process: {}", errno))?;
+        for event in events {
+            match event {
+                AddrEvent::Added(addr) => {
+                    println!("{}: {}/{} added", addr.index, addr.local, addr.prefixlen)
+                }
+                AddrEvent::Removed(addr) => {
+                    println!("{}: {}/{} removed", addr.index, addr.local, addr.prefixlen)
+                }
+                AddrEvent::Updated { new, .. } => println!(
+                    "{}: {}/{} updated, flags {:#x}",
+                    new.index, new.local, new.prefixlen, new.flags.0
+                ),
+                AddrEvent::DadStateChanged {
+                    index,
+                    addr,
+                    old,
+                    new,
+                } => println!("{}: {} {:?} -> {:?}", index, addr, old, new),
+            }
+        }
+    }
+}
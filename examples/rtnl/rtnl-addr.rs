@@ -0,0 +1,191 @@
+use std::{env, ffi::CString, net::IpAddr, process, time::Duration};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    if_addr::{self, Address, IfaFlag},
+    rtnetlink,
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show [inet|inet6]", prog);
+    println!(
+        "       {} <add|replace|del> <ifname> <addr/plen> [key value]...",
+        prog
+    );
+    println!("key: peer broadcast label scope metric valid-lft preferred-lft");
+    println!("     and flag for nodad optimistic homeaddress managetempaddr noprefixroute");
+    println!("     mcautojoin");
+    println!(
+        "Example: {} add eth0 192.0.2.1/24 valid-lft 60 preferred-lft 30",
+        prog
+    );
+    println!(
+        "         {} add eth0 2001:db8::1/64 flag nodad flag noprefixroute",
+        prog
+    );
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    if unsafe { libc::if_indextoname(index, buf.as_mut_ptr()).is_null() } {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+const FLAGS: [(&str, IfaFlag); 12] = [
+    ("secondary", IfaFlag::Secondary),
+    ("nodad", IfaFlag::Nodad),
+    ("optimistic", IfaFlag::Optimistic),
+    ("dadfailed", IfaFlag::Dadfailed),
+    ("homeaddress", IfaFlag::Homeaddress),
+    ("deprecated", IfaFlag::Deprecated),
+    ("tentative", IfaFlag::Tentative),
+    ("permanent", IfaFlag::Permanent),
+    ("managetempaddr", IfaFlag::Managetempaddr),
+    ("noprefixroute", IfaFlag::Noprefixroute),
+    ("mcautojoin", IfaFlag::Mcautojoin),
+    ("stable-privacy", IfaFlag::StablePrivacy),
+];
+
+fn parse_address(prog: &str, args: &[String]) -> Result<Address, String> {
+    let mut it = args[1].splitn(2, '/');
+    let local: IpAddr = parse(it.next().unwrap())?;
+    let prefixlen = match (it.next(), local) {
+        (Some(plen), _) => parse(plen)?,
+        (None, IpAddr::V4(_)) => 32,
+        (None, IpAddr::V6(_)) => 128,
+    };
+    let mut addr = Address::new(ifindex(&args[0])?, local, prefixlen);
+    for kv in args[2..].chunks(2) {
+        if kv.len() != 2 {
+            usage(prog);
+        }
+        let v = &kv[1];
+        match kv[0].as_ref() {
+            "peer" => addr.peer = Some(parse(v)?),
+            "broadcast" => addr.broadcast = Some(parse(v)?),
+            "label" => addr.label = Some(v.to_string()),
+            "scope" => {
+                addr.scope = match v.as_ref() {
+                    "global" => rtnetlink::RT_SCOPE_UNIVERSE,
+                    "site" => rtnetlink::RT_SCOPE_SITE,
+                    "link" => rtnetlink::RT_SCOPE_LINK,
+                    "host" => rtnetlink::RT_SCOPE_HOST,
+                    _ => parse(v)?,
+                }
+            }
+            "metric" => addr.metric = Some(parse(v)?),
+            "valid-lft" => addr.valid_lifetime = Some(Duration::from_secs(parse(v)?)),
+            "preferred-lft" => addr.preferred_lifetime = Some(Duration::from_secs(parse(v)?)),
+            "flag" => match FLAGS.iter().find(|(name, _)| name == v) {
+                Some((_, flag)) => {
+                    addr.flags.insert(*flag);
+                }
+                None => return Err(format!("unknown flag: {}", v)),
+            },
+            _ => usage(prog),
+        }
+    }
+    Ok(addr)
+}
+
+fn lifetime(d: Option<Duration>) -> String {
+    match d {
+        Some(d) => format!("{}sec", d.as_secs()),
+        None => "forever".to_string(),
+    }
+}
+
+fn show(addr: &Address) {
+    print!("{}: {}", ifname(addr.index), addr.local);
+    if let Some(peer) = addr.peer {
+        print!(" peer {}", peer);
+    }
+    print!("/{}", addr.prefixlen);
+    if let Some(brd) = addr.broadcast {
+        print!(" brd {}", brd);
+    }
+    print!(" scope ");
+    match addr.scope {
+        rtnetlink::RT_SCOPE_UNIVERSE => print!("global"),
+        rtnetlink::RT_SCOPE_SITE => print!("site"),
+        rtnetlink::RT_SCOPE_LINK => print!("link"),
+        rtnetlink::RT_SCOPE_HOST => print!("host"),
+        v => print!("{}", v),
+    }
+    for (name, flag) in FLAGS.iter() {
+        if addr.flags.contains(*flag) {
+            print!(" {}", name);
+        }
+    }
+    if let Some(label) = &addr.label {
+        print!(" {}", label);
+    }
+    if let Some(metric) = addr.metric {
+        print!(" metric {}", metric);
+    }
+    println!();
+    println!(
+        "    valid_lft {} preferred_lft {}",
+        lifetime(addr.valid_lifetime),
+        lifetime(addr.preferred_lifetime)
+    );
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match (args[1].as_ref(), args.len()) {
+        ("show", 2) | ("show", 3) => {
+            let family = match args.get(2).map(|s| s.as_ref()) {
+                None => libc::AF_UNSPEC,
+                Some("inet") => libc::AF_INET,
+                Some("inet6") => libc::AF_INET6,
+                _ => usage(&args[0]),
+            };
+            let addrs = if_addr::dump(&mut nl, family as u8, None)
+                .map_err(|errno| format!("failed to dump: {}", errno))?;
+            addrs.iter().for_each(show);
+        }
+        ("add", n) | ("replace", n) | ("del", n) if n >= 4 => {
+            let addr = parse_address(&args[0], &args[2..])?;
+            match args[1].as_ref() {
+                "add" => if_addr::add(&mut nl, &addr),
+                "replace" => if_addr::replace(&mut nl, &addr),
+                _ => if_addr::delete(&mut nl, &addr),
+            }
+            .map_err(|errno| format!("failed to {}: {}", args[1], errno))?;
+        }
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
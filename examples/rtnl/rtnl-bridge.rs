@@ -0,0 +1,202 @@
+use std::{env, ffi::CString, io, net::IpAddr, process};
+
+extern crate libc;
+use libc::if_nametoindex;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::if_bridge::{self, BridgeVlan, MdbEntry};
+
+fn usage(prog: &str) -> ! {
+    println!(
+        "Usage: {} vlan add dev <port> vid <vid>[-<vid>] [pvid] [untagged]",
+        prog
+    );
+    println!("       {} vlan del dev <port> vid <vid>[-<vid>]", prog);
+    println!("       {} vlan show [dev <port>]", prog);
+    println!("       {} vlan stats [dev <port>]", prog);
+    println!(
+        "       {} mdb add dev <bridge> port <port> grp <group> [permanent] [vid <vid>]",
+        prog
+    );
+    println!(
+        "       {} mdb del dev <bridge> port <port> grp <group> [vid <vid>]",
+        prog
+    );
+    println!("       {} mdb show [dev <bridge>]", prog);
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    unsafe {
+        let ptr = CString::new(name).unwrap();
+        match if_nametoindex(ptr.as_ptr()) {
+            0 => Err(format!("if_nametoindex: {}", io::Error::last_os_error())),
+            i => Ok(i),
+        }
+    }
+}
+
+struct Opts<'a> {
+    dev: Option<&'a str>,
+    port: Option<&'a str>,
+    grp: Option<IpAddr>,
+    vid: u16,
+    range: Option<u16>,
+    vlan_flags: u16,
+    permanent: bool,
+}
+
+fn parse_opts<'a>(args: &'a [String]) -> Result<Opts<'a>, String> {
+    let mut opts = Opts {
+        dev: None,
+        port: None,
+        grp: None,
+        vid: 0,
+        range: None,
+        vlan_flags: 0,
+        permanent: false,
+    };
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or(format!("{} requires a value", arg));
+        match arg.as_ref() {
+            "dev" => opts.dev = Some(value()?),
+            "port" => opts.port = Some(value()?),
+            "grp" => {
+                let v = value()?;
+                opts.grp = Some(v.parse().map_err(|_| format!("invalid group: {}", v))?);
+            }
+            "vid" => {
+                let v = value()?;
+                let mut range = v.splitn(2, '-').map(str::parse::<u16>);
+                let (first, last) = match (range.next(), range.next()) {
+                    (Some(Ok(first)), None) => (first, None),
+                    (Some(Ok(first)), Some(Ok(last))) => (first, Some(last)),
+                    _ => return Err(format!("invalid vid: {}", v)),
+                };
+                opts.vid = first;
+                opts.range = last;
+            }
+            "pvid" => opts.vlan_flags |= if_bridge::BRIDGE_VLAN_INFO_PVID,
+            "untagged" => opts.vlan_flags |= if_bridge::BRIDGE_VLAN_INFO_UNTAGGED,
+            "permanent" => opts.permanent = true,
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+    Ok(opts)
+}
+
+fn print_vlan(vlan: &BridgeVlan) {
+    print!("ifindex={} vid={}", vlan.index, vlan.vid);
+    if let Some(last) = vlan.range {
+        print!("-{}", last);
+    }
+    if vlan.flags & if_bridge::BRIDGE_VLAN_INFO_PVID != 0 {
+        print!(" PVID");
+    }
+    if vlan.flags & if_bridge::BRIDGE_VLAN_INFO_UNTAGGED != 0 {
+        print!(" Egress Untagged");
+    }
+    if let Some(stats) = vlan.stats {
+        print!(
+            " rx_bytes={} rx_packets={} tx_bytes={} tx_packets={}",
+            stats.rx_bytes, stats.rx_packets, stats.tx_bytes, stats.tx_packets
+        );
+    }
+    println!();
+}
+
+fn print_mdb(mdb: &MdbEntry) {
+    let entry = &mdb.entry;
+    print!("dev {} port {} ", mdb.bridge, entry.ifindex);
+    match entry.group() {
+        Some(grp) => print!("grp {} ", grp),
+        None => print!(
+            "grp {} ",
+            unsafe { entry.addr.u.mac_addr }
+                .iter()
+                .map(|e| format!("{:02x}", e))
+                .collect::<Vec<_>>()
+                .join(":")
+        ),
+    }
+    if let Some(src) = mdb.source {
+        print!("src {} ", src);
+    }
+    if entry.state == if_bridge::MDB_PERMANENT {
+        print!("permanent ");
+    } else {
+        print!("temp ");
+    }
+    if entry.vid != 0 {
+        print!("vid {} ", entry.vid);
+    }
+    if let Some(timer) = mdb.timer {
+        print!("{}.{:02} ", timer / 100, timer % 100);
+    }
+    println!();
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+    let opts = parse_opts(&args[3..])?;
+    let dev = match opts.dev {
+        Some(name) => Some(ifindex(name)?),
+        None => None,
+    };
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    let ret = match (args[1].as_ref(), args[2].as_ref()) {
+        ("vlan", "add") | ("vlan", "del") => {
+            let mut vlan = BridgeVlan::new(dev.unwrap_or_else(|| usage(&args[0])), opts.vid);
+            vlan.range = opts.range;
+            vlan.flags = opts.vlan_flags;
+            if args[2] == "add" {
+                if_bridge::vlan_add(&mut nl, &vlan)
+            } else {
+                if_bridge::vlan_delete(&mut nl, &vlan)
+            }
+        }
+        ("vlan", "show") | ("vlan", "stats") => {
+            if_bridge::vlan_dump(&mut nl, dev, args[2] == "stats").map(|vlans| {
+                for vlan in &vlans {
+                    print_vlan(vlan);
+                }
+            })
+        }
+        ("mdb", "add") | ("mdb", "del") => {
+            let port = ifindex(opts.port.unwrap_or_else(|| usage(&args[0])))?;
+            let mut mdb = MdbEntry::new(
+                dev.unwrap_or_else(|| usage(&args[0])),
+                port,
+                opts.grp.unwrap_or_else(|| usage(&args[0])),
+            );
+            mdb.entry.vid = opts.vid;
+            if opts.permanent {
+                mdb.entry.state = if_bridge::MDB_PERMANENT;
+            }
+            if args[2] == "add" {
+                if_bridge::mdb_add(&mut nl, &mdb)
+            } else {
+                if_bridge::mdb_delete(&mut nl, &mdb)
+            }
+        }
+        ("mdb", "show") => if_bridge::mdb_dump(&mut nl, dev).map(|entries| {
+            for mdb in &entries {
+                print_mdb(mdb);
+            }
+        }),
+        _ => usage(&args[0]),
+    };
+    ret.map_err(|errno| format!("failed to {} {}: {}", args[1], args[2], errno))
+}
@@ -0,0 +1,222 @@
+use std::{env, ffi::CString, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    neighbour::{self, Fdb, FdbEntry, Ntf, NtfFlags, Nud, NudState},
+    netns,
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show [br <bridge>] [port <ifname>]", prog);
+    println!(
+        "       {} <add|append|replace|del> <ifname> <mac> [key value | flag]...",
+        prog
+    );
+    println!("key: dst vlan vni src_vni port via nhid");
+    println!("flag: self master local static dynamic extern_learn sticky");
+    println!("      activity_notify inactive norefresh");
+    println!(
+        "Example: {} add vx0 00:00:00:00:00:00 self dst 192.0.2.1",
+        prog
+    );
+    println!(
+        "         {} append vx0 00:00:00:00:00:00 self dst 192.0.2.2",
+        prog
+    );
+    println!(
+        "         {} add eth0 02:00:00:00:00:01 master static vlan 10",
+        prog
+    );
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    if unsafe { libc::if_indextoname(index, buf.as_mut_ptr()).is_null() } {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+fn parse_mac(v: &str) -> Result<Vec<u8>, String> {
+    let mac = v
+        .split(':')
+        .map(|b| u8::from_str_radix(b, 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid mac: {}", v))?;
+    if mac.len() != 6 {
+        return Err(format!("invalid mac: {}", v));
+    }
+    Ok(mac)
+}
+
+fn parse_entry(prog: &str, args: &[String]) -> Result<FdbEntry, String> {
+    let mut entry = FdbEntry::new(ifindex(&args[0])?, &parse_mac(&args[1])?);
+    let mut it = args[2..].iter();
+    let mut flags = 0u8;
+    while let Some(key) = it.next() {
+        match key.as_ref() {
+            "self" => flags |= Ntf::Self_ as u8,
+            "master" => flags |= Ntf::Master as u8,
+            "extern_learn" => flags |= Ntf::ExtLearned as u8,
+            "sticky" => flags |= Ntf::Sticky as u8,
+            "local" => entry.state = NudState::from(Nud::Permanent),
+            // NUD_REACHABLE is for vxlan, the bridge prefers NUD_NOARP
+            "static" => entry.state = NudState(Nud::Noarp as u16 | Nud::Reachable as u16),
+            "dynamic" => entry.state = NudState::from(Nud::Reachable),
+            "activity_notify" => {
+                *entry.activity_notify.get_or_insert(0) |= Fdb::NotifyBit as u8;
+            }
+            "inactive" => {
+                *entry.activity_notify.get_or_insert(0) |= Fdb::NotifyInactiveBit as u8;
+            }
+            "norefresh" => entry.dont_refresh = true,
+            _ => {
+                let v = it.next().unwrap_or_else(|| usage(prog));
+                match key.as_ref() {
+                    "dst" => entry.dst = Some(parse(v)?),
+                    "vlan" => entry.vlan = Some(parse(v)?),
+                    "vni" => entry.vni = Some(parse(v)?),
+                    "src_vni" => entry.src_vni = Some(parse(v)?),
+                    "port" => entry.port = Some(parse(v)?),
+                    "via" => entry.ifindex = Some(ifindex(v)?),
+                    "nhid" => entry.nh_id = Some(parse(v)?),
+                    _ => usage(prog),
+                }
+            }
+        }
+    }
+    // NTF_MASTER if none of self or master
+    if flags & (Ntf::Self_ as u8 | Ntf::Master as u8) == 0 {
+        flags |= Ntf::Master as u8;
+    }
+    entry.flags = NtfFlags(flags);
+    // vxlan accepts only NUD_PERMANENT or NUD_REACHABLE, as FdbEntry::vxlan()
+    if flags & Ntf::Self_ as u8 != 0
+        && !entry.state.contains(Nud::Permanent)
+        && !entry.state.contains(Nud::Reachable)
+    {
+        entry.state.insert(Nud::Permanent);
+    }
+    Ok(entry)
+}
+
+fn show(entry: &FdbEntry) {
+    let mac: Vec<_> = entry.lladdr.iter().map(|b| format!("{:02x}", b)).collect();
+    print!("{} dev {}", mac.join(":"), ifname(entry.index));
+    if let Some(dst) = entry.dst {
+        print!(" dst {}", dst);
+    }
+    if let Some(port) = entry.port {
+        print!(" port {}", port);
+    }
+    if let Some(vni) = entry.vni {
+        print!(" vni {}", vni);
+    }
+    if let Some(vni) = entry.src_vni {
+        print!(" src_vni {}", vni);
+    }
+    if let Some(index) = entry.ifindex {
+        print!(" via {}", ifname(index));
+    }
+    if let Some(id) = entry.nh_id {
+        print!(" nhid {}", id);
+    }
+    if let Some(vlan) = entry.vlan {
+        print!(" vlan {}", vlan);
+    }
+    if let Some(master) = entry.master {
+        print!(" master {}", ifname(master));
+    }
+    for (name, flag) in [
+        ("self", Ntf::Self_),
+        ("master", Ntf::Master),
+        ("extern_learn", Ntf::ExtLearned),
+        ("offload", Ntf::Offloaded),
+        ("sticky", Ntf::Sticky),
+    ]
+    .iter()
+    {
+        if entry.flags.contains(*flag) {
+            print!(" {}", name);
+        }
+    }
+    if entry.state.contains(Nud::Permanent) {
+        print!(" permanent");
+    } else if entry.state.contains(Nud::Noarp) {
+        print!(" static");
+    } else if entry.state.contains(Nud::Stale) {
+        print!(" stale");
+    }
+    if let Some(v) = entry.activity_notify {
+        if v & Fdb::NotifyBit as u8 != 0 {
+            print!(" activity_notify");
+        }
+        if v & Fdb::NotifyInactiveBit as u8 != 0 {
+            print!(" inactive");
+        }
+    }
+    println!();
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match args[1].as_ref() {
+        "show" if args.len() & 1 == 0 => {
+            let (mut master, mut port) = (None, None);
+            for kv in args[2..].chunks(2) {
+                match kv[0].as_ref() {
+                    "br" => master = Some(ifindex(&kv[1])?),
+                    "port" => port = Some(ifindex(&kv[1])?),
+                    _ => usage(&args[0]),
+                }
+            }
+            // lets the kernel filter
+            netns::set_strict_check(&nl, true)
+                .map_err(|errno| format!("failed to set strict check: {}", errno))?;
+            let entries = neighbour::fdb_dump(&mut nl, master, port)
+                .map_err(|errno| format!("failed to dump: {}", errno))?;
+            entries.iter().for_each(show);
+        }
+        "add" | "append" | "replace" | "del" if args.len() >= 4 => {
+            let entry = parse_entry(&args[0], &args[2..])?;
+            match args[1].as_ref() {
+                "add" => neighbour::fdb_add(&mut nl, &entry),
+                "append" => neighbour::fdb_append(&mut nl, &entry),
+                "replace" => neighbour::fdb_replace(&mut nl, &entry),
+                _ => neighbour::fdb_delete(&mut nl, &entry),
+            }
+            .map_err(|errno| format!("failed to {}: {}", args[1], errno))?;
+        }
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
@@ -0,0 +1,308 @@
+use std::{env, ffi::CString, mem, net::Ipv4Addr, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{AttrTbl, MsgVec, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    pkt_cls::{self, Filter, FilterOptions, Flower, Matchall, TcU32Key, TcU32Sel, U32},
+    pkt_sched,
+    rtnetlink::{self, Tca, TcaTbl, Tcmsg},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show <ifname> [<parent>]", prog);
+    println!(
+        "       {} add <ifname> <parent> <prio> <kind> [chain <n>] <match> ... classid <major:minor>",
+        prog
+    );
+    println!("       {} del <ifname> <parent> <prio>", prog);
+    println!("kind and match: u32 dst <addr/len>");
+    println!("                flower [dst_ip <addr>] [ip_proto tcp|udp] [dst_port <port>]");
+    println!("                matchall");
+    println!("parent: major:minor, root, ingress");
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+fn parse_handle(s: &str) -> Result<u32, String> {
+    match s {
+        "root" => return Ok(pkt_sched::TC_H_ROOT),
+        "ingress" => return Ok(pkt_sched::tc_h_make(pkt_sched::TC_H_INGRESS, 0)),
+        _ => {}
+    }
+    let mut it = s.splitn(2, ':');
+    let maj = u32::from_str_radix(it.next().unwrap_or(""), 16)
+        .map_err(|_| format!("invalid handle: {}", s))?;
+    let min = match it.next() {
+        Some("") | None => 0,
+        Some(v) => u32::from_str_radix(v, 16).map_err(|_| format!("invalid handle: {}", s))?,
+    };
+    Ok(pkt_sched::tc_h_make(maj << 16, min))
+}
+
+fn handle_str(h: u32) -> String {
+    format!("{:x}:{:x}", h >> 16, pkt_sched::tc_h_min(h))
+}
+
+fn show_actions(acts: Option<Vec<pkt_cls::ActTbl>>) -> mnl::Result<String> {
+    let mut s = String::new();
+    for act in acts.unwrap_or_default() {
+        s.push_str(&format!(" action {}", act.kind()?.unwrap_or("?")));
+    }
+    Ok(s)
+}
+
+fn show_options(nlh: &mnl::Msghdr) -> mnl::Result<String> {
+    let tb = TcaTbl::from_nlmsg(mem::size_of::<Tcmsg>(), nlh)?;
+    Ok(match tb.filter_options()? {
+        None => String::new(),
+        Some(FilterOptions::U32(tb)) => {
+            let mut s = String::new();
+            if let Some(classid) = tb.classid()? {
+                s.push_str(&format!(" classid {}", handle_str(*classid)));
+            }
+            if let Some(divisor) = tb.divisor()? {
+                s.push_str(&format!(" divisor {}", divisor));
+            }
+            for key in tb.keys()?.unwrap_or_default() {
+                s.push_str(&format!(
+                    " match {:08x}/{:08x} at {}",
+                    u32::from_be(key.val),
+                    u32::from_be(key.mask),
+                    key.off
+                ));
+            }
+            s + &show_actions(tb.actions()?)?
+        }
+        Some(FilterOptions::Flower(tb)) => {
+            let mut s = String::new();
+            if let Some(classid) = tb.classid()? {
+                s.push_str(&format!(" classid {}", handle_str(*classid)));
+            }
+            if let Some(proto) = tb.key_ip_proto()? {
+                s.push_str(&format!(" ip_proto {}", proto));
+            }
+            if let Some(addr) = tb.key_ipv4_dst()? {
+                s.push_str(&format!(" dst_ip {}", addr));
+            }
+            if let Some(port) = tb.key_tcp_dst()?.or(tb.key_udp_dst()?) {
+                s.push_str(&format!(" dst_port {}", u16::from_be(*port)));
+            }
+            if let Some(state) = tb.key_ct_state()? {
+                s.push_str(&format!(" ct_state {:#x}", state));
+            }
+            s + &show_actions(tb.actions()?)?
+        }
+        Some(FilterOptions::Matchall(tb)) => {
+            let mut s = String::new();
+            if let Some(classid) = tb.classid()? {
+                s.push_str(&format!(" classid {}", handle_str(*classid)));
+            }
+            s + &show_actions(tb.actions()?)?
+        }
+        Some(FilterOptions::Bpf(tb)) => format!(
+            " {}{}",
+            tb.name()?.unwrap_or("bpf"),
+            show_actions(tb.actions()?)?
+        ),
+        Some(FilterOptions::Basic(tb)) => show_actions(tb.actions()?)?,
+        Some(FilterOptions::Fw(tb)) => show_actions(tb.actions()?)?,
+        Some(_) => String::new(),
+    })
+}
+
+// Filter does not hold the options, dumps again to decode them.
+fn show(nl: &mut Socket, index: u32, parent: u32) -> Result<(), String> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETTFILTER;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let tcm = nlv
+        .put_extra_header::<Tcmsg>()
+        .map_err(|errno| format!("failed to build: {}", errno))?;
+    tcm.tcm_ifindex = index;
+    tcm.tcm_parent = parent;
+    nl.sendto(&nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+    let portid = nl.portid();
+    let mut buf = mnl::dump_buffer();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+        match mnl::cb_run(
+            &buf[..nrecv],
+            0,
+            portid,
+            Some(|nlh: &mnl::Msghdr| {
+                let filter = Filter::from_nlmsg(nlh)?;
+                println!(
+                    "filter {} parent {} protocol {:#06x} pref {} chain {} handle {:#x}{}",
+                    filter.kind,
+                    handle_str(filter.parent),
+                    filter.protocol,
+                    filter.priority,
+                    filter.chain.unwrap_or(0),
+                    filter.handle,
+                    show_options(nlh)?
+                );
+                Ok(mnl::CbStatus::Ok)
+            }),
+        ) {
+            Ok(mnl::CbStatus::Ok) => continue,
+            Ok(mnl::CbStatus::Stop) => break,
+            Err(err) => return Err(format!("mnl_cb_run: {}", err)),
+        }
+    }
+    Ok(())
+}
+
+fn u32_key(prog: &str, v: &str) -> Result<TcU32Key, String> {
+    let mut it = v.splitn(2, '/');
+    let addr: Ipv4Addr = parse(it.next().unwrap_or_else(|| usage(prog)))?;
+    let len: u32 = it.next().map_or(Ok(32), parse)?;
+    if len > 32 {
+        return Err(format!("invalid prefix length: {}", len));
+    }
+    let mask = if len == 0 { 0 } else { !0u32 << (32 - len) };
+    Ok(TcU32Key {
+        mask: mask.to_be(),
+        val: (u32::from(addr) & mask).to_be(),
+        off: 16, // destination of IPv4 header
+        offmask: 0,
+    })
+}
+
+fn add(nl: &mut Socket, prog: &str, args: &[String]) -> Result<(), String> {
+    let mut filter = Filter::new(
+        ifindex(&args[0])?,
+        parse_handle(&args[1])?,
+        parse(&args[2])?,
+        libc::ETH_P_IP as u16,
+        &args[3],
+    );
+    let mut classid = None;
+    let mut u32_keys = Vec::new();
+    let mut dst_ip: Option<Ipv4Addr> = None;
+    let mut ip_proto: Option<u8> = None;
+    let mut dst_port: Option<u16> = None;
+    let mut it = args[4..].iter();
+    while let Some(key) = it.next() {
+        let v = it.next().unwrap_or_else(|| usage(prog));
+        match (filter.kind.as_ref(), key.as_ref()) {
+            (_, "classid") => classid = Some(parse_handle(v)?),
+            (_, "chain") => filter.chain = Some(parse(v)?),
+            ("u32", "dst") => u32_keys.push(u32_key(prog, v)?),
+            ("flower", "dst_ip") => dst_ip = Some(parse(v)?),
+            ("flower", "ip_proto") => {
+                ip_proto = Some(match v.as_ref() {
+                    "tcp" => libc::IPPROTO_TCP as u8,
+                    "udp" => libc::IPPROTO_UDP as u8,
+                    _ => parse(v)?,
+                })
+            }
+            ("flower", "dst_port") => dst_port = Some(parse(v)?),
+            _ => usage(prog),
+        }
+    }
+    let classid = classid.unwrap_or_else(|| usage(prog));
+
+    let ret = match filter.kind.as_ref() {
+        "u32" => pkt_cls::add(nl, &filter, |nlv| {
+            Tca::options_start(nlv)?;
+            nlv.put(U32::Classid, &classid)?;
+            TcU32Sel {
+                flags: pkt_cls::TC_U32_TERMINAL,
+                offshift: 0,
+                nkeys: 0,
+                offmask: 0,
+                off: 0,
+                offoff: 0,
+                hoff: 0,
+                hmask: 0,
+            }
+            .put(nlv, &u32_keys)?;
+            nlv.nest_end()?;
+            Ok(())
+        }),
+        "flower" => pkt_cls::add(nl, &filter, |nlv| {
+            Tca::options_start(nlv)?;
+            nlv.put(Flower::Classid, &classid)?;
+            nlv.put(Flower::KeyEthType, &(libc::ETH_P_IP as u16).to_be())?;
+            if let Some(addr) = dst_ip {
+                nlv.put(Flower::KeyIpv4Dst, &u32::from(addr).to_be())?;
+                nlv.put(Flower::KeyIpv4DstMask, &!0u32)?;
+            }
+            if let Some(proto) = ip_proto {
+                nlv.put(Flower::KeyIpProto, &proto)?;
+                if let Some(port) = dst_port {
+                    if proto == libc::IPPROTO_TCP as u8 {
+                        nlv.put(Flower::KeyTcpDst, &port.to_be())?;
+                    } else {
+                        nlv.put(Flower::KeyUdpDst, &port.to_be())?;
+                    }
+                }
+            }
+            nlv.nest_end()?;
+            Ok(())
+        }),
+        "matchall" => pkt_cls::add(nl, &filter, |nlv| {
+            Tca::options_start(nlv)?;
+            nlv.put(Matchall::Classid, &classid)?;
+            nlv.nest_end()?;
+            Ok(())
+        }),
+        _ => usage(prog),
+    };
+    ret.map_err(|errno| format!("failed to add: {}", errno))
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match args[1].as_ref() {
+        "show" if args.len() <= 4 => {
+            let parent = match args.get(3) {
+                Some(parent) => parse_handle(parent)?,
+                None => 0,
+            };
+            show(&mut nl, ifindex(&args[2])?, parent)?;
+        }
+        "add" if args.len() >= 8 => add(&mut nl, &args[0], &args[2..])?,
+        "del" if args.len() == 5 => {
+            let filter = Filter::new(
+                ifindex(&args[2])?,
+                parse_handle(&args[3])?,
+                parse(&args[4])?,
+                libc::ETH_P_IP as u16,
+                "",
+            );
+            pkt_cls::delete(&mut nl, &filter)
+                .map_err(|errno| format!("failed to delete: {}", errno))?;
+        }
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
@@ -0,0 +1,659 @@
+use std::{
+    collections::HashMap,
+    env,
+    ffi::CString,
+    fs::File,
+    io,
+    net::{IpAddr, Ipv4Addr},
+    os::unix::io::IntoRawFd,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+extern crate libc;
+use libc::if_nametoindex;
+
+extern crate rsmnl as mnl;
+use mnl::{MsgVec, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    can::{
+        netlink::{self as can, Can, CanCtrlmode},
+        vxcan::VxcanInfo,
+    },
+    if_bonding::{BondAdSelect, BondLacpRate, BondMode, BondXmitPolicy},
+    if_link::{self, BareUdp, Br, Geneve, Ifla, IflaVlanFlags, IflaVlanQosMapping, Ipvlan},
+    if_link::{Bond, LinkRequest, Macvlan, Vlan, VlanQos, Vrf, Vxlan, Xfrm},
+    if_tunnel::{self, Gre, Iptun, Vti},
+    veth::VethInfo,
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} add <ifname> <kind> [key value]...", prog);
+    println!("       {} set <ifname> [<kind>] [key value]...", prog);
+    println!("       {} del <ifname>", prog);
+    println!(
+        "kind: dummy veth bridge bond vlan macvlan macvtap ipvlan vxlan geneve vrf xfrm bareudp"
+    );
+    println!("      ipip sit ip6tnl gre gretap ip6gre ip6gretap erspan ip6erspan vti vti6");
+    println!("      vcan vxcan wireguard team, and can for set");
+    println!("Example: {} add eth0.100 vlan link eth0 id 100", prog);
+    println!("         {} add veth0 veth peer veth1 peer-netns ns0", prog);
+    println!(
+        "         {} add bond0 bond mode 802.3ad miimon 100 lacp_rate fast",
+        prog
+    );
+    println!(
+        "         {} add gre1 gre remote 10.0.0.2 local 10.0.0.1 key 100",
+        prog
+    );
+    println!(
+        "         {} add vx0 vxlan id 42 remote 192.168.1.1 dstport 4789",
+        prog
+    );
+    println!(
+        "         {} set can0 can bitrate 500000 sample-point 0.875 fd on dbitrate 2000000",
+        prog
+    );
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    unsafe {
+        let ptr = CString::new(name).unwrap();
+        match if_nametoindex(ptr.as_ptr()) {
+            0 => Err(format!("if_nametoindex: {}", io::Error::last_os_error())),
+            i => Ok(i),
+        }
+    }
+}
+
+fn parse<T: std::str::FromStr>(opts: &HashMap<&str, &str>, key: &str) -> Result<Option<T>, String> {
+    match opts.get(key) {
+        Some(v) => v
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("failed to parse {}: {}", key, v)),
+        None => Ok(None),
+    }
+}
+
+// "from:to"
+fn parse_qos(v: &str) -> Result<IflaVlanQosMapping, String> {
+    let mut it = v.splitn(2, ':');
+    match (it.next().map(str::parse), it.next().map(str::parse)) {
+        (Some(Ok(from)), Some(Ok(to))) => Ok(IflaVlanQosMapping { from, to }),
+        _ => Err(format!("failed to parse qos mapping: {}", v)),
+    }
+}
+
+// "xx:xx:xx:xx:xx:xx"
+fn parse_lladdr(v: &str) -> Result<Vec<u8>, String> {
+    let addr = v
+        .split(':')
+        .map(|x| u8::from_str_radix(x, 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("failed to parse lladdr: {}", v))?;
+    if addr.len() != 6 {
+        return Err(format!("invalid lladdr length: {}", v));
+    }
+    Ok(addr)
+}
+
+// "key" sets both of ikey and okey
+fn keys(opts: &HashMap<&str, &str>) -> Result<(Option<u32>, Option<u32>), String> {
+    let key = parse::<u32>(opts, "key")?;
+    Ok((
+        parse::<u32>(opts, "ikey")?.or(key),
+        parse::<u32>(opts, "okey")?.or(key),
+    ))
+}
+
+fn encap_type(opts: &HashMap<&str, &str>) -> Result<Option<u16>, String> {
+    match opts.get("encap") {
+        Some(&"none") => Ok(Some(if_tunnel::TUNNEL_ENCAP_NONE)),
+        Some(&"fou") => Ok(Some(if_tunnel::TUNNEL_ENCAP_FOU)),
+        Some(&"gue") => Ok(Some(if_tunnel::TUNNEL_ENCAP_GUE)),
+        Some(t) => Err(format!("unknown encap type: {}", t)),
+        None => Ok(None),
+    }
+}
+
+// "0.875" to 875, in one-tenth of a percent
+fn parse_sample_point(opts: &HashMap<&str, &str>, key: &str) -> Result<u32, String> {
+    match parse::<f64>(opts, key)? {
+        Some(v) if (0.0..1.0).contains(&v) => Ok((v * 1000.0).round() as u32),
+        Some(v) => Err(format!("{} must be in [0, 1): {}", key, v)),
+        None => Ok(0),
+    }
+}
+
+fn can_ctrlmode(opts: &HashMap<&str, &str>) -> Result<Option<CanCtrlmode>, String> {
+    let mut ctrlmode = CanCtrlmode::default();
+    for (key, mode) in &[
+        ("loopback", can::CAN_CTRLMODE_LOOPBACK),
+        ("listen-only", can::CAN_CTRLMODE_LISTENONLY),
+        ("triple-sampling", can::CAN_CTRLMODE_3_SAMPLES),
+        ("one-shot", can::CAN_CTRLMODE_ONE_SHOT),
+        ("berr-reporting", can::CAN_CTRLMODE_BERR_REPORTING),
+        ("fd", can::CAN_CTRLMODE_FD),
+        ("fd-non-iso", can::CAN_CTRLMODE_FD_NON_ISO),
+        ("presume-ack", can::CAN_CTRLMODE_PRESUME_ACK),
+        ("cc-len8-dlc", can::CAN_CTRLMODE_CC_LEN8_DLC),
+    ] {
+        match opts.get(key) {
+            Some(&"on") => ctrlmode.set(*mode, true),
+            Some(&"off") => ctrlmode.set(*mode, false),
+            Some(v) => return Err(format!("{} is neither on nor off: {}", key, v)),
+            None => continue,
+        };
+    }
+    if ctrlmode.mask == 0 {
+        return Ok(None);
+    }
+    Ok(Some(ctrlmode))
+}
+
+fn bond_mode(v: &str) -> Result<BondMode, String> {
+    match v {
+        "balance-rr" => Ok(BondMode::Roundrobin),
+        "active-backup" => Ok(BondMode::Activebackup),
+        "balance-xor" => Ok(BondMode::Xor),
+        "broadcast" => Ok(BondMode::Broadcast),
+        "802.3ad" => Ok(BondMode::Ieee8023ad),
+        "balance-tlb" => Ok(BondMode::Tlb),
+        "balance-alb" => Ok(BondMode::Alb),
+        _ => Err(format!("unknown bond mode: {}", v)),
+    }
+}
+
+fn bond_xmit_policy(v: &str) -> Result<BondXmitPolicy, String> {
+    match v {
+        "layer2" => Ok(BondXmitPolicy::Layer2),
+        "layer3+4" => Ok(BondXmitPolicy::Layer34),
+        "layer2+3" => Ok(BondXmitPolicy::Layer23),
+        "encap2+3" => Ok(BondXmitPolicy::Encap23),
+        "encap3+4" => Ok(BondXmitPolicy::Encap34),
+        "vlan+srcmac" => Ok(BondXmitPolicy::VlanSrcmac),
+        _ => Err(format!("unknown xmit_hash_policy: {}", v)),
+    }
+}
+
+// puts IFLA_INFO_DATA attributes by the kind, LinkRequest puts the nest
+fn put_info_data(nlv: &mut MsgVec, kind: &str, opts: &HashMap<&str, &str>) -> Result<(), String> {
+    match kind {
+        "vlan" => {
+            if let Some(id) = parse::<u16>(opts, "id")? {
+                Vlan::put_id(nlv, &id).unwrap();
+            }
+            match opts.get("protocol") {
+                Some(&"802.1q") | Some(&"802.1Q") => {
+                    Vlan::put_protocol(nlv, &(libc::ETH_P_8021Q as u16).to_be()).unwrap();
+                }
+                Some(&"802.1ad") => {
+                    Vlan::put_protocol(nlv, &(libc::ETH_P_8021AD as u16).to_be()).unwrap();
+                }
+                Some(p) => return Err(format!("unknown vlan protocol: {}", p)),
+                None => {}
+            }
+            let reorder_hdr = match opts.get("reorder_hdr") {
+                Some(&"on") => Some(if_link::VLAN_FLAG_REORDER_HDR),
+                Some(&"off") => Some(0),
+                Some(v) => return Err(format!("reorder_hdr is neither on nor off: {}", v)),
+                None => None,
+            };
+            if let Some(flags) = reorder_hdr {
+                let vf = IflaVlanFlags {
+                    flags,
+                    mask: if_link::VLAN_FLAG_REORDER_HDR,
+                };
+                Vlan::put_flags(nlv, &vf).unwrap();
+            }
+            if let Some(v) = opts.get("egress-qos") {
+                let mapping = parse_qos(v)?;
+                Vlan::egress_qos_start(nlv).unwrap();
+                VlanQos::put_mapping(nlv, &mapping).unwrap();
+                nlv.nest_end().unwrap();
+            }
+            if let Some(v) = opts.get("ingress-qos") {
+                let mapping = parse_qos(v)?;
+                Vlan::ingress_qos_start(nlv).unwrap();
+                VlanQos::put_mapping(nlv, &mapping).unwrap();
+                nlv.nest_end().unwrap();
+            }
+        }
+        "macvlan" | "macvtap" => {
+            let mode = match opts.get("mode") {
+                Some(&"private") => if_link::MACVLAN_MODE_PRIVATE,
+                Some(&"vepa") => if_link::MACVLAN_MODE_VEPA,
+                Some(&"bridge") => if_link::MACVLAN_MODE_BRIDGE,
+                Some(&"passthru") => if_link::MACVLAN_MODE_PASSTHRU,
+                Some(&"source") => if_link::MACVLAN_MODE_SOURCE,
+                Some(m) => return Err(format!("unknown macvlan mode: {}", m)),
+                None => if_link::MACVLAN_MODE_VEPA,
+            };
+            Macvlan::put_mode(nlv, &mode).unwrap();
+        }
+        "ipvlan" => {
+            let mode = match opts.get("mode") {
+                Some(&"l2") | None => if_link::IPVLAN_MODE_L2,
+                Some(&"l3") => if_link::IPVLAN_MODE_L3,
+                Some(&"l3s") => if_link::IPVLAN_MODE_L3S,
+                Some(m) => return Err(format!("unknown ipvlan mode: {}", m)),
+            };
+            Ipvlan::put_mode(nlv, &mode).unwrap();
+            let flags = match opts.get("flags") {
+                Some(&"private") => Some(if_link::IPVLAN_F_PRIVATE),
+                Some(&"vepa") => Some(if_link::IPVLAN_F_VEPA),
+                Some(&"bridge") => Some(0),
+                Some(f) => return Err(format!("unknown ipvlan flags: {}", f)),
+                None => None,
+            };
+            if let Some(flags) = flags {
+                Ipvlan::put_flags(nlv, &flags).unwrap();
+            }
+        }
+        "vxlan" => {
+            if let Some(id) = parse::<u32>(opts, "id")? {
+                Vxlan::put_id(nlv, &id).unwrap();
+            }
+            match parse::<IpAddr>(opts, "remote")? {
+                Some(IpAddr::V4(addr)) => Vxlan::put_group(nlv, &addr).unwrap(),
+                Some(IpAddr::V6(addr)) => Vxlan::put_group6(nlv, &addr).unwrap(),
+                None => nlv,
+            };
+            match parse::<IpAddr>(opts, "local")? {
+                Some(IpAddr::V4(addr)) => Vxlan::put_local(nlv, &addr).unwrap(),
+                Some(IpAddr::V6(addr)) => Vxlan::put_local6(nlv, &addr).unwrap(),
+                None => nlv,
+            };
+            if let Some(port) = parse::<u16>(opts, "dstport")? {
+                Vxlan::put_port(nlv, &port.to_be()).unwrap();
+            }
+            if let Some(ttl) = parse::<u8>(opts, "ttl")? {
+                Vxlan::put_ttl(nlv, &ttl).unwrap();
+            }
+            if let Some(v) = parse::<u8>(opts, "learning")? {
+                Vxlan::put_learning(nlv, &v).unwrap();
+            }
+            if let Some(dev) = opts.get("dev") {
+                Vxlan::put_link(nlv, &ifindex(dev)?).unwrap();
+            }
+        }
+        "geneve" => {
+            if let Some(id) = parse::<u32>(opts, "id")? {
+                Geneve::put_id(nlv, &id).unwrap();
+            }
+            match parse::<IpAddr>(opts, "remote")? {
+                Some(IpAddr::V4(addr)) => Geneve::put_remote(nlv, &addr).unwrap(),
+                Some(IpAddr::V6(addr)) => Geneve::put_remote6(nlv, &addr).unwrap(),
+                None => nlv,
+            };
+            if let Some(port) = parse::<u16>(opts, "dstport")? {
+                Geneve::put_port(nlv, &port.to_be()).unwrap();
+            }
+            if let Some(ttl) = parse::<u8>(opts, "ttl")? {
+                Geneve::put_ttl(nlv, &ttl).unwrap();
+            }
+        }
+        "vrf" => {
+            if let Some(t) = parse::<u32>(opts, "table")? {
+                Vrf::put_table(nlv, &t).unwrap();
+            }
+        }
+        "xfrm" => {
+            if let Some(id) = parse::<u32>(opts, "if_id")? {
+                Xfrm::put_if_id(nlv, &id).unwrap();
+            }
+            if let Some(dev) = opts.get("dev") {
+                Xfrm::put_link(nlv, &ifindex(dev)?).unwrap();
+            }
+        }
+        "bareudp" => {
+            if let Some(port) = parse::<u16>(opts, "dstport")? {
+                BareUdp::put_port(nlv, &port.to_be()).unwrap();
+            }
+            let ethertype = match opts.get("ethertype") {
+                Some(&"ipv4") => Some(libc::ETH_P_IP as u16),
+                Some(&"ipv6") => Some(libc::ETH_P_IPV6 as u16),
+                Some(&"mpls_uc") => Some(libc::ETH_P_MPLS_UC as u16),
+                Some(&"mpls_mc") => Some(libc::ETH_P_MPLS_MC as u16),
+                Some(t) => return Err(format!("unknown ethertype: {}", t)),
+                None => None,
+            };
+            if let Some(t) = ethertype {
+                BareUdp::put_ethertype(nlv, &t.to_be()).unwrap();
+            }
+            if let Some(p) = parse::<u16>(opts, "srcportmin")? {
+                BareUdp::put_srcport_min(nlv, &p).unwrap();
+            }
+            if opts.contains_key("multiproto") {
+                BareUdp::put_multiproto_mode(nlv).unwrap();
+            }
+        }
+        "ipip" | "sit" | "ip6tnl" => {
+            if let Some(dev) = opts.get("dev") {
+                Iptun::put_link(nlv, &ifindex(dev)?).unwrap();
+            }
+            if let Some(addr) = parse::<IpAddr>(opts, "remote")? {
+                Iptun::put_remote(nlv, &addr).unwrap();
+            }
+            if let Some(addr) = parse::<IpAddr>(opts, "local")? {
+                Iptun::put_local(nlv, &addr).unwrap();
+            }
+            if let Some(ttl) = parse::<u8>(opts, "ttl")? {
+                Iptun::put_ttl(nlv, &ttl).unwrap();
+            }
+            if let Some(tos) = parse::<u8>(opts, "tos")? {
+                Iptun::put_tos(nlv, &tos).unwrap();
+            }
+            let proto = match opts.get("mode") {
+                Some(&"ip6ip6") => Some(libc::IPPROTO_IPV6 as u8),
+                Some(&"ipip6") | Some(&"ip4ip6") => Some(libc::IPPROTO_IPIP as u8),
+                Some(&"any") => Some(0),
+                Some(m) => return Err(format!("unknown tunnel mode: {}", m)),
+                None => None,
+            };
+            if let Some(proto) = proto {
+                Iptun::put_proto(nlv, &proto).unwrap();
+            }
+            if let Some(limit) = parse::<u8>(opts, "encaplimit")? {
+                Iptun::put_encap_limit(nlv, &limit).unwrap();
+            }
+            if let Some(t) = encap_type(opts)? {
+                Iptun::put_encap_type(nlv, &t).unwrap();
+            }
+            if let Some(port) = parse::<u16>(opts, "encap-sport")? {
+                Iptun::put_encap_sport(nlv, &port.to_be()).unwrap();
+            }
+            if let Some(port) = parse::<u16>(opts, "encap-dport")? {
+                Iptun::put_encap_dport(nlv, &port.to_be()).unwrap();
+            }
+            if let Some(mark) = parse::<u32>(opts, "fwmark")? {
+                Iptun::put_fwmark(nlv, &mark).unwrap();
+            }
+        }
+        "gre" | "gretap" | "ip6gre" | "ip6gretap" | "erspan" | "ip6erspan" => {
+            if let Some(dev) = opts.get("dev") {
+                Gre::put_link(nlv, &ifindex(dev)?).unwrap();
+            }
+            if let Some(addr) = parse::<IpAddr>(opts, "remote")? {
+                Gre::put_remote(nlv, &addr).unwrap();
+            }
+            if let Some(addr) = parse::<IpAddr>(opts, "local")? {
+                Gre::put_local(nlv, &addr).unwrap();
+            }
+            if let Some(ttl) = parse::<u8>(opts, "ttl")? {
+                Gre::put_ttl(nlv, &ttl).unwrap();
+            }
+            if let Some(tos) = parse::<u8>(opts, "tos")? {
+                Gre::put_tos(nlv, &tos).unwrap();
+            }
+            let mut flags = 0;
+            if opts.get("csum") == Some(&"on") {
+                flags |= if_tunnel::GRE_CSUM;
+            }
+            if opts.get("seq") == Some(&"on") {
+                flags |= if_tunnel::GRE_SEQ;
+            }
+            let (ikey, okey) = keys(opts)?;
+            Gre::put_keys(nlv, flags, ikey, flags, okey).unwrap();
+            if let Some(t) = encap_type(opts)? {
+                Gre::put_encap_type(nlv, &t).unwrap();
+            }
+            if let Some(port) = parse::<u16>(opts, "encap-sport")? {
+                Gre::put_encap_sport(nlv, &port.to_be()).unwrap();
+            }
+            if let Some(port) = parse::<u16>(opts, "encap-dport")? {
+                Gre::put_encap_dport(nlv, &port.to_be()).unwrap();
+            }
+            if let Some(mark) = parse::<u32>(opts, "fwmark")? {
+                Gre::put_fwmark(nlv, &mark).unwrap();
+            }
+            if let Some(index) = parse::<u32>(opts, "erspan")? {
+                Gre::put_erspan_index(nlv, &index).unwrap();
+            }
+            if let Some(ver) = parse::<u8>(opts, "erspan_ver")? {
+                Gre::put_erspan_ver(nlv, &ver).unwrap();
+            }
+            let dir = match opts.get("erspan_dir") {
+                Some(&"ingress") => Some(0),
+                Some(&"egress") => Some(1),
+                Some(d) => return Err(format!("erspan_dir is neither ingress nor egress: {}", d)),
+                None => None,
+            };
+            if let Some(dir) = dir {
+                Gre::put_erspan_dir(nlv, &dir).unwrap();
+            }
+            if let Some(hwid) = parse::<u16>(opts, "erspan_hwid")? {
+                Gre::put_erspan_hwid(nlv, &hwid).unwrap();
+            }
+        }
+        "vti" | "vti6" => {
+            if let Some(dev) = opts.get("dev") {
+                Vti::put_link(nlv, &ifindex(dev)?).unwrap();
+            }
+            if let Some(addr) = parse::<IpAddr>(opts, "remote")? {
+                Vti::put_remote(nlv, &addr).unwrap();
+            }
+            if let Some(addr) = parse::<IpAddr>(opts, "local")? {
+                Vti::put_local(nlv, &addr).unwrap();
+            }
+            let (ikey, okey) = keys(opts)?;
+            if let Some(key) = ikey {
+                Vti::put_ikey(nlv, &key.to_be()).unwrap();
+            }
+            if let Some(key) = okey {
+                Vti::put_okey(nlv, &key.to_be()).unwrap();
+            }
+            if let Some(mark) = parse::<u32>(opts, "fwmark")? {
+                Vti::put_fwmark(nlv, &mark).unwrap();
+            }
+        }
+        "bridge" => {
+            if let Some(v) = parse::<u32>(opts, "forward_delay")? {
+                Br::put_forward_delay(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "hello_time")? {
+                Br::put_hello_time(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "max_age")? {
+                Br::put_max_age(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "ageing_time")? {
+                Br::put_ageing_time(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "stp_state")? {
+                Br::put_stp_state(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u16>(opts, "priority")? {
+                Br::put_priority(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u8>(opts, "vlan_filtering")? {
+                Br::put_vlan_filtering(nlv, &v).unwrap();
+            }
+        }
+        "bond" => {
+            if let Some(v) = opts.get("mode") {
+                Bond::put_mode(nlv, &(bond_mode(v)? as u8)).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "miimon")? {
+                Bond::put_miimon(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "updelay")? {
+                Bond::put_updelay(nlv, &v).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "downdelay")? {
+                Bond::put_downdelay(nlv, &v).unwrap();
+            }
+            if let Some(v) = opts.get("xmit_hash_policy") {
+                Bond::put_xmit_hash_policy(nlv, &(bond_xmit_policy(v)? as u8)).unwrap();
+            }
+            let lacp_rate = match opts.get("lacp_rate") {
+                Some(&"slow") => Some(BondLacpRate::Slow),
+                Some(&"fast") => Some(BondLacpRate::Fast),
+                Some(r) => return Err(format!("lacp_rate is neither slow nor fast: {}", r)),
+                None => None,
+            };
+            if let Some(rate) = lacp_rate {
+                Bond::put_ad_lacp_rate(nlv, &(rate as u8)).unwrap();
+            }
+            let ad_select = match opts.get("ad_select") {
+                Some(&"stable") => Some(BondAdSelect::Stable),
+                Some(&"bandwidth") => Some(BondAdSelect::Bandwidth),
+                Some(&"count") => Some(BondAdSelect::Count),
+                Some(s) => return Err(format!("unknown ad_select: {}", s)),
+                None => None,
+            };
+            if let Some(select) = ad_select {
+                Bond::put_ad_select(nlv, &(select as u8)).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "arp_interval")? {
+                Bond::put_arp_interval(nlv, &v).unwrap();
+            }
+            // comma separated addresses
+            if let Some(v) = opts.get("arp_ip_target") {
+                let addrs = v
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<Vec<Ipv4Addr>, _>>()
+                    .map_err(|_| format!("failed to parse arp_ip_target: {}", v))?;
+                Bond::put_arp_ip_target(nlv, &addrs).unwrap();
+            }
+            if let Some(v) = parse::<u32>(opts, "min_links")? {
+                Bond::put_min_links(nlv, &v).unwrap();
+            }
+        }
+        "can" => {
+            if let Some(bitrate) = parse::<u32>(opts, "bitrate")? {
+                let sample_point = parse_sample_point(opts, "sample-point")?;
+                Can::put_bitrate(nlv, bitrate, sample_point).unwrap();
+            }
+            if let Some(bitrate) = parse::<u32>(opts, "dbitrate")? {
+                let sample_point = parse_sample_point(opts, "dsample-point")?;
+                Can::put_data_bitrate(nlv, bitrate, sample_point).unwrap();
+            }
+            if let Some(ctrlmode) = can_ctrlmode(opts)? {
+                Can::put_ctrlmode(nlv, &ctrlmode).unwrap();
+            }
+            if let Some(ms) = parse::<u32>(opts, "restart-ms")? {
+                Can::put_restart_ms(nlv, &ms).unwrap();
+            }
+            if opts.contains_key("restart") {
+                Can::put_restart(nlv, &1).unwrap();
+            }
+            if let Some(ohm) = parse::<u16>(opts, "termination")? {
+                Can::put_termination(nlv, &ohm).unwrap();
+            }
+        }
+        "veth" | "vxcan" => {
+            let ifm = if kind == "veth" {
+                VethInfo::peer_start(nlv).unwrap()
+            } else {
+                VxcanInfo::peer_start(nlv).unwrap()
+            };
+            ifm.ifi_family = libc::AF_UNSPEC as u8;
+            if let Some(name) = opts.get("peer") {
+                Ifla::put_ifname(nlv, name).unwrap();
+            }
+            if let Some(v) = opts.get("peer-address") {
+                Ifla::put_address(nlv, &parse_lladdr(v)?).unwrap();
+            }
+            if let Some(mtu) = parse::<u32>(opts, "peer-mtu")? {
+                Ifla::put_mtu(nlv, &mtu).unwrap();
+            }
+            if let Some(name) = opts.get("peer-netns") {
+                // leave it opened until the request is sent
+                let fd = File::open(format!("/run/netns/{}", name))
+                    .map_err(|err| format!("failed to open netns {}: {}", name, err))?
+                    .into_raw_fd();
+                Ifla::put_net_ns_fd(nlv, &(fd as u32)).unwrap();
+            }
+            nlv.nest_end().unwrap();
+        }
+        _ => {} // created without IFLA_INFO_DATA
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut rest = &args[3..];
+    let kind = match args[1].as_ref() {
+        "add" => {
+            if rest.is_empty() {
+                usage(&args[0]);
+            }
+            rest = &args[4..];
+            Some(args[3].as_str())
+        }
+        "set" if rest.len() % 2 == 1 => {
+            rest = &args[4..];
+            Some(args[3].as_str())
+        }
+        "set" | "del" => None,
+        _ => usage(&args[0]),
+    };
+    if rest.len() % 2 != 0 {
+        usage(&args[0]);
+    }
+    let opts: HashMap<&str, &str> = rest
+        .chunks(2)
+        .map(|kv| (kv[0].as_str(), kv[1].as_str()))
+        .collect();
+
+    let req = LinkRequest::new(&args[2], kind.unwrap_or(""));
+    let mut nlv = MsgVec::new();
+    let seq = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    let mut data = Ok(());
+    let put_data = |nlv: &mut MsgVec| {
+        data = put_info_data(nlv, &req.kind, &opts);
+        Ok(())
+    };
+    match (args[1].as_ref(), kind) {
+        ("add", _) => req.put_add(&mut nlv, seq, put_data),
+        ("set", Some(_)) => req.put_change(&mut nlv, seq, put_data),
+        ("set", None) => req.put_set(&mut nlv, seq),
+        _ => req.put_delete(&mut nlv, seq),
+    }
+    .map_err(|errno| format!("failed to build the request: {}", errno))?;
+    data?;
+
+    if let Some(link) = opts.get("link") {
+        Ifla::put_link(&mut nlv, &ifindex(link)?).unwrap();
+    }
+    if let Some(master) = opts.get("master") {
+        Ifla::put_master(&mut nlv, &ifindex(master)?).unwrap();
+    }
+    if let Some(mtu) = parse::<u32>(&opts, "mtu")? {
+        Ifla::put_mtu(&mut nlv, &mtu).unwrap();
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let portid = nl.portid();
+
+    nl.sendto(&nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+
+    let mut buf = mnl::default_buffer();
+    let nrecv = nl
+        .recvfrom(&mut buf)
+        .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+
+    mnl::cb_run(&buf[0..nrecv], seq, portid, mnl::NOCB)
+        .map_err(|errno| format!("mnl_cb_run: {}", errno))?;
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::{
+    convert::TryFrom,
+    env, mem,
+    net::Ipv6Addr,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{AttrTbl, CbResult, CbStatus, MsgVec, Msghdr, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    if_link::{Ifla, IflaTbl, In6AddrGenMode, Inet, Inet6},
+    ip::Ipv4Devconf,
+    rtnetlink::{self, Ifinfomsg},
+    snmp::{Icmp6Mib, IpstatsMib},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show <ifname>", prog);
+    println!("       {} set <ifname> token <ipv6 addr>", prog);
+    println!(
+        "       {} set <ifname> addrgenmode <eui64|none|stable_secret|random>",
+        prog
+    );
+    println!("       {} set <ifname> forwarding <0|1>", prog);
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn data_cb(nlh: &Msghdr) -> CbResult {
+    let ifm = nlh.payload::<Ifinfomsg>()?;
+    let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
+    println!(
+        "{}: {}",
+        ifm.ifi_index,
+        tb.ifname()?.unwrap_or("").trim_end_matches('\0')
+    );
+
+    if let Some(inet) = tb.af_spec_inet()? {
+        if let Some(conf) = inet.conf()? {
+            println!(
+                "    inet forwarding {} rp_filter {} arp_ignore {}",
+                conf.forwarding, conf.rp_filter, conf.arp_ignore
+            );
+        }
+    }
+    let inet6 = match tb.af_spec_inet6()? {
+        Some(tb) => tb,
+        None => return Ok(CbStatus::Ok),
+    };
+    print!("    inet6");
+    if let Some(flags) = inet6.flags()? {
+        print!(" flags 0x{:x}", flags);
+    }
+    if let Some(mode) = inet6.addr_gen_mode()? {
+        match In6AddrGenMode::try_from(*mode) {
+            Ok(mode) => print!(" addrgenmode {:?}", mode),
+            Err(_) => print!(" addrgenmode {}", mode),
+        }
+    }
+    if let Some(token) = inet6.token()? {
+        print!(" token {}", token);
+    }
+    println!();
+    if let Some(conf) = inet6.conf()? {
+        println!(
+            "    inet6 forwarding {} hop_limit {} mtu {} disable_ipv6 {}",
+            conf.forwarding, conf.hop_limit, conf.mtu6, conf.disable_ipv6
+        );
+    }
+    if let Some(ci) = inet6.cacheinfo()? {
+        println!(
+            "    inet6 max_reasm_len {} reachable_time {} retrans_time {}",
+            ci.max_reasm_len, ci.reachable_time, ci.retrans_time
+        );
+    }
+    if let Some(stats) = inet6.stats()? {
+        if stats.len() > IpstatsMib::Outpkts as usize {
+            println!(
+                "    inet6 InReceives {} OutTransmits {}",
+                stats[IpstatsMib::Inpkts as usize],
+                stats[IpstatsMib::Outpkts as usize]
+            );
+        }
+    }
+    if let Some(stats) = inet6.icmp6stats()? {
+        if stats.len() > Icmp6Mib::Outmsgs as usize {
+            println!(
+                "    icmp6 InMsgs {} OutMsgs {}",
+                stats[Icmp6Mib::Inmsgs as usize],
+                stats[Icmp6Mib::Outmsgs as usize]
+            );
+        }
+    }
+    Ok(CbStatus::Ok)
+}
+
+fn put_af_spec(nlv: &mut MsgVec, key: &str, value: &str) -> Result<(), String> {
+    Ifla::af_spec_start(nlv).unwrap();
+    match key {
+        "token" => {
+            let token: Ipv6Addr = value
+                .parse()
+                .map_err(|_| format!("invalid token: {}", value))?;
+            Inet6::af_start(nlv).unwrap();
+            Inet6::put_token(nlv, &token).unwrap();
+        }
+        "addrgenmode" => {
+            let mode = match value {
+                "eui64" => In6AddrGenMode::Eui64,
+                "none" => In6AddrGenMode::None,
+                "stable_secret" => In6AddrGenMode::StablePrivacy,
+                "random" => In6AddrGenMode::Random,
+                _ => return Err(format!("unknown addrgenmode: {}", value)),
+            };
+            Inet6::af_start(nlv).unwrap();
+            Inet6::put_addr_gen_mode(nlv, &(mode as u8)).unwrap();
+        }
+        "forwarding" => {
+            let v: u32 = value
+                .parse()
+                .map_err(|_| format!("invalid forwarding: {}", value))?;
+            Inet::af_start(nlv).unwrap();
+            Inet::put_conf(nlv, &[(Ipv4Devconf::Forwarding, v)]).unwrap();
+        }
+        _ => return Err(format!("unknown key: {}", key)),
+    }
+    nlv.nest_end().unwrap();
+    nlv.nest_end().unwrap();
+    Ok(())
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    let seq = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    nlh.nlmsg_seq = seq;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+    match (args[1].as_ref(), args.len()) {
+        ("show", 3) => nlh.nlmsg_type = rtnetlink::RTM_GETLINK,
+        ("set", 5) => nlh.nlmsg_type = rtnetlink::RTM_SETLINK,
+        _ => usage(&args[0]),
+    }
+    let ifm: &mut Ifinfomsg = nlv.put_extra_header().unwrap();
+    ifm.ifi_family = libc::AF_UNSPEC as u8;
+    Ifla::put_ifname(&mut nlv, &args[2]).unwrap();
+    if args[1] == "set" {
+        put_af_spec(&mut nlv, &args[3], &args[4])?;
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let portid = nl.portid();
+
+    nl.sendto(&nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+
+    let mut buf = mnl::default_buffer();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+
+        match mnl::cb_run(&buf[..nrecv], seq, portid, Some(data_cb)) {
+            Ok(CbStatus::Ok) => continue,
+            Ok(CbStatus::Stop) => break,
+            Err(errno) => return Err(format!("mnl_cb_run: {}", errno)),
+        };
+    }
+
+    Ok(())
+}
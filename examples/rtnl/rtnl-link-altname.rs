@@ -0,0 +1,103 @@
+use std::{
+    env, mem, process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{AttrTbl, CbResult, CbStatus, MsgVec, Msghdr, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    if_link::{Ifla, IflaTbl},
+    ifh,
+    rtnetlink::{self, Ifinfomsg},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} add <ifname> <altname>...", prog);
+    println!("       {} del <ifname> <altname>...", prog);
+    println!("       {} show <ifname or altname>", prog);
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn data_cb(nlh: &Msghdr) -> CbResult {
+    let ifm = nlh.payload::<Ifinfomsg>()?;
+    let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
+    print!("{}: ", ifm.ifi_index);
+    if let Some(name) = tb.ifname()? {
+        print!("{}", name.trim_end_matches('\0'));
+    }
+    println!();
+    for name in tb.alt_ifnames()?.unwrap_or_default() {
+        println!("    altname {}", name);
+    }
+    Ok(CbStatus::Ok)
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    let seq = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    nlh.nlmsg_seq = seq;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+    match (args[1].as_ref(), args.len()) {
+        ("add", n) if n > 3 => {
+            nlh.nlmsg_type = rtnetlink::RTM_NEWLINKPROP;
+            nlh.nlmsg_flags |= (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16;
+        }
+        ("del", n) if n > 3 => nlh.nlmsg_type = rtnetlink::RTM_DELLINKPROP,
+        ("show", 3) => nlh.nlmsg_type = rtnetlink::RTM_GETLINK,
+        _ => usage(&args[0]),
+    }
+    let ifm: &mut Ifinfomsg = nlv.put_extra_header().unwrap();
+    ifm.ifi_family = libc::AF_UNSPEC as u8;
+
+    if args[1] == "show" {
+        // kernel looks up the link by IFLA_ALT_IFNAME as well as IFLA_IFNAME
+        if args[2].len() < ifh::IFNAMSIZ {
+            Ifla::put_ifname(&mut nlv, &args[2]).unwrap();
+        } else {
+            Ifla::put_alt_ifname(&mut nlv, &args[2]).unwrap();
+        }
+    } else {
+        Ifla::put_ifname(&mut nlv, &args[2]).unwrap();
+        let names: Vec<_> = args[3..].iter().map(String::as_str).collect();
+        Ifla::put_alt_ifnames(&mut nlv, &names)
+            .map_err(|errno| format!("invalid altname: {}", errno))?;
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let portid = nl.portid();
+
+    nl.sendto(&nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+
+    let mut buf = mnl::default_buffer();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+
+        match mnl::cb_run(&buf[..nrecv], seq, portid, Some(data_cb)) {
+            Ok(CbStatus::Ok) => continue,
+            Ok(CbStatus::Stop) => break,
+            Err(errno) => return Err(format!("mnl_cb_run: {}", errno)),
+        };
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+extern crate rsmnl_linux as linux;
+use linux::link_cache::{LinkCache, LinkEvent};
+
+fn main() -> Result<(), String> {
+    let mut cache = LinkCache::new().map_err(|errno| format!("LinkCache::new: {}", errno))?;
+
+    let mut links: Vec<_> = cache.links().collect();
+    links.sort_by_key(|link| link.index);
+    for link in links {
+        print!(
+            "{}: {} mtu {} state {:?}",
+            link.index, link.name, link.mtu, link.operstate
+        );
+        if let Some(kind) = &link.kind {
+            print!(" kind {}", kind);
+        }
+        if let Some(master) = link.master {
+            print!(" master {}", cache.name(master).unwrap_or("?"));
+        }
+        for name in &link.alt_names {
+            print!(" altname {}", name);
+        }
+        println!();
+    }
+
+    loop {
+        let events = cache
+            .process()
+            .map_err(|errno| format!("LinkCache::process: {}", errno))?;
+        for event in events {
+            match event {
+                LinkEvent::Removed(link) => println!("{}: {} removed", link.index, link.name),
+                LinkEvent::MovedToNetns { link, netnsid } => {
+                    println!("{}: {} moved to netns {}", link.index, link.name, netnsid)
+                }
+                LinkEvent::Renamed { index, old, new } => {
+                    println!("{}: renamed {} to {}", index, old, new)
+                }
+                LinkEvent::Enslaved { index, master } => println!(
+                    "{}: {} enslaved to {}",
+                    index,
+                    cache.name(index).unwrap_or("?"),
+                    cache.name(master).unwrap_or("?")
+                ),
+                ev => println!("{:?}", ev),
+            }
+        }
+    }
+}
@@ -31,6 +31,13 @@ fn data_cb(nlh: &Msghdr) -> CbResult {
     let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
     tb.mtu()?.map(|x| print!("mtu={} ", x));
     tb.ifname()?.map(|x| print!("name={} ", x));
+    if let Some(names) = tb.alt_ifnames()? {
+        print!("altnames={} ", names.join(","));
+    }
+    if let Some(info_tb) = tb.linkinfo()? {
+        info_tb.kind()?.map(|x| print!("kind={} ", x));
+        info_tb.slave_kind()?.map(|x| print!("slave_kind={} ", x));
+    }
     tb.address()?.map(|x| {
         print!(
             "hwaddr={}",
@@ -0,0 +1,251 @@
+use std::{
+    collections::HashMap,
+    env, mem, process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{AttrTbl, CbResult, CbStatus, MsgVec, Msghdr, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    if_link::{self, IflaTbl, IflaVfGuid, IflaVfLinkState, IflaVfMac, IflaVfRate},
+    if_link::{Ifla, IflaVfRssQueryEn, IflaVfSpoofchk, IflaVfTrust, IflaVfVlanInfo},
+    if_link::{Vf, VfInfo, VfVlanInfo},
+    rtnetlink::{self, Ifinfomsg},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show <ifname>", prog);
+    println!("       {} set <ifname> vf <N> [key value]...", prog);
+    println!("key: mac vlan qos proto min_tx_rate max_tx_rate spoofchk query_rss");
+    println!("     state trust node_guid port_guid");
+    println!(
+        "Example: {} set eth0 vf 1 vlan 100 qos 2 proto 802.1ad",
+        prog
+    );
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn parse<T: std::str::FromStr>(opts: &HashMap<&str, &str>, key: &str) -> Result<Option<T>, String> {
+    match opts.get(key) {
+        Some(v) => v
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("failed to parse {}: {}", key, v)),
+        None => Ok(None),
+    }
+}
+
+fn parse_onoff(opts: &HashMap<&str, &str>, key: &str) -> Result<Option<u32>, String> {
+    match opts.get(key) {
+        Some(&"on") => Ok(Some(1)),
+        Some(&"off") => Ok(Some(0)),
+        Some(v) => Err(format!("{} is neither on nor off: {}", key, v)),
+        None => Ok(None),
+    }
+}
+
+// "xx:xx:xx:xx:xx:xx"
+fn parse_lladdr(v: &str) -> Result<Vec<u8>, String> {
+    v.split(':')
+        .map(|x| u8::from_str_radix(x, 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("failed to parse lladdr: {}", v))
+}
+
+fn onoff(v: Option<bool>) -> &'static str {
+    match v {
+        Some(true) => "on",
+        Some(false) => "off",
+        None => "n/a",
+    }
+}
+
+fn data_cb(nlh: &Msghdr) -> CbResult {
+    let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
+    if let Some(n) = tb.num_vf()? {
+        println!("num_vf={}", n);
+    }
+    for vf in tb.virtual_functions()?.unwrap_or_default() {
+        print!(
+            "vf {} mac {} ",
+            vf.vf,
+            vf.mac[..6]
+                .iter()
+                .map(|e| format!("{:02x}", e))
+                .collect::<Vec<_>>()
+                .join(":")
+        );
+        for vlan in &vf.vlans {
+            print!("vlan {} ", vlan.vlan);
+            if vlan.qos != 0 {
+                print!("qos {} ", vlan.qos);
+            }
+            if u16::from_be(vlan.vlan_proto) == libc::ETH_P_8021AD as u16 {
+                print!("proto 802.1ad ");
+            }
+        }
+        if let Some(rate) = vf.max_tx_rate {
+            print!("max_tx_rate {}Mbps ", rate);
+        }
+        if let Some(rate) = vf.min_tx_rate {
+            print!("min_tx_rate {}Mbps ", rate);
+        }
+        print!(
+            "spoofchk {} query_rss {} trust {} ",
+            onoff(vf.spoofchk),
+            onoff(vf.rss_query_en),
+            onoff(vf.trust)
+        );
+        if let Some(state) = vf.link_state {
+            print!("link-state {:?} ", state);
+        }
+        println!();
+        if let Some(stats) = vf.stats {
+            println!(
+                "    rx_bytes={} rx_packets={} tx_bytes={} tx_packets={} rx_dropped={} tx_dropped={}",
+                stats.rx_bytes,
+                stats.rx_packets,
+                stats.tx_bytes,
+                stats.tx_packets,
+                stats.rx_dropped,
+                stats.tx_dropped
+            );
+        }
+    }
+    Ok(CbStatus::Ok)
+}
+
+fn put_vf_info(nlv: &mut MsgVec, vf: u32, opts: &HashMap<&str, &str>) -> Result<(), String> {
+    Ifla::vfinfo_list_start(nlv).unwrap();
+    VfInfo::info_start(nlv).unwrap();
+
+    if let Some(v) = opts.get("mac") {
+        Vf::put_mac(nlv, &IflaVfMac::new(vf, &parse_lladdr(v)?)).unwrap();
+    }
+    if let Some(vlan) = parse::<u32>(opts, "vlan")? {
+        let proto = match opts.get("proto") {
+            Some(&"802.1Q") | Some(&"802.1q") | None => libc::ETH_P_8021Q as u16,
+            Some(&"802.1ad") => libc::ETH_P_8021AD as u16,
+            Some(p) => return Err(format!("unknown vlan protocol: {}", p)),
+        };
+        let info = IflaVfVlanInfo {
+            vf,
+            vlan,
+            qos: parse::<u32>(opts, "qos")?.unwrap_or(0),
+            vlan_proto: proto.to_be(),
+        };
+        Vf::vlan_list_start(nlv).unwrap();
+        VfVlanInfo::put_info(nlv, &info).unwrap();
+        nlv.nest_end().unwrap();
+    }
+    let min = parse::<u32>(opts, "min_tx_rate")?;
+    let max = parse::<u32>(opts, "max_tx_rate")?;
+    if min.is_some() || max.is_some() {
+        // IFLA_VF_RATE sets both, 0 means unlimited
+        let rate = IflaVfRate {
+            vf,
+            min_tx_rate: min.unwrap_or(0),
+            max_tx_rate: max.unwrap_or(0),
+        };
+        Vf::put_rate(nlv, &rate).unwrap();
+    }
+    if let Some(setting) = parse_onoff(opts, "spoofchk")? {
+        Vf::put_spoofchk(nlv, &IflaVfSpoofchk { vf, setting }).unwrap();
+    }
+    if let Some(setting) = parse_onoff(opts, "query_rss")? {
+        Vf::put_rss_query_en(nlv, &IflaVfRssQueryEn { vf, setting }).unwrap();
+    }
+    if let Some(setting) = parse_onoff(opts, "trust")? {
+        Vf::put_trust(nlv, &IflaVfTrust { vf, setting }).unwrap();
+    }
+    let link_state = match opts.get("state") {
+        Some(&"auto") => Some(if_link::IFLA_VF_LINK_STATE_AUTO),
+        Some(&"enable") => Some(if_link::IFLA_VF_LINK_STATE_ENABLE),
+        Some(&"disable") => Some(if_link::IFLA_VF_LINK_STATE_DISABLE),
+        Some(s) => return Err(format!("unknown link state: {}", s)),
+        None => None,
+    };
+    if let Some(link_state) = link_state {
+        Vf::put_link_state(nlv, &IflaVfLinkState { vf, link_state }).unwrap();
+    }
+    if let Some(guid) = parse::<u64>(opts, "node_guid")? {
+        Vf::put_ib_node_guid(nlv, &IflaVfGuid { vf, guid }).unwrap();
+    }
+    if let Some(guid) = parse::<u64>(opts, "port_guid")? {
+        Vf::put_ib_port_guid(nlv, &IflaVfGuid { vf, guid }).unwrap();
+    }
+
+    nlv.nest_end().unwrap();
+    nlv.nest_end().unwrap();
+    Ok(())
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    let seq = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    nlh.nlmsg_seq = seq;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+    match args[1].as_ref() {
+        "show" => nlh.nlmsg_type = rtnetlink::RTM_GETLINK,
+        "set" => nlh.nlmsg_type = rtnetlink::RTM_SETLINK,
+        _ => usage(&args[0]),
+    }
+    let ifm: &mut Ifinfomsg = nlv.put_extra_header().unwrap();
+    ifm.ifi_family = libc::AF_UNSPEC as u8;
+    Ifla::put_ifname(&mut nlv, &args[2]).unwrap();
+
+    if args[1] == "show" {
+        Ifla::put_ext_mask(&mut nlv, &rtnetlink::RTEXT_FILTER_VF).unwrap();
+    } else {
+        let rest = &args[3..];
+        if rest.len() < 2 || rest.len() % 2 != 0 || rest[0] != "vf" {
+            usage(&args[0]);
+        }
+        let vf = rest[1]
+            .parse::<u32>()
+            .map_err(|_| format!("failed to parse vf: {}", rest[1]))?;
+        let opts: HashMap<&str, &str> = rest[2..]
+            .chunks(2)
+            .map(|kv| (kv[0].as_str(), kv[1].as_str()))
+            .collect();
+        put_vf_info(&mut nlv, vf, &opts)?;
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let portid = nl.portid();
+
+    nl.sendto(&nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+
+    let mut buf = mnl::dump_buffer();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+
+        match mnl::cb_run(&buf[..nrecv], seq, portid, Some(data_cb)) {
+            Ok(CbStatus::Ok) => continue,
+            Ok(CbStatus::Stop) => break,
+            Err(errno) => return Err(format!("mnl_cb_run: {}", errno)),
+        };
+    }
+
+    Ok(())
+}
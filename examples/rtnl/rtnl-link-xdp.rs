@@ -0,0 +1,171 @@
+use std::{
+    env,
+    ffi::CString,
+    io, mem, process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{AttrTbl, CbResult, CbStatus, MsgVec, Msghdr, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    if_link::{self, Ifla, IflaTbl, Xdp},
+    rtnetlink::{self, Ifinfomsg},
+};
+
+fn usage(prog: &str) -> ! {
+    println!(
+        "Usage: {} attach <ifname> <pinned prog> [skb|drv|hw] [noexist] [replace <pinned prog>]",
+        prog
+    );
+    println!("       {} detach <ifname> [skb|drv|hw]", prog);
+    println!("       {} show [ifname]", prog);
+    process::exit(libc::EXIT_FAILURE);
+}
+
+// union bpf_attr for BPF_OBJ_GET
+#[repr(C)]
+struct BpfObjGetAttr {
+    pathname: u64,
+    bpf_fd: u32,
+    file_flags: u32,
+}
+const BPF_OBJ_GET: libc::c_long = 7;
+
+fn bpf_obj_get(path: &str) -> Result<libc::c_int, String> {
+    let cpath = CString::new(path).unwrap();
+    let attr = BpfObjGetAttr {
+        pathname: cpath.as_ptr() as u64,
+        bpf_fd: 0,
+        file_flags: 0,
+    };
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_bpf,
+            BPF_OBJ_GET,
+            &attr as *const _,
+            mem::size_of::<BpfObjGetAttr>(),
+        )
+    };
+    if fd < 0 {
+        return Err(format!(
+            "BPF_OBJ_GET {}: {}",
+            path,
+            io::Error::last_os_error()
+        ));
+    }
+    Ok(fd as libc::c_int)
+}
+
+fn data_cb(nlh: &Msghdr) -> CbResult {
+    let ifm = nlh.payload::<Ifinfomsg>()?;
+    let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
+    let xdp = match tb.xdp()? {
+        Some(xdp) => xdp,
+        None => return Ok(CbStatus::Ok),
+    };
+    let progs = xdp.programs()?;
+    if progs.is_empty() {
+        return Ok(CbStatus::Ok);
+    }
+    print!("{}: {} ", ifm.ifi_index, tb.ifname()?.unwrap_or(""));
+    if let Some(mode) = xdp.attached_mode()? {
+        print!("attached={:?} ", mode);
+    }
+    for (mode, id) in progs {
+        print!("{:?}:{} ", mode, id);
+    }
+    println!();
+    Ok(CbStatus::Ok)
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    let seq = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    nlh.nlmsg_seq = seq;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+    match (args[1].as_ref(), args.len()) {
+        ("attach", n) if n >= 4 => nlh.nlmsg_type = rtnetlink::RTM_SETLINK,
+        ("detach", n) if n >= 3 => nlh.nlmsg_type = rtnetlink::RTM_SETLINK,
+        ("show", 2) => {
+            nlh.nlmsg_type = rtnetlink::RTM_GETLINK;
+            nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+        }
+        ("show", 3) => nlh.nlmsg_type = rtnetlink::RTM_GETLINK,
+        _ => usage(&args[0]),
+    }
+    let ifm: &mut Ifinfomsg = nlv.put_extra_header().unwrap();
+    ifm.ifi_family = libc::AF_UNSPEC as u8;
+    if args.len() > 2 {
+        Ifla::put_ifname(&mut nlv, &args[2]).unwrap();
+    }
+
+    let rest = if args[1] == "attach" {
+        &args[4..]
+    } else {
+        &args[args.len().min(3)..]
+    };
+    let mut flags = 0;
+    let mut expected_fd = None;
+    let mut it = rest.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_ref() {
+            "skb" => flags |= if_link::XDP_FLAGS_SKB_MODE,
+            "drv" => flags |= if_link::XDP_FLAGS_DRV_MODE,
+            "hw" => flags |= if_link::XDP_FLAGS_HW_MODE,
+            "noexist" => flags |= if_link::XDP_FLAGS_UPDATE_IF_NOEXIST,
+            "replace" => {
+                let path = it.next().unwrap_or_else(|| usage(&args[0]));
+                expected_fd = Some(bpf_obj_get(path)?);
+            }
+            _ => usage(&args[0]),
+        }
+    }
+    match args[1].as_ref() {
+        "attach" => {
+            let fd = bpf_obj_get(&args[3])?;
+            Xdp::put_attach(&mut nlv, fd, flags, expected_fd).unwrap();
+        }
+        "detach" => {
+            Xdp::put_detach(&mut nlv, flags).unwrap();
+        }
+        _ => {}
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+    let portid = nl.portid();
+
+    nl.sendto(&nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+
+    let mut buf = mnl::dump_buffer();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+
+        match mnl::cb_run(&buf[..nrecv], seq, portid, Some(data_cb)) {
+            Ok(CbStatus::Ok) => continue,
+            Ok(CbStatus::Stop) => break,
+            Err(errno) => return Err(format!("mnl_cb_run: {}", errno)),
+        };
+    }
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::{env, ffi::CString, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::if_addr::{self, CastAddress};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} <multicast|anycast> [ifname]", prog);
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    if unsafe { libc::if_indextoname(index, buf.as_mut_ptr()).is_null() } {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() != 2 && args.len() != 3 {
+        usage(&args[0]);
+    }
+    let index = match args.get(2) {
+        Some(name) => Some(ifindex(name)?),
+        None => None,
+    };
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    let mut addrs = match args[1].as_ref() {
+        "multicast" => if_addr::multicast_dump(&mut nl, index),
+        "anycast" => if_addr::anycast_dump(&mut nl, index),
+        _ => usage(&args[0]),
+    }
+    .map_err(|errno| format!("failed to dump: {}", errno))?;
+
+    // grouped by the link, as /proc/net/igmp6
+    addrs.sort_by_key(|addr| addr.index);
+    let mut prev = None;
+    for CastAddress {
+        index,
+        addr,
+        cstamp,
+        tstamp,
+        ..
+    } in addrs
+    {
+        if prev != Some(index) {
+            println!("{}: {}", index, ifname(index));
+            prev = Some(index);
+        }
+        // hundredths of seconds since boot
+        println!(
+            "    {} created {}.{:02} updated {}.{:02}",
+            addr,
+            cstamp / 100,
+            cstamp % 100,
+            tstamp / 100,
+            tstamp % 100
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use std::{env, ffi::CString, net::IpAddr, process, time::Duration};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::neighbour::{self, Neighbour, Ntf, Nud, NudState, NTF_PROXY};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show [inet|inet6] [proxy]", prog);
+    println!(
+        "       {} <add|replace|del> <ifname> <dst> [lladdr <mac>] [state <state>] [proxy] [router]",
+        prog
+    );
+    println!("       {} resolve <ifname> <dst> [timeout secs]", prog);
+    println!("state: permanent noarp reachable stale");
+    println!(
+        "Example: {} add eth0 192.0.2.1 lladdr 02:00:00:00:00:01",
+        prog
+    );
+    println!("         {} add eth0 2001:db8::1 proxy", prog);
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    if unsafe { libc::if_indextoname(index, buf.as_mut_ptr()).is_null() } {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+const STATES: [(&str, Nud); 8] = [
+    ("incomplete", Nud::Incomplete),
+    ("reachable", Nud::Reachable),
+    ("stale", Nud::Stale),
+    ("delay", Nud::Delay),
+    ("probe", Nud::Probe),
+    ("failed", Nud::Failed),
+    ("noarp", Nud::Noarp),
+    ("permanent", Nud::Permanent),
+];
+
+fn parse_lladdr(v: &str) -> Result<Vec<u8>, String> {
+    v.split(':')
+        .map(|b| u8::from_str_radix(b, 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid lladdr: {}", v))
+}
+
+fn parse_neigh(prog: &str, args: &[String]) -> Result<Neighbour, String> {
+    let dst: IpAddr = args[1]
+        .parse()
+        .map_err(|_| format!("invalid address: {}", args[1]))?;
+    let mut neigh = Neighbour::new(ifindex(&args[0])?, dst);
+    let mut it = args[2..].iter();
+    while let Some(key) = it.next() {
+        match key.as_ref() {
+            "proxy" => {
+                neigh.flags.insert(Ntf::Proxy);
+            }
+            "router" => {
+                neigh.flags.insert(Ntf::Router);
+            }
+            "lladdr" => {
+                let v = it.next().unwrap_or_else(|| usage(prog));
+                neigh.lladdr = Some(parse_lladdr(v)?);
+            }
+            "state" => {
+                let v = it.next().unwrap_or_else(|| usage(prog));
+                match STATES.iter().find(|(name, _)| name == v) {
+                    Some((_, state)) => neigh.state = NudState::from(*state),
+                    None => return Err(format!("unknown state: {}", v)),
+                }
+            }
+            _ => usage(prog),
+        }
+    }
+    Ok(neigh)
+}
+
+fn show(neigh: &Neighbour) {
+    print!("{} dev {}", neigh.dst, ifname(neigh.index));
+    if let Some(lladdr) = &neigh.lladdr {
+        let s: Vec<_> = lladdr.iter().map(|b| format!("{:02x}", b)).collect();
+        print!(" lladdr {}", s.join(":"));
+    }
+    if neigh.flags.contains(Ntf::Router) {
+        print!(" router");
+    }
+    if neigh.flags.contains(Ntf::Proxy) {
+        print!(" proxy");
+    }
+    for (name, state) in STATES.iter() {
+        if neigh.state.contains(*state) {
+            print!(" {}", name.to_uppercase());
+        }
+    }
+    if let (Some(used), Some(confirmed), Some(updated)) =
+        (neigh.used, neigh.confirmed, neigh.updated)
+    {
+        print!(
+            " used {}/{}/{}",
+            used.as_secs(),
+            confirmed.as_secs(),
+            updated.as_secs()
+        );
+    }
+    if let Some(probes) = neigh.probes {
+        print!(" probes {}", probes);
+    }
+    println!();
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match args[1].as_ref() {
+        "show" => {
+            let mut family = libc::AF_UNSPEC;
+            let mut flags = 0;
+            for arg in &args[2..] {
+                match arg.as_ref() {
+                    "inet" => family = libc::AF_INET,
+                    "inet6" => family = libc::AF_INET6,
+                    "proxy" => flags = NTF_PROXY,
+                    _ => usage(&args[0]),
+                }
+            }
+            let neighs = neighbour::dump(&mut nl, family as u8, flags)
+                .map_err(|errno| format!("failed to dump: {}", errno))?;
+            neighs.iter().for_each(show);
+        }
+        "add" | "replace" | "del" if args.len() >= 4 => {
+            let neigh = parse_neigh(&args[0], &args[2..])?;
+            match args[1].as_ref() {
+                "add" => neighbour::add(&mut nl, &neigh),
+                "replace" => neighbour::replace(&mut nl, &neigh),
+                _ => neighbour::delete(&mut nl, &neigh),
+            }
+            .map_err(|errno| format!("failed to {}: {}", args[1], errno))?;
+        }
+        "resolve" if args.len() == 4 || args.len() == 5 => {
+            let dst: IpAddr = args[3]
+                .parse()
+                .map_err(|_| format!("invalid address: {}", args[3]))?;
+            let secs = match args.get(4) {
+                Some(v) => v.parse().map_err(|_| format!("invalid timeout: {}", v))?,
+                None => 10,
+            };
+            let neigh =
+                neighbour::resolve(&mut nl, ifindex(&args[2])?, dst, Duration::from_secs(secs))
+                    .map_err(|errno| format!("failed to resolve: {}", errno))?;
+            show(&neigh);
+        }
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use std::{env, ffi::CString, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::Socket;
+
+extern crate rsmnl_linux as linux;
+use linux::neighbour::{self, NeighParms, NeighTable, NeighTableConfig};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show [inet|inet6]", prog);
+    println!(
+        "       {} set <table> [dev <ifname>] <param> <value> ...",
+        prog
+    );
+    println!("table: {} {}", neighbour::ARP_TABLE, neighbour::NDISC_TABLE);
+    println!("param: thresh1 thresh2 thresh3 gc_interval (table only)");
+    println!("       base_reachable retrans gc_stale delay_probe anycast_delay proxy_delay locktime (msecs)");
+    println!("       queue_len queue_lenbytes app_probes ucast_probes mcast_probes mcast_reprobes proxy_qlen");
+    println!(
+        "Example: {} set {} thresh1 8192 thresh2 32768 thresh3 65536",
+        prog,
+        neighbour::ARP_TABLE
+    );
+    println!(
+        "         {} set {} dev eth0 base_reachable 60000 queue_lenbytes 1048576",
+        prog,
+        neighbour::ARP_TABLE
+    );
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    if unsafe { libc::if_indextoname(index, buf.as_mut_ptr()).is_null() } {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+fn parse_config(prog: &str, args: &[String]) -> Result<NeighTableConfig, String> {
+    let mut config = NeighTableConfig::new(libc::AF_UNSPEC as u8, &args[0]);
+    let mut parms = NeighParms::default();
+    let mut it = args[1..].iter();
+    while let Some(key) = it.next() {
+        let v = it.next().unwrap_or_else(|| usage(prog));
+        match key.as_ref() {
+            "dev" => parms.ifindex = Some(ifindex(v)?),
+            "thresh1" => config.thresh1 = Some(parse(v)?),
+            "thresh2" => config.thresh2 = Some(parse(v)?),
+            "thresh3" => config.thresh3 = Some(parse(v)?),
+            "gc_interval" => config.gc_interval = Some(parse(v)?),
+            "base_reachable" => parms.base_reachable_time = Some(parse(v)?),
+            "retrans" => parms.retrans_time = Some(parse(v)?),
+            "gc_stale" => parms.gc_staletime = Some(parse(v)?),
+            "delay_probe" => parms.delay_probe_time = Some(parse(v)?),
+            "anycast_delay" => parms.anycast_delay = Some(parse(v)?),
+            "proxy_delay" => parms.proxy_delay = Some(parse(v)?),
+            "locktime" => parms.locktime = Some(parse(v)?),
+            "queue_len" => parms.queue_len = Some(parse(v)?),
+            "queue_lenbytes" => parms.queue_lenbytes = Some(parse(v)?),
+            "app_probes" => parms.app_probes = Some(parse(v)?),
+            "ucast_probes" => parms.ucast_probes = Some(parse(v)?),
+            "mcast_probes" => parms.mcast_probes = Some(parse(v)?),
+            "mcast_reprobes" => parms.mcast_reprobes = Some(parse(v)?),
+            "proxy_qlen" => parms.proxy_qlen = Some(parse(v)?),
+            _ => usage(prog),
+        }
+    }
+    if parms != NeighParms::default() {
+        config.parms = Some(parms);
+    }
+    Ok(config)
+}
+
+fn show_parms(parms: &NeighParms) {
+    let mut kvs = Vec::new();
+    for (k, v) in [
+        ("base_reachable", parms.base_reachable_time),
+        ("reachable", parms.reachable_time),
+        ("retrans", parms.retrans_time),
+        ("gc_stale", parms.gc_staletime),
+        ("delay_probe", parms.delay_probe_time),
+        ("anycast_delay", parms.anycast_delay),
+        ("proxy_delay", parms.proxy_delay),
+        ("locktime", parms.locktime),
+    ] {
+        if let Some(v) = v {
+            kvs.push(format!("{} {}", k, v));
+        }
+    }
+    for (k, v) in [
+        ("refcnt", parms.refcnt),
+        ("queue_len", parms.queue_len),
+        ("queue_lenbytes", parms.queue_lenbytes),
+        ("app_probes", parms.app_probes),
+        ("ucast_probes", parms.ucast_probes),
+        ("mcast_probes", parms.mcast_probes),
+        ("mcast_reprobes", parms.mcast_reprobes),
+        ("proxy_qlen", parms.proxy_qlen),
+    ] {
+        if let Some(v) = v {
+            kvs.push(format!("{} {}", k, v));
+        }
+    }
+    println!("    {}", kvs.join(" "));
+}
+
+fn show(table: &NeighTable) {
+    let family = match table.family as i32 {
+        libc::AF_INET => "inet",
+        libc::AF_INET6 => "inet6",
+        _ => "unknown",
+    };
+    println!(
+        "{} {} thresh1 {} thresh2 {} thresh3 {} gc_interval {}",
+        family, table.name, table.thresh1, table.thresh2, table.thresh3, table.gc_interval
+    );
+    println!(
+        "    entries {} allocs {} destroys {} lookups {} hits {} res_failed {} forced_gc_runs {} table_fulls {}",
+        table.config.ndtc_entries,
+        table.stats.ndts_allocs,
+        table.stats.ndts_destroys,
+        table.stats.ndts_lookups,
+        table.stats.ndts_hits,
+        table.stats.ndts_res_failed,
+        table.stats.ndts_forced_gc_runs,
+        table.stats.ndts_table_fulls,
+    );
+    show_parms(&table.default_parms);
+    let mut indexes: Vec<_> = table.per_dev.keys().collect();
+    indexes.sort();
+    for index in indexes {
+        println!("  dev {}", ifname(*index));
+        show_parms(&table.per_dev[index]);
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match args[1].as_ref() {
+        "show" if args.len() <= 3 => {
+            let family = match args.get(2).map(|s| s.as_ref()) {
+                None => libc::AF_UNSPEC,
+                Some("inet") => libc::AF_INET,
+                Some("inet6") => libc::AF_INET6,
+                _ => usage(&args[0]),
+            };
+            let tables = neighbour::table_dump(&mut nl, family as u8)
+                .map_err(|errno| format!("failed to dump: {}", errno))?;
+            tables.iter().for_each(show);
+        }
+        "set" if args.len() >= 5 => {
+            let config = parse_config(&args[0], &args[2..])?;
+            neighbour::table_set(&mut nl, &config)
+                .map_err(|errno| format!("failed to set: {}", errno))?;
+        }
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
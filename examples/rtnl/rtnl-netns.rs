@@ -0,0 +1,156 @@
+use std::{
+    env, mem,
+    os::unix::io::AsRawFd,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{AttrTbl, CbResult, CbStatus, MsgVec, Msghdr, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    if_addr,
+    if_link::{Ifla, IflaTbl, Info},
+    link_cache,
+    netns::{self, Netns},
+    rtnetlink::{self, Ifinfomsg},
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} move <ifname> <netns name or pid>", prog);
+    println!("       {} add <ifname> <kind> <netns name>", prog);
+    println!("       {} dump <netns name>", prog);
+    println!("       {} enter <netns name>", prog);
+    println!("dump queries by nsid from the current netns,");
+    println!("enter does by a socket opened in the netns.");
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn link_cb(nlh: &Msghdr) -> CbResult {
+    let ifm = nlh.payload::<Ifinfomsg>()?;
+    let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
+    println!(
+        "link {}: {}",
+        ifm.ifi_index,
+        tb.ifname()?.unwrap_or("").trim_end_matches('\0')
+    );
+    Ok(CbStatus::Ok)
+}
+
+fn show(nl: &mut Socket, nsid: Option<i32>) -> Result<(), String> {
+    let links =
+        link_cache::dump(nl, nsid).map_err(|errno| format!("link_cache::dump: {}", errno))?;
+    for link in &links {
+        println!("link {}: {}", link.index, link.name);
+    }
+    let addrs = if_addr::dump(nl, libc::AF_UNSPEC as u8, nsid)
+        .map_err(|errno| format!("if_addr::dump: {}", errno))?;
+    for addr in &addrs {
+        println!("addr {}: {}/{}", addr.index, addr.local, addr.prefixlen);
+    }
+    Ok(())
+}
+
+fn seq() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32
+}
+
+fn run(nl: &mut Socket, nlv: &MsgVec, cb: fn(&Msghdr) -> CbResult) -> Result<(), String> {
+    let seq = nlv.msghdr().unwrap().nlmsg_seq;
+    nl.sendto(nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+
+    let portid = nl.portid();
+    let mut buf = mnl::dump_buffer();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+
+        match mnl::cb_run(&buf[..nrecv], seq, portid, Some(cb)) {
+            Ok(CbStatus::Ok) => continue,
+            Ok(CbStatus::Stop) => return Ok(()),
+            Err(errno) => return Err(format!("mnl_cb_run: {}", errno)),
+        };
+    }
+}
+
+fn link_request(mtype: u16, flags: i32) -> MsgVec {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = mtype;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | flags) as u16;
+    nlh.nlmsg_seq = seq();
+    let ifm: &mut Ifinfomsg = nlv.put_extra_header().unwrap();
+    ifm.ifi_family = libc::AF_UNSPEC as u8;
+    nlv
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    let mut nl = match (args[1].as_ref(), args.len()) {
+        ("enter", 3) => netns::socket(&args[2], libc::NETLINK_ROUTE)
+            .map_err(|errno| format!("netns::socket: {}", errno))?,
+        ("move", 4) | ("add", 5) | ("dump", 3) => Socket::open(libc::NETLINK_ROUTE, 0)
+            .map_err(|errno| format!("mnl_socket_open: {}", errno))?,
+        _ => usage(&args[0]),
+    };
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match args[1].as_ref() {
+        "move" => {
+            let mut nlv = link_request(rtnetlink::RTM_SETLINK, libc::NLM_F_ACK);
+            Ifla::put_ifname(&mut nlv, &args[2]).unwrap();
+            // ns needs to be open until the request is done
+            let ns = match args[3].parse::<u32>() {
+                Ok(pid) => {
+                    Ifla::put_netns(&mut nlv, Netns::Pid(pid)).unwrap();
+                    None
+                }
+                Err(_) => {
+                    let ns =
+                        netns::open(&args[3]).map_err(|errno| format!("netns::open: {}", errno))?;
+                    Ifla::put_netns(&mut nlv, Netns::Fd(ns.as_raw_fd())).unwrap();
+                    Some(ns)
+                }
+            };
+            run(&mut nl, &nlv, link_cb)?;
+            drop(ns);
+        }
+        "add" => {
+            let ns = netns::open(&args[4]).map_err(|errno| format!("netns::open: {}", errno))?;
+            let mut nlv = link_request(
+                rtnetlink::RTM_NEWLINK,
+                libc::NLM_F_CREATE | libc::NLM_F_EXCL | libc::NLM_F_ACK,
+            );
+            Ifla::put_ifname(&mut nlv, &args[2]).unwrap();
+            Ifla::put_netns(&mut nlv, Netns::Fd(ns.as_raw_fd())).unwrap();
+            Ifla::linkinfo_start(&mut nlv).unwrap();
+            Info::put_kind(&mut nlv, &args[3]).unwrap();
+            nlv.nest_end().unwrap();
+            run(&mut nl, &nlv, link_cb)?;
+        }
+        "dump" => {
+            let ns = netns::open(&args[2]).map_err(|errno| format!("netns::open: {}", errno))?;
+            let nsid = netns::ensure_nsid(&mut nl, ns.as_raw_fd())
+                .map_err(|errno| format!("netns::ensure_nsid: {}", errno))?;
+            println!("nsid {}", nsid);
+            show(&mut nl, Some(nsid))?;
+        }
+        "enter" => show(&mut nl, None)?,
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
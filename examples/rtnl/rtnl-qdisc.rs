@@ -0,0 +1,385 @@
+use std::{env, ffi::CString, process};
+
+extern crate libc;
+
+extern crate rsmnl as mnl;
+use mnl::{AttrTbl, MsgVec, Socket};
+
+extern crate rsmnl_linux as linux;
+use linux::{
+    pkt_sched::{
+        self, Class, Htb, Netem, Qdisc, QdiscOptions, Tbf, TcHtbGlob, TcHtbOpt, TcNetemQopt,
+        TcRatespec, TcTbfQopt,
+    },
+    rtnetlink::Tca,
+};
+
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} show [<ifname>]", prog);
+    println!("       {} add <ifname> netem delay <msec> [jitter <msec>] [loss <percent>] [limit <packets>]", prog);
+    println!(
+        "       {} add <ifname> tbf rate <bytes/s> burst <bytes> limit <bytes>",
+        prog
+    );
+    println!("       {} add <ifname> htb [default <minor>]", prog);
+    println!(
+        "       {} class <ifname> <major:minor> rate <bytes/s> [ceil <bytes/s>]",
+        prog
+    );
+    println!("       {} del <ifname>", prog);
+    println!("root qdisc for add and del, htb handle is 1:");
+    process::exit(libc::EXIT_FAILURE);
+}
+
+fn ifindex(name: &str) -> Result<u32, String> {
+    let cname = CString::new(name).unwrap();
+    match unsafe { libc::if_nametoindex(cname.as_ptr()) } {
+        0 => Err(format!("unknown interface: {}", name)),
+        i => Ok(i),
+    }
+}
+
+fn ifname(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    if unsafe { libc::if_indextoname(index, buf.as_mut_ptr()).is_null() } {
+        return format!("if{}", index);
+    }
+    unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("invalid value: {}", v))
+}
+
+fn handle_str(h: u32) -> String {
+    match h {
+        pkt_sched::TC_H_ROOT => "root".to_string(),
+        pkt_sched::TC_H_INGRESS => "ingress".to_string(),
+        _ if pkt_sched::tc_h_min(h) == 0 => format!("{:x}:", h >> 16),
+        _ => format!("{:x}:{:x}", h >> 16, pkt_sched::tc_h_min(h)),
+    }
+}
+
+fn parse_handle(s: &str) -> Result<u32, String> {
+    let mut it = s.splitn(2, ':');
+    let maj = u32::from_str_radix(it.next().unwrap_or(""), 16)
+        .map_err(|_| format!("invalid handle: {}", s))?;
+    let min = match it.next() {
+        Some("") | None => 0,
+        Some(v) => u32::from_str_radix(v, 16).map_err(|_| format!("invalid handle: {}", s))?,
+    };
+    Ok(pkt_sched::tc_h_make(maj << 16, min))
+}
+
+// percent to the probability of netem, 0 = none, ~0 = 100%
+fn probability(v: &str) -> Result<u32, String> {
+    let percent: f64 = parse(v)?;
+    Ok((percent / 100. * u32::MAX as f64) as u32)
+}
+
+fn show_options(nlh: &mnl::Msghdr) -> mnl::Result<String> {
+    let tb =
+        linux::rtnetlink::TcaTbl::from_nlmsg(std::mem::size_of::<linux::rtnetlink::Tcmsg>(), nlh)?;
+    Ok(match tb.qdisc_options()? {
+        None | Some(QdiscOptions::Ingress) | Some(QdiscOptions::Clsact) => String::new(),
+        Some(QdiscOptions::Pfifo(qopt)) => format!("limit {}p", qopt.limit),
+        Some(QdiscOptions::Bfifo(qopt)) => format!("limit {}b", qopt.limit),
+        Some(QdiscOptions::PfifoHeadDrop(qopt)) => format!("limit {}p", qopt.limit),
+        Some(QdiscOptions::Prio(qopt)) | Some(QdiscOptions::PfifoFast(qopt)) => {
+            format!("bands {} priomap {:?}", qopt.bands, qopt.priomap)
+        }
+        Some(QdiscOptions::Tbf(tb)) => {
+            let mut s = String::new();
+            if let Some(qopt) = tb.parms()? {
+                let rate = tb.rate64()?.unwrap_or(qopt.rate.rate as u64);
+                s = format!(
+                    "rate {}B/s limit {}b buffer {}",
+                    rate, qopt.limit, qopt.buffer
+                );
+            }
+            if let Some(burst) = tb.burst()? {
+                s.push_str(&format!(" burst {}b", burst));
+            }
+            s
+        }
+        Some(QdiscOptions::Htb(tb)) => match (tb.init()?, tb.parms()?) {
+            (Some(glob), _) => format!(
+                "r2q {} default {:x} direct_packets_stat {}",
+                glob.rate2quantum, glob.defcls, glob.direct_pkts
+            ),
+            (_, Some(opt)) => format!(
+                "rate {}B/s ceil {}B/s prio {} quantum {}",
+                tb.rate64()?.unwrap_or(opt.rate.rate as u64),
+                tb.ceil64()?.unwrap_or(opt.ceil.rate as u64),
+                opt.prio,
+                opt.quantum
+            ),
+            _ => String::new(),
+        },
+        Some(QdiscOptions::Netem(qopt, tb)) => {
+            let mut s = format!("limit {}", qopt.limit);
+            // TCA_NETEM_LATENCY64 overrides latency in psched ticks
+            if let Some(latency) = tb.latency64()? {
+                s.push_str(&format!(" delay {}us", latency / 1000));
+            }
+            if let Some(jitter) = tb.jitter64()? {
+                s.push_str(&format!(" jitter {}us", jitter / 1000));
+            }
+            if qopt.loss != 0 {
+                s.push_str(&format!(
+                    " loss {:.2}%",
+                    qopt.loss as f64 / u32::MAX as f64 * 100.
+                ));
+            }
+            s
+        }
+        Some(QdiscOptions::FqCodel(tb)) => format!(
+            "limit {}p flows {} target {}us interval {}us",
+            tb.limit()?.copied().unwrap_or(0),
+            tb.flows()?.copied().unwrap_or(0),
+            tb.target()?.copied().unwrap_or(0),
+            tb.interval()?.copied().unwrap_or(0)
+        ),
+        Some(QdiscOptions::Fq(tb)) => format!(
+            "limit {}p flow_limit {}p quantum {}",
+            tb.plimit()?.copied().unwrap_or(0),
+            tb.flow_plimit()?.copied().unwrap_or(0),
+            tb.quantum()?.copied().unwrap_or(0)
+        ),
+        Some(QdiscOptions::Cake(tb)) => {
+            format!("bandwidth {}B/s", tb.base_rate64()?.unwrap_or(0))
+        }
+        Some(QdiscOptions::Mqprio(qopt, _)) => format!("tc {}", qopt.num_tc),
+        Some(QdiscOptions::Red(tb)) => match tb.parms()? {
+            Some(qopt) => format!(
+                "limit {}b min {}b max {}b",
+                qopt.limit, qopt.qth_min, qopt.qth_max
+            ),
+            None => String::new(),
+        },
+        Some(QdiscOptions::Unknown(_, b)) => format!("({} bytes)", b.len()),
+    })
+}
+
+// Qdisc and Class does not hold the options, dumps again to decode them.
+fn show(nl: &mut Socket, index: Option<u32>) -> Result<(), String> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = linux::rtnetlink::RTM_GETQDISC;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    nlv.put_extra_header::<linux::rtnetlink::Tcmsg>()
+        .map_err(|errno| format!("failed to build: {}", errno))?;
+    nl.sendto(&nlv)
+        .map_err(|errno| format!("mnl_socket_sendto: {}", errno))?;
+    let portid = nl.portid();
+    let mut buf = mnl::dump_buffer();
+    let mut indexes = Vec::new();
+    loop {
+        let nrecv = nl
+            .recvfrom(&mut buf)
+            .map_err(|errno| format!("mnl_socket_recvfrom: {}", errno))?;
+        match mnl::cb_run(
+            &buf[..nrecv],
+            0,
+            portid,
+            Some(|nlh: &mnl::Msghdr| {
+                let qdisc = Qdisc::from_nlmsg(nlh)?;
+                if index.is_some() && index != Some(qdisc.index) {
+                    return Ok(mnl::CbStatus::Ok);
+                }
+                if !indexes.contains(&qdisc.index) {
+                    indexes.push(qdisc.index);
+                }
+                print!(
+                    "qdisc {} {} dev {} parent {} {}",
+                    qdisc.kind,
+                    handle_str(qdisc.handle),
+                    ifname(qdisc.index),
+                    handle_str(qdisc.parent),
+                    show_options(nlh)?
+                );
+                if let Some(stats) = qdisc.stats {
+                    print!(
+                        "\n  sent {} bytes {} pkt (dropped {}, overlimits {})",
+                        stats.bytes, stats.packets, stats.drops, stats.overlimits
+                    );
+                }
+                println!();
+                Ok(mnl::CbStatus::Ok)
+            }),
+        ) {
+            Ok(mnl::CbStatus::Ok) => continue,
+            Ok(mnl::CbStatus::Stop) => break,
+            Err(err) => return Err(format!("mnl_cb_run: {}", err)),
+        }
+    }
+
+    for index in indexes {
+        let classes = pkt_sched::class_dump(nl, index)
+            .map_err(|errno| format!("failed to dump classes: {}", errno))?;
+        for class in classes {
+            print!(
+                "class {} {} dev {} parent {}",
+                class.kind,
+                handle_str(class.handle),
+                ifname(class.index),
+                handle_str(class.parent),
+            );
+            if let Some(stats) = class.stats {
+                print!(" sent {} bytes {} pkt", stats.bytes, stats.packets);
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn add(nl: &mut Socket, prog: &str, index: u32, args: &[String]) -> Result<(), String> {
+    let kind = args.first().unwrap_or_else(|| usage(prog));
+    let mut params = std::collections::HashMap::new();
+    let mut it = args[1..].iter();
+    while let Some(key) = it.next() {
+        let v = it.next().unwrap_or_else(|| usage(prog));
+        params.insert(key.as_str(), v.as_str());
+    }
+    let mut qdisc = Qdisc::new(index, kind);
+
+    let ret = match kind.as_ref() {
+        "netem" => {
+            let qopt = TcNetemQopt {
+                latency: 0,
+                limit: params.get("limit").map_or(Ok(1000), |v| parse(v))?,
+                loss: params.get("loss").map_or(Ok(0), |v| probability(v))?,
+                gap: 0,
+                duplicate: 0,
+                jitter: 0,
+            };
+            let delay: i64 = params.get("delay").map_or(Ok(0), |v| parse(v))?;
+            let jitter: i64 = params.get("jitter").map_or(Ok(0), |v| parse(v))?;
+            pkt_sched::qdisc_add(nl, &qdisc, |nlv| {
+                qopt.put_start(nlv)?;
+                Netem::put_latency64(nlv, delay * 1_000_000)?;
+                Netem::put_jitter64(nlv, jitter * 1_000_000)?;
+                nlv.nest_end()?;
+                Ok(())
+            })
+        }
+        "tbf" => {
+            let rate: u64 = parse(params.get("rate").unwrap_or_else(|| usage(prog)))?;
+            let burst: u32 = parse(params.get("burst").unwrap_or_else(|| usage(prog)))?;
+            let qopt = TcTbfQopt {
+                rate: TcRatespec::new(rate),
+                peakrate: TcRatespec::new(0),
+                limit: parse(params.get("limit").unwrap_or_else(|| usage(prog)))?,
+                buffer: pkt_sched::tc_calc_xmittime(rate, burst),
+                mtu: 0,
+            };
+            pkt_sched::qdisc_add(nl, &qdisc, |nlv| {
+                Tca::options_start(nlv)?;
+                nlv.put(Tbf::Parms, &qopt)?;
+                nlv.put(Tbf::Burst, &burst)?;
+                if rate >= u32::MAX as u64 {
+                    Tbf::put_rate64(nlv, rate)?;
+                }
+                nlv.nest_end()?;
+                Ok(())
+            })
+        }
+        "htb" => {
+            qdisc.handle = pkt_sched::tc_h_make(1 << 16, 0);
+            let glob = TcHtbGlob {
+                version: pkt_sched::TC_HTB_PROTOVER,
+                rate2quantum: 10,
+                defcls: params
+                    .get("default")
+                    .map_or(Ok(0), |v| u32::from_str_radix(v, 16))
+                    .map_err(|_| "invalid default".to_string())?,
+                debug: 0,
+                direct_pkts: 0,
+            };
+            pkt_sched::qdisc_add(nl, &qdisc, |nlv| {
+                Tca::options_start(nlv)?;
+                nlv.put(Htb::Init, &glob)?;
+                nlv.nest_end()?;
+                Ok(())
+            })
+        }
+        _ => usage(prog),
+    };
+    ret.map_err(|errno| format!("failed to add: {}", errno))
+}
+
+fn add_class(nl: &mut Socket, prog: &str, index: u32, args: &[String]) -> Result<(), String> {
+    if args.len() < 3 || args.len() % 2 != 1 {
+        usage(prog);
+    }
+    let class = Class::new(index, parse_handle(&args[0])?, "htb");
+    let mut rate = None;
+    let mut ceil = None;
+    for kv in args[1..].chunks(2) {
+        match kv[0].as_ref() {
+            "rate" => rate = Some(parse::<u64>(&kv[1])?),
+            "ceil" => ceil = Some(parse::<u64>(&kv[1])?),
+            _ => usage(prog),
+        }
+    }
+    let rate = rate.unwrap_or_else(|| usage(prog));
+    let ceil = ceil.unwrap_or(rate);
+    // burst of 1600 bytes or more, as tc by HZ
+    let burst = std::cmp::max(1600, (rate / 100) as u32);
+    let opt = TcHtbOpt {
+        rate: TcRatespec::new(rate),
+        ceil: TcRatespec::new(ceil),
+        buffer: pkt_sched::tc_calc_xmittime(rate, burst),
+        cbuffer: pkt_sched::tc_calc_xmittime(ceil, burst),
+        quantum: 0,
+        level: 0,
+        prio: 0,
+    };
+    pkt_sched::class_add(nl, &class, |nlv| {
+        Tca::options_start(nlv)?;
+        nlv.put(Htb::Parms, &opt)?;
+        if rate >= u32::MAX as u64 {
+            Htb::put_rate64(nlv, rate)?;
+        }
+        if ceil >= u32::MAX as u64 {
+            Htb::put_ceil64(nlv, ceil)?;
+        }
+        nlv.nest_end()?;
+        Ok(())
+    })
+    .map_err(|errno| format!("failed to add class: {}", errno))
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)
+        .map_err(|errno| format!("mnl_socket_open: {}", errno))?;
+    nl.bind(0, mnl::SOCKET_AUTOPID)
+        .map_err(|errno| format!("mnl_socket_bind: {}", errno))?;
+
+    match args[1].as_ref() {
+        "show" if args.len() <= 3 => {
+            let index = match args.get(2) {
+                Some(name) => Some(ifindex(name)?),
+                None => None,
+            };
+            show(&mut nl, index)?;
+        }
+        "add" if args.len() >= 4 => add(&mut nl, &args[0], ifindex(&args[2])?, &args[3..])?,
+        "class" if args.len() >= 4 => add_class(&mut nl, &args[0], ifindex(&args[2])?, &args[3..])?,
+        "del" if args.len() == 3 => {
+            let qdisc = Qdisc::new(ifindex(&args[2])?, "");
+            pkt_sched::qdisc_delete(&mut nl, &qdisc)
+                .map_err(|errno| format!("failed to delete: {}", errno))?;
+        }
+        _ => usage(&args[0]),
+    }
+    Ok(())
+}
@@ -0,0 +1,389 @@
+use errno::Errno;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    mem,
+    net::{IpAddr, Ipv6Addr},
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use mnl::{self, CbStatus, MsgVec, Msghdr, Result, Socket};
+
+use if_addr::{Address, DadState, Ifaddrmsg};
+use rtnetlink;
+use util::{dump_ctl, nlmsgs, seq, to_errno};
+
+// Identifies an address, the kernel allows the same local address with the
+// different prefix length or peer on IPv4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AddrKey {
+    pub index: u32,
+    pub local: IpAddr,
+    pub peer: Option<IpAddr>,
+    pub prefixlen: u8,
+}
+
+impl AddrKey {
+    pub fn new(addr: &Address) -> Self {
+        Self {
+            index: addr.index,
+            local: addr.local,
+            peer: addr.peer,
+            prefixlen: addr.prefixlen,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddrEvent {
+    Added(Address),
+    Removed(Address),
+    Updated {
+        old: Address,
+        new: Address,
+    },
+    // IPv6 only, old is None on added
+    DadStateChanged {
+        index: u32,
+        addr: Ipv6Addr,
+        old: Option<DadState>,
+        new: DadState,
+    },
+}
+
+fn addr_events(old: Option<&Address>, new: &Address) -> Vec<AddrEvent> {
+    let mut events = Vec::new();
+    match old {
+        Some(old) if old == new => return events,
+        Some(old) => events.push(AddrEvent::Updated {
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        None => events.push(AddrEvent::Added(new.clone())),
+    }
+    if let (IpAddr::V6(addr), Some(state)) = (new.local, new.dad_state()) {
+        let prev = old.and_then(Address::dad_state);
+        if prev != Some(state) {
+            events.push(AddrEvent::DadStateChanged {
+                index: new.index,
+                addr,
+                old: prev,
+                new: state,
+            });
+        }
+    }
+    events
+}
+
+struct DadWaiter {
+    index: u32,
+    addr: Ipv6Addr,
+    seen: bool,
+    result: Option<Result<()>>,
+    waker: Option<Waker>,
+}
+
+// Resolves when the address becomes usable, DadState::is_usable(), with
+// EADDRINUSE if DAD failed or EADDRNOTAVAIL if it was removed. It is
+// resolved by AddrCache::process(), which needs to be run by the event loop.
+pub struct DadFuture(Arc<Mutex<DadWaiter>>);
+
+impl DadFuture {
+    // Without polling, None if not resolved yet.
+    pub fn result(&self) -> Option<Result<()>> {
+        self.0.lock().unwrap().result
+    }
+}
+
+impl Future for DadFuture {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut waiter = self.0.lock().unwrap();
+        match waiter.result {
+            Some(ret) => Poll::Ready(ret),
+            None => {
+                waiter.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+// IPv4 and IPv6 addresses in the netns of the socket, filled by RTM_GETADDR
+// dump then updated by RTNLGRP_IPV4_IFADDR and RTNLGRP_IPV6_IFADDR
+// notifications. Call process() when the socket, as_raw_fd(), is readable.
+pub struct AddrCache {
+    nl: Socket,
+    buf: Vec<u8>,
+    addrs: HashMap<AddrKey, Address>,
+    subscribers: Vec<Sender<AddrEvent>>,
+    waiters: Vec<Arc<Mutex<DadWaiter>>>,
+}
+
+impl AddrCache {
+    pub fn new() -> Result<Self> {
+        let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)?;
+        nl.bind(
+            rtnetlink::RTMGRP_IPV4_IFADDR | rtnetlink::RTMGRP_IPV6_IFADDR,
+            mnl::SOCKET_AUTOPID,
+        )?;
+        let mut cache = Self {
+            nl,
+            buf: vec![0u8; mnl::SOCKET_DUMP_SIZE],
+            addrs: HashMap::new(),
+            subscribers: Vec::new(),
+            waiters: Vec::new(),
+        };
+        cache.refresh()?;
+        Ok(cache)
+    }
+
+    // Receives the change events in addition to process() return value.
+    pub fn subscribe(&mut self) -> Receiver<AddrEvent> {
+        let (tx, rx) = channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    pub fn get(&self, key: &AddrKey) -> Option<&Address> {
+        self.addrs.get(key)
+    }
+
+    pub fn addrs(&self) -> impl Iterator<Item = &Address> {
+        self.addrs.values()
+    }
+
+    pub fn by_index(&self, index: u32) -> Vec<&Address> {
+        self.addrs
+            .values()
+            .filter(|addr| addr.index == index)
+            .collect()
+    }
+
+    fn find6(&self, index: u32, addr: Ipv6Addr) -> Option<&Address> {
+        self.addrs
+            .values()
+            .find(|a| a.index == index && a.local == IpAddr::V6(addr))
+    }
+
+    // Waits for DAD of the address on the link, which may not have been
+    // added yet.
+    pub fn dad(&mut self, index: u32, addr: Ipv6Addr) -> DadFuture {
+        let waiter = Arc::new(Mutex::new(DadWaiter {
+            index,
+            addr,
+            seen: false,
+            result: None,
+            waker: None,
+        }));
+        self.waiters.push(waiter.clone());
+        self.resolve_waiters();
+        DadFuture(waiter)
+    }
+
+    // Blocking version of dad(), processes notifications until resolved.
+    pub fn wait_dad(&mut self, index: u32, addr: Ipv6Addr) -> Result<()> {
+        let fut = self.dad(index, addr);
+        loop {
+            if let Some(ret) = fut.result() {
+                return ret;
+            }
+            self.process()?;
+        }
+    }
+
+    // Dumps all addresses and returns the differences from the current.
+    // This is needed after the socket reported ENOBUFS, process() does it.
+    pub fn refresh(&mut self) -> Result<Vec<AddrEvent>> {
+        // the events applied by the interrupted dumps are kept
+        let mut events = Vec::new();
+        let ret = loop {
+            match self.dump(&mut events) {
+                Err(Errno(libc::EINTR)) => continue, // NLM_F_DUMP_INTR
+                ret => break ret,
+            }
+        };
+        self.notify(&events);
+        ret.map(|_| events)
+    }
+
+    fn dump(&mut self, events: &mut Vec<AddrEvent>) -> Result<()> {
+        let mut nlv = MsgVec::new();
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = rtnetlink::RTM_GETADDR;
+        nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+        let seq = seq();
+        nlh.nlmsg_seq = seq;
+        let ifa: &mut Ifaddrmsg = nlv.put_extra_header()?;
+        ifa.ifa_family = libc::AF_UNSPEC as u8;
+        self.nl.sendto(&nlv)?;
+
+        let portid = self.nl.portid();
+        let mut buf = mem::take(&mut self.buf);
+        let mut seen = HashSet::new();
+        let mut intr = false;
+        let ret = 'recv: loop {
+            let nrecv = match self.nl.recvfrom(&mut buf) {
+                Ok(n) => n,
+                Err(errno) => break Err(errno),
+            };
+            for nlh in nlmsgs(&buf[..nrecv]) {
+                // notifications may be interleaved, which have the portid and
+                // seq of the request if it was made by the other socket
+                if nlh.nlmsg_pid == portid && nlh.nlmsg_seq == seq {
+                    if let Some(ret) = dump_ctl(nlh, &mut intr) {
+                        break 'recv ret;
+                    }
+                }
+                if nlh.nlmsg_type < libc::NLMSG_MIN_TYPE as u16 {
+                    continue;
+                }
+                if let Err(errno) = self.dumped(nlh, &mut seen, events) {
+                    break 'recv Err(errno);
+                }
+            }
+        };
+        self.buf = buf;
+        ret?;
+
+        let gone: Vec<AddrKey> = self
+            .addrs
+            .keys()
+            .filter(|k| !seen.contains(k))
+            .copied()
+            .collect();
+        if gone.is_empty() {
+            return Ok(());
+        }
+        for key in gone {
+            if let Some(addr) = self.addrs.remove(&key) {
+                events.push(AddrEvent::Removed(addr));
+            }
+        }
+        self.resolve_waiters();
+        Ok(())
+    }
+
+    // Applies a reply of the dump or a notification, seen is the addresses
+    // which exist at the end of the dump.
+    fn dumped(
+        &mut self,
+        nlh: &Msghdr,
+        seen: &mut HashSet<AddrKey>,
+        events: &mut Vec<AddrEvent>,
+    ) -> Result<()> {
+        let mtype = nlh.nlmsg_type;
+        if mtype == rtnetlink::RTM_NEWADDR || mtype == rtnetlink::RTM_DELADDR {
+            match Address::from_nlmsg(nlh) {
+                Ok(addr) if mtype == rtnetlink::RTM_NEWADDR => {
+                    seen.insert(AddrKey::new(&addr));
+                }
+                Ok(addr) => {
+                    seen.remove(&AddrKey::new(&addr));
+                }
+                Err(Errno(libc::EAFNOSUPPORT)) => {}
+                Err(errno) => return Err(errno),
+            }
+        }
+        events.append(&mut self.update(nlh)?);
+        Ok(())
+    }
+
+    // Receives once and applies notifications, blocks if the socket is.
+    pub fn process(&mut self) -> Result<Vec<AddrEvent>> {
+        let mut buf = mem::take(&mut self.buf);
+        let nrecv = match self.nl.recvfrom(&mut buf) {
+            Ok(n) => n,
+            Err(Errno(libc::ENOBUFS)) => {
+                self.buf = buf;
+                return self.refresh();
+            }
+            Err(errno) => {
+                self.buf = buf;
+                return Err(errno);
+            }
+        };
+        let mut events = Vec::new();
+        let status = mnl::cb_run(
+            &buf[..nrecv],
+            0,
+            0,
+            Some(|nlh: &Msghdr| {
+                events.append(&mut self.update(nlh)?);
+                Ok(CbStatus::Ok)
+            }),
+        );
+        self.buf = buf;
+        status.map_err(to_errno)?;
+        self.notify(&events);
+        Ok(events)
+    }
+
+    // Applies RTM_NEWADDR or RTM_DELADDR, received by the other socket.
+    // Subscribers are not notified, but DadFutures are resolved.
+    pub fn update(&mut self, nlh: &Msghdr) -> Result<Vec<AddrEvent>> {
+        let mtype = nlh.nlmsg_type;
+        if mtype != rtnetlink::RTM_NEWADDR && mtype != rtnetlink::RTM_DELADDR {
+            return Ok(Vec::new());
+        }
+        let addr = match Address::from_nlmsg(nlh) {
+            Ok(addr) => addr,
+            Err(Errno(libc::EAFNOSUPPORT)) => return Ok(Vec::new()),
+            Err(errno) => return Err(errno),
+        };
+        let key = AddrKey::new(&addr);
+        let events = if mtype == rtnetlink::RTM_NEWADDR {
+            let events = addr_events(self.addrs.get(&key), &addr);
+            self.addrs.insert(key, addr);
+            events
+        } else {
+            match self.addrs.remove(&key) {
+                Some(addr) => vec![AddrEvent::Removed(addr)],
+                None => Vec::new(),
+            }
+        };
+        self.resolve_waiters();
+        Ok(events)
+    }
+
+    fn resolve_waiters(&mut self) {
+        let mut waiters = mem::take(&mut self.waiters);
+        waiters.retain(|waiter| {
+            let mut w = waiter.lock().unwrap();
+            match self.find6(w.index, w.addr).and_then(Address::dad_state) {
+                Some(DadState::Failed) => w.result = Some(Err(Errno(libc::EADDRINUSE))),
+                Some(state) if state.is_usable() => w.result = Some(Ok(())),
+                Some(_) => w.seen = true,
+                None if w.seen => w.result = Some(Err(Errno(libc::EADDRNOTAVAIL))),
+                None => {}
+            }
+            if w.result.is_none() {
+                // keep unless the future was dropped
+                return Arc::strong_count(waiter) > 1;
+            }
+            if let Some(waker) = w.waker.take() {
+                waker.wake();
+            }
+            false
+        });
+        self.waiters = waiters;
+    }
+
+    fn notify(&mut self, events: &[AddrEvent]) {
+        self.subscribers
+            .retain(|tx| events.iter().all(|ev| tx.send(ev.clone()).is_ok()));
+    }
+}
+
+impl AsRawFd for AddrCache {
+    fn as_raw_fd(&self) -> RawFd {
+        self.nl.as_raw_fd()
+    }
+}
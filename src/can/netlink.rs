@@ -0,0 +1,325 @@
+use errno::Errno;
+use std::convert::TryFrom;
+
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+// CAN bit-timing parameters
+//
+// For further information, please read chapter "8 BIT TIMING
+// REQUIREMENTS" of the "Bosch CAN Specification version 2.0"
+// at http://www.semiconductors.bosch.de/pdf/can2spec.pdf.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CanBittiming {
+    pub bitrate: u32,      // Bit-rate in bits/second
+    pub sample_point: u32, // Sample point in one-tenth of a percent
+    pub tq: u32,           // Time quanta (TQ) in nanoseconds
+    pub prop_seg: u32,     // Propagation segment in TQs
+    pub phase_seg1: u32,   // Phase buffer segment 1 in TQs
+    pub phase_seg2: u32,   // Phase buffer segment 2 in TQs
+    pub sjw: u32,          // Synchronisation jump width in TQs
+    pub brp: u32,          // Bit-rate prescaler
+}
+
+impl CanBittiming {
+    // The driver calculates the rest from the bitrate, sample_point 0 lets
+    // it choose the CiA recommended one.
+    pub fn new(bitrate: u32, sample_point: u32) -> Self {
+        Self {
+            bitrate,
+            sample_point,
+            ..Default::default()
+        }
+    }
+}
+
+// CAN hardware-dependent bit-timing constant
+//
+// Used for calculating and checking bit-timing parameters
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CanBittimingConst {
+    pub name: [u8; 16], // Name of the CAN controller hardware
+    pub tseg1_min: u32, // Time segment 1 = prop_seg + phase_seg1
+    pub tseg1_max: u32,
+    pub tseg2_min: u32, // Time segment 2 = phase_seg2
+    pub tseg2_max: u32,
+    pub sjw_max: u32, // Synchronisation jump width
+    pub brp_min: u32, // Bit-rate prescaler
+    pub brp_max: u32,
+    pub brp_inc: u32,
+}
+
+// CAN clock parameters
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CanClock {
+    pub freq: u32, // CAN system clock frequency in Hz
+}
+
+// CAN operational and error states
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CanState {
+    // CAN_STATE_
+    ErrorActive = 0, // RX/TX error count < 96
+    ErrorWarning,    // RX/TX error count < 128
+    ErrorPassive,    // RX/TX error count < 256
+    BusOff,          // RX/TX error count >= 256
+    Stopped,         // Device is stopped
+    Sleeping,        // Device is sleeping
+}
+pub const CAN_STATE_ERROR_ACTIVE: u32 = CanState::ErrorActive as u32;
+pub const CAN_STATE_ERROR_WARNING: u32 = CanState::ErrorWarning as u32;
+pub const CAN_STATE_ERROR_PASSIVE: u32 = CanState::ErrorPassive as u32;
+pub const CAN_STATE_BUS_OFF: u32 = CanState::BusOff as u32;
+pub const CAN_STATE_STOPPED: u32 = CanState::Stopped as u32;
+pub const CAN_STATE_SLEEPING: u32 = CanState::Sleeping as u32;
+
+impl TryFrom<u32> for CanState {
+    type Error = Errno;
+
+    fn try_from(v: u32) -> Result<Self> {
+        match v {
+            CAN_STATE_ERROR_ACTIVE => Ok(Self::ErrorActive),
+            CAN_STATE_ERROR_WARNING => Ok(Self::ErrorWarning),
+            CAN_STATE_ERROR_PASSIVE => Ok(Self::ErrorPassive),
+            CAN_STATE_BUS_OFF => Ok(Self::BusOff),
+            CAN_STATE_STOPPED => Ok(Self::Stopped),
+            CAN_STATE_SLEEPING => Ok(Self::Sleeping),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// CAN bus error counters
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CanBerrCounter {
+    pub txerr: u16,
+    pub rxerr: u16,
+}
+
+// CAN controller mode
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CanCtrlmode {
+    pub mask: u32,
+    pub flags: u32,
+}
+
+impl CanCtrlmode {
+    // Changes only the CAN_CTRLMODE_ bits in mode, keeps others as they are.
+    pub fn new(mode: u32, on: bool) -> Self {
+        Self {
+            mask: mode,
+            flags: if on { mode } else { 0 },
+        }
+    }
+
+    pub fn set(&mut self, mode: u32, on: bool) -> &mut Self {
+        self.mask |= mode;
+        if on {
+            self.flags |= mode;
+        } else {
+            self.flags &= !mode;
+        }
+        self
+    }
+}
+
+pub const CAN_CTRLMODE_LOOPBACK: u32 = 0x01; // Loopback mode
+pub const CAN_CTRLMODE_LISTENONLY: u32 = 0x02; // Listen-only mode
+pub const CAN_CTRLMODE_3_SAMPLES: u32 = 0x04; // Triple sampling mode
+pub const CAN_CTRLMODE_ONE_SHOT: u32 = 0x08; // One-Shot mode
+pub const CAN_CTRLMODE_BERR_REPORTING: u32 = 0x10; // Bus-error reporting
+pub const CAN_CTRLMODE_FD: u32 = 0x20; // CAN FD mode
+pub const CAN_CTRLMODE_PRESUME_ACK: u32 = 0x40; // Ignore missing CAN ACKs
+pub const CAN_CTRLMODE_FD_NON_ISO: u32 = 0x80; // CAN FD in non-ISO mode
+pub const CAN_CTRLMODE_CC_LEN8_DLC: u32 = 0x100; // Classic CAN DLC option
+pub const CAN_CTRLMODE_TDC_AUTO: u32 = 0x200; // CAN transiver automatically calculates TDCV
+pub const CAN_CTRLMODE_TDC_MANUAL: u32 = 0x400; // TDCV is manually set up by user
+
+// CAN device statistics, IFLA_INFO_XSTATS
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CanDeviceStats {
+    pub bus_error: u32,        // Bus errors
+    pub error_warning: u32,    // Changes to error warning state
+    pub error_passive: u32,    // Changes to error passive state
+    pub bus_off: u32,          // Changes to bus off state
+    pub arbitration_lost: u32, // Arbitration lost errors
+    pub restarts: u32,         // CAN controller re-starts
+}
+
+// CAN netlink interface
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "CanTbl"]
+pub enum Can {
+    // IFLA_CAN_
+    Unspec = 0,
+
+    #[nla_type(CanBittiming, bittiming)]
+    Bittiming,
+
+    #[nla_type(CanBittimingConst, bittiming_const)]
+    BittimingConst,
+
+    #[nla_type(CanClock, clock)]
+    Clock,
+
+    #[nla_type(u32, state)]
+    State, // CanState
+
+    #[nla_type(CanCtrlmode, ctrlmode)]
+    Ctrlmode,
+
+    #[nla_type(u32, restart_ms)]
+    RestartMs,
+
+    #[nla_type(u32, restart)]
+    Restart,
+
+    #[nla_type(CanBerrCounter, berr_counter)]
+    BerrCounter,
+
+    #[nla_type(CanBittiming, data_bittiming)]
+    DataBittiming,
+
+    #[nla_type(CanBittimingConst, data_bittiming_const)]
+    DataBittimingConst,
+
+    #[nla_type(u16, termination)]
+    Termination,
+
+    TerminationConst, // u16 array, see CanTbl::termination_const()
+    BitrateConst,     // u32 array, see CanTbl::bitrate_const()
+    DataBitrateConst, // u32 array, see CanTbl::data_bitrate_const()
+
+    #[nla_type(u32, bitrate_max)]
+    BitrateMax,
+
+    #[nla_nest(CanTdcTbl, tdc)]
+    Tdc,
+
+    #[nla_nest(CanCtrlmodeExtTbl, ctrlmode_ext)]
+    CtrlmodeExt,
+
+    _MAX,
+}
+
+// CAN FD Transmitter Delay Compensation (TDC)
+//
+// Please refer to struct can_tdc_const and can_tdc in
+// include/linux/can/bittiming.h for further details.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "CanTdcTbl"]
+pub enum CanTdc {
+    // IFLA_CAN_TDC_
+    Unspec = 0,
+
+    #[nla_type(u32, tdcv_min)]
+    TdcvMin, // u32
+
+    #[nla_type(u32, tdcv_max)]
+    TdcvMax, // u32
+
+    #[nla_type(u32, tdco_min)]
+    TdcoMin, // u32
+
+    #[nla_type(u32, tdco_max)]
+    TdcoMax, // u32
+
+    #[nla_type(u32, tdcf_min)]
+    TdcfMin, // u32
+
+    #[nla_type(u32, tdcf_max)]
+    TdcfMax, // u32
+
+    #[nla_type(u32, tdcv)]
+    Tdcv, // u32
+
+    #[nla_type(u32, tdco)]
+    Tdco, // u32
+
+    #[nla_type(u32, tdcf)]
+    Tdcf, // u32
+
+    _MAX,
+}
+
+// IFLA_CAN_CTRLMODE_EXT nest: controller mode extensions
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "CanCtrlmodeExtTbl"]
+pub enum CanCtrlmodeExt {
+    // IFLA_CAN_CTRLMODE_
+    Unspec = 0,
+
+    #[nla_type(u32, supported)]
+    Supported, // u32
+
+    _MAX,
+}
+
+// u16 termination range: 1..65535 Ohms
+pub const CAN_TERMINATION_DISABLED: u16 = 0;
+
+fn u16_array(attr: &Attr) -> Vec<u16> {
+    attr.bytes_ref()
+        .chunks_exact(2)
+        .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+        .collect()
+}
+
+fn u32_array(attr: &Attr) -> Vec<u32> {
+    attr.bytes_ref()
+        .chunks_exact(4)
+        .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+impl<'a> CanTbl<'a> {
+    // supported termination values in Ohms
+    pub fn termination_const(&self) -> Result<Option<Vec<u16>>> {
+        Ok(self[Can::TerminationConst].map(u16_array))
+    }
+
+    // supported bitrates, for devices which can not calculate bittiming
+    pub fn bitrate_const(&self) -> Result<Option<Vec<u32>>> {
+        Ok(self[Can::BitrateConst].map(u32_array))
+    }
+
+    pub fn data_bitrate_const(&self) -> Result<Option<Vec<u32>>> {
+        Ok(self[Can::DataBitrateConst].map(u32_array))
+    }
+}
+
+impl Can {
+    // sample_point is in one-tenth of a percent, e.g. 875 for 87.5%, and 0
+    // leaves it to the driver.
+    pub fn put_bitrate(nlv: &mut MsgVec, bitrate: u32, sample_point: u32) -> Result<&mut MsgVec> {
+        if sample_point >= 1000 {
+            return Err(Errno(libc::EINVAL));
+        }
+        Can::put_bittiming(nlv, &CanBittiming::new(bitrate, sample_point))
+    }
+
+    // bitrate of the data phase, CAN FD needs to be enabled by put_fd()
+    pub fn put_data_bitrate(
+        nlv: &mut MsgVec,
+        bitrate: u32,
+        sample_point: u32,
+    ) -> Result<&mut MsgVec> {
+        if sample_point >= 1000 {
+            return Err(Errno(libc::EINVAL));
+        }
+        Can::put_data_bittiming(nlv, &CanBittiming::new(bitrate, sample_point))
+    }
+
+    pub fn put_fd(nlv: &mut MsgVec, on: bool) -> Result<&mut MsgVec> {
+        Can::put_ctrlmode(nlv, &CanCtrlmode::new(CAN_CTRLMODE_FD, on))
+    }
+}
@@ -0,0 +1,37 @@
+use errno::Errno;
+
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use if_link::IflaTbl;
+use rtnetlink::Ifinfomsg;
+use veth;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "VxcanInfoTbl"]
+pub enum VxcanInfo {
+    // VXCAN_INFO_
+    Unspec = 0,
+
+    // struct ifinfomsg followed by IFLA_* attributes,
+    // see VxcanInfo::peer_start() and VxcanInfoTbl::peer()
+    Peer,
+
+    _MAX,
+}
+
+impl VxcanInfo {
+    // Fill the returned ifinfomsg and put Ifla attributes of the peer, e.g.
+    // Ifla::put_ifname() or Ifla::put_net_ns_fd(), then nest_end()
+    pub fn peer_start(nlv: &mut MsgVec) -> Result<&mut Ifinfomsg> {
+        veth::peer_start(nlv, VxcanInfo::Peer)
+    }
+}
+
+impl<'a> VxcanInfoTbl<'a> {
+    // Kernel does not dump VXCAN_INFO_PEER, this is for RTM_NEWLINK
+    // requests built by VxcanInfo::peer_start().
+    pub fn peer(&self) -> Result<Option<(&'a Ifinfomsg, IflaTbl<'a>)>> {
+        veth::peer(self[VxcanInfo::Peer])
+    }
+}
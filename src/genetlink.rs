@@ -1,7 +1,8 @@
 use errno::Errno;
-use mnl::{Attr, AttrTbl, MsgVec, Result};
+use mnl::{Attr, AttrTbl, MsgVec, Result, Socket};
 use netlink;
-use std::mem;
+use std::{collections::HashMap, mem};
+use util::{run, seq};
 
 pub const GENL_NAMSIZ: usize = 16;
 
@@ -152,3 +153,56 @@ pub enum CtrlAttrPolicy {
 
     _MAX,
 }
+
+// A family resolved by name through the controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Family {
+    pub id: u16, // nlmsg_type of the family requests
+    pub name: String,
+    pub version: u32,
+    pub hdrsize: u32,
+    pub maxattr: u32,
+    pub mcast_groups: HashMap<String, u32>,
+}
+
+impl Family {
+    // CTRL_CMD_GETFAMILY on the NETLINK_GENERIC socket, ENOENT if the family
+    // is not registered, e.g. its module is not loaded.
+    pub fn resolve(nl: &mut Socket, name: &str) -> Result<Self> {
+        let mut nlv = MsgVec::new();
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = GENL_ID_CTRL;
+        nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+        let seq = seq();
+        nlh.nlmsg_seq = seq;
+        let genlh = nlv.put_extra_header::<Genlmsghdr>()?;
+        genlh.cmd = CTRL_CMD_GETFAMILY;
+        genlh.version = 1;
+        CtrlAttr::put_family_name(&mut nlv, name)?;
+
+        let mut family = None;
+        run(nl, &nlv, seq, |nlh| {
+            let tb = CtrlAttrTbl::from_nlmsg(mem::size_of::<Genlmsghdr>(), nlh)?;
+            let mut mcast_groups = HashMap::new();
+            for grp in tb.mcast_groups()?.unwrap_or_default() {
+                if let (Some(name), Some(id)) = (grp.name()?, grp.id()?) {
+                    mcast_groups.insert(name.to_string(), *id);
+                }
+            }
+            family = Some(Family {
+                id: *tb.family_id()?.ok_or(Errno(libc::EPROTO))?,
+                name: tb.family_name()?.unwrap_or(name).to_string(),
+                version: tb.version()?.copied().unwrap_or(0),
+                hdrsize: tb.hdrsize()?.copied().unwrap_or(0),
+                maxattr: tb.maxattr()?.copied().unwrap_or(0),
+                mcast_groups,
+            });
+            Ok(())
+        })?;
+        family.ok_or(Errno(libc::ENOENT))
+    }
+
+    pub fn mcast_group(&self, name: &str) -> Option<u32> {
+        self.mcast_groups.get(name).copied()
+    }
+}
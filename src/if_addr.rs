@@ -1,6 +1,15 @@
 use errno::Errno;
-use mnl::{Attr, AttrTbl, MsgVec, Result};
-use std::net::{Ipv4Addr, Ipv6Addr};
+use mnl::{Attr, AttrTbl, MsgVec, Msghdr, Result, Socket};
+use std::{
+    mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::Duration,
+};
+
+use ifh::IFNAMSIZ;
+use netns;
+use rtnetlink::{self, RT_SCOPE_UNIVERSE};
+use util::{run, seq};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -63,20 +72,74 @@ pub enum IfAddr {
     _MAX = 11,
 }
 
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IfaFlag {
+    // IFA_F_
+    Secondary = 0x01, // also IFA_F_TEMPORARY for IPv6
+    Nodad = 0x02,
+    Optimistic = 0x04,
+    Dadfailed = 0x08,
+    Homeaddress = 0x10,
+    Deprecated = 0x20,
+    Tentative = 0x40,
+    Permanent = 0x80,
+    Managetempaddr = 0x100,
+    Noprefixroute = 0x200,
+    Mcautojoin = 0x400,
+    StablePrivacy = 0x800,
+}
+
 // ifa_flags
-pub const IFA_F_SECONDARY: u32 = 0x01;
+pub const IFA_F_SECONDARY: u32 = IfaFlag::Secondary as u32;
 pub const IFA_F_TEMPORARY: u32 = IFA_F_SECONDARY;
-pub const IFA_F_NODAD: u32 = 0x02;
-pub const IFA_F_OPTIMISTIC: u32 = 0x04;
-pub const IFA_F_DADFAILED: u32 = 0x08;
-pub const IFA_F_HOMEADDRESS: u32 = 0x10;
-pub const IFA_F_DEPRECATED: u32 = 0x20;
-pub const IFA_F_TENTATIVE: u32 = 0x40;
-pub const IFA_F_PERMANENT: u32 = 0x80;
-pub const IFA_F_MANAGETEMPADDR: u32 = 0x100;
-pub const IFA_F_NOPREFIXROUTE: u32 = 0x200;
-pub const IFA_F_MCAUTOJOIN: u32 = 0x400;
-pub const IFA_F_STABLE_PRIVACY: u32 = 0x800;
+pub const IFA_F_NODAD: u32 = IfaFlag::Nodad as u32;
+pub const IFA_F_OPTIMISTIC: u32 = IfaFlag::Optimistic as u32;
+pub const IFA_F_DADFAILED: u32 = IfaFlag::Dadfailed as u32;
+pub const IFA_F_HOMEADDRESS: u32 = IfaFlag::Homeaddress as u32;
+pub const IFA_F_DEPRECATED: u32 = IfaFlag::Deprecated as u32;
+pub const IFA_F_TENTATIVE: u32 = IfaFlag::Tentative as u32;
+pub const IFA_F_PERMANENT: u32 = IfaFlag::Permanent as u32;
+pub const IFA_F_MANAGETEMPADDR: u32 = IfaFlag::Managetempaddr as u32;
+pub const IFA_F_NOPREFIXROUTE: u32 = IfaFlag::Noprefixroute as u32;
+pub const IFA_F_MCAUTOJOIN: u32 = IfaFlag::Mcautojoin as u32;
+pub const IFA_F_STABLE_PRIVACY: u32 = IfaFlag::StablePrivacy as u32;
+
+// A set of IFA_F_, ifa_flags in struct ifaddrmsg extended by IFA_FLAGS.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct IfaFlags(pub u32);
+
+impl IfaFlags {
+    pub fn contains(&self, flag: IfaFlag) -> bool {
+        self.0 & flag as u32 != 0
+    }
+
+    pub fn insert(&mut self, flag: IfaFlag) -> &mut Self {
+        self.0 |= flag as u32;
+        self
+    }
+
+    pub fn remove(&mut self, flag: IfaFlag) -> &mut Self {
+        self.0 &= !(flag as u32);
+        self
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for IfaFlags {
+    fn from(v: u32) -> Self {
+        Self(v)
+    }
+}
+
+impl From<IfaFlag> for IfaFlags {
+    fn from(flag: IfaFlag) -> Self {
+        Self(flag as u32)
+    }
+}
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -86,3 +149,417 @@ pub struct IfaCacheinfo {
     pub cstamp: u32, // created timestamp, hundredths of seconds
     pub tstamp: u32, // updated timestamp, hundredths of seconds
 }
+
+// ifa_prefered and ifa_valid of no expiration
+pub const INFINITY_LIFE_TIME: u32 = 0xFFFFFFFF;
+
+// IPv6 duplicate address detection state, derived from IFA_F_ flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DadState {
+    Tentative,  // IFA_F_TENTATIVE, not usable until DAD completes
+    Optimistic, // IFA_F_OPTIMISTIC, usable while DAD is in progress
+    Failed,     // IFA_F_DADFAILED, a duplicate was found
+    Valid,
+    Deprecated, // IFA_F_DEPRECATED, preferred lifetime expired
+}
+
+impl DadState {
+    pub fn from_flags(flags: IfaFlags) -> Self {
+        if flags.contains(IfaFlag::Dadfailed) {
+            DadState::Failed
+        } else if flags.contains(IfaFlag::Optimistic) && flags.contains(IfaFlag::Tentative) {
+            DadState::Optimistic
+        } else if flags.contains(IfaFlag::Tentative) {
+            DadState::Tentative
+        } else if flags.contains(IfaFlag::Deprecated) {
+            DadState::Deprecated
+        } else {
+            DadState::Valid
+        }
+    }
+
+    // can be bound to
+    pub fn is_usable(&self) -> bool {
+        matches!(
+            self,
+            DadState::Optimistic | DadState::Valid | DadState::Deprecated
+        )
+    }
+}
+
+// An address on a link, RTM_NEWADDR and RTM_DELADDR.
+//
+// local is IFA_LOCAL, or IFA_ADDRESS if there is no IFA_LOCAL as IPv6 does
+// for non point-to-point. peer is the destination IFA_ADDRESS on
+// point-to-point, which differs from IFA_LOCAL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address {
+    pub index: u32,
+    pub local: IpAddr,
+    pub peer: Option<IpAddr>,
+    pub prefixlen: u8,
+    pub broadcast: Option<Ipv4Addr>,
+    pub label: Option<String>, // IPv4 only, must start with the link name
+    pub scope: u8,             // RT_SCOPE_
+    pub flags: IfaFlags,
+    pub metric: Option<u32>, // IFA_RT_PRIORITY, of the prefix route
+    // IFA_CACHEINFO, None is INFINITY_LIFE_TIME
+    pub valid_lifetime: Option<Duration>,
+    pub preferred_lifetime: Option<Duration>,
+    pub cstamp: u32, // created, hundredths of seconds since boot
+    pub tstamp: u32, // updated
+}
+
+impl Address {
+    pub fn new(index: u32, local: IpAddr, prefixlen: u8) -> Self {
+        Self {
+            index,
+            local,
+            peer: None,
+            prefixlen,
+            broadcast: None,
+            label: None,
+            scope: RT_SCOPE_UNIVERSE,
+            flags: IfaFlags::default(),
+            metric: None,
+            valid_lifetime: None,
+            preferred_lifetime: None,
+            cstamp: 0,
+            tstamp: 0,
+        }
+    }
+
+    pub fn family(&self) -> u8 {
+        match self.local {
+            IpAddr::V4(_) => libc::AF_INET as u8,
+            IpAddr::V6(_) => libc::AF_INET6 as u8,
+        }
+    }
+
+    // None for IPv4, which does no DAD.
+    pub fn dad_state(&self) -> Option<DadState> {
+        match self.local {
+            IpAddr::V4(_) => None,
+            IpAddr::V6(_) => Some(DadState::from_flags(self.flags)),
+        }
+    }
+
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let ifa = nlh.payload::<Ifaddrmsg>()?;
+        let tb = IfAddrTbl::from_nlmsg(mem::size_of::<Ifaddrmsg>(), nlh)?;
+        let (local, address) = match ifa.ifa_family as i32 {
+            libc::AF_INET => (
+                tb.local4()?.map(|a| IpAddr::V4(*a)),
+                tb.address4()?.map(|a| IpAddr::V4(*a)),
+            ),
+            libc::AF_INET6 => (
+                tb.local6()?.map(|a| IpAddr::V6(*a)),
+                tb.address6()?.map(|a| IpAddr::V6(*a)),
+            ),
+            _ => return Err(Errno(libc::EAFNOSUPPORT)),
+        };
+        let (local, peer) = match (local, address) {
+            (Some(local), Some(address)) if local != address => (local, Some(address)),
+            (Some(local), _) => (local, None),
+            (None, Some(address)) => (address, None),
+            (None, None) => return Err(Errno(libc::EINVAL)),
+        };
+        let lifetime = |v: u32| {
+            if v == INFINITY_LIFE_TIME {
+                None
+            } else {
+                Some(Duration::from_secs(v as u64))
+            }
+        };
+        let cacheinfo = tb.cacheinfo()?;
+        Ok(Self {
+            index: ifa.ifa_index,
+            local,
+            peer,
+            prefixlen: ifa.ifa_prefixlen,
+            broadcast: tb.broadcast()?.copied(),
+            label: tb.label()?.map(|s| s.trim_end_matches('\0').to_string()),
+            scope: ifa.ifa_scope,
+            // IFA_FLAGS overrides ifa_flags if present
+            flags: IfaFlags(tb.flags()?.copied().unwrap_or(ifa.ifa_flags as u32)),
+            metric: tb.rt_priority()?.copied(),
+            valid_lifetime: cacheinfo.and_then(|ci| lifetime(ci.ifa_valid)),
+            preferred_lifetime: cacheinfo.and_then(|ci| lifetime(ci.ifa_prefered)),
+            cstamp: cacheinfo.map(|ci| ci.cstamp).unwrap_or(0),
+            tstamp: cacheinfo.map(|ci| ci.tstamp).unwrap_or(0),
+        })
+    }
+
+    fn put_header<'a>(
+        &self,
+        nlv: &'a mut MsgVec,
+        mtype: u16,
+        flags: u16,
+        seq: u32,
+    ) -> Result<&'a mut MsgVec> {
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = mtype;
+        nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+        nlh.nlmsg_seq = seq;
+        let ifa = nlv.put_extra_header::<Ifaddrmsg>()?;
+        ifa.ifa_family = self.family();
+        ifa.ifa_prefixlen = self.prefixlen;
+        ifa.ifa_flags = self.flags.0 as u8;
+        ifa.ifa_scope = self.scope;
+        ifa.ifa_index = self.index;
+
+        match self.local {
+            IpAddr::V4(addr) => IfAddr::put_local4(nlv, &addr)?,
+            IpAddr::V6(addr) => IfAddr::put_local6(nlv, &addr)?,
+        };
+        match (self.local, self.peer.unwrap_or(self.local)) {
+            (IpAddr::V4(_), IpAddr::V4(addr)) => IfAddr::put_address4(nlv, &addr)?,
+            (IpAddr::V6(_), IpAddr::V6(addr)) => IfAddr::put_address6(nlv, &addr)?,
+            _ => return Err(Errno(libc::EINVAL)),
+        };
+        Ok(nlv)
+    }
+
+    fn put_new<'a>(&self, nlv: &'a mut MsgVec, flags: u16, seq: u32) -> Result<&'a mut MsgVec> {
+        let max = match self.local {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if self.prefixlen > max {
+            return Err(Errno(libc::EINVAL));
+        }
+        self.put_header(nlv, rtnetlink::RTM_NEWADDR, flags, seq)?;
+        if let Some(addr) = self.broadcast {
+            if self.family() != libc::AF_INET as u8 {
+                return Err(Errno(libc::EINVAL));
+            }
+            IfAddr::put_broadcast(nlv, &addr)?;
+        }
+        if let Some(label) = &self.label {
+            if label.len() >= IFNAMSIZ {
+                return Err(Errno(libc::EINVAL));
+            }
+            IfAddr::put_label(nlv, label)?;
+        }
+        IfAddr::put_flags(nlv, &self.flags.0)?;
+        if let Some(metric) = self.metric {
+            IfAddr::put_rt_priority(nlv, &metric)?;
+        }
+        if self.valid_lifetime.is_some() || self.preferred_lifetime.is_some() {
+            let secs = |d: Option<Duration>| match d {
+                Some(d) if d.as_secs() < INFINITY_LIFE_TIME as u64 => d.as_secs() as u32,
+                _ => INFINITY_LIFE_TIME,
+            };
+            let ci = IfaCacheinfo {
+                // as valid if not specified, like iproute2
+                ifa_prefered: secs(self.preferred_lifetime.or(self.valid_lifetime)),
+                ifa_valid: secs(self.valid_lifetime),
+                cstamp: 0,
+                tstamp: 0,
+            };
+            // the kernel rejects zero valid or preferred longer than valid
+            if ci.ifa_valid == 0 || ci.ifa_prefered > ci.ifa_valid {
+                return Err(Errno(libc::EINVAL));
+            }
+            IfAddr::put_cacheinfo(nlv, &ci)?;
+        }
+        Ok(nlv)
+    }
+
+    // RTM_NEWADDR with NLM_F_CREATE | NLM_F_EXCL, EEXIST if exists.
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_new(
+            nlv,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+        )
+    }
+
+    // RTM_NEWADDR with NLM_F_CREATE | NLM_F_REPLACE, updates the flags,
+    // metric and lifetimes of the existing address.
+    pub fn put_replace<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_new(
+            nlv,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_REPLACE) as u16,
+            seq,
+        )
+    }
+
+    // RTM_DELADDR, identified by index, local, peer and prefixlen.
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_header(nlv, rtnetlink::RTM_DELADDR, libc::NLM_F_ACK as u16, seq)
+    }
+}
+
+// A joined IPv6 multicast group, or an anycast address of a link, in the
+// replies to RTM_GETMULTICAST or RTM_GETANYCAST dump. IPv6 only like
+// /proc/net/igmp6 and /proc/net/anycast6.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CastAddress {
+    pub index: u32,
+    pub addr: Ipv6Addr,
+    pub scope: u8,   // RT_SCOPE_SITE for site local multicast, or UNIVERSE
+    pub cstamp: u32, // IFA_CACHEINFO, created, hundredths of seconds since boot
+    pub tstamp: u32, // updated, e.g. on the report
+}
+
+impl CastAddress {
+    // Either of IFA_MULTICAST or IFA_ANYCAST is required.
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let ifa = nlh.payload::<Ifaddrmsg>()?;
+        if ifa.ifa_family != libc::AF_INET6 as u8 {
+            return Err(Errno(libc::EAFNOSUPPORT));
+        }
+        let tb = IfAddrTbl::from_nlmsg(mem::size_of::<Ifaddrmsg>(), nlh)?;
+        let addr = match (tb.multicast()?, tb.anycast()?) {
+            (Some(addr), _) | (None, Some(addr)) => *addr,
+            (None, None) => return Err(Errno(libc::EINVAL)),
+        };
+        let cacheinfo = tb.cacheinfo()?;
+        Ok(Self {
+            index: ifa.ifa_index,
+            addr,
+            scope: ifa.ifa_scope,
+            cstamp: cacheinfo.map(|ci| ci.cstamp).unwrap_or(0),
+            tstamp: cacheinfo.map(|ci| ci.tstamp).unwrap_or(0),
+        })
+    }
+}
+
+pub fn add(nl: &mut Socket, addr: &Address) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    addr.put_add(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn replace(nl: &mut Socket, addr: &Address) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    addr.put_replace(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn delete(nl: &mut Socket, addr: &Address) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    addr.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_GETADDR dump, family is AF_INET, AF_INET6 or AF_UNSPEC for both.
+// target_nsid is the netns to dump instead of the socket's one, see
+// netns::nsid(). The kernel takes it only on the strict checking socket,
+// which is turned on then and kept.
+pub fn dump(nl: &mut Socket, family: u8, target_nsid: Option<i32>) -> Result<Vec<Address>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETADDR;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let ifa = nlv.put_extra_header::<Ifaddrmsg>()?;
+    ifa.ifa_family = family;
+    if let Some(nsid) = target_nsid {
+        IfAddr::put_target_netnsid(&mut nlv, &nsid)?;
+        netns::set_strict_check(nl, true)?;
+    }
+
+    let mut addrs = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        match Address::from_nlmsg(nlh) {
+            Ok(addr) => addrs.push(addr),
+            // e.g. AF_MPLS, AF_DECnet
+            Err(Errno(libc::EAFNOSUPPORT)) => {}
+            Err(errno) => return Err(errno),
+        }
+        Ok(())
+    })?;
+    Ok(addrs)
+}
+
+fn cast_dump(nl: &mut Socket, mtype: u16, index: Option<u32>) -> Result<Vec<CastAddress>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = mtype;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let ifa = nlv.put_extra_header::<Ifaddrmsg>()?;
+    ifa.ifa_family = libc::AF_INET6 as u8;
+    ifa.ifa_index = index.unwrap_or(0);
+
+    let mut addrs = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        let addr = CastAddress::from_nlmsg(nlh)?;
+        if index.is_none() || index == Some(addr.index) {
+            addrs.push(addr);
+        }
+        Ok(())
+    })?;
+    Ok(addrs)
+}
+
+// RTM_GETMULTICAST dump of the joined IPv6 multicast groups, of all links if
+// index is None. The kernel filters by the index only on the socket with
+// netns::set_strict_check(), it is also applied here.
+pub fn multicast_dump(nl: &mut Socket, index: Option<u32>) -> Result<Vec<CastAddress>> {
+    cast_dump(nl, rtnetlink::RTM_GETMULTICAST, index)
+}
+
+// RTM_GETANYCAST dump of the IPv6 anycast addresses, as multicast_dump().
+pub fn anycast_dump(nl: &mut Socket, index: Option<u32>) -> Result<Vec<CastAddress>> {
+    cast_dump(nl, rtnetlink::RTM_GETANYCAST, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(v: &[IfaFlag]) -> IfaFlags {
+        let mut flags = IfaFlags(0);
+        for &flag in v {
+            flags.insert(flag);
+        }
+        flags
+    }
+
+    #[test]
+    fn dad_state_from_flags() {
+        assert_eq!(DadState::from_flags(flags(&[])), DadState::Valid);
+        assert_eq!(
+            DadState::from_flags(flags(&[IfaFlag::Permanent])),
+            DadState::Valid
+        );
+        assert_eq!(
+            DadState::from_flags(flags(&[IfaFlag::Tentative])),
+            DadState::Tentative
+        );
+        // optimistic is only while tentative
+        assert_eq!(
+            DadState::from_flags(flags(&[IfaFlag::Tentative, IfaFlag::Optimistic])),
+            DadState::Optimistic
+        );
+        assert_eq!(
+            DadState::from_flags(flags(&[IfaFlag::Optimistic])),
+            DadState::Valid
+        );
+        assert_eq!(
+            DadState::from_flags(flags(&[IfaFlag::Tentative, IfaFlag::Dadfailed])),
+            DadState::Failed
+        );
+        assert_eq!(
+            DadState::from_flags(flags(&[IfaFlag::Deprecated])),
+            DadState::Deprecated
+        );
+        assert_eq!(
+            DadState::from_flags(flags(&[IfaFlag::Tentative, IfaFlag::Deprecated])),
+            DadState::Tentative
+        );
+
+        assert!(!DadState::Tentative.is_usable());
+        assert!(!DadState::Failed.is_usable());
+        assert!(DadState::Optimistic.is_usable());
+        assert!(DadState::Deprecated.is_usable());
+    }
+}
@@ -0,0 +1,290 @@
+use errno::Errno;
+use std::convert::TryFrom;
+
+// Values of IFLA_BOND_* and IFLA_BOND_SLAVE_* attributes. BOND_MODE_*,
+// BOND_STATE_*, BOND_LINK_*, BOND_XMIT_POLICY_* and LACP_STATE_* are from
+// if_bonding.h, others are not exported to uapi, from net/bonding.h and
+// net/bond_3ad.h.
+
+// IFLA_BOND_MODE
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondMode {
+    // BOND_MODE_
+    Roundrobin = 0,
+    Activebackup = 1,
+    Xor = 2,
+    Broadcast = 3,
+    Ieee8023ad = 4,
+    Tlb = 5,
+    Alb = 6, // TLB + RLB (receive load balancing)
+}
+pub const BOND_MODE_ROUNDROBIN: u8 = BondMode::Roundrobin as u8;
+pub const BOND_MODE_ACTIVEBACKUP: u8 = BondMode::Activebackup as u8;
+pub const BOND_MODE_XOR: u8 = BondMode::Xor as u8;
+pub const BOND_MODE_BROADCAST: u8 = BondMode::Broadcast as u8;
+pub const BOND_MODE_8023AD: u8 = BondMode::Ieee8023ad as u8;
+pub const BOND_MODE_TLB: u8 = BondMode::Tlb as u8;
+pub const BOND_MODE_ALB: u8 = BondMode::Alb as u8;
+
+impl TryFrom<u8> for BondMode {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_MODE_ROUNDROBIN => Ok(BondMode::Roundrobin),
+            BOND_MODE_ACTIVEBACKUP => Ok(BondMode::Activebackup),
+            BOND_MODE_XOR => Ok(BondMode::Xor),
+            BOND_MODE_BROADCAST => Ok(BondMode::Broadcast),
+            BOND_MODE_8023AD => Ok(BondMode::Ieee8023ad),
+            BOND_MODE_TLB => Ok(BondMode::Tlb),
+            BOND_MODE_ALB => Ok(BondMode::Alb),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_SLAVE_STATE
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondState {
+    // BOND_STATE_
+    Active = 0, // link is active
+    Backup = 1, // link is backup
+}
+pub const BOND_STATE_ACTIVE: u8 = BondState::Active as u8;
+pub const BOND_STATE_BACKUP: u8 = BondState::Backup as u8;
+
+impl TryFrom<u8> for BondState {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_STATE_ACTIVE => Ok(BondState::Active),
+            BOND_STATE_BACKUP => Ok(BondState::Backup),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_SLAVE_MII_STATUS
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondLink {
+    // BOND_LINK_
+    Up = 0,   // link is up and running
+    Fail = 1, // link has just gone down
+    Down = 2, // link has been down for too long time
+    Back = 3, // link is going back
+}
+pub const BOND_LINK_UP: u8 = BondLink::Up as u8;
+pub const BOND_LINK_FAIL: u8 = BondLink::Fail as u8;
+pub const BOND_LINK_DOWN: u8 = BondLink::Down as u8;
+pub const BOND_LINK_BACK: u8 = BondLink::Back as u8;
+
+impl TryFrom<u8> for BondLink {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_LINK_UP => Ok(BondLink::Up),
+            BOND_LINK_FAIL => Ok(BondLink::Fail),
+            BOND_LINK_DOWN => Ok(BondLink::Down),
+            BOND_LINK_BACK => Ok(BondLink::Back),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_XMIT_HASH_POLICY
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondXmitPolicy {
+    // BOND_XMIT_POLICY_
+    Layer2 = 0,     // layer 2 (MAC only), default
+    Layer34 = 1,    // layer 3+4 (IP ^ (TCP || UDP))
+    Layer23 = 2,    // layer 2+3 (IP ^ MAC)
+    Encap23 = 3,    // encapsulated layer 2+3
+    Encap34 = 4,    // encapsulated layer 3+4
+    VlanSrcmac = 5, // vlan + source MAC
+}
+pub const BOND_XMIT_POLICY_LAYER2: u8 = BondXmitPolicy::Layer2 as u8;
+pub const BOND_XMIT_POLICY_LAYER34: u8 = BondXmitPolicy::Layer34 as u8;
+pub const BOND_XMIT_POLICY_LAYER23: u8 = BondXmitPolicy::Layer23 as u8;
+pub const BOND_XMIT_POLICY_ENCAP23: u8 = BondXmitPolicy::Encap23 as u8;
+pub const BOND_XMIT_POLICY_ENCAP34: u8 = BondXmitPolicy::Encap34 as u8;
+pub const BOND_XMIT_POLICY_VLAN_SRCMAC: u8 = BondXmitPolicy::VlanSrcmac as u8;
+
+impl TryFrom<u8> for BondXmitPolicy {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_XMIT_POLICY_LAYER2 => Ok(BondXmitPolicy::Layer2),
+            BOND_XMIT_POLICY_LAYER34 => Ok(BondXmitPolicy::Layer34),
+            BOND_XMIT_POLICY_LAYER23 => Ok(BondXmitPolicy::Layer23),
+            BOND_XMIT_POLICY_ENCAP23 => Ok(BondXmitPolicy::Encap23),
+            BOND_XMIT_POLICY_ENCAP34 => Ok(BondXmitPolicy::Encap34),
+            BOND_XMIT_POLICY_VLAN_SRCMAC => Ok(BondXmitPolicy::VlanSrcmac),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_AD_LACP_RATE
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondLacpRate {
+    // AD_LACP_
+    Slow = 0,
+    Fast = 1,
+}
+pub const AD_LACP_SLOW: u8 = BondLacpRate::Slow as u8;
+pub const AD_LACP_FAST: u8 = BondLacpRate::Fast as u8;
+
+impl TryFrom<u8> for BondLacpRate {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            AD_LACP_SLOW => Ok(BondLacpRate::Slow),
+            AD_LACP_FAST => Ok(BondLacpRate::Fast),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_AD_SELECT
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondAdSelect {
+    // BOND_AD_
+    Stable = 0,
+    Bandwidth = 1,
+    Count = 2,
+}
+pub const BOND_AD_STABLE: u8 = BondAdSelect::Stable as u8;
+pub const BOND_AD_BANDWIDTH: u8 = BondAdSelect::Bandwidth as u8;
+pub const BOND_AD_COUNT: u8 = BondAdSelect::Count as u8;
+
+impl TryFrom<u8> for BondAdSelect {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_AD_STABLE => Ok(BondAdSelect::Stable),
+            BOND_AD_BANDWIDTH => Ok(BondAdSelect::Bandwidth),
+            BOND_AD_COUNT => Ok(BondAdSelect::Count),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_FAIL_OVER_MAC
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondFom {
+    // BOND_FOM_
+    None = 0,
+    Active = 1,
+    Follow = 2,
+}
+pub const BOND_FOM_NONE: u8 = BondFom::None as u8;
+pub const BOND_FOM_ACTIVE: u8 = BondFom::Active as u8;
+pub const BOND_FOM_FOLLOW: u8 = BondFom::Follow as u8;
+
+impl TryFrom<u8> for BondFom {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_FOM_NONE => Ok(BondFom::None),
+            BOND_FOM_ACTIVE => Ok(BondFom::Active),
+            BOND_FOM_FOLLOW => Ok(BondFom::Follow),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_PRIMARY_RESELECT
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondPriReselect {
+    // BOND_PRI_RESELECT_
+    Always = 0,
+    Better = 1,
+    Failure = 2,
+}
+pub const BOND_PRI_RESELECT_ALWAYS: u8 = BondPriReselect::Always as u8;
+pub const BOND_PRI_RESELECT_BETTER: u8 = BondPriReselect::Better as u8;
+pub const BOND_PRI_RESELECT_FAILURE: u8 = BondPriReselect::Failure as u8;
+
+impl TryFrom<u8> for BondPriReselect {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_PRI_RESELECT_ALWAYS => Ok(BondPriReselect::Always),
+            BOND_PRI_RESELECT_BETTER => Ok(BondPriReselect::Better),
+            BOND_PRI_RESELECT_FAILURE => Ok(BondPriReselect::Failure),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_ARP_VALIDATE
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondArpValidate {
+    // BOND_ARP_VALIDATE_
+    None = 0,
+    Active = 1,
+    Backup = 2,
+    All = 3,
+    Filter = 4,
+    FilterActive = 5,
+    FilterBackup = 6,
+}
+pub const BOND_ARP_VALIDATE_NONE: u32 = BondArpValidate::None as u32;
+pub const BOND_ARP_VALIDATE_ACTIVE: u32 = BondArpValidate::Active as u32;
+pub const BOND_ARP_VALIDATE_BACKUP: u32 = BondArpValidate::Backup as u32;
+pub const BOND_ARP_VALIDATE_ALL: u32 = BondArpValidate::All as u32;
+pub const BOND_ARP_VALIDATE_FILTER: u32 = BondArpValidate::Filter as u32;
+pub const BOND_ARP_VALIDATE_FILTER_ACTIVE: u32 = BondArpValidate::FilterActive as u32;
+pub const BOND_ARP_VALIDATE_FILTER_BACKUP: u32 = BondArpValidate::FilterBackup as u32;
+
+impl TryFrom<u32> for BondArpValidate {
+    type Error = Errno;
+
+    fn try_from(v: u32) -> std::result::Result<Self, Errno> {
+        match v {
+            BOND_ARP_VALIDATE_NONE => Ok(BondArpValidate::None),
+            BOND_ARP_VALIDATE_ACTIVE => Ok(BondArpValidate::Active),
+            BOND_ARP_VALIDATE_BACKUP => Ok(BondArpValidate::Backup),
+            BOND_ARP_VALIDATE_ALL => Ok(BondArpValidate::All),
+            BOND_ARP_VALIDATE_FILTER => Ok(BondArpValidate::Filter),
+            BOND_ARP_VALIDATE_FILTER_ACTIVE => Ok(BondArpValidate::FilterActive),
+            BOND_ARP_VALIDATE_FILTER_BACKUP => Ok(BondArpValidate::FilterBackup),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// IFLA_BOND_ARP_ALL_TARGETS
+pub const BOND_ARP_TARGETS_ANY: u32 = 0;
+pub const BOND_ARP_TARGETS_ALL: u32 = 1;
+
+// maximum number of IFLA_BOND_ARP_IP_TARGET and IFLA_BOND_NS_IP6_TARGET
+pub const BOND_MAX_ARP_TARGETS: usize = 16;
+pub const BOND_MAX_NS_TARGETS: usize = BOND_MAX_ARP_TARGETS;
+
+// LACP port state bits of IFLA_BOND_SLAVE_AD_ACTOR_OPER_PORT_STATE and
+// IFLA_BOND_SLAVE_AD_PARTNER_OPER_PORT_STATE
+pub const LACP_STATE_LACP_ACTIVITY: u8 = 0x1;
+pub const LACP_STATE_LACP_TIMEOUT: u8 = 0x2;
+pub const LACP_STATE_AGGREGATION: u8 = 0x4;
+pub const LACP_STATE_SYNCHRONIZATION: u8 = 0x8;
+pub const LACP_STATE_COLLECTING: u8 = 0x10;
+pub const LACP_STATE_DISTRIBUTING: u8 = 0x20;
+pub const LACP_STATE_DEFAULTED: u8 = 0x40;
+pub const LACP_STATE_EXPIRED: u8 = 0x80;
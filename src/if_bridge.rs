@@ -0,0 +1,1229 @@
+use errno::Errno;
+use std::{
+    mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use mnl::{Attr, AttrTbl, CbStatus, MsgVec, Msghdr, NestAttr, Result, Socket};
+use rtnetlink;
+use util::{run, seq, to_errno};
+
+// Bridge Flags
+pub const BRIDGE_FLAGS_MASTER: u16 = 1; // Bridge command to/from master
+pub const BRIDGE_FLAGS_SELF: u16 = 2; // Bridge command to/from lowerdev
+
+pub const BRIDGE_MODE_VEB: u16 = 0; // Default loopback mode
+pub const BRIDGE_MODE_VEPA: u16 = 1; // 802.1Qbg defined VEPA mode
+pub const BRIDGE_MODE_UNDEF: u16 = 0xFFFF; // mode undefined
+
+// Bridge management nested attributes
+// [IFLA_AF_SPEC] = {
+//     [IFLA_BRIDGE_FLAGS]
+//     [IFLA_BRIDGE_MODE]
+//     [IFLA_BRIDGE_VLAN_INFO]
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "IflaBridgeTbl"]
+pub enum IflaBridge {
+    // IFLA_BRIDGE_
+    Unspec = 0,
+
+    #[nla_type(u16, flags)]
+    Flags, // BRIDGE_FLAGS_
+
+    #[nla_type(u16, mode)]
+    Mode, // BRIDGE_MODE_
+
+    // may appear multiple times, see vlan_infos()
+    #[nla_type(BridgeVlanInfo, vlan_info)]
+    VlanInfo,
+
+    // nested IFLA_BRIDGE_VLAN_TUNNEL_, may appear multiple times
+    VlanTunnelInfo,
+
+    Mrp,
+    Cfm,
+    Mst,
+    _MAX,
+}
+
+pub const BRIDGE_VLAN_INFO_MASTER: u16 = 1 << 0; // Operate on Bridge device as well
+pub const BRIDGE_VLAN_INFO_PVID: u16 = 1 << 1; // VLAN is PVID, ingress untagged
+pub const BRIDGE_VLAN_INFO_UNTAGGED: u16 = 1 << 2; // VLAN egresses untagged
+pub const BRIDGE_VLAN_INFO_RANGE_BEGIN: u16 = 1 << 3; // VLAN is start of vlan range
+pub const BRIDGE_VLAN_INFO_RANGE_END: u16 = 1 << 4; // VLAN is end of vlan range
+pub const BRIDGE_VLAN_INFO_BRENTRY: u16 = 1 << 5; // Global bridge VLAN entry
+pub const BRIDGE_VLAN_INFO_ONLY_OPTS: u16 = 1 << 6; // Skip create/delete/flags
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeVlanInfo {
+    pub flags: u16,
+    pub vid: u16,
+}
+
+impl IflaBridge {
+    // Put IFLA_BRIDGE_VLAN_INFO for each vid. Consecutive vids are
+    // compressed into a pair of BRIDGE_VLAN_INFO_RANGE_BEGIN and _RANGE_END,
+    // except with BRIDGE_VLAN_INFO_PVID which can not be a range.
+    pub fn put_vlan_infos<'a>(
+        nlv: &'a mut MsgVec,
+        flags: u16,
+        vids: &[u16],
+    ) -> Result<&'a mut MsgVec> {
+        let mut vids = vids.to_vec();
+        vids.sort_unstable();
+        vids.dedup();
+
+        let mut i = 0;
+        while i < vids.len() {
+            let mut j = i;
+            while j + 1 < vids.len() && vids[j + 1] == vids[j] + 1 {
+                j += 1;
+            }
+            if i == j || flags & BRIDGE_VLAN_INFO_PVID != 0 {
+                for &vid in &vids[i..=j] {
+                    IflaBridge::put_vlan_info(nlv, &BridgeVlanInfo { flags, vid })?;
+                }
+            } else {
+                let begin = BridgeVlanInfo {
+                    flags: flags | BRIDGE_VLAN_INFO_RANGE_BEGIN,
+                    vid: vids[i],
+                };
+                let end = BridgeVlanInfo {
+                    flags: flags | BRIDGE_VLAN_INFO_RANGE_END,
+                    vid: vids[j],
+                };
+                IflaBridge::put_vlan_info(nlv, &begin)?;
+                IflaBridge::put_vlan_info(nlv, &end)?;
+            }
+            i = j + 1;
+        }
+        Ok(nlv)
+    }
+}
+
+// IFLA_AF_SPEC of AF_BRIDGE contains multiple IFLA_BRIDGE_VLAN_INFO, which
+// are compressed into ranges by RTEXT_FILTER_BRVLAN_COMPRESSED. Returns them
+// with ranges expanded to each vid, without RANGE_BEGIN and _END flags.
+pub fn vlan_infos<'a>(af_spec: &'a Attr<'a>) -> Result<Vec<BridgeVlanInfo>> {
+    let mut v = Vec::new();
+    let mut begin: Option<&BridgeVlanInfo> = None;
+    let mut attrs = NestAttr::new(af_spec);
+    while let Some(attr) = attrs.next() {
+        if attr.atype() != IflaBridge::VlanInfo as u16 {
+            continue;
+        }
+        let info = attr.value_ref::<BridgeVlanInfo>()?;
+        if info.flags & BRIDGE_VLAN_INFO_RANGE_BEGIN != 0 {
+            begin = Some(info);
+            continue;
+        }
+        let flags = info.flags & !BRIDGE_VLAN_INFO_RANGE_END;
+        let start = match begin.take() {
+            Some(b) if info.flags & BRIDGE_VLAN_INFO_RANGE_END != 0 => b.vid,
+            Some(_) => return Err(Errno(libc::EINVAL)),
+            None => info.vid,
+        };
+        for vid in start..=info.vid {
+            v.push(BridgeVlanInfo { flags, vid });
+        }
+    }
+    Ok(v)
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BridgeVlanTunnelTbl"]
+pub enum BridgeVlanTunnel {
+    // IFLA_BRIDGE_VLAN_TUNNEL_
+    Unspec = 0,
+
+    #[nla_type(u32, id)]
+    Id,
+
+    #[nla_type(u16, vid)]
+    Vid,
+
+    #[nla_type(u16, flags)]
+    Flags, // BRIDGE_VLAN_INFO_RANGE_
+
+    _MAX,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeVlanXstats {
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub vid: u16,
+    pub flags: u16,
+    pub pad2: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeStpXstats {
+    pub transition_blk: u64,
+    pub transition_fwd: u64,
+    pub rx_bpdu: u64,
+    pub tx_bpdu: u64,
+    pub rx_tcn: u64,
+    pub tx_tcn: u64,
+}
+
+// bridge vlan RTM header
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BrVlanMsg {
+    pub family: u8,
+    pub reserved1: u8,
+    pub reserved2: u16,
+    pub ifindex: u32,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BridgeVlandbDumpTbl"]
+pub enum BridgeVlandbDump {
+    // BRIDGE_VLANDB_DUMP_
+    Unspec = 0,
+
+    #[nla_type(u32, flags)]
+    Flags, // BRIDGE_VLANDB_DUMPF_
+
+    _MAX,
+}
+
+// flags used in BRIDGE_VLANDB_DUMP_FLAGS attribute to affect dumps
+pub const BRIDGE_VLANDB_DUMPF_STATS: u32 = 1 << 0; // Include stats in the dump
+pub const BRIDGE_VLANDB_DUMPF_GLOBAL: u32 = 1 << 1; // Dump global vlan options only
+
+// Bridge vlan RTM attributes
+// [BRIDGE_VLANDB_ENTRY] = {
+//     [BRIDGE_VLANDB_ENTRY_INFO]
+//     ...
+// }
+// [BRIDGE_VLANDB_GLOBAL_OPTIONS] = {
+//     [BRIDGE_VLANDB_GOPTS_ID]
+//     ...
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum BridgeVlandb {
+    // BRIDGE_VLANDB_
+    Unspec = 0,
+
+    // may appear multiple times, see vlandb_entries()
+    Entry,
+
+    // may appear multiple times, see vlandb_global_options()
+    GlobalOptions,
+
+    _MAX,
+}
+
+impl BridgeVlandb {
+    // put BridgeVlandbEntry attributes, then nest_end()
+    pub fn entry_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(BridgeVlandb::Entry)
+    }
+
+    // put BridgeVlandbGopts attributes, then nest_end()
+    pub fn global_options_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(BridgeVlandb::GlobalOptions)
+    }
+}
+
+fn vlandb_nests<'a, T: AttrTbl<'a>>(nlh: &'a Msghdr, atype: BridgeVlandb) -> Result<Vec<T>> {
+    let mut v = Vec::new();
+    nlh.parse(mem::size_of::<BrVlanMsg>(), |attr| {
+        if attr.atype() == atype as u16 {
+            v.push(T::from_nest(attr)?);
+        }
+        Ok(CbStatus::Ok)
+    })
+    .map_err(to_errno)?;
+    Ok(v)
+}
+
+// BRIDGE_VLANDB_ENTRY of RTM_NEWVLAN, one for each vlan or range
+pub fn vlandb_entries<'a>(nlh: &'a Msghdr) -> Result<Vec<BridgeVlandbEntryTbl<'a>>> {
+    vlandb_nests(nlh, BridgeVlandb::Entry)
+}
+
+// BRIDGE_VLANDB_GLOBAL_OPTIONS of RTM_NEWVLAN, dumped by
+// BRIDGE_VLANDB_DUMPF_GLOBAL
+pub fn vlandb_global_options<'a>(nlh: &'a Msghdr) -> Result<Vec<BridgeVlandbGoptsTbl<'a>>> {
+    vlandb_nests(nlh, BridgeVlandb::GlobalOptions)
+}
+
+// BRIDGE_VLANDB_ENTRY_STATS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BridgeVlanStats {
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+}
+
+// A vlan, or a range of vlans, in BRIDGE_VLANDB_ENTRY of RTM_NEWVLAN and
+// RTM_DELVLAN. index is the port, or the bridge for its own vlans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BridgeVlan {
+    pub index: u32,
+    pub vid: u16,
+    pub range: Option<u16>, // the last vid of the range starts from vid
+    pub flags: u16,         // BRIDGE_VLAN_INFO_PVID, _UNTAGGED
+    pub state: Option<u8>,  // BR_STATE_, the per vlan STP state
+    pub stats: Option<BridgeVlanStats>, // dumped with BRIDGE_VLANDB_DUMPF_STATS
+}
+
+impl BridgeVlan {
+    pub fn new(index: u32, vid: u16) -> Self {
+        Self {
+            index,
+            vid,
+            range: None,
+            flags: 0,
+            state: None,
+            stats: None,
+        }
+    }
+
+    // A RTM_NEWVLAN message has BRIDGE_VLANDB_ENTRY for each vlan or range
+    // of the port.
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Vec<Self>> {
+        let bvm = nlh.payload::<BrVlanMsg>()?;
+        let mut v = Vec::new();
+        for entry in vlandb_entries(nlh)? {
+            let info = entry.info()?.ok_or(Errno(libc::EINVAL))?;
+            let stats = match entry.stats()? {
+                Some(stats) => Some(BridgeVlanStats {
+                    rx_bytes: stats.rx_bytes()?.copied().unwrap_or(0),
+                    rx_packets: stats.rx_packets()?.copied().unwrap_or(0),
+                    tx_bytes: stats.tx_bytes()?.copied().unwrap_or(0),
+                    tx_packets: stats.tx_packets()?.copied().unwrap_or(0),
+                }),
+                None => None,
+            };
+            v.push(Self {
+                index: bvm.ifindex,
+                vid: info.vid,
+                range: entry.range()?.copied(),
+                flags: info.flags,
+                state: entry.state()?.copied(),
+                stats,
+            });
+        }
+        Ok(v)
+    }
+
+    fn put_request<'a>(&self, nlv: &'a mut MsgVec, mtype: u16, seq: u32) -> Result<&'a mut MsgVec> {
+        // 0 and 4095 are reserved
+        let valid = |vid: u16| vid > 0 && vid < 4095;
+        let range_ok = match self.range {
+            Some(last) => valid(last) && last > self.vid,
+            None => true,
+        };
+        if !valid(self.vid) || !range_ok {
+            return Err(Errno(libc::EINVAL));
+        }
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = mtype;
+        nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+        nlh.nlmsg_seq = seq;
+        let bvm = nlv.put_extra_header::<BrVlanMsg>()?;
+        bvm.family = libc::AF_BRIDGE as u8;
+        bvm.ifindex = self.index;
+        BridgeVlandb::entry_start(nlv)?;
+        // the range is specified by BRIDGE_VLANDB_ENTRY_RANGE, not by flags
+        let flags = self.flags & !(BRIDGE_VLAN_INFO_RANGE_BEGIN | BRIDGE_VLAN_INFO_RANGE_END);
+        BridgeVlandbEntry::put_info(
+            nlv,
+            &BridgeVlanInfo {
+                flags,
+                vid: self.vid,
+            },
+        )?;
+        if let Some(last) = self.range {
+            BridgeVlandbEntry::put_range(nlv, &last)?;
+        }
+        if mtype == rtnetlink::RTM_NEWVLAN {
+            if let Some(state) = self.state {
+                BridgeVlandbEntry::put_state(nlv, &state)?;
+            }
+        }
+        nlv.nest_end()?;
+        Ok(nlv)
+    }
+
+    // RTM_NEWVLAN, adds the vlans or updates their flags and state.
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_NEWVLAN, seq)
+    }
+
+    // RTM_DELVLAN
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_DELVLAN, seq)
+    }
+}
+
+pub fn vlan_add(nl: &mut Socket, vlan: &BridgeVlan) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    vlan.put_add(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn vlan_delete(nl: &mut Socket, vlan: &BridgeVlan) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    vlan.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_GETVLAN dump of the bridges and the ports, or the index only. The
+// ranges are compressed by the kernel, unless stats which differs by vlan.
+pub fn vlan_dump(nl: &mut Socket, index: Option<u32>, stats: bool) -> Result<Vec<BridgeVlan>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETVLAN;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let bvm = nlv.put_extra_header::<BrVlanMsg>()?;
+    bvm.family = libc::AF_BRIDGE as u8;
+    bvm.ifindex = index.unwrap_or(0);
+    if stats {
+        BridgeVlandbDump::put_flags(&mut nlv, &BRIDGE_VLANDB_DUMPF_STATS)?;
+    }
+
+    let mut vlans = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        vlans.append(&mut BridgeVlan::from_nlmsg(nlh)?);
+        Ok(())
+    })?;
+    Ok(vlans)
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BridgeVlandbEntryTbl"]
+pub enum BridgeVlandbEntry {
+    // BRIDGE_VLANDB_ENTRY_
+    Unspec = 0,
+
+    #[nla_type(BridgeVlanInfo, info)]
+    Info,
+
+    #[nla_type(u16, range)]
+    Range, // last vid of the range starts from Info
+
+    #[nla_type(u8, state)]
+    State, // BR_STATE_
+
+    #[nla_nest(BridgeVlandbTinfoTbl, tunnel_info)]
+    TunnelInfo,
+
+    #[nla_nest(BridgeVlandbStatsTbl, stats)]
+    Stats,
+
+    #[nla_type(u8, mcast_router)]
+    McastRouter,
+
+    #[nla_type(u32, mcast_n_groups)]
+    McastNGroups,
+
+    #[nla_type(u32, mcast_max_groups)]
+    McastMaxGroups,
+
+    #[nla_type(u8, neigh_suppress)]
+    NeighSuppress,
+
+    _MAX,
+}
+
+// [BRIDGE_VLANDB_ENTRY] = {
+//     [BRIDGE_VLANDB_ENTRY_TUNNEL_INFO] = {
+//         [BRIDGE_VLANDB_TINFO_ID]
+//         ...
+//     }
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BridgeVlandbTinfoTbl"]
+pub enum BridgeVlandbTinfo {
+    // BRIDGE_VLANDB_TINFO_
+    Unspec = 0,
+
+    #[nla_type(u32, id)]
+    Id,
+
+    #[nla_type(u32, cmd)]
+    Cmd, // RTM_SETLINK or RTM_DELLINK
+
+    _MAX,
+}
+
+// [BRIDGE_VLANDB_ENTRY] = {
+//     [BRIDGE_VLANDB_ENTRY_STATS] = {
+//         [BRIDGE_VLANDB_STATS_RX_BYTES]
+//         ...
+//     }
+//     ...
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BridgeVlandbStatsTbl"]
+pub enum BridgeVlandbStats {
+    // BRIDGE_VLANDB_STATS_
+    Unspec = 0,
+
+    #[nla_type(u64, rx_bytes)]
+    RxBytes,
+
+    #[nla_type(u64, rx_packets)]
+    RxPackets,
+
+    #[nla_type(u64, tx_bytes)]
+    TxBytes,
+
+    #[nla_type(u64, tx_packets)]
+    TxPackets,
+
+    Pad,
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BridgeVlandbGoptsTbl"]
+pub enum BridgeVlandbGopts {
+    // BRIDGE_VLANDB_GOPTS_
+    Unspec = 0,
+
+    #[nla_type(u16, id)]
+    Id,
+
+    #[nla_type(u16, range)]
+    Range,
+
+    #[nla_type(u8, mcast_snooping)]
+    McastSnooping,
+
+    #[nla_type(u8, mcast_igmp_version)]
+    McastIgmpVersion,
+
+    #[nla_type(u8, mcast_mld_version)]
+    McastMldVersion,
+
+    #[nla_type(u32, mcast_last_member_cnt)]
+    McastLastMemberCnt,
+
+    #[nla_type(u32, mcast_startup_query_cnt)]
+    McastStartupQueryCnt,
+
+    #[nla_type(u64, mcast_last_member_intvl)]
+    McastLastMemberIntvl,
+
+    Pad,
+
+    #[nla_type(u64, mcast_membership_intvl)]
+    McastMembershipIntvl,
+
+    #[nla_type(u64, mcast_querier_intvl)]
+    McastQuerierIntvl,
+
+    #[nla_type(u64, mcast_query_intvl)]
+    McastQueryIntvl,
+
+    #[nla_type(u64, mcast_query_response_intvl)]
+    McastQueryResponseIntvl,
+
+    #[nla_type(u64, mcast_startup_query_intvl)]
+    McastStartupQueryIntvl,
+
+    #[nla_type(u8, mcast_querier)]
+    McastQuerier,
+
+    McastRouterPorts,
+    McastQuerierState,
+
+    #[nla_type(u16, msti)]
+    Msti,
+
+    _MAX,
+}
+
+// Bridge multicast database attributes
+// [MDBA_MDB] = {
+//     [MDBA_MDB_ENTRY] = {
+//         [MDBA_MDB_ENTRY_INFO] {
+//             struct br_mdb_entry
+//             [MDBA_MDB_EATTR attributes]
+//         }
+//     }
+// }
+// [MDBA_ROUTER] = {
+//    [MDBA_ROUTER_PORT] = {
+//        u32 ifindex
+//        [MDBA_ROUTER_PATTR attributes]
+//    }
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MdbaTbl"]
+pub enum Mdba {
+    // MDBA_
+    Unspec = 0,
+
+    // see MdbaTbl::mdb_entries()
+    Mdb,
+
+    // see MdbaTbl::router_ports()
+    Router,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum MdbaMdb {
+    // MDBA_MDB_
+    Unspec = 0,
+    Entry,
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum MdbaMdbEntry {
+    // MDBA_MDB_ENTRY_
+    Unspec = 0,
+    Info,
+    _MAX,
+}
+
+// per mdb entry additional attributes
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MdbaMdbEattrTbl"]
+pub enum MdbaMdbEattr {
+    // MDBA_MDB_EATTR_
+    Unspec = 0,
+
+    #[nla_type(u32, timer)]
+    Timer,
+
+    #[nla_nest([MdbaMdbSrcattrTbl], src_list)]
+    SrcList,
+
+    #[nla_type(u8, group_mode)]
+    GroupMode, // MCAST_EXCLUDE or MCAST_INCLUDE
+
+    #[nla_type(Ipv4Addr, source4)]
+    #[nla_type(Ipv6Addr, source6)]
+    Source,
+
+    #[nla_type(u8, rtprot)]
+    Rtprot,
+
+    #[nla_type(Ipv4Addr, dst4)]
+    #[nla_type(Ipv6Addr, dst6)]
+    Dst,
+
+    #[nla_type(u16, dst_port)]
+    DstPort,
+
+    #[nla_type(u32, vni)]
+    Vni,
+
+    #[nla_type(u32, ifindex)]
+    Ifindex,
+
+    #[nla_type(u32, src_vni)]
+    SrcVni,
+
+    _MAX,
+}
+
+// [MDBA_MDB_EATTR_SRC_LIST] = {
+//     [MDBA_MDB_SRCLIST_ENTRY] = {
+//         [MDBA_MDB_SRCATTR_ADDRESS]
+//         [MDBA_MDB_SRCATTR_TIMER]
+//     }
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum MdbaMdbSrclist {
+    // MDBA_MDB_SRCLIST_
+    Unspec = 0,
+    Entry,
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MdbaMdbSrcattrTbl"]
+pub enum MdbaMdbSrcattr {
+    // MDBA_MDB_SRCATTR_
+    Unspec = 0,
+
+    #[nla_type(Ipv4Addr, address4)]
+    #[nla_type(Ipv6Addr, address6)]
+    Address,
+
+    #[nla_type(u32, timer)]
+    Timer,
+
+    _MAX,
+}
+
+// multicast router types
+pub const MDB_RTR_TYPE_DISABLED: u8 = 0;
+pub const MDB_RTR_TYPE_TEMP_QUERY: u8 = 1;
+pub const MDB_RTR_TYPE_PERM: u8 = 2;
+pub const MDB_RTR_TYPE_TEMP: u8 = 3;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum MdbaRouter {
+    // MDBA_ROUTER_
+    Unspec = 0,
+    Port,
+    _MAX,
+}
+
+// router port attributes
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MdbaRouterPattrTbl"]
+pub enum MdbaRouterPattr {
+    // MDBA_ROUTER_PATTR_
+    Unspec = 0,
+
+    #[nla_type(u32, timer)]
+    Timer,
+
+    #[nla_type(u8, rtype)]
+    Type, // MDB_RTR_TYPE_
+
+    #[nla_type(u32, inet_timer)]
+    InetTimer,
+
+    #[nla_type(u32, inet6_timer)]
+    Inet6Timer,
+
+    #[nla_type(u16, vid)]
+    Vid,
+
+    _MAX,
+}
+
+impl<'a> MdbaTbl<'a> {
+    pub fn mdb_entries(&self) -> Result<Option<Vec<(&'a BrMdbEntry, MdbaMdbEattrTbl<'a>)>>> {
+        let mdb = match self[Mdba::Mdb] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let mut v = Vec::new();
+        let mut entries = NestAttr::new(mdb);
+        while let Some(entry) = entries.next() {
+            if entry.atype() != MdbaMdb::Entry as u16 {
+                continue;
+            }
+            let mut infos = NestAttr::new(entry);
+            while let Some(info) = infos.next() {
+                if info.atype() == MdbaMdbEntry::Info as u16 {
+                    v.push(rtnetlink::parse_nest_with_header(info)?);
+                }
+            }
+        }
+        Ok(Some(v))
+    }
+
+    // returns ifindex and its attributes
+    pub fn router_ports(&self) -> Result<Option<Vec<(&'a u32, MdbaRouterPattrTbl<'a>)>>> {
+        let router = match self[Mdba::Router] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let mut v = Vec::new();
+        let mut ports = NestAttr::new(router);
+        while let Some(port) = ports.next() {
+            if port.atype() == MdbaRouter::Port as u16 {
+                v.push(rtnetlink::parse_nest_with_header(port)?);
+            }
+        }
+        Ok(Some(v))
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BrPortMsg {
+    pub family: u8,
+    pub ifindex: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BrMdbEntry {
+    pub ifindex: u32,
+    pub state: u8, // MDB_TEMPORARY or MDB_PERMANENT
+    pub flags: u8, // MDB_FLAGS_
+    pub vid: u16,
+    pub addr: BrMdbEntryAddr,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BrMdbEntryAddr {
+    pub u: _BrMdbEntryAddrUnion,
+    pub proto: u16, // __be16, ETH_P_IP, ETH_P_IPV6 or 0 for L2
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union _BrMdbEntryAddrUnion {
+    pub ip4: u32, // __be32
+    pub ip6: [u8; 16],
+    pub mac_addr: [u8; 6],
+}
+
+pub const MDB_TEMPORARY: u8 = 0;
+pub const MDB_PERMANENT: u8 = 1;
+
+pub const MDB_FLAGS_OFFLOAD: u8 = 1 << 0;
+pub const MDB_FLAGS_FAST_LEAVE: u8 = 1 << 1;
+pub const MDB_FLAGS_STAR_EXCL: u8 = 1 << 2;
+pub const MDB_FLAGS_BLOCKED: u8 = 1 << 3;
+
+impl BrMdbEntry {
+    pub fn new(ifindex: u32, group: IpAddr) -> Self {
+        let mut entry = Self {
+            ifindex,
+            state: MDB_TEMPORARY,
+            flags: 0,
+            vid: 0,
+            addr: BrMdbEntryAddr {
+                u: _BrMdbEntryAddrUnion { ip6: [0; 16] },
+                proto: 0,
+            },
+        };
+        entry.set_group(group);
+        entry
+    }
+
+    pub fn set_group(&mut self, group: IpAddr) {
+        match group {
+            IpAddr::V4(addr) => {
+                self.addr.u = _BrMdbEntryAddrUnion { ip6: [0; 16] };
+                self.addr.u.ip4 = u32::from(addr).to_be();
+                self.addr.proto = (libc::ETH_P_IP as u16).to_be();
+            }
+            IpAddr::V6(addr) => {
+                self.addr.u.ip6 = addr.octets();
+                self.addr.proto = (libc::ETH_P_IPV6 as u16).to_be();
+            }
+        }
+    }
+
+    // returns None for L2 entry, see addr.u.mac_addr
+    pub fn group(&self) -> Option<IpAddr> {
+        match u16::from_be(self.addr.proto) as i32 {
+            libc::ETH_P_IP => Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(unsafe {
+                self.addr.u.ip4
+            })))),
+            libc::ETH_P_IPV6 => Some(IpAddr::V6(Ipv6Addr::from(unsafe { self.addr.u.ip6 }))),
+            _ => None,
+        }
+    }
+}
+
+// RTM_NEWMDB and RTM_DELMDB request
+// [MDBA_SET_ENTRY] = struct br_mdb_entry
+// [MDBA_SET_ENTRY_ATTRS] = {
+//    [MDBE_ATTR_xxx]
+//    ...
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MdbaSetEntryTbl"]
+pub enum MdbaSetEntry {
+    // MDBA_SET_ENTRY_
+    Unspec = 0,
+
+    #[nla_type(BrMdbEntry, entry)]
+    Entry,
+
+    #[nla_nest(MdbeAttrTbl, attrs)]
+    Attrs,
+
+    _MAX,
+}
+
+// [MDBA_SET_ENTRY_ATTRS] = {
+//    [MDBE_ATTR_xxx]
+//    ...
+// }
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MdbeAttrTbl"]
+pub enum MdbeAttr {
+    // MDBE_ATTR_
+    Unspec = 0,
+
+    #[nla_type(Ipv4Addr, source4)]
+    #[nla_type(Ipv6Addr, source6)]
+    Source,
+
+    #[nla_nest([MdbeSrcattrTbl], src_list)]
+    SrcList,
+
+    #[nla_type(u8, group_mode)]
+    GroupMode, // MCAST_EXCLUDE or MCAST_INCLUDE
+
+    #[nla_type(u8, rtprot)]
+    Rtprot,
+
+    #[nla_type(Ipv4Addr, dst4)]
+    #[nla_type(Ipv6Addr, dst6)]
+    Dst,
+
+    #[nla_type(u16, dst_port)]
+    DstPort,
+
+    #[nla_type(u32, vni)]
+    Vni,
+
+    #[nla_type(u32, ifindex)]
+    Ifindex,
+
+    #[nla_type(u32, src_vni)]
+    SrcVni,
+
+    #[nla_type(u8, state_mask)]
+    StateMask,
+
+    _MAX,
+}
+
+// per mdb entry source
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum MdbeSrcList {
+    // MDBE_SRC_LIST_
+    Unspec = 0,
+    Entry,
+    _MAX,
+}
+
+impl MdbeSrcList {
+    // put MdbeSrcattr attributes, then nest_end()
+    pub fn entry_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(MdbeSrcList::Entry)
+    }
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MdbeSrcattrTbl"]
+pub enum MdbeSrcattr {
+    // MDBE_SRCATTR_
+    Unspec = 0,
+
+    #[nla_type(Ipv4Addr, address4)]
+    #[nla_type(Ipv6Addr, address6)]
+    Address,
+
+    _MAX,
+}
+
+// An entry of the multicast database of the bridge, RTM_NEWMDB and
+// RTM_DELMDB. entry has the port, the group, vid and state.
+#[derive(Clone, Copy)]
+pub struct MdbEntry {
+    pub bridge: u32,
+    pub entry: BrMdbEntry,
+    pub source: Option<IpAddr>, // of the (S, G) entry
+    pub timer: Option<u32>,     // dump only, hundredths of seconds to expire
+}
+
+impl MdbEntry {
+    // A temporary entry of the port without vid.
+    pub fn new(bridge: u32, port: u32, group: IpAddr) -> Self {
+        Self {
+            bridge,
+            entry: BrMdbEntry::new(port, group),
+            source: None,
+            timer: None,
+        }
+    }
+
+    fn put_request<'a>(
+        &self,
+        nlv: &'a mut MsgVec,
+        mtype: u16,
+        flags: u16,
+        seq: u32,
+    ) -> Result<&'a mut MsgVec> {
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = mtype;
+        nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+        nlh.nlmsg_seq = seq;
+        let bpm = nlv.put_extra_header::<BrPortMsg>()?;
+        bpm.family = libc::AF_BRIDGE as u8;
+        bpm.ifindex = self.bridge;
+        MdbaSetEntry::put_entry(nlv, &self.entry)?;
+        if let Some(source) = self.source {
+            MdbaSetEntry::attrs_start(nlv)?;
+            match source {
+                IpAddr::V4(addr) => MdbeAttr::put_source4(nlv, &addr)?,
+                IpAddr::V6(addr) => MdbeAttr::put_source6(nlv, &addr)?,
+            };
+            nlv.nest_end()?;
+        }
+        Ok(nlv)
+    }
+
+    // RTM_NEWMDB with NLM_F_CREATE | NLM_F_EXCL, EEXIST if exists.
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(
+            nlv,
+            rtnetlink::RTM_NEWMDB,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+        )
+    }
+
+    // RTM_DELMDB
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_DELMDB, libc::NLM_F_ACK as u16, seq)
+    }
+}
+
+pub fn mdb_add(nl: &mut Socket, entry: &MdbEntry) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    entry.put_add(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn mdb_delete(nl: &mut Socket, entry: &MdbEntry) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    entry.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_GETMDB dump, filtered by the bridge here since the kernel does not.
+// The router ports, MDBA_ROUTER, are not returned.
+pub fn mdb_dump(nl: &mut Socket, bridge: Option<u32>) -> Result<Vec<MdbEntry>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETMDB;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let bpm = nlv.put_extra_header::<BrPortMsg>()?;
+    bpm.family = libc::AF_BRIDGE as u8;
+
+    let mut entries = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        let bpm = nlh.payload::<BrPortMsg>()?;
+        if bridge.is_some() && bridge != Some(bpm.ifindex) {
+            return Ok(());
+        }
+        let tb = MdbaTbl::from_nlmsg(mem::size_of::<BrPortMsg>(), nlh)?;
+        for (entry, eattrs) in tb.mdb_entries()?.unwrap_or_default() {
+            // IPv4 or IPv6 by the length
+            let source = match eattrs[MdbaMdbEattr::Source] {
+                Some(attr) if attr.payload_len() == 4 => {
+                    Some(IpAddr::V4(*eattrs.source4()?.unwrap()))
+                }
+                Some(attr) if attr.payload_len() == 16 => {
+                    Some(IpAddr::V6(*eattrs.source6()?.unwrap()))
+                }
+                Some(_) => return Err(Errno(libc::EINVAL)),
+                None => None,
+            };
+            entries.push(MdbEntry {
+                bridge: bpm.ifindex,
+                entry: *entry,
+                source,
+                timer: eattrs.timer()?.copied(),
+            });
+        }
+        Ok(())
+    })?;
+    Ok(entries)
+}
+
+// Embedded inside LINK_XSTATS_TYPE_BRIDGE
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BridgeXstatsTbl"]
+pub enum BridgeXstats {
+    // BRIDGE_XSTATS_
+    Unspec = 0,
+
+    // may appear multiple times, see vlan_xstats()
+    Vlan,
+
+    #[nla_type(BrMcastStats, mcast)]
+    Mcast,
+
+    Pad,
+
+    #[nla_type(BridgeStpXstats, stp)]
+    Stp,
+
+    _MAX,
+}
+
+// LINK_XSTATS_TYPE_BRIDGE contains BRIDGE_XSTATS_VLAN for each vlan
+pub fn vlan_xstats<'a>(nest: &'a Attr<'a>) -> Result<Vec<&'a BridgeVlanXstats>> {
+    let mut v = Vec::new();
+    let mut attrs = NestAttr::new(nest);
+    while let Some(attr) = attrs.next() {
+        if attr.atype() == BridgeXstats::Vlan as u16 {
+            v.push(attr.value_ref::<BridgeVlanXstats>()?);
+        }
+    }
+    Ok(v)
+}
+
+pub const BR_MCAST_DIR_RX: usize = 0;
+pub const BR_MCAST_DIR_TX: usize = 1;
+pub const BR_MCAST_DIR_SIZE: usize = 2;
+
+// IGMP/MLD statistics
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BrMcastStats {
+    pub igmp_v1queries: [u64; BR_MCAST_DIR_SIZE],
+    pub igmp_v2queries: [u64; BR_MCAST_DIR_SIZE],
+    pub igmp_v3queries: [u64; BR_MCAST_DIR_SIZE],
+    pub igmp_leaves: [u64; BR_MCAST_DIR_SIZE],
+    pub igmp_v1reports: [u64; BR_MCAST_DIR_SIZE],
+    pub igmp_v2reports: [u64; BR_MCAST_DIR_SIZE],
+    pub igmp_v3reports: [u64; BR_MCAST_DIR_SIZE],
+    pub igmp_parse_errors: u64,
+
+    pub mld_v1queries: [u64; BR_MCAST_DIR_SIZE],
+    pub mld_v2queries: [u64; BR_MCAST_DIR_SIZE],
+    pub mld_leaves: [u64; BR_MCAST_DIR_SIZE],
+    pub mld_v1reports: [u64; BR_MCAST_DIR_SIZE],
+    pub mld_v2reports: [u64; BR_MCAST_DIR_SIZE],
+    pub mld_parse_errors: u64,
+
+    pub mcast_bytes: [u64; BR_MCAST_DIR_SIZE],
+    pub mcast_packets: [u64; BR_MCAST_DIR_SIZE],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const U: u16 = BRIDGE_VLAN_INFO_UNTAGGED;
+    const BEGIN: u16 = BRIDGE_VLAN_INFO_RANGE_BEGIN;
+    const END: u16 = BRIDGE_VLAN_INFO_RANGE_END;
+
+    // IFLA_AF_SPEC like nest of put_vlan_infos()
+    fn af_spec(flags: u16, vids: &[u16]) -> MsgVec {
+        let mut nlv = MsgVec::new();
+        nlv.put_header();
+        nlv.nest_start(1u16).unwrap();
+        IflaBridge::put_vlan_infos(&mut nlv, flags, vids).unwrap();
+        nlv.nest_end().unwrap();
+        nlv
+    }
+
+    fn raw_infos(nlv: &MsgVec) -> Vec<(u16, u16)> {
+        let mut v = Vec::new();
+        nlv.msghdr()
+            .unwrap()
+            .parse(0, |nest| {
+                let mut attrs = NestAttr::new(nest);
+                while let Some(attr) = attrs.next() {
+                    assert_eq!(attr.atype(), IflaBridge::VlanInfo as u16);
+                    let info = attr.value_ref::<BridgeVlanInfo>()?;
+                    v.push((info.flags, info.vid));
+                }
+                Ok(CbStatus::Ok)
+            })
+            .unwrap();
+        v
+    }
+
+    fn expanded(nlv: &MsgVec) -> Vec<(u16, u16)> {
+        let mut v = Vec::new();
+        nlv.msghdr()
+            .unwrap()
+            .parse(0, |nest| {
+                for info in vlan_infos(nest)? {
+                    v.push((info.flags, info.vid));
+                }
+                Ok(CbStatus::Ok)
+            })
+            .unwrap();
+        v
+    }
+
+    #[test]
+    fn put_vlan_infos_range() {
+        // unsorted and duplicated
+        let nlv = af_spec(U, &[8, 1, 3, 2, 5, 7, 2]);
+        assert_eq!(
+            raw_infos(&nlv),
+            [
+                (U | BEGIN, 1),
+                (U | END, 3),
+                (U, 5),
+                (U | BEGIN, 7),
+                (U | END, 8)
+            ]
+        );
+        assert_eq!(
+            expanded(&nlv),
+            [(U, 1), (U, 2), (U, 3), (U, 5), (U, 7), (U, 8)]
+        );
+    }
+
+    #[test]
+    fn put_vlan_infos_pvid() {
+        // PVID can not be a range
+        let flags = BRIDGE_VLAN_INFO_PVID | U;
+        let nlv = af_spec(flags, &[10, 11]);
+        assert_eq!(raw_infos(&nlv), [(flags, 10), (flags, 11)]);
+        assert_eq!(expanded(&nlv), [(flags, 10), (flags, 11)]);
+    }
+
+    #[test]
+    fn vlan_infos_invalid_range() {
+        // RANGE_BEGIN not followed by RANGE_END
+        let mut nlv = MsgVec::new();
+        nlv.put_header();
+        nlv.nest_start(1u16).unwrap();
+        for info in &[
+            BridgeVlanInfo {
+                flags: BEGIN,
+                vid: 1,
+            },
+            BridgeVlanInfo { flags: 0, vid: 5 },
+        ] {
+            IflaBridge::put_vlan_info(&mut nlv, info).unwrap();
+        }
+        nlv.nest_end().unwrap();
+        let ret = nlv.msghdr().unwrap().parse(0, |nest| {
+            vlan_infos(nest)?;
+            Ok(CbStatus::Ok)
+        });
+        assert_eq!(ret.map_err(to_errno).unwrap_err(), Errno(libc::EINVAL));
+    }
+}
@@ -4,8 +4,23 @@
 use errno::Errno;
 use libc::c_int;
 
-use mnl::{Attr, AttrTbl, MsgVec, Result};
-// use linux::ipv6;
+use can::netlink::CanTbl;
+use can::vxcan::VxcanInfoTbl;
+use if_bonding::{
+    BondAdSelect, BondArpValidate, BondFom, BondLacpRate, BondLink, BondMode, BondPriReselect,
+    BondState, BondXmitPolicy, BOND_MAX_ARP_TARGETS, BOND_MAX_NS_TARGETS,
+};
+use if_tunnel::{GreTbl, IptunTbl, VtiTbl};
+use ifh::ALTIFNAMSIZ;
+use ip::Ipv4Devconf;
+use ipv6::Devconf;
+use mnl::{Attr, AttrTbl, MsgVec, NestAttr, Result};
+use rtnetlink::{self, Ifinfomsg};
+use std::convert::TryFrom;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::{mem, ptr};
+use util::payload_bytes;
+use veth::VethInfoTbl;
 
 // This struct should be in sync with struct rtnl_link_stats64
 #[repr(C)]
@@ -146,49 +161,158 @@ pub enum Ifla {
     Txqlen,
     Map,
     Weight,
-    Operstate,
+
+    #[nla_type(u8, operstate)]
+    Operstate, // IF_OPER_
+
     Linkmode,
+
+    #[nla_nest(InfoTbl, linkinfo)]
     Linkinfo,
+
+    #[nla_type(u32, net_ns_pid)]
     NetNsPid,
+
     Ifalias,
+
+    #[nla_type(u32, num_vf)]
     NumVf,
+
+    // see IflaTbl::virtual_functions()
+    #[nla_nest([VfTbl], vfinfo_list)]
     VfinfoList,
+
     Stats64,
+
+    #[nla_nest([PortTbl], vf_ports)]
     VfPorts,
+
+    #[nla_nest(PortTbl, port_self)]
     PortSelf,
+
     AfSpec,
     Group,
+
+    #[nla_type(u32, net_ns_fd)]
     NetNsFd,
-    ExtMask,
+
+    #[nla_type(u32, ext_mask)]
+    ExtMask, // RTEXT_FILTER_
+
     Promiscuity,
     NumTxQueues,
     NumRxQueues,
+
+    #[nla_type(u8, carrier)]
     Carrier,
+
     PhysPortId,
     CarrierChanges,
     PhysSwitchId,
+
+    #[nla_type(i32, link_netnsid)]
     LinkNetnsid,
+
     PhysPortName,
     ProtoDown,
     GsoMaxSegs,
     GsoMaxSize,
     Pad,
+
+    // see Xdp::put_attach(), Xdp::put_detach() and XdpTbl::programs()
+    #[nla_nest(XdpTbl, xdp)]
     Xdp,
+
     Event,
-    NewNetnsid,
-    IfNetnsid,
+
+    #[nla_type(i32, new_netnsid)]
+    NewNetnsid, // RTM_DELLINK on moving to another netns
+
+    #[nla_type(i32, target_netnsid)]
+    IfNetnsid, // IFLA_TARGET_NETNSID, new alias
+
     CarrierUpCount,
     CarrierDownCount,
     NewIfindex,
     MinMtu,
     MaxMtu,
+
+    // nested IFLA_ALT_IFNAME, see IflaTbl::alt_ifnames()
+    // and Ifla::put_alt_ifnames()
     PropList,
+
+    // also used to specify the link in RTM_GETLINK, RTM_NEWLINK...
+    #[nla_type(cstr, alt_ifname)]
     AltIfname,
+
     PermAddress,
     ProtoDownReason,
     _MAX,
 }
 
+impl Ifla {
+    // For RTM_NEWLINKPROP to add or RTM_DELLINKPROP to remove names.
+    pub fn put_alt_ifnames<'a>(nlv: &'a mut MsgVec, names: &[&str]) -> Result<&'a mut MsgVec> {
+        if names.iter().any(|name| name.len() >= ALTIFNAMSIZ) {
+            return Err(Errno(libc::EINVAL));
+        }
+        nlv.nest_start(Ifla::PropList)?;
+        for name in names {
+            Ifla::put_alt_ifname(nlv, name)?;
+        }
+        nlv.nest_end()
+    }
+
+    // per address family attributes for RTM_SETLINK are nested in this, by
+    // Inet::af_start() or Inet6::af_start().
+    pub fn af_spec_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Ifla::AfSpec)
+    }
+}
+
+impl<'a> IflaTbl<'a> {
+    // IFLA_AF_SPEC nests attributes typed address family on AF_UNSPEC
+    // message, but IFLA_BRIDGE_ directly on AF_BRIDGE one.
+    fn af_spec(&self, family: c_int) -> Option<&'a Attr<'a>> {
+        let mut attrs = NestAttr::new(self[Ifla::AfSpec]?);
+        while let Some(attr) = attrs.next() {
+            if attr.atype() == family as u16 {
+                return Some(attr);
+            }
+        }
+        None
+    }
+
+    pub fn af_spec_inet(&self) -> Result<Option<InetTbl<'a>>> {
+        match self.af_spec(libc::AF_INET) {
+            Some(nest) => Ok(Some(InetTbl::from_nest(nest)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn af_spec_inet6(&self) -> Result<Option<IflaInet6Tbl<'a>>> {
+        match self.af_spec(libc::AF_INET6) {
+            Some(nest) => Ok(Some(IflaInet6Tbl::from_nest(nest)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn alt_ifnames(&self) -> Result<Option<Vec<&'a str>>> {
+        let nest = match self[Ifla::PropList] {
+            Some(nest) => nest,
+            None => return Ok(None),
+        };
+        let mut v = Vec::new();
+        let mut attrs = NestAttr::new(nest);
+        while let Some(attr) = attrs.next() {
+            if attr.atype() == Ifla::AltIfname as u16 {
+                v.push(attr.cstr()?);
+            }
+        }
+        Ok(Some(v))
+    }
+}
+
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
 pub enum ProtoDownReason {
@@ -254,9 +378,13 @@ pub enum Inet6 {
 
     #[nla_type(IflaCacheinfo, cacheinfo)]
     Cacheinfo, // time values and max reasm size
-    Icmp6stats,  // statistics (icmpv6)
-    Token,       // device token
-    AddrGenMode, // implicit address generator mode
+    Icmp6stats, // statistics (icmpv6)
+
+    #[nla_type(Ipv6Addr, token)]
+    Token, // device token
+
+    #[nla_type(u8, addr_gen_mode)]
+    AddrGenMode, // implicit address generator mode, In6AddrGenMode
     _MAX,
 }
 
@@ -274,6 +402,216 @@ pub const IN6_ADDR_GEN_MODE_NONE: u32 = In6AddrGenMode::None as u32;
 pub const IN6_ADDR_GEN_MODE_STABLE_PRIVACY: u32 = In6AddrGenMode::StablePrivacy as u32;
 pub const IN6_ADDR_GEN_MODE_RANDOM: u32 = In6AddrGenMode::Random as u32;
 
+impl TryFrom<u8> for In6AddrGenMode {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> Result<Self> {
+        match v as u32 {
+            IN6_ADDR_GEN_MODE_EUI64 => Ok(Self::Eui64),
+            IN6_ADDR_GEN_MODE_NONE => Ok(Self::None),
+            IN6_ADDR_GEN_MODE_STABLE_PRIVACY => Ok(Self::StablePrivacy),
+            IN6_ADDR_GEN_MODE_RANDOM => Ok(Self::Random),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// struct for IFLA_INET_CONF, values ordered by IPV4_DEVCONF_ from 1
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InetConf {
+    pub forwarding: u32,
+    pub mc_forwarding: u32,
+    pub proxy_arp: u32,
+    pub accept_redirects: u32,
+    pub secure_redirects: u32,
+    pub send_redirects: u32,
+    pub shared_media: u32,
+    pub rp_filter: u32,
+    pub accept_source_route: u32,
+    pub bootp_relay: u32,
+    pub log_martians: u32,
+    pub tag: u32,
+    pub arpfilter: u32,
+    pub medium_id: u32,
+    pub noxfrm: u32,
+    pub nopolicy: u32,
+    pub force_igmp_version: u32,
+    pub arp_announce: u32,
+    pub arp_ignore: u32,
+    pub promote_secondaries: u32,
+    pub arp_accept: u32,
+    pub arp_notify: u32,
+    pub accept_local: u32,
+    pub src_vmark: u32,
+    pub proxy_arp_pvlan: u32,
+    pub route_localnet: u32,
+    pub igmpv2_unsolicited_report_interval: u32,
+    pub igmpv3_unsolicited_report_interval: u32,
+    pub ignore_routes_with_linkdown: u32,
+    pub drop_unicast_in_l2_multicast: u32,
+    pub drop_gratuitous_arp: u32,
+    pub bc_forwarding: u32,
+    pub arp_evict_nocarrier: u32,
+}
+
+// struct for IFLA_INET6_CONF, values ordered by DEVCONF_
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Inet6Conf {
+    pub forwarding: i32,
+    pub hop_limit: i32,
+    pub mtu6: i32,
+    pub accept_ra: i32,
+    pub accept_redirects: i32,
+    pub autoconf: i32,
+    pub dad_transmits: i32,
+    pub rtr_solicits: i32,
+    pub rtr_solicit_interval: i32,
+    pub rtr_solicit_delay: i32,
+    pub use_tempaddr: i32,
+    pub temp_valid_lft: i32,
+    pub temp_prefered_lft: i32,
+    pub regen_max_retry: i32,
+    pub max_desync_factor: i32,
+    pub max_addresses: i32,
+    pub force_mld_version: i32,
+    pub accept_ra_defrtr: i32,
+    pub accept_ra_pinfo: i32,
+    pub accept_ra_rtr_pref: i32,
+    pub rtr_probe_interval: i32,
+    pub accept_ra_rt_info_max_plen: i32,
+    pub proxy_ndp: i32,
+    pub optimistic_dad: i32,
+    pub accept_source_route: i32,
+    pub mc_forwarding: i32,
+    pub disable_ipv6: i32,
+    pub accept_dad: i32,
+    pub force_tllao: i32,
+    pub ndisc_notify: i32,
+    pub mldv1_unsolicited_report_interval: i32,
+    pub mldv2_unsolicited_report_interval: i32,
+    pub suppress_frag_ndisc: i32,
+    pub accept_ra_from_local: i32,
+    pub use_optimistic: i32,
+    pub accept_ra_mtu: i32,
+    pub stable_secret: i32, // omitted, always 0
+    pub use_oif_addrs_only: i32,
+    pub accept_ra_min_hop_limit: i32,
+    pub ignore_routes_with_linkdown: i32,
+    pub drop_unicast_in_l2_multicast: i32,
+    pub drop_unsolicited_na: i32,
+    pub keep_addr_on_down: i32,
+    pub rtr_solicit_max_interval: i32,
+    pub seg6_enabled: i32,
+    pub seg6_require_hmac: i32,
+    pub enhanced_dad: i32,
+    pub addr_gen_mode: i32,
+    pub disable_policy: i32,
+    pub accept_ra_rt_info_min_plen: i32,
+    pub ndisc_tclass: i32,
+    pub rpl_seg_enabled: i32,
+    pub ra_defrtr_metric: u32,
+}
+
+// IFLA_INET_CONF and IFLA_INET6_CONF are not nested attributes but arrays of
+// 32bit values. Values the running kernel does not report are left 0.
+fn conf_array<T: Copy + Default>(attr: &Attr) -> T {
+    let mut conf = T::default();
+    let src = payload_bytes(attr);
+    unsafe {
+        ptr::copy_nonoverlapping(
+            src.as_ptr(),
+            &mut conf as *mut T as *mut u8,
+            src.len().min(mem::size_of::<T>()),
+        );
+    }
+    conf
+}
+
+fn conf_value(attr: &Attr, i: usize) -> Option<[u8; 4]> {
+    payload_bytes(attr)
+        .chunks_exact(4)
+        .nth(i)
+        .map(|b| [b[0], b[1], b[2], b[3]])
+}
+
+// u64 array, the first element is the number of items.
+fn stats_array(attr: &Attr) -> Vec<u64> {
+    payload_bytes(attr)
+        .chunks_exact(8)
+        .map(|b| {
+            let mut v = [0u8; 8];
+            v.copy_from_slice(b);
+            u64::from_ne_bytes(v)
+        })
+        .collect()
+}
+
+impl<'a> InetTbl<'a> {
+    pub fn conf(&self) -> Result<Option<InetConf>> {
+        Ok(self[Inet::Conf].map(conf_array))
+    }
+
+    // None if the attribute is not or the kernel does not know the index.
+    pub fn conf_value(&self, i: Ipv4Devconf) -> Option<u32> {
+        self[Inet::Conf]
+            .and_then(|attr| conf_value(attr, i as usize - 1))
+            .map(u32::from_ne_bytes)
+    }
+}
+
+impl Inet {
+    // nest in Ifla::af_spec_start()
+    pub fn af_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(libc::AF_INET as u16)
+    }
+
+    // Unlike dumped one, IFLA_INET_CONF for RTM_SETLINK is a nest of u32
+    // attributes typed IPV4_DEVCONF_.
+    pub fn put_conf<'a>(
+        nlv: &'a mut MsgVec,
+        confs: &[(Ipv4Devconf, u32)],
+    ) -> Result<&'a mut MsgVec> {
+        nlv.nest_start(Inet::Conf)?;
+        for (i, v) in confs {
+            nlv.put(*i as u16, v)?;
+        }
+        nlv.nest_end()
+    }
+}
+
+impl<'a> IflaInet6Tbl<'a> {
+    pub fn conf(&self) -> Result<Option<Inet6Conf>> {
+        Ok(self[Inet6::Conf].map(conf_array))
+    }
+
+    // None if the attribute is not or the kernel does not know the index.
+    pub fn conf_value(&self, i: Devconf) -> Option<i32> {
+        self[Inet6::Conf]
+            .and_then(|attr| conf_value(attr, i as usize))
+            .map(i32::from_ne_bytes)
+    }
+
+    // indexed by snmp::IpstatsMib
+    pub fn stats(&self) -> Result<Option<Vec<u64>>> {
+        Ok(self[Inet6::Stats].map(stats_array))
+    }
+
+    // indexed by snmp::Icmp6Mib
+    pub fn icmp6stats(&self) -> Result<Option<Vec<u64>>> {
+        Ok(self[Inet6::Icmp6stats].map(stats_array))
+    }
+}
+
+impl Inet6 {
+    // nest in Ifla::af_spec_start(), only token and addr_gen_mode can be
+    // changed by RTM_SETLINK.
+    pub fn af_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(libc::AF_INET6 as u16)
+    }
+}
+
 // Bridge section
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
@@ -281,55 +619,156 @@ pub const IN6_ADDR_GEN_MODE_RANDOM: u32 = In6AddrGenMode::Random as u32;
 pub enum Br {
     // IFLA_BR_
     Unspec = 0,
-    ForwardDelay,
-    HelloTime,
-    MasAge,
-    AgeingTime,
+
+    #[nla_type(u32, forward_delay)]
+    ForwardDelay, // clock_t
+
+    #[nla_type(u32, hello_time)]
+    HelloTime, // clock_t
+
+    #[nla_type(u32, max_age)]
+    MaxAge, // clock_t
+
+    #[nla_type(u32, ageing_time)]
+    AgeingTime, // clock_t
+
+    #[nla_type(u32, stp_state)]
     StpState,
+
+    #[nla_type(u16, priority)]
     Priority,
+
+    #[nla_type(u8, vlan_filtering)]
     VlanFiltering,
-    VlanProtocol,
+
+    #[nla_type(u16, vlan_protocol)]
+    VlanProtocol, // __be16
+
+    #[nla_type(u16, group_fwd_mask)]
     GroupFwdMask,
+
+    #[nla_type(IflaBridgeId, root_id)]
     RootId,
+
+    #[nla_type(IflaBridgeId, bridge_id)]
     BridgeId,
+
+    #[nla_type(u16, root_port)]
     RootPort,
+
+    #[nla_type(u32, root_path_cost)]
     RootPathCost,
+
+    #[nla_type(u8, topology_change)]
     TopologyChange,
-    TopologyChangeDetedted,
+
+    #[nla_type(u8, topology_change_detected)]
+    TopologyChangeDetected,
+
+    #[nla_type(u64, hello_timer)]
     HelloTimer,
+
+    #[nla_type(u64, tcn_timer)]
     TcnTimer,
+
+    #[nla_type(u64, topology_change_timer)]
     TopologyChangeTimer,
+
+    #[nla_type(u64, gc_timer)]
     GcTimer,
+
+    #[nla_type(bytes, group_addr)]
     GroupAddr,
+
+    #[nla_type(flag, fdb_flush)]
     FdbFlush,
+
+    #[nla_type(u8, mcast_router)]
     McastRouter,
+
+    #[nla_type(u8, mcast_snooping)]
     McastSnooping,
+
+    #[nla_type(u8, mcast_query_use_ifaddr)]
     McastQueryUseIfaddr,
+
+    #[nla_type(u8, mcast_querier)]
     McastQuerier,
+
+    #[nla_type(u32, mcast_hash_elasticity)]
     McastHashElasticity,
+
+    #[nla_type(u32, mcast_hash_max)]
     McastHashMax,
+
+    #[nla_type(u32, mcast_last_member_cnt)]
     McastLastMemberCnt,
+
+    #[nla_type(u32, mcast_startup_query_cnt)]
     McastStartupQueryCnt,
-    McastLastMemberIntvl,
-    McastMembershipIntvl,
-    McastQuerierIntvl,
-    McastQueryIntvl,
-    McastQueryResponseIntvl,
-    McastStartupQueryIntvl,
+
+    #[nla_type(u64, mcast_last_member_intvl)]
+    McastLastMemberIntvl, // clock_t
+
+    #[nla_type(u64, mcast_membership_intvl)]
+    McastMembershipIntvl, // clock_t
+
+    #[nla_type(u64, mcast_querier_intvl)]
+    McastQuerierIntvl, // clock_t
+
+    #[nla_type(u64, mcast_query_intvl)]
+    McastQueryIntvl, // clock_t
+
+    #[nla_type(u64, mcast_query_response_intvl)]
+    McastQueryResponseIntvl, // clock_t
+
+    #[nla_type(u64, mcast_startup_query_intvl)]
+    McastStartupQueryIntvl, // clock_t
+
+    #[nla_type(u8, nf_call_iptables)]
     NfCallIptables,
+
+    #[nla_type(u8, nf_call_ip6tables)]
     NfCallIp6Tables,
+
+    #[nla_type(u8, nf_call_arptables)]
     NfCallArptables,
+
+    #[nla_type(u16, vlan_default_pvid)]
     VlanDefaultPvid,
+
     Pad,
+
+    #[nla_type(u8, vlan_stats_enabled)]
     VlanStatsEnabled,
+
+    #[nla_type(u8, mcast_stats_enabled)]
     McastStatsEnabled,
+
+    #[nla_type(u8, mcast_igmp_version)]
     McastIgmpVersion,
+
+    #[nla_type(u8, mcast_mld_version)]
     McastMldVersion,
+
+    #[nla_type(u8, vlan_stats_per_port)]
     VlanStatsPerPort,
+
+    #[nla_type(BrBooloptMulti, multi_boolopt)]
     MultiBoolopt,
+
     _MAX,
 }
 
+// misspelled names of the earlier versions
+#[allow(non_upper_case_globals)]
+impl Br {
+    #[deprecated(note = "misspelled, use Br::MaxAge")]
+    pub const MasAge: Br = Br::MaxAge;
+    #[deprecated(note = "misspelled, use Br::TopologyChangeDetected")]
+    pub const TopologyChangeDetedted: Br = Br::TopologyChangeDetected;
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct IflaBridgeId {
@@ -337,6 +776,19 @@ pub struct IflaBridgeId {
     pub addr: [u8; 6usize],
 }
 
+// enum br_boolopt_id in if_bridge.h
+pub const BR_BOOLOPT_NO_LL_LEARN: u32 = 0;
+pub const BR_BOOLOPT_MCAST_VLAN_SNOOPING: u32 = 1;
+pub const BR_BOOLOPT_MST_ENABLE: u32 = 2;
+
+// struct br_boolopt_multi in if_bridge.h, bitmaps of BR_BOOLOPT_
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BrBooloptMulti {
+    pub optval: u32,
+    pub optmask: u32,
+}
+
 // XXX: unused?
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -385,6 +837,7 @@ pub enum Brport {
     #[nla_type(u8, proxyarp)]
     Proxyarp, // proxy ARP
 
+    #[nla_type(u8, learning_sync)]
     LearningSync, // mac learning sync from device
 
     #[nla_type(u8, proxyarp_wifi)]
@@ -423,6 +876,7 @@ pub enum Brport {
     #[nla_type(u64, hold_timer)]
     HoldTimer,
 
+    #[nla_type(flag, flush)]
     Flush,
 
     #[nla_type(u8, multicast_router)]
@@ -484,11 +938,22 @@ pub struct IflaCacheinfo {
 pub enum Info {
     // IFLA_INFO_
     Unspec = 0,
+
+    #[nla_type(cstr, kind)]
     Kind,
+
+    // see InfoTbl::data()
     Data,
+
+    #[nla_type(bytes, xstats)]
     Xstats,
+
+    #[nla_type(cstr, slave_kind)]
     SlaveKind,
+
+    // see InfoTbl::slave_data()
     SlaveData,
+
     _MAX,
 }
 
@@ -499,11 +964,22 @@ pub enum Info {
 pub enum Vlan {
     // IFLA_VLAN_
     Unspec = 0,
+
+    #[nla_type(u16, id)]
     Id,
+
+    #[nla_type(IflaVlanFlags, flags)]
     Flags,
+
+    // nested IFLA_VLAN_QOS_MAPPING, see VlanTbl::egress_qos()
     EgressQos,
+
+    // nested IFLA_VLAN_QOS_MAPPING, see VlanTbl::ingress_qos()
     IngressQos,
-    Protocol,
+
+    #[nla_type(u16, protocol)]
+    Protocol, // __be16, ETH_P_8021Q or ETH_P_8021AD
+
     _MAX,
 }
 
@@ -514,13 +990,23 @@ pub struct IflaVlanFlags {
     pub mask: u32,
 }
 
+// enum vlan_flags in if_vlan.h
+pub const VLAN_FLAG_REORDER_HDR: u32 = 0x1;
+pub const VLAN_FLAG_GVRP: u32 = 0x2;
+pub const VLAN_FLAG_LOOSE_BINDING: u32 = 0x4;
+pub const VLAN_FLAG_MVRP: u32 = 0x8;
+pub const VLAN_FLAG_BRIDGE_BINDING: u32 = 0x10;
+
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
 #[tbname = "VlanQosTbl"]
 pub enum VlanQos {
     // IFLA_VLAN_QOS_
     Unspec = 0,
+
+    #[nla_type(IflaVlanQosMapping, mapping)]
     Mapping,
+
     _MAX,
 }
 
@@ -531,6 +1017,46 @@ pub struct IflaVlanQosMapping {
     pub to: u32,
 }
 
+// IFLA_VLAN_EGRESS_QOS and IFLA_VLAN_INGRESS_QOS contain multiple
+// IFLA_VLAN_QOS_MAPPING attributes, which can not be put in VlanQosTbl.
+fn vlan_qos_mappings<'a>(nest: &'a Attr<'a>) -> Result<Vec<&'a IflaVlanQosMapping>> {
+    let mut v = Vec::new();
+    let mut attrs = NestAttr::new(nest);
+    while let Some(attr) = attrs.next() {
+        if attr.atype() == VlanQos::Mapping as u16 {
+            v.push(attr.value_ref::<IflaVlanQosMapping>()?);
+        }
+    }
+    Ok(v)
+}
+
+impl<'a> VlanTbl<'a> {
+    pub fn egress_qos(&self) -> Result<Option<Vec<&'a IflaVlanQosMapping>>> {
+        match self[Vlan::EgressQos] {
+            Some(nest) => Ok(Some(vlan_qos_mappings(nest)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn ingress_qos(&self) -> Result<Option<Vec<&'a IflaVlanQosMapping>>> {
+        match self[Vlan::IngressQos] {
+            Some(nest) => Ok(Some(vlan_qos_mappings(nest)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Vlan {
+    // put VlanQos::put_mapping() in these nests, then nest_end()
+    pub fn egress_qos_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Vlan::EgressQos)
+    }
+
+    pub fn ingress_qos_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Vlan::IngressQos)
+    }
+}
+
 // MACVLAN section
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
@@ -604,7 +1130,10 @@ pub const MACVLAN_MACADDR_SET: u32 = MacvlanMacaddrMode::Set as u32;
 pub enum Vrf {
     // IFLA_VRF_
     Unspec,
+
+    #[nla_type(u32, table)]
     Table,
+
     _MAX,
 }
 
@@ -614,7 +1143,10 @@ pub enum Vrf {
 pub enum VrfPort {
     // IFLA_VRF_PORT_
     Unspec = 0,
+
+    #[nla_type(u32, table)]
     Table,
+
     _MAX,
 }
 
@@ -625,21 +1157,51 @@ pub enum VrfPort {
 pub enum Macsec {
     // IFLA_MACSEC_
     Unspec = 0,
+
+    #[nla_type(u64, sci)]
     Sci,
-    Port,
+
+    #[nla_type(u16, port)]
+    Port, // network byte order
+
+    #[nla_type(u8, icv_len)]
     IcvLen,
-    CipherSuite,
+
+    #[nla_type(u64, cipher_suite)]
+    CipherSuite, // MACSEC_CIPHER_ID_
+
+    #[nla_type(u32, window)]
     Window,
+
+    #[nla_type(u8, encoding_sa)]
     EncodingSa,
+
+    #[nla_type(u8, encrypt)]
     Encrypt,
+
+    #[nla_type(u8, protect)]
     Protect,
+
+    #[nla_type(u8, inc_sci)]
     IncSci,
+
+    #[nla_type(u8, es)]
     Es,
+
+    #[nla_type(u8, scb)]
     Scb,
+
+    #[nla_type(u8, replay_protect)]
     ReplayProtect,
-    Validation,
+
+    #[nla_type(u8, validation)]
+    Validation, // MacsecValidationType
+
     Pad,
-    Offload,
+
+    #[nla_type(u8, offload)]
+    Offload, // MacsecOffload
+
     _MAX,
 }
 
@@ -650,8 +1212,13 @@ pub enum Macsec {
 pub enum Xfrm {
     // IFLA_XFRM_
     Unspec = 0,
+
+    #[nla_type(u32, link)]
     Link,
+
+    #[nla_type(u32, if_id)]
     IfId,
+
     _MAX,
 }
 
@@ -670,6 +1237,19 @@ pub const MACSEC_VALIDATE_STRICT: u8 = MacsecValidationType::Strict as u8;
 pub const __MACSEC_VALIDATE_END: u8 = MacsecValidationType::_END as u8;
 pub const MACSEC_VALIDATE_MAX: u8 = __MACSEC_VALIDATE_END - 1;
 
+impl TryFrom<u8> for MacsecValidationType {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> Result<Self> {
+        match v {
+            MACSEC_VALIDATE_DISABLED => Ok(Self::Disabled),
+            MACSEC_VALIDATE_CHECK => Ok(Self::Check),
+            MACSEC_VALIDATE_STRICT => Ok(Self::Strict),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MacsecOffload {
@@ -685,6 +1265,19 @@ pub const MACSEC_OFFLOAD_MAC: c_int = MacsecOffload::Mac as c_int;
 pub const __MACSEC_OFFLOAD_END: c_int = MacsecOffload::_END as c_int;
 pub const MACSEC_OFFLOAD_MAX: c_int = __MACSEC_OFFLOAD_END - 1;
 
+impl TryFrom<u8> for MacsecOffload {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> Result<Self> {
+        match v as c_int {
+            MACSEC_OFFLOAD_OFF => Ok(Self::Off),
+            MACSEC_OFFLOAD_PHY => Ok(Self::Phy),
+            MACSEC_OFFLOAD_MAC => Ok(Self::Mac),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
 // IPVLAN section
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
@@ -692,8 +1285,13 @@ pub const MACSEC_OFFLOAD_MAX: c_int = __MACSEC_OFFLOAD_END - 1;
 pub enum Ipvlan {
     // IFLA_IPVLAN_
     Unspec = 0,
+
+    #[nla_type(u16, mode)]
     Mode,
+
+    #[nla_type(u16, flags)]
     Flags,
+
     _MAX,
 }
 
@@ -721,35 +1319,94 @@ pub const IPVLAN_F_VEPA: u16 = 0x02;
 pub enum Vxlan {
     // IFLA_VXLAN_
     Unspec = 0,
+
+    #[nla_type(u32, id)]
     Id,
+
+    #[nla_type(Ipv4Addr, group)]
     Group, // group or remote address
+
+    #[nla_type(u32, link)]
     Link,
+
+    #[nla_type(Ipv4Addr, local)]
     Local,
+
+    #[nla_type(u8, ttl)]
     Ttl,
+
+    #[nla_type(u8, tos)]
     Tos,
+
+    #[nla_type(u8, learning)]
     Learning,
+
+    #[nla_type(u32, ageing)]
     Ageing,
+
+    #[nla_type(u32, limit)]
     Limit,
+
+    #[nla_type(IflaVxlanPortRange, port_range)]
     PortRange, // source port
+
+    #[nla_type(u8, proxy)]
     Proxy,
+
+    #[nla_type(u8, rsc)]
     Rsc,
+
+    #[nla_type(u8, l2miss)]
     L2Miss,
+
+    #[nla_type(u8, l3miss)]
     L3Miss,
-    Port, // destination port
+
+    #[nla_type(u16, port)]
+    Port, // destination port, __be16
+
+    #[nla_type(Ipv6Addr, group6)]
     Group6,
+
+    #[nla_type(Ipv6Addr, local6)]
     Local6,
+
+    #[nla_type(u8, udp_csum)]
     UdpCsum,
+
+    #[nla_type(u8, udp_zero_csum6_tx)]
     UdpZeroCsum6Tx,
+
+    #[nla_type(u8, udp_zero_csum6_rx)]
     UdpZeroCsum6Rx,
+
+    #[nla_type(u8, remcsum_tx)]
     RemcsumTx,
+
+    #[nla_type(u8, remcsum_rx)]
     RemcsumRx,
+
+    #[nla_type(flag, gbp)]
     Gbp,
+
+    #[nla_type(flag, remcsum_nopartial)]
     RemcsumNopartial,
+
+    #[nla_type(u8, collect_metadata)]
     CollectMetadata,
-    Label,
+
+    #[nla_type(u32, label)]
+    Label, // __be32
+
+    #[nla_type(flag, gpe)]
     Gpe,
+
+    #[nla_type(u8, ttl_inherit)]
     TtlInherit,
+
+    #[nla_type(u8, df)]
     Df,
+
     _MAX,
 }
 
@@ -782,19 +1439,46 @@ pub const VXLAN_DF_MAX: c_int = __VXLAN_DF_END - 1;
 pub enum Geneve {
     // IFLA_GENEVE_
     Unspec = 0,
+
+    #[nla_type(u32, id)]
     Id,
+
+    #[nla_type(Ipv4Addr, remote)]
     Remote,
+
+    #[nla_type(u8, ttl)]
     Ttl,
+
+    #[nla_type(u8, tos)]
     Tos,
-    Port,
+
+    #[nla_type(u16, port)]
+    Port, // destination port, __be16
+
+    #[nla_type(flag, collect_metadata)]
     CollectMetadata,
+
+    #[nla_type(Ipv6Addr, remote6)]
     Remote6,
+
+    #[nla_type(u8, udp_csum)]
     UdpCsum,
+
+    #[nla_type(u8, udp_zero_csum6_tx)]
     UdpZeroCsum6Tx,
+
+    #[nla_type(u8, udp_zero_csum6_rx)]
     UdpZeroCsum6Rx,
-    Label,
+
+    #[nla_type(u32, label)]
+    Label, // __be32
+
+    #[nla_type(u8, ttl_inherit)]
     TtlInherit,
+
+    #[nla_type(u8, df)]
     Df,
+
     _MAX,
 }
 
@@ -820,10 +1504,19 @@ pub const GENEVE_DF_MAX: c_int = __GENEVE_DF_END - 1;
 pub enum BareUdp {
     // IFLA_BAREUDP_
     Unspec,
-    Port,
-    Ethertype,
+
+    #[nla_type(u16, port)]
+    Port, // __be16
+
+    #[nla_type(u16, ethertype)]
+    Ethertype, // __be16
+
+    #[nla_type(u16, srcport_min)]
     SrcportMin,
+
+    #[nla_type(flag, multiproto_mode)]
     MultiprotoMode,
+
     _MAX,
 }
 
@@ -868,48 +1561,238 @@ pub enum Gtp {
 pub enum Bond {
     // IFLA_BOND_
     Unspec = 0,
-    Mode,
+
+    #[nla_type(u8, mode)]
+    Mode, // BOND_MODE_
+
+    #[nla_type(u32, active_slave)]
     ActiveSlave,
+
+    #[nla_type(u32, miimon)]
     Miimon,
+
+    #[nla_type(u32, updelay)]
     Updelay,
+
+    #[nla_type(u32, downdelay)]
     Downdelay,
+
+    #[nla_type(u8, use_carrier)]
     UseCarrier,
+
+    #[nla_type(u32, arp_interval)]
     ArpInterval,
+
+    // nested IPv4 addresses, see BondTbl::arp_ip_target()
+    // and Bond::put_arp_ip_target()
     ArpIpTarget,
-    ArpValidate,
-    ArpAllTargets,
+
+    #[nla_type(u32, arp_validate)]
+    ArpValidate, // BOND_ARP_VALIDATE_
+
+    #[nla_type(u32, arp_all_targets)]
+    ArpAllTargets, // BOND_ARP_TARGETS_
+
+    #[nla_type(u32, primary)]
     Primary,
-    PrimaryReselect,
-    FailOverMac,
-    XmitHashPolicy,
+
+    #[nla_type(u8, primary_reselect)]
+    PrimaryReselect, // BOND_PRI_RESELECT_
+
+    #[nla_type(u8, fail_over_mac)]
+    FailOverMac, // BOND_FOM_
+
+    #[nla_type(u8, xmit_hash_policy)]
+    XmitHashPolicy, // BOND_XMIT_POLICY_
+
+    #[nla_type(u32, resend_igmp)]
     ResendIgmp,
+
+    #[nla_type(u8, num_peer_notif)]
     NumPeerNotif,
+
+    #[nla_type(u8, all_slaves_active)]
     AllSlavesActive,
+
+    #[nla_type(u32, min_links)]
     MinLinks,
+
+    #[nla_type(u32, lp_interval)]
     LpInterval,
+
+    #[nla_type(u32, packets_per_slave)]
     PacketsPerSlave,
-    AdLacpRate,
-    AdSelect,
+
+    #[nla_type(u8, ad_lacp_rate)]
+    AdLacpRate, // AD_LACP_
+
+    #[nla_type(u8, ad_select)]
+    AdSelect, // BOND_AD_
+
+    #[nla_nest(BondAdInfoTbl, ad_info)]
     AdInfo,
+
+    #[nla_type(u16, ad_actor_sys_prio)]
     AdActorSysPrio,
+
+    #[nla_type(u16, ad_user_port_key)]
     AdUserPortKey,
+
+    #[nla_type([u8; 6], ad_actor_system)]
     AdActorSystem,
+
+    #[nla_type(u8, tlb_dynamic_lb)]
     TlbDynamicLb,
+
+    #[nla_type(u32, peer_notif_delay)]
     PeerNotifDelay,
+
+    #[nla_type(u8, ad_lacp_active)]
+    AdLacpActive,
+
+    #[nla_type(u8, missed_max)]
+    MissedMax,
+
+    // nested IPv6 addresses, see BondTbl::ns_ip6_target()
+    // and Bond::put_ns_ip6_target()
+    NsIp6Target,
+
+    #[nla_type(u8, coupled_control)]
+    CoupledControl,
+
     _MAX,
 }
 
+// IFLA_BOND_ARP_IP_TARGET and IFLA_BOND_NS_IP6_TARGET nest addresses
+// whose attribute type is just an index.
+fn bond_targets<'a, T>(nest: &'a Attr<'a>) -> Result<Vec<&'a T>> {
+    let mut v = Vec::new();
+    let mut attrs = NestAttr::new(nest);
+    while let Some(attr) = attrs.next() {
+        v.push(attr.value_ref::<T>()?);
+    }
+    Ok(v)
+}
+
+impl<'a> BondTbl<'a> {
+    pub fn arp_ip_target(&self) -> Result<Option<Vec<&'a Ipv4Addr>>> {
+        match self[Bond::ArpIpTarget] {
+            Some(nest) => Ok(Some(bond_targets(nest)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn ns_ip6_target(&self) -> Result<Option<Vec<&'a Ipv6Addr>>> {
+        match self[Bond::NsIp6Target] {
+            Some(nest) => Ok(Some(bond_targets(nest)?)),
+            None => Ok(None),
+        }
+    }
+
+    // The following return the values above as the enums of if_bonding.
+    pub fn bond_mode(&self) -> Result<Option<BondMode>> {
+        match self.mode()? {
+            Some(v) => Ok(Some(BondMode::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn bond_arp_validate(&self) -> Result<Option<BondArpValidate>> {
+        match self.arp_validate()? {
+            Some(v) => Ok(Some(BondArpValidate::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn bond_pri_reselect(&self) -> Result<Option<BondPriReselect>> {
+        match self.primary_reselect()? {
+            Some(v) => Ok(Some(BondPriReselect::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn bond_fom(&self) -> Result<Option<BondFom>> {
+        match self.fail_over_mac()? {
+            Some(v) => Ok(Some(BondFom::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn bond_xmit_policy(&self) -> Result<Option<BondXmitPolicy>> {
+        match self.xmit_hash_policy()? {
+            Some(v) => Ok(Some(BondXmitPolicy::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn bond_lacp_rate(&self) -> Result<Option<BondLacpRate>> {
+        match self.ad_lacp_rate()? {
+            Some(v) => Ok(Some(BondLacpRate::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn bond_ad_select(&self) -> Result<Option<BondAdSelect>> {
+        match self.ad_select()? {
+            Some(v) => Ok(Some(BondAdSelect::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Bond {
+    // an empty list clears all targets
+    pub fn put_arp_ip_target<'a>(
+        nlv: &'a mut MsgVec,
+        addrs: &[Ipv4Addr],
+    ) -> Result<&'a mut MsgVec> {
+        if addrs.len() > BOND_MAX_ARP_TARGETS {
+            return Err(Errno(libc::EINVAL));
+        }
+        nlv.nest_start(Bond::ArpIpTarget)?;
+        for (i, addr) in addrs.iter().enumerate() {
+            nlv.put(i as u16, addr)?;
+        }
+        nlv.nest_end()
+    }
+
+    pub fn put_ns_ip6_target<'a>(
+        nlv: &'a mut MsgVec,
+        addrs: &[Ipv6Addr],
+    ) -> Result<&'a mut MsgVec> {
+        if addrs.len() > BOND_MAX_NS_TARGETS {
+            return Err(Errno(libc::EINVAL));
+        }
+        nlv.nest_start(Bond::NsIp6Target)?;
+        for (i, addr) in addrs.iter().enumerate() {
+            nlv.put(i as u16, addr)?;
+        }
+        nlv.nest_end()
+    }
+}
+
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
 #[tbname = "BondAdInfoTbl"]
 pub enum BondAdInfo {
     // IFLA_BOND_AD_INFO_
     Unspec = 0,
+
+    #[nla_type(u16, aggregator)]
     Aggregator,
+
+    #[nla_type(u16, num_ports)]
     NumPorts,
+
+    #[nla_type(u16, actor_key)]
     ActorKey,
+
+    #[nla_type(u16, partner_key)]
     PartnerKey,
+
+    #[nla_type([u8; 6], partner_mac)]
     PartnerMac,
+
     _MAX,
 }
 
@@ -919,25 +1802,82 @@ pub enum BondAdInfo {
 pub enum BondBondSlave {
     // IFLA_BOND_SLAVE_
     Unspec = 0,
-    State,
-    MiiStatus,
+
+    #[nla_type(u8, state)]
+    State, // BOND_STATE_
+
+    #[nla_type(u8, mii_status)]
+    MiiStatus, // BOND_LINK_
+
+    #[nla_type(u32, link_failure_count)]
     LinkFailureCount,
+
+    #[nla_type(bytes, perm_hwaddr)]
     PermHwaddr,
+
+    #[nla_type(u16, queue_id)]
     QueueId,
+
+    #[nla_type(u16, ad_aggregator_id)]
     AdAggregatorId,
-    AdActorOperPortState,
-    AdPartnerOperPortState,
+
+    #[nla_type(u8, ad_actor_oper_port_state)]
+    AdActorOperPortState, // LACP_STATE_
+
+    #[nla_type(u16, ad_partner_oper_port_state)]
+    AdPartnerOperPortState, // LACP_STATE_, u16 though
+
+    #[nla_type(u32, prio)]
+    Prio,
+
     _MAX,
 }
 
+impl<'a> BondSlaveTbl<'a> {
+    pub fn bond_state(&self) -> Result<Option<BondState>> {
+        match self.state()? {
+            Some(v) => Ok(Some(BondState::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn bond_link(&self) -> Result<Option<BondLink>> {
+        match self.mii_status()? {
+            Some(v) => Ok(Some(BondLink::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+}
+
 // SR-IOV virtual function management section
+//
+//	Nested layout of set/get msg is:
+//
+//		[IFLA_NUM_VF]
+//		[IFLA_VFINFO_LIST]
+//			[IFLA_VF_INFO]
+//				[IFLA_VF_*], ...
+//			[IFLA_VF_INFO]
+//				[IFLA_VF_*], ...
+//			...
+//
+// Each IFLA_VF_* struct starts with the VF index. For RTM_SETLINK, e.g.
+//
+//	Ifla::vfinfo_list_start(nlv)?;
+//	VfInfo::info_start(nlv)?;
+//	Vf::put_mac(nlv, &IflaVfMac::new(vf, &mac))?;
+//	nlv.nest_end()?;
+//	nlv.nest_end()?;
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
 #[tbname = "VfInfoTbl"]
 pub enum VfInfo {
     // IFLA_VF_INFO_
     Unspec = 0,
+
+    #[nla_nest(VfTbl, info)]
     Info = 1, // XXX: origin - IFLA_VF_INFO
+
     _MAX,
 }
 
@@ -947,23 +1887,74 @@ pub enum VfInfo {
 pub enum Vf {
     // IFLA_VF_
     Unspec = 0,
-    Mac,        // Hardware queue specific attributes
-    Vlan,       // VLAN ID and QoS
-    TxRate,     // Max TX Bandwidth Allocation
-    Spoofchk,   // Spoof Checking on/off switch
-    LinkState,  // link state enable/disable/auto switch
-    Rate,       // Min and Max TX Bandwidth Allocation
+
+    #[nla_type(IflaVfMac, mac)]
+    Mac, // Hardware queue specific attributes
+
+    #[nla_type(IflaVfVlan, vlan)]
+    Vlan, // VLAN ID and QoS
+
+    #[nla_type(IflaVfTxRate, tx_rate)]
+    TxRate, // Max TX Bandwidth Allocation
+
+    #[nla_type(IflaVfSpoofchk, spoofchk)]
+    Spoofchk, // Spoof Checking on/off switch
+
+    #[nla_type(IflaVfLinkState, link_state)]
+    LinkState, // link state enable/disable/auto switch
+
+    #[nla_type(IflaVfRate, rate)]
+    Rate, // Min and Max TX Bandwidth Allocation
+
+    #[nla_type(IflaVfRssQueryEn, rss_query_en)]
     RssQueryEn, // RSS Redirection Table and Hash Key query
     // on/off switch
-    Stats,      // network device statistics
-    Trust,      // Trust VF
+    #[nla_nest(VfStatsTbl, stats)]
+    Stats, // network device statistics
+
+    #[nla_type(IflaVfTrust, trust)]
+    Trust, // Trust VF
+
+    #[nla_type(IflaVfGuid, ib_node_guid)]
     IbNodeGuid, // VF Infiniband node GUID
+
+    #[nla_type(IflaVfGuid, ib_port_guid)]
     IbPortGuid, // VF Infiniband port GUID
-    VlanList,   // nested list of vlans, option for QinQ
-    Broadcast,  // VF broadcast
+
+    // nested list of vlans, option for QinQ
+    // see VfTbl::vlan_list() and Vf::vlan_list_start()
+    VlanList,
+
+    #[nla_type(IflaVfBroadcast, broadcast)]
+    Broadcast, // VF broadcast
+
     _MAX,
 }
 
+impl<'a> VfTbl<'a> {
+    pub fn vlan_list(&self) -> Result<Option<Vec<&'a IflaVfVlanInfo>>> {
+        let nest = match self[Vf::VlanList] {
+            Some(nest) => nest,
+            None => return Ok(None),
+        };
+        let mut v = Vec::new();
+        let mut attrs = NestAttr::new(nest);
+        while let Some(attr) = attrs.next() {
+            if attr.atype() == VfVlanInfo::Info as u16 {
+                v.push(attr.value_ref::<IflaVfVlanInfo>()?);
+            }
+        }
+        Ok(Some(v))
+    }
+}
+
+impl Vf {
+    // put VfVlanInfo::put_info() up to MAX_VLAN_LIST_LEN, then nest_end()
+    pub fn vlan_list_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Vf::VlanList)
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct IflaVfMac {
@@ -971,6 +1962,15 @@ pub struct IflaVfMac {
     pub mac: [u8; 32usize], // MAX_ADDR_LEN
 }
 
+impl IflaVfMac {
+    pub fn new(vf: u32, mac: &[u8]) -> Self {
+        let mut m = Self { vf, mac: [0; 32] };
+        let len = mac.len().min(m.mac.len());
+        m.mac[..len].copy_from_slice(&mac[..len]);
+        m
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct IflaVfBroadcast {
@@ -991,6 +1991,8 @@ pub struct IflaVfVlan {
 pub enum VfVlanInfo {
     // IFLA_VF_VLAN_INFO_
     Unspec = 0,
+
+    #[nla_type(IflaVfVlanInfo, info)]
     Info, // VLAN ID, QoS and VLAN protocol
     // XXX: original - IFLA_VF_VLAN_INFO
     _MAX,
@@ -1004,7 +2006,7 @@ pub struct IflaVfVlanInfo {
     pub vf: u32,
     pub vlan: u32, // 0 - 4095, 0 disables VLAN filter
     pub qos: u32,
-    pub vlan_proto: u16, // VLAN protocol either 802.1Q or 802.1ad
+    pub vlan_proto: u16, // VLAN protocol either 802.1Q or 802.1ad, __be16
 }
 
 #[repr(C)]
@@ -1050,6 +2052,19 @@ pub const IFLA_VF_LINK_STATE_ENABLE: u32 = VfLinkState::Enable as u32;
 pub const IFLA_VF_LINK_STATE_DISABLE: u32 = VfLinkState::Disable as u32;
 pub const __IFLA_VF_LINK_STATE_MAX: u32 = VfLinkState::_MAX as u32;
 
+impl TryFrom<u32> for VfLinkState {
+    type Error = Errno;
+
+    fn try_from(v: u32) -> std::result::Result<Self, Errno> {
+        match v {
+            IFLA_VF_LINK_STATE_AUTO => Ok(VfLinkState::Auto),
+            IFLA_VF_LINK_STATE_ENABLE => Ok(VfLinkState::Enable),
+            IFLA_VF_LINK_STATE_DISABLE => Ok(VfLinkState::Disable),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct IflaVfLinkState {
@@ -1069,26 +2084,152 @@ pub struct IflaVfRssQueryEn {
 #[tbname = "VfStatsTbl"]
 pub enum VfStats {
     // IFLA_VF_STATS_
+    #[nla_type(u64, rx_packets)]
     RxPackets = 0,
+
+    #[nla_type(u64, tx_packets)]
     TxPackets,
+
+    #[nla_type(u64, rx_bytes)]
     RxBytes,
+
+    #[nla_type(u64, tx_bytes)]
     TxBytes,
+
+    #[nla_type(u64, broadcast)]
     Broadcast,
+
+    #[nla_type(u64, multicast)]
     Multicast,
+
     Pad,
+
+    #[nla_type(u64, rx_dropped)]
     RxDropped,
+
+    #[nla_type(u64, tx_dropped)]
     TxDropped,
+
     _MAX,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
-pub struct ifla_vf_trust {
+pub struct IflaVfTrust {
     pub vf: u32,
     pub setting: u32,
 }
-
-// XXXXXXXX: here
+#[deprecated(note = "use IflaVfTrust")]
+#[allow(non_camel_case_types)]
+pub type ifla_vf_trust = IflaVfTrust;
+
+// Decoded IFLA_VF_STATS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VfStatsValues {
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub broadcast: u64,
+    pub multicast: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+}
+
+// Decoded IFLA_VF_INFO. Spoofchk, RSS query and trust are reported as
+// None if the driver does not support it, kernel puts -1 as the setting.
+#[derive(Debug, Clone)]
+pub struct VirtualFunction {
+    pub vf: u32,
+    pub mac: [u8; 32usize],
+    pub broadcast: Option<[u8; 32usize]>,
+    // from IFLA_VF_VLAN_LIST if exists, IFLA_VF_VLAN with 802.1Q otherwise,
+    // excluding vlan 0 which the kernel puts for no vlan
+    pub vlans: Vec<IflaVfVlanInfo>,
+    pub tx_rate: Option<u32>,
+    pub min_tx_rate: Option<u32>,
+    pub max_tx_rate: Option<u32>,
+    pub spoofchk: Option<bool>,
+    pub link_state: Option<VfLinkState>, // None if unknown to VfLinkState
+    pub rss_query_en: Option<bool>,
+    pub trust: Option<bool>,
+    pub ib_node_guid: Option<u64>,
+    pub ib_port_guid: Option<u64>,
+    pub stats: Option<VfStatsValues>,
+}
+
+fn vf_setting(setting: u32) -> Option<bool> {
+    match setting {
+        u32::MAX => None,
+        v => Some(v != 0),
+    }
+}
+
+impl VirtualFunction {
+    pub fn from_tbl(tb: &VfTbl) -> Result<Self> {
+        // kernel always puts IFLA_VF_MAC
+        let mac = tb.mac()?.ok_or(Errno(libc::EINVAL))?;
+        let vlans = match tb.vlan_list()? {
+            Some(v) => v.into_iter().filter(|v| v.vlan != 0).cloned().collect(),
+            None => match tb.vlan()? {
+                Some(v) if v.vlan != 0 => vec![IflaVfVlanInfo {
+                    vf: v.vf,
+                    vlan: v.vlan,
+                    qos: v.qos,
+                    vlan_proto: (libc::ETH_P_8021Q as u16).to_be(),
+                }],
+                _ => Vec::new(),
+            },
+        };
+        let stats = match tb.stats()? {
+            Some(stb) => Some(VfStatsValues {
+                rx_packets: *stb.rx_packets()?.unwrap_or(&0),
+                tx_packets: *stb.tx_packets()?.unwrap_or(&0),
+                rx_bytes: *stb.rx_bytes()?.unwrap_or(&0),
+                tx_bytes: *stb.tx_bytes()?.unwrap_or(&0),
+                broadcast: *stb.broadcast()?.unwrap_or(&0),
+                multicast: *stb.multicast()?.unwrap_or(&0),
+                rx_dropped: *stb.rx_dropped()?.unwrap_or(&0),
+                tx_dropped: *stb.tx_dropped()?.unwrap_or(&0),
+            }),
+            None => None,
+        };
+        let link_state = tb
+            .link_state()?
+            .and_then(|ls| VfLinkState::try_from(ls.link_state).ok());
+
+        Ok(Self {
+            vf: mac.vf,
+            mac: mac.mac,
+            broadcast: tb.broadcast()?.map(|b| b.broadcast),
+            vlans,
+            tx_rate: tb.tx_rate()?.map(|r| r.rate),
+            min_tx_rate: tb.rate()?.map(|r| r.min_tx_rate),
+            max_tx_rate: tb.rate()?.map(|r| r.max_tx_rate),
+            spoofchk: tb.spoofchk()?.and_then(|s| vf_setting(s.setting)),
+            link_state,
+            rss_query_en: tb.rss_query_en()?.and_then(|s| vf_setting(s.setting)),
+            trust: tb.trust()?.and_then(|s| vf_setting(s.setting)),
+            ib_node_guid: tb.ib_node_guid()?.map(|g| g.guid),
+            ib_port_guid: tb.ib_port_guid()?.map(|g| g.guid),
+            stats,
+        })
+    }
+}
+
+impl<'a> IflaTbl<'a> {
+    // RTM_GETLINK request needs RTEXT_FILTER_VF in IFLA_EXT_MASK
+    pub fn virtual_functions(&self) -> Result<Option<Vec<VirtualFunction>>> {
+        match self.vfinfo_list()? {
+            Some(tbs) => Ok(Some(
+                tbs.iter()
+                    .map(VirtualFunction::from_tbl)
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
 
 // VF ports management section
 //
@@ -1109,7 +2250,10 @@ pub struct ifla_vf_trust {
 pub enum VfPort {
     // IFLA_VF_PORT_
     Unspec,
-    Port, // nest
+
+    #[nla_nest(PortTbl, port)]
+    Port,
+
     _MAX,
 }
 
@@ -1119,14 +2263,29 @@ pub enum VfPort {
 pub enum Port {
     // IFLA_PORT_
     Unspec = 0,
-    Vf,           // __u32
-    Profile,      // string
-    VsiType,      // 802.1Qbg (pre-)standard VDP
+
+    #[nla_type(u32, vf)]
+    Vf,
+
+    #[nla_type(cstr, profile)]
+    Profile, // up to PORT_PROFILE_MAX
+
+    #[nla_type(IflaPortVsi, vsi_type)]
+    VsiType, // 802.1Qbg (pre-)standard VDP
+
+    #[nla_type([u8; 16], instance_uuid)]
     InstanceUuid, // binary UUID
-    HostUuid,     // binary UUID
-    Request,      // __u8
-    Response,     // __u16, output only
-    _MAX = 8,
+
+    #[nla_type([u8; 16], host_uuid)]
+    HostUuid, // binary UUID
+
+    #[nla_type(u8, request)]
+    Request, // PORT_REQUEST_
+
+    #[nla_type(u16, response)]
+    Response, // PORT_VDP_RESPONSE_, PORT_PROFILE_RESPONSE_, output only
+
+    _MAX,
 }
 pub const PORT_PROFILE_MAX: usize = 40;
 pub const PORT_UUID_MAX: usize = 16;
@@ -1184,12 +2343,15 @@ pub const PORT_PROFILE_RESPONSE_ERROR: u16 = PortResponse::ProfileError as u16;
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
-pub struct ifla_port_vsi {
+pub struct IflaPortVsi {
     pub vsi_mgr_id: u8,
     pub vsi_type_id: [u8; 3usize],
     pub vsi_type_version: u8,
     pub pad: [u8; 3usize],
 }
+#[deprecated(note = "use IflaPortVsi")]
+#[allow(non_camel_case_types)]
+pub type ifla_port_vsi = IflaPortVsi;
 
 // IPoIB section
 #[repr(u16)]
@@ -1304,8 +2466,9 @@ pub const XDP_FLAGS_UPDATE_IF_NOEXIST: u32 = 1 << 0;
 pub const XDP_FLAGS_SKB_MODE: u32 = 1 << 1;
 pub const XDP_FLAGS_DRV_MODE: u32 = 1 << 2;
 pub const XDP_FLAGS_HW_MODE: u32 = 1 << 3;
+pub const XDP_FLAGS_REPLACE: u32 = 1 << 4;
 pub const XDP_FLAGS_MODES: u32 = XDP_FLAGS_SKB_MODE | XDP_FLAGS_DRV_MODE | XDP_FLAGS_HW_MODE;
-pub const XDP_FLAGS_MASK: u32 = XDP_FLAGS_UPDATE_IF_NOEXIST | XDP_FLAGS_MODES;
+pub const XDP_FLAGS_MASK: u32 = XDP_FLAGS_UPDATE_IF_NOEXIST | XDP_FLAGS_MODES | XDP_FLAGS_REPLACE;
 
 // These are stored into IFLA_XDP_ATTACHED on dump.
 #[repr(u8)]
@@ -1323,23 +2486,126 @@ pub const XDP_ATTACHED_SKB: u8 = XdpAttached::Skb as u8;
 pub const XDP_ATTACHED_HW: u8 = XdpAttached::Hw as u8;
 pub const XDP_ATTACHED_MULTI: u8 = XdpAttached::Multi as u8;
 
+impl TryFrom<u8> for XdpAttached {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            XDP_ATTACHED_NONE => Ok(XdpAttached::None),
+            XDP_ATTACHED_DRV => Ok(XdpAttached::Drv),
+            XDP_ATTACHED_SKB => Ok(XdpAttached::Skb),
+            XDP_ATTACHED_HW => Ok(XdpAttached::Hw),
+            XDP_ATTACHED_MULTI => Ok(XdpAttached::Multi),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
 #[tbname = "XdpTbl"]
 pub enum Xdp {
     // IFLA_XDP_
     Unspec,
-    Fd,
-    Attached,
-    Flags,
-    ProgId,
-    DrvProgId,
-    SkbProgId,
-    HwProgId,
-    ExpectedFd,
+
+    #[nla_type(i32, fd)]
+    Fd, // BPF program fd, -1 to detach
+
+    #[nla_type(u8, attached)]
+    Attached, // XDP_ATTACHED_, output only
+
+    #[nla_type(u32, flags)]
+    Flags, // XDP_FLAGS_
+
+    #[nla_type(u32, prog_id)]
+    ProgId, // output only
+
+    #[nla_type(u32, drv_prog_id)]
+    DrvProgId, // output only
+
+    #[nla_type(u32, skb_prog_id)]
+    SkbProgId, // output only
+
+    #[nla_type(u32, hw_prog_id)]
+    HwProgId, // output only
+
+    #[nla_type(i32, expected_fd)]
+    ExpectedFd, // with XDP_FLAGS_REPLACE
+
     _MAX,
 }
 
+impl Xdp {
+    // Put IFLA_XDP nest which attaches the program. flags are XDP_FLAGS_,
+    // mode and XDP_FLAGS_UPDATE_IF_NOEXIST. expected_fd replaces the
+    // program atomically, only if it is the currently attached one, and
+    // XDP_FLAGS_REPLACE is added for it.
+    pub fn put_attach(
+        nlv: &mut MsgVec,
+        fd: c_int,
+        flags: u32,
+        expected_fd: Option<c_int>,
+    ) -> Result<&mut MsgVec> {
+        let mut flags = flags;
+        if expected_fd.is_some() {
+            flags |= XDP_FLAGS_REPLACE;
+        }
+        if flags & !XDP_FLAGS_MASK != 0 {
+            return Err(Errno(libc::EINVAL));
+        }
+        nlv.nest_start(Ifla::Xdp)?;
+        Xdp::put_fd(nlv, &fd)?;
+        if flags != 0 {
+            Xdp::put_flags(nlv, &flags)?;
+        }
+        if let Some(efd) = expected_fd {
+            Xdp::put_expected_fd(nlv, &efd)?;
+        }
+        nlv.nest_end()
+    }
+
+    // mode is one of XDP_FLAGS_SKB_MODE, XDP_FLAGS_DRV_MODE and
+    // XDP_FLAGS_HW_MODE, or 0 for the kernel's choice.
+    pub fn put_detach(nlv: &mut MsgVec, mode: u32) -> Result<&mut MsgVec> {
+        Xdp::put_attach(nlv, -1, mode, None)
+    }
+}
+
+impl<'a> XdpTbl<'a> {
+    pub fn attached_mode(&self) -> Result<Option<XdpAttached>> {
+        match self.attached()? {
+            Some(v) => Ok(Some(XdpAttached::try_from(*v)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Attached programs' mode and ID. IFLA_XDP_PROG_ID is for the only
+    // program, and IFLA_XDP_{DRV,SKB,HW}_PROG_ID exist for each mode.
+    pub fn programs(&self) -> Result<Vec<(XdpAttached, u32)>> {
+        let mut v = Vec::new();
+        match self.attached_mode()? {
+            None | Some(XdpAttached::None) => {}
+            Some(XdpAttached::Multi) => {
+                if let Some(id) = self.drv_prog_id()? {
+                    v.push((XdpAttached::Drv, *id));
+                }
+                if let Some(id) = self.skb_prog_id()? {
+                    v.push((XdpAttached::Skb, *id));
+                }
+                if let Some(id) = self.hw_prog_id()? {
+                    v.push((XdpAttached::Hw, *id));
+                }
+            }
+            Some(mode) => {
+                if let Some(id) = self.prog_id()? {
+                    v.push((mode, *id));
+                }
+            }
+        }
+        Ok(v)
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Event {
@@ -1402,3 +2668,311 @@ pub struct IflaRmnetFlags {
     flags: u32,
     mask: u32,
 }
+
+// IFLA_INFO_DATA and IFLA_INFO_SLAVE_DATA are nested attributes whose
+// contents depend on IFLA_INFO_KIND and IFLA_INFO_SLAVE_KIND.
+pub enum LinkInfo<'a> {
+    Bridge(BrTbl<'a>),
+    Vlan(VlanTbl<'a>),
+    Macvlan(MacvlanTbl<'a>),
+    Macvtap(MacvlanTbl<'a>),
+    Vrf(VrfTbl<'a>),
+    Macsec(MacsecTbl<'a>),
+    Xfrm(XfrmTbl<'a>),
+    Ipvlan(IpvlanTbl<'a>),
+    Ipvtap(IpvlanTbl<'a>),
+    Vxlan(VxlanTbl<'a>),
+    Geneve(GeneveTbl<'a>),
+    Bareudp(BareUdpTbl<'a>),
+    Ppp(PppTbl<'a>),
+    Gtp(GtpTbl<'a>),
+    Bond(BondTbl<'a>),
+    Ipoib(IopbTbl<'a>),
+    Hsr(HsrTbl<'a>),
+    Tun(TunTbl<'a>),
+    Rmnet(RmnetTbl<'a>),
+    Veth(VethInfoTbl<'a>),
+    Ipip(IptunTbl<'a>),
+    Sit(IptunTbl<'a>),
+    Ip6tnl(IptunTbl<'a>),
+    Gre(GreTbl<'a>),
+    Gretap(GreTbl<'a>),
+    Ip6gre(GreTbl<'a>),
+    Ip6gretap(GreTbl<'a>),
+    Erspan(GreTbl<'a>),
+    Ip6erspan(GreTbl<'a>),
+    Vti(VtiTbl<'a>),
+    Vti6(VtiTbl<'a>),
+    Can(CanTbl<'a>),
+    Vxcan(VxcanInfoTbl<'a>),
+    Unknown(&'a str, &'a [u8]), // kind, raw IFLA_INFO_DATA payload
+}
+
+pub enum LinkSlaveInfo<'a> {
+    Bridge(Box<BrportTbl<'a>>), // much larger than the others
+    Bond(BondSlaveTbl<'a>),
+    Vrf(VrfPortTbl<'a>),
+    Unknown(&'a str, &'a [u8]), // slave kind, raw IFLA_INFO_SLAVE_DATA payload
+}
+
+impl Info {
+    // put attributes of the table for IFLA_INFO_KIND, then nest_end()
+    pub fn data_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Info::Data)
+    }
+
+    pub fn slave_data_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Info::SlaveData)
+    }
+}
+
+impl<'a> InfoTbl<'a> {
+    pub fn data(&self) -> Result<Option<LinkInfo<'a>>> {
+        let data = match self[Info::Data] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let kind = self[Info::Kind].ok_or(Errno(libc::EINVAL))?.cstr()?;
+        Ok(Some(match kind {
+            "bridge" => LinkInfo::Bridge(BrTbl::from_nest(data)?),
+            "vlan" => LinkInfo::Vlan(VlanTbl::from_nest(data)?),
+            "macvlan" => LinkInfo::Macvlan(MacvlanTbl::from_nest(data)?),
+            "macvtap" => LinkInfo::Macvtap(MacvlanTbl::from_nest(data)?),
+            "vrf" => LinkInfo::Vrf(VrfTbl::from_nest(data)?),
+            "macsec" => LinkInfo::Macsec(MacsecTbl::from_nest(data)?),
+            "xfrm" => LinkInfo::Xfrm(XfrmTbl::from_nest(data)?),
+            "ipvlan" => LinkInfo::Ipvlan(IpvlanTbl::from_nest(data)?),
+            "ipvtap" => LinkInfo::Ipvtap(IpvlanTbl::from_nest(data)?),
+            "vxlan" => LinkInfo::Vxlan(VxlanTbl::from_nest(data)?),
+            "geneve" => LinkInfo::Geneve(GeneveTbl::from_nest(data)?),
+            "bareudp" => LinkInfo::Bareudp(BareUdpTbl::from_nest(data)?),
+            "ppp" => LinkInfo::Ppp(PppTbl::from_nest(data)?),
+            "gtp" => LinkInfo::Gtp(GtpTbl::from_nest(data)?),
+            "bond" => LinkInfo::Bond(BondTbl::from_nest(data)?),
+            "ipoib" => LinkInfo::Ipoib(IopbTbl::from_nest(data)?),
+            "hsr" => LinkInfo::Hsr(HsrTbl::from_nest(data)?),
+            "tun" => LinkInfo::Tun(TunTbl::from_nest(data)?),
+            "rmnet" => LinkInfo::Rmnet(RmnetTbl::from_nest(data)?),
+            "veth" => LinkInfo::Veth(VethInfoTbl::from_nest(data)?),
+            "ipip" => LinkInfo::Ipip(IptunTbl::from_nest(data)?),
+            "sit" => LinkInfo::Sit(IptunTbl::from_nest(data)?),
+            "ip6tnl" => LinkInfo::Ip6tnl(IptunTbl::from_nest(data)?),
+            "gre" => LinkInfo::Gre(GreTbl::from_nest(data)?),
+            "gretap" => LinkInfo::Gretap(GreTbl::from_nest(data)?),
+            "ip6gre" => LinkInfo::Ip6gre(GreTbl::from_nest(data)?),
+            "ip6gretap" => LinkInfo::Ip6gretap(GreTbl::from_nest(data)?),
+            "erspan" => LinkInfo::Erspan(GreTbl::from_nest(data)?),
+            "ip6erspan" => LinkInfo::Ip6erspan(GreTbl::from_nest(data)?),
+            "vti" => LinkInfo::Vti(VtiTbl::from_nest(data)?),
+            "vti6" => LinkInfo::Vti6(VtiTbl::from_nest(data)?),
+            "can" => LinkInfo::Can(CanTbl::from_nest(data)?),
+            "vxcan" => LinkInfo::Vxcan(VxcanInfoTbl::from_nest(data)?),
+            _ => LinkInfo::Unknown(kind, payload_bytes(data)),
+        }))
+    }
+
+    pub fn slave_data(&self) -> Result<Option<LinkSlaveInfo<'a>>> {
+        let data = match self[Info::SlaveData] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let kind = self[Info::SlaveKind].ok_or(Errno(libc::EINVAL))?.cstr()?;
+        Ok(Some(match kind {
+            "bridge" => LinkSlaveInfo::Bridge(Box::new(BrportTbl::from_nest(data)?)),
+            "bond" => LinkSlaveInfo::Bond(BondSlaveTbl::from_nest(data)?),
+            "vrf" => LinkSlaveInfo::Vrf(VrfPortTbl::from_nest(data)?),
+            _ => LinkSlaveInfo::Unknown(kind, payload_bytes(data)),
+        }))
+    }
+}
+
+// A link to create, change or delete. The existing link is specified by
+// index, or by name if index is 0. kind is IFLA_INFO_KIND, required on
+// creating and changing the kind specific parameters.
+//
+// The builders put the header, ifinfomsg and IFLA_IFNAME, other IFLA_
+// attributes can be put after that, e.g. Ifla::put_mtu().
+#[derive(Debug, Clone, Default)]
+pub struct LinkRequest {
+    pub index: i32,
+    pub name: String,
+    pub kind: String,
+    pub flags: u32,  // ifi_flags, IFF_*
+    pub change: u32, // ifi_change, mask of the flags to change
+}
+
+impl LinkRequest {
+    pub fn new(name: &str, kind: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn put_request<'a>(
+        &self,
+        nlv: &'a mut MsgVec,
+        mtype: u16,
+        flags: u16,
+        seq: u32,
+    ) -> Result<&'a mut MsgVec> {
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = mtype;
+        nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16 | flags;
+        nlh.nlmsg_seq = seq;
+        let ifm = nlv.put_extra_header::<Ifinfomsg>()?;
+        ifm.ifi_family = libc::AF_UNSPEC as u8;
+        ifm.ifi_index = self.index;
+        ifm.ifi_flags = self.flags;
+        ifm.ifi_change = self.change;
+        if !self.name.is_empty() {
+            Ifla::put_ifname(nlv, &self.name)?;
+        }
+        Ok(nlv)
+    }
+
+    // IFLA_LINKINFO with IFLA_INFO_KIND and IFLA_INFO_DATA, which is not
+    // put if data puts nothing.
+    fn put_linkinfo<'a, F: FnOnce(&mut MsgVec) -> Result<()>>(
+        &self,
+        nlv: &'a mut MsgVec,
+        data: F,
+    ) -> Result<&'a mut MsgVec> {
+        if self.kind.is_empty() {
+            return Err(Errno(libc::EINVAL));
+        }
+        Ifla::linkinfo_start(nlv)?;
+        Info::put_kind(nlv, &self.kind)?;
+        Info::data_start(nlv)?;
+        let len = nlv.len();
+        data(nlv)?;
+        if nlv.len() == len {
+            nlv.nest_cancel()?;
+        } else {
+            nlv.nest_end()?;
+        }
+        nlv.nest_end()
+    }
+
+    // RTM_NEWLINK, EEXIST if the name exists. data puts the attributes by
+    // the kind in IFLA_INFO_DATA, e.g.
+    //   |nlv| { Vlan::put_id(nlv, &100)?; Ok(()) }
+    pub fn put_add<'a, F: FnOnce(&mut MsgVec) -> Result<()>>(
+        &self,
+        nlv: &'a mut MsgVec,
+        seq: u32,
+        data: F,
+    ) -> Result<&'a mut MsgVec> {
+        self.put_request(
+            nlv,
+            rtnetlink::RTM_NEWLINK,
+            (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+        )?;
+        self.put_linkinfo(nlv, data)
+    }
+
+    // RTM_NEWLINK without NLM_F_CREATE, changes the kind specific parameters
+    // of the existing, which RTM_SETLINK does not handle.
+    pub fn put_change<'a, F: FnOnce(&mut MsgVec) -> Result<()>>(
+        &self,
+        nlv: &'a mut MsgVec,
+        seq: u32,
+        data: F,
+    ) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_NEWLINK, 0, seq)?;
+        self.put_linkinfo(nlv, data)
+    }
+
+    // RTM_SETLINK, changes the generic parameters only, kind is ignored.
+    pub fn put_set<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_SETLINK, 0, seq)
+    }
+
+    // RTM_DELLINK, the peer of veth is also deleted.
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_DELLINK, 0, seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mnl::CbStatus;
+
+    // IFLA_AF_SPEC like nest which has the conf array of n values, 1 to n
+    fn af_spec<T: Into<u16>>(atype: T, n: u32) -> MsgVec {
+        let b: Vec<u8> = (1..=n).flat_map(|v| v.to_ne_bytes().to_vec()).collect();
+        let mut nlv = MsgVec::new();
+        nlv.put_header();
+        nlv.nest_start(1u16).unwrap();
+        nlv.put_bytes(atype, &b).unwrap();
+        nlv.nest_end().unwrap();
+        nlv
+    }
+
+    #[test]
+    fn inet_conf() {
+        // IPV4_DEVCONF_ starts from 1, stored at [0]
+        let nlv = af_spec(Inet::Conf, 3);
+        nlv.msghdr()
+            .unwrap()
+            .parse(0, |nest| {
+                let tb = InetTbl::from_nest(nest)?;
+                let conf = tb.conf()?.unwrap();
+                assert_eq!(conf.forwarding, 1);
+                assert_eq!(conf.proxy_arp, 3);
+                // not reported by the kernel
+                assert_eq!(conf.accept_redirects, 0);
+                assert_eq!(tb.conf_value(Ipv4Devconf::Forwarding), Some(1));
+                assert_eq!(tb.conf_value(Ipv4Devconf::ProxyArp), Some(3));
+                assert_eq!(tb.conf_value(Ipv4Devconf::AcceptRedirects), None);
+                Ok(CbStatus::Ok)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn inet6_conf() {
+        // DEVCONF_ starts from 0, the kernel newer than this knows more
+        let n = Devconf::RaDefrtrMetric as u32 + 5;
+        let nlv = af_spec(Inet6::Conf, n);
+        nlv.msghdr()
+            .unwrap()
+            .parse(0, |nest| {
+                let tb = IflaInet6Tbl::from_nest(nest)?;
+                let conf = tb.conf()?.unwrap();
+                assert_eq!(conf.forwarding, 1);
+                assert_eq!(conf.mtu6, Devconf::Mtu6 as i32 + 1);
+                assert_eq!(conf.ra_defrtr_metric, Devconf::RaDefrtrMetric as u32 + 1);
+                assert_eq!(tb.conf_value(Devconf::Hoplimit), Some(2));
+                assert_eq!(
+                    tb.conf_value(Devconf::RplSegEnabled),
+                    Some(Devconf::RplSegEnabled as i32 + 1)
+                );
+                Ok(CbStatus::Ok)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn empty_conf() {
+        // the empty attribute at the end of the nest is not iterated
+        let mut nlv = MsgVec::new();
+        nlv.put_header();
+        nlv.nest_start(1u16).unwrap();
+        nlv.put_flag(Inet::Conf).unwrap();
+        nlv.put(Inet::Unspec, &0u32).unwrap();
+        nlv.nest_end().unwrap();
+        nlv.msghdr()
+            .unwrap()
+            .parse(0, |nest| {
+                let tb = InetTbl::from_nest(nest)?;
+                assert_eq!(tb.conf()?.unwrap().forwarding, 0);
+                assert_eq!(tb.conf_value(Ipv4Devconf::Forwarding), None);
+                Ok(CbStatus::Ok)
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,906 @@
+use errno::Errno;
+use std::mem;
+
+use mnl::{Attr, AttrTbl, MsgVec, Msghdr, Result, Socket};
+
+use genetlink::{Family, Genlmsghdr};
+use util::{put_64bit, run, seq};
+
+pub const MACSEC_GENL_NAME: &str = "macsec";
+pub const MACSEC_GENL_VERSION: u8 = 1;
+
+pub const MACSEC_MAX_KEY_LEN: usize = 128;
+
+pub const MACSEC_KEYID_LEN: usize = 16;
+
+pub const MACSEC_SALT_LEN: usize = 12;
+
+// cipher IDs as per IEEE802.1AE-2018 (Table 14-1)
+pub const MACSEC_CIPHER_ID_GCM_AES_128: u64 = 0x0080C20001000001;
+pub const MACSEC_CIPHER_ID_GCM_AES_256: u64 = 0x0080C20001000002;
+pub const MACSEC_CIPHER_ID_GCM_AES_XPN_128: u64 = 0x0080C20001000003;
+pub const MACSEC_CIPHER_ID_GCM_AES_XPN_256: u64 = 0x0080C20001000004;
+
+// deprecated cipher ID for GCM-AES-128
+pub const MACSEC_DEFAULT_CIPHER_ID: u64 = 0x0080020001000001;
+pub const MACSEC_DEFAULT_CIPHER_ALT: u64 = MACSEC_CIPHER_ID_GCM_AES_128;
+
+pub const MACSEC_MIN_ICV_LEN: u8 = 8;
+pub const MACSEC_MAX_ICV_LEN: u8 = 32;
+// upper limit for ICV length as recommended by IEEE802.1AE-2006
+pub const MACSEC_STD_ICV_LEN: u8 = 16;
+
+// association number, 2 bits
+pub const MACSEC_NUM_AN: u8 = 4;
+
+// SCI is the MAC address followed by the port number in network byte order,
+// and is treated as u64 in native byte order on netlink.
+pub fn macsec_sci(addr: &[u8; 6], port: u16) -> u64 {
+    let port = port.to_be_bytes();
+    u64::from_ne_bytes([
+        addr[0], addr[1], addr[2], addr[3], addr[4], addr[5], port[0], port[1],
+    ])
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecAttrTbl"]
+pub enum MacsecAttr {
+    // MACSEC_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u32, ifindex)]
+    Ifindex, // u32, ifindex of the MACsec netdevice
+
+    #[nla_nest(MacsecRxscAttrTbl, rxsc_config)]
+    RxscConfig, // config, nested macsec_rxsc_attrs
+
+    #[nla_nest(MacsecSaAttrTbl, sa_config)]
+    SaConfig, // config, nested macsec_sa_attrs
+
+    #[nla_nest(MacsecSecyAttrTbl, secy)]
+    Secy, // dump, nested macsec_secy_attrs
+
+    #[nla_nest([MacsecSaAttrTbl], txsa_list)]
+    TxsaList, // dump, nested, macsec_sa_attrs for each TXSA
+
+    #[nla_nest([MacsecRxscAttrTbl], rxsc_list)]
+    RxscList, // dump, nested, macsec_rxsc_attrs for each RXSC
+
+    #[nla_nest(MacsecTxscStatsAttrTbl, txsc_stats)]
+    TxscStats, // dump, nested, macsec_txsc_stats_attr
+
+    #[nla_nest(MacsecSecyStatsAttrTbl, secy_stats)]
+    SecyStats, // dump, nested, macsec_secy_stats_attr
+
+    #[nla_nest(MacsecOffloadAttrTbl, offload)]
+    Offload, // config, nested, macsec_offload_attrs
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecSecyAttrTbl"]
+pub enum MacsecSecyAttr {
+    // MACSEC_SECY_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u64, sci)]
+    Sci,
+
+    #[nla_type(u8, encoding_sa)]
+    EncodingSa,
+
+    #[nla_type(u32, window)]
+    Window,
+
+    #[nla_type(u64, cipher_suite)]
+    CipherSuite,
+
+    #[nla_type(u8, icv_len)]
+    IcvLen,
+
+    #[nla_type(u8, protect)]
+    Protect,
+
+    #[nla_type(u8, replay)]
+    Replay,
+
+    #[nla_type(u8, oper)]
+    Oper,
+
+    #[nla_type(u8, validate)]
+    Validate, // MacsecValidationType
+
+    #[nla_type(u8, encrypt)]
+    Encrypt,
+
+    #[nla_type(u8, inc_sci)]
+    IncSci,
+
+    #[nla_type(u8, es)]
+    Es,
+
+    #[nla_type(u8, scb)]
+    Scb,
+
+    Pad,
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecRxscAttrTbl"]
+pub enum MacsecRxscAttr {
+    // MACSEC_RXSC_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u64, sci)]
+    Sci, // config/dump, u64
+
+    #[nla_type(u8, active)]
+    Active, // config/dump, u8 0..1
+
+    #[nla_nest([MacsecSaAttrTbl], sa_list)]
+    SaList, // dump, nested
+
+    #[nla_nest(MacsecRxscStatsAttrTbl, stats)]
+    Stats, // dump, nested, macsec_rxsc_stats_attr
+
+    Pad,
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecSaAttrTbl"]
+pub enum MacsecSaAttr {
+    // MACSEC_SA_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u8, an)]
+    An, // config/dump, u8 0..3
+
+    #[nla_type(u8, active)]
+    Active, // config/dump, u8 0..1
+
+    Pn, // config/dump, u32/u64 (u64 if XPN), see MacsecSaAttrTbl::pn()
+
+    #[nla_type(bytes, key)]
+    Key, // config, data
+
+    #[nla_type([u8; 16], keyid)]
+    Keyid, // config/dump, 128-bit
+
+    #[nla_nest(MacsecSaStatsAttrTbl, stats)]
+    Stats, // dump, nested, macsec_sa_stats_attr
+
+    Pad,
+
+    #[nla_type(u32, ssci)]
+    Ssci, // config/dump, u32 - XPN only
+
+    #[nla_type([u8; 12], salt)]
+    Salt, // config, 96-bit - XPN only
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecOffloadAttrTbl"]
+pub enum MacsecOffloadAttr {
+    // MACSEC_OFFLOAD_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u8, otype)]
+    Type, // config/dump, u8 0..2, MacsecOffload
+
+    Pad,
+    _MAX,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MacsecNlCommands {
+    // MACSEC_CMD_
+    GetTxsc = 0,
+    AddRxsc,
+    DelRxsc,
+    UpdRxsc,
+    AddTxsa,
+    DelTxsa,
+    UpdTxsa,
+    AddRxsa,
+    DelRxsa,
+    UpdRxsa,
+    UpdOffload,
+}
+pub const MACSEC_CMD_GET_TXSC: u8 = MacsecNlCommands::GetTxsc as u8;
+pub const MACSEC_CMD_ADD_RXSC: u8 = MacsecNlCommands::AddRxsc as u8;
+pub const MACSEC_CMD_DEL_RXSC: u8 = MacsecNlCommands::DelRxsc as u8;
+pub const MACSEC_CMD_UPD_RXSC: u8 = MacsecNlCommands::UpdRxsc as u8;
+pub const MACSEC_CMD_ADD_TXSA: u8 = MacsecNlCommands::AddTxsa as u8;
+pub const MACSEC_CMD_DEL_TXSA: u8 = MacsecNlCommands::DelTxsa as u8;
+pub const MACSEC_CMD_UPD_TXSA: u8 = MacsecNlCommands::UpdTxsa as u8;
+pub const MACSEC_CMD_ADD_RXSA: u8 = MacsecNlCommands::AddRxsa as u8;
+pub const MACSEC_CMD_DEL_RXSA: u8 = MacsecNlCommands::DelRxsa as u8;
+pub const MACSEC_CMD_UPD_RXSA: u8 = MacsecNlCommands::UpdRxsa as u8;
+pub const MACSEC_CMD_UPD_OFFLOAD: u8 = MacsecNlCommands::UpdOffload as u8;
+
+// u64 per-RXSC stats
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecRxscStatsAttrTbl"]
+pub enum MacsecRxscStatsAttr {
+    // MACSEC_RXSC_STATS_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u64, in_octets_validated)]
+    InOctetsValidated,
+
+    #[nla_type(u64, in_octets_decrypted)]
+    InOctetsDecrypted,
+
+    #[nla_type(u64, in_pkts_unchecked)]
+    InPktsUnchecked,
+
+    #[nla_type(u64, in_pkts_delayed)]
+    InPktsDelayed,
+
+    #[nla_type(u64, in_pkts_ok)]
+    InPktsOk,
+
+    #[nla_type(u64, in_pkts_invalid)]
+    InPktsInvalid,
+
+    #[nla_type(u64, in_pkts_late)]
+    InPktsLate,
+
+    #[nla_type(u64, in_pkts_not_valid)]
+    InPktsNotValid,
+
+    #[nla_type(u64, in_pkts_not_using_sa)]
+    InPktsNotUsingSa,
+
+    #[nla_type(u64, in_pkts_unused_sa)]
+    InPktsUnusedSa,
+
+    Pad,
+    _MAX,
+}
+
+// u32 per-{RX,TX}SA stats
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecSaStatsAttrTbl"]
+pub enum MacsecSaStatsAttr {
+    // MACSEC_SA_STATS_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u32, in_pkts_ok)]
+    InPktsOk,
+
+    #[nla_type(u32, in_pkts_invalid)]
+    InPktsInvalid,
+
+    #[nla_type(u32, in_pkts_not_valid)]
+    InPktsNotValid,
+
+    #[nla_type(u32, in_pkts_not_using_sa)]
+    InPktsNotUsingSa,
+
+    #[nla_type(u32, in_pkts_unused_sa)]
+    InPktsUnusedSa,
+
+    #[nla_type(u32, out_pkts_protected)]
+    OutPktsProtected,
+
+    #[nla_type(u32, out_pkts_encrypted)]
+    OutPktsEncrypted,
+
+    Pad,
+    _MAX,
+}
+
+// u64 per-TXSC stats
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecTxscStatsAttrTbl"]
+pub enum MacsecTxscStatsAttr {
+    // MACSEC_TXSC_STATS_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u64, out_pkts_protected)]
+    OutPktsProtected,
+
+    #[nla_type(u64, out_pkts_encrypted)]
+    OutPktsEncrypted,
+
+    #[nla_type(u64, out_octets_protected)]
+    OutOctetsProtected,
+
+    #[nla_type(u64, out_octets_encrypted)]
+    OutOctetsEncrypted,
+
+    Pad,
+    _MAX,
+}
+
+// u64 per-SecY stats
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MacsecSecyStatsAttrTbl"]
+pub enum MacsecSecyStatsAttr {
+    // MACSEC_SECY_STATS_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u64, out_pkts_untagged)]
+    OutPktsUntagged,
+
+    #[nla_type(u64, in_pkts_untagged)]
+    InPktsUntagged,
+
+    #[nla_type(u64, out_pkts_too_long)]
+    OutPktsTooLong,
+
+    #[nla_type(u64, in_pkts_no_tag)]
+    InPktsNoTag,
+
+    #[nla_type(u64, in_pkts_bad_tag)]
+    InPktsBadTag,
+
+    #[nla_type(u64, in_pkts_unknown_sci)]
+    InPktsUnknownSci,
+
+    #[nla_type(u64, in_pkts_no_sci)]
+    InPktsNoSci,
+
+    #[nla_type(u64, in_pkts_overrun)]
+    InPktsOverrun,
+
+    Pad,
+    _MAX,
+}
+
+impl<'a> MacsecSaAttrTbl<'a> {
+    // u64 for XPN cipher suites, u32 for others
+    pub fn pn(&self) -> Result<Option<u64>> {
+        let attr = match self[MacsecSaAttr::Pn] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let b = attr.bytes_ref();
+        match b.len() {
+            4 => Ok(Some(u32::from_ne_bytes([b[0], b[1], b[2], b[3]]) as u64)),
+            8 => Ok(Some(u64::from_ne_bytes([
+                b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7],
+            ]))),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+// SA parameters in MACSEC_ATTR_SA_CONFIG, for MACSEC_CMD_{ADD,DEL,UPD}_{TX,RX}SA.
+// Adding requires pn and key, and keyid, also ssci and salt for XPN. xpn must
+// match the cipher suite of the SecY, the kernel rejects the PN otherwise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MacsecSaConfig<'a> {
+    pub an: u8,
+    pub xpn: bool, // pn is put in 64 bits for GCM-AES-XPN-*, 32 bits if not
+    pub pn: Option<u64>,
+    pub active: Option<bool>,
+    pub key: Option<&'a [u8]>,
+    pub keyid: Option<&'a [u8; MACSEC_KEYID_LEN]>,
+    pub ssci: Option<u32>,
+    pub salt: Option<&'a [u8; MACSEC_SALT_LEN]>,
+}
+
+impl MacsecAttr {
+    // Puts MACSEC_ATTR_RXSC_CONFIG which identifies the RXSC by sci, for
+    // MACSEC_CMD_{ADD,DEL,UPD}_RXSC and _RXSA.
+    pub fn put_rxsc(nlv: &mut MsgVec, sci: u64, active: Option<bool>) -> Result<&mut MsgVec> {
+        MacsecAttr::rxsc_config_start(nlv)?;
+        put_64bit(nlv, MacsecRxscAttr::Sci, MacsecRxscAttr::Pad, &sci)?;
+        if let Some(active) = active {
+            MacsecRxscAttr::put_active(nlv, &(active as u8))?;
+        }
+        nlv.nest_end()
+    }
+
+    pub fn put_sa<'a>(nlv: &'a mut MsgVec, sa: &MacsecSaConfig) -> Result<&'a mut MsgVec> {
+        if sa.an >= MACSEC_NUM_AN {
+            return Err(Errno(libc::EINVAL));
+        }
+        if let Some(key) = sa.key {
+            if key.is_empty() || key.len() > MACSEC_MAX_KEY_LEN {
+                return Err(Errno(libc::EINVAL));
+            }
+        }
+        MacsecAttr::sa_config_start(nlv)?;
+        MacsecSaAttr::put_an(nlv, &sa.an)?;
+        match sa.pn {
+            Some(0) => return Err(Errno(libc::EINVAL)),
+            Some(pn) if sa.xpn => {
+                put_64bit(nlv, MacsecSaAttr::Pn, MacsecSaAttr::Pad, &pn)?;
+            }
+            Some(pn) if pn <= u32::MAX as u64 => {
+                nlv.put(MacsecSaAttr::Pn, &(pn as u32))?;
+            }
+            Some(_) => return Err(Errno(libc::EINVAL)),
+            None => {}
+        }
+        if let Some(active) = sa.active {
+            MacsecSaAttr::put_active(nlv, &(active as u8))?;
+        }
+        if let Some(key) = sa.key {
+            MacsecSaAttr::put_key(nlv, key)?;
+        }
+        if let Some(keyid) = sa.keyid {
+            MacsecSaAttr::put_keyid(nlv, keyid)?;
+        }
+        if let Some(ssci) = sa.ssci {
+            MacsecSaAttr::put_ssci(nlv, &ssci)?;
+        }
+        if let Some(salt) = sa.salt {
+            MacsecSaAttr::put_salt(nlv, salt)?;
+        }
+        nlv.nest_end()
+    }
+}
+
+// MACSEC_SA_ATTR_STATS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MacsecSaStats {
+    pub in_pkts_ok: u32,
+    pub in_pkts_invalid: u32,
+    pub in_pkts_not_valid: u32,
+    pub in_pkts_not_using_sa: u32,
+    pub in_pkts_unused_sa: u32,
+    pub out_pkts_protected: u32,
+    pub out_pkts_encrypted: u32,
+}
+
+impl MacsecSaStats {
+    pub fn from_tbl(tb: &MacsecSaStatsAttrTbl) -> Result<Self> {
+        Ok(Self {
+            in_pkts_ok: tb.in_pkts_ok()?.copied().unwrap_or(0),
+            in_pkts_invalid: tb.in_pkts_invalid()?.copied().unwrap_or(0),
+            in_pkts_not_valid: tb.in_pkts_not_valid()?.copied().unwrap_or(0),
+            in_pkts_not_using_sa: tb.in_pkts_not_using_sa()?.copied().unwrap_or(0),
+            in_pkts_unused_sa: tb.in_pkts_unused_sa()?.copied().unwrap_or(0),
+            out_pkts_protected: tb.out_pkts_protected()?.copied().unwrap_or(0),
+            out_pkts_encrypted: tb.out_pkts_encrypted()?.copied().unwrap_or(0),
+        })
+    }
+}
+
+// MACSEC_RXSC_ATTR_STATS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MacsecRxscStats {
+    pub in_octets_validated: u64,
+    pub in_octets_decrypted: u64,
+    pub in_pkts_unchecked: u64,
+    pub in_pkts_delayed: u64,
+    pub in_pkts_ok: u64,
+    pub in_pkts_invalid: u64,
+    pub in_pkts_late: u64,
+    pub in_pkts_not_valid: u64,
+    pub in_pkts_not_using_sa: u64,
+    pub in_pkts_unused_sa: u64,
+}
+
+impl MacsecRxscStats {
+    pub fn from_tbl(tb: &MacsecRxscStatsAttrTbl) -> Result<Self> {
+        Ok(Self {
+            in_octets_validated: tb.in_octets_validated()?.copied().unwrap_or(0),
+            in_octets_decrypted: tb.in_octets_decrypted()?.copied().unwrap_or(0),
+            in_pkts_unchecked: tb.in_pkts_unchecked()?.copied().unwrap_or(0),
+            in_pkts_delayed: tb.in_pkts_delayed()?.copied().unwrap_or(0),
+            in_pkts_ok: tb.in_pkts_ok()?.copied().unwrap_or(0),
+            in_pkts_invalid: tb.in_pkts_invalid()?.copied().unwrap_or(0),
+            in_pkts_late: tb.in_pkts_late()?.copied().unwrap_or(0),
+            in_pkts_not_valid: tb.in_pkts_not_valid()?.copied().unwrap_or(0),
+            in_pkts_not_using_sa: tb.in_pkts_not_using_sa()?.copied().unwrap_or(0),
+            in_pkts_unused_sa: tb.in_pkts_unused_sa()?.copied().unwrap_or(0),
+        })
+    }
+}
+
+// MACSEC_ATTR_TXSC_STATS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MacsecTxscStats {
+    pub out_pkts_protected: u64,
+    pub out_pkts_encrypted: u64,
+    pub out_octets_protected: u64,
+    pub out_octets_encrypted: u64,
+}
+
+impl MacsecTxscStats {
+    pub fn from_tbl(tb: &MacsecTxscStatsAttrTbl) -> Result<Self> {
+        Ok(Self {
+            out_pkts_protected: tb.out_pkts_protected()?.copied().unwrap_or(0),
+            out_pkts_encrypted: tb.out_pkts_encrypted()?.copied().unwrap_or(0),
+            out_octets_protected: tb.out_octets_protected()?.copied().unwrap_or(0),
+            out_octets_encrypted: tb.out_octets_encrypted()?.copied().unwrap_or(0),
+        })
+    }
+}
+
+// MACSEC_ATTR_SECY_STATS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MacsecSecyStats {
+    pub out_pkts_untagged: u64,
+    pub in_pkts_untagged: u64,
+    pub out_pkts_too_long: u64,
+    pub in_pkts_no_tag: u64,
+    pub in_pkts_bad_tag: u64,
+    pub in_pkts_unknown_sci: u64,
+    pub in_pkts_no_sci: u64,
+    pub in_pkts_overrun: u64,
+}
+
+impl MacsecSecyStats {
+    pub fn from_tbl(tb: &MacsecSecyStatsAttrTbl) -> Result<Self> {
+        Ok(Self {
+            out_pkts_untagged: tb.out_pkts_untagged()?.copied().unwrap_or(0),
+            in_pkts_untagged: tb.in_pkts_untagged()?.copied().unwrap_or(0),
+            out_pkts_too_long: tb.out_pkts_too_long()?.copied().unwrap_or(0),
+            in_pkts_no_tag: tb.in_pkts_no_tag()?.copied().unwrap_or(0),
+            in_pkts_bad_tag: tb.in_pkts_bad_tag()?.copied().unwrap_or(0),
+            in_pkts_unknown_sci: tb.in_pkts_unknown_sci()?.copied().unwrap_or(0),
+            in_pkts_no_sci: tb.in_pkts_no_sci()?.copied().unwrap_or(0),
+            in_pkts_overrun: tb.in_pkts_overrun()?.copied().unwrap_or(0),
+        })
+    }
+}
+
+// A TXSA or RXSA in MACSEC_CMD_GET_TXSC, the key is not dumped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MacsecSa {
+    pub an: u8,
+    pub active: bool,
+    pub pn: u64, // the next PN
+    pub keyid: [u8; MACSEC_KEYID_LEN],
+    pub ssci: Option<u32>, // XPN only
+    pub stats: MacsecSaStats,
+}
+
+impl MacsecSa {
+    pub fn from_tbl(tb: &MacsecSaAttrTbl) -> Result<Self> {
+        Ok(Self {
+            an: *tb.an()?.ok_or(Errno(libc::EINVAL))?,
+            active: tb.active()? == Some(&1),
+            pn: tb.pn()?.unwrap_or(0),
+            keyid: tb.keyid()?.copied().unwrap_or_default(),
+            ssci: tb.ssci()?.copied(),
+            stats: match tb.stats()? {
+                Some(stats) => MacsecSaStats::from_tbl(&stats)?,
+                None => MacsecSaStats::default(),
+            },
+        })
+    }
+}
+
+fn sa_list(v: Option<Vec<MacsecSaAttrTbl>>) -> Result<Vec<MacsecSa>> {
+    v.unwrap_or_default()
+        .iter()
+        .map(MacsecSa::from_tbl)
+        .collect()
+}
+
+// A receive secure channel and its SAs
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MacsecRxsc {
+    pub sci: u64,
+    pub active: bool,
+    pub sas: Vec<MacsecSa>,
+    pub stats: MacsecRxscStats,
+}
+
+impl MacsecRxsc {
+    pub fn from_tbl(tb: &MacsecRxscAttrTbl) -> Result<Self> {
+        Ok(Self {
+            sci: *tb.sci()?.ok_or(Errno(libc::EINVAL))?,
+            active: tb.active()? == Some(&1),
+            sas: sa_list(tb.sa_list()?)?,
+            stats: match tb.stats()? {
+                Some(stats) => MacsecRxscStats::from_tbl(&stats)?,
+                None => MacsecRxscStats::default(),
+            },
+        })
+    }
+}
+
+// MACSEC_ATTR_SECY, the parameters set by the link, IFLA_MACSEC_
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MacsecSecy {
+    pub sci: u64,
+    pub cipher_suite: u64, // MACSEC_CIPHER_ID_
+    pub icv_len: u8,
+    pub encoding_sa: u8,
+    pub window: Option<u32>, // only if replay
+    pub oper: bool,
+    pub protect: bool,
+    pub replay: bool,
+    pub validate: u8, // MacsecValidationType
+    pub encrypt: bool,
+    pub inc_sci: bool,
+    pub es: bool,
+    pub scb: bool,
+}
+
+impl MacsecSecy {
+    pub fn from_tbl(tb: &MacsecSecyAttrTbl) -> Result<Self> {
+        Ok(Self {
+            sci: *tb.sci()?.ok_or(Errno(libc::EINVAL))?,
+            cipher_suite: tb.cipher_suite()?.copied().unwrap_or(0),
+            icv_len: tb.icv_len()?.copied().unwrap_or(0),
+            encoding_sa: tb.encoding_sa()?.copied().unwrap_or(0),
+            window: tb.window()?.copied(),
+            oper: tb.oper()? == Some(&1),
+            protect: tb.protect()? == Some(&1),
+            replay: tb.replay()? == Some(&1),
+            validate: tb.validate()?.copied().unwrap_or(0),
+            encrypt: tb.encrypt()? == Some(&1),
+            inc_sci: tb.inc_sci()? == Some(&1),
+            es: tb.es()? == Some(&1),
+            scb: tb.scb()? == Some(&1),
+        })
+    }
+}
+
+// A message of MACSEC_CMD_GET_TXSC dump, for each MACsec device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MacsecDevice {
+    pub ifindex: u32,
+    pub secy: MacsecSecy,
+    pub offload: Option<u8>, // MacsecOffload
+    pub txsc_stats: MacsecTxscStats,
+    pub secy_stats: MacsecSecyStats,
+    pub txsas: Vec<MacsecSa>,
+    pub rxscs: Vec<MacsecRxsc>,
+}
+
+impl MacsecDevice {
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let tb = MacsecAttrTbl::from_nlmsg(mem::size_of::<Genlmsghdr>(), nlh)?;
+        Ok(Self {
+            ifindex: *tb.ifindex()?.ok_or(Errno(libc::EINVAL))?,
+            secy: match tb.secy()? {
+                Some(secy) => MacsecSecy::from_tbl(&secy)?,
+                None => return Err(Errno(libc::EINVAL)),
+            },
+            offload: match tb.offload()? {
+                Some(offload) => offload.otype()?.copied(),
+                None => None,
+            },
+            txsc_stats: match tb.txsc_stats()? {
+                Some(stats) => MacsecTxscStats::from_tbl(&stats)?,
+                None => MacsecTxscStats::default(),
+            },
+            secy_stats: match tb.secy_stats()? {
+                Some(stats) => MacsecSecyStats::from_tbl(&stats)?,
+                None => MacsecSecyStats::default(),
+            },
+            txsas: sa_list(tb.txsa_list()?)?,
+            rxscs: tb
+                .rxsc_list()?
+                .unwrap_or_default()
+                .iter()
+                .map(MacsecRxsc::from_tbl)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+fn put_request(
+    nlv: &mut MsgVec,
+    family: &Family,
+    cmd: u8,
+    flags: u16,
+    ifindex: Option<u32>,
+) -> Result<u32> {
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = family.id;
+    nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let genlh = nlv.put_extra_header::<Genlmsghdr>()?;
+    genlh.cmd = cmd;
+    genlh.version = MACSEC_GENL_VERSION;
+    if let Some(ifindex) = ifindex {
+        MacsecAttr::put_ifindex(nlv, &ifindex)?;
+    }
+    Ok(seq)
+}
+
+// family is the resolved MACSEC_GENL_NAME. The kernel does not filter the
+// dump by ifindex, all MACsec devices in the netns are returned.
+pub fn dump(nl: &mut Socket, family: &Family) -> Result<Vec<MacsecDevice>> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(
+        &mut nlv,
+        family,
+        MACSEC_CMD_GET_TXSC,
+        libc::NLM_F_DUMP as u16,
+        None,
+    )?;
+    let mut devices = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        devices.push(MacsecDevice::from_nlmsg(nlh)?);
+        Ok(())
+    })?;
+    Ok(devices)
+}
+
+// Sends cmd with RXSC_CONFIG if rxsc, and SA_CONFIG if sa, then waits the
+// ack.
+fn request(
+    nl: &mut Socket,
+    family: &Family,
+    cmd: u8,
+    ifindex: u32,
+    rxsc: Option<(u64, Option<bool>)>,
+    sa: Option<&MacsecSaConfig>,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(&mut nlv, family, cmd, libc::NLM_F_ACK as u16, Some(ifindex))?;
+    if let Some((sci, active)) = rxsc {
+        MacsecAttr::put_rxsc(&mut nlv, sci, active)?;
+    }
+    if let Some(sa) = sa {
+        MacsecAttr::put_sa(&mut nlv, sa)?;
+    }
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// active is true by default
+pub fn add_rxsc(
+    nl: &mut Socket,
+    family: &Family,
+    ifindex: u32,
+    sci: u64,
+    active: Option<bool>,
+) -> Result<()> {
+    request(
+        nl,
+        family,
+        MACSEC_CMD_ADD_RXSC,
+        ifindex,
+        Some((sci, active)),
+        None,
+    )
+}
+
+pub fn del_rxsc(nl: &mut Socket, family: &Family, ifindex: u32, sci: u64) -> Result<()> {
+    request(
+        nl,
+        family,
+        MACSEC_CMD_DEL_RXSC,
+        ifindex,
+        Some((sci, None)),
+        None,
+    )
+}
+
+pub fn upd_rxsc(
+    nl: &mut Socket,
+    family: &Family,
+    ifindex: u32,
+    sci: u64,
+    active: bool,
+) -> Result<()> {
+    request(
+        nl,
+        family,
+        MACSEC_CMD_UPD_RXSC,
+        ifindex,
+        Some((sci, Some(active))),
+        None,
+    )
+}
+
+pub fn add_txsa(nl: &mut Socket, family: &Family, ifindex: u32, sa: &MacsecSaConfig) -> Result<()> {
+    request(nl, family, MACSEC_CMD_ADD_TXSA, ifindex, None, Some(sa))
+}
+
+pub fn del_txsa(nl: &mut Socket, family: &Family, ifindex: u32, an: u8) -> Result<()> {
+    let sa = MacsecSaConfig {
+        an,
+        ..Default::default()
+    };
+    request(nl, family, MACSEC_CMD_DEL_TXSA, ifindex, None, Some(&sa))
+}
+
+// Only pn and active can be updated.
+pub fn upd_txsa(nl: &mut Socket, family: &Family, ifindex: u32, sa: &MacsecSaConfig) -> Result<()> {
+    request(nl, family, MACSEC_CMD_UPD_TXSA, ifindex, None, Some(sa))
+}
+
+pub fn add_rxsa(
+    nl: &mut Socket,
+    family: &Family,
+    ifindex: u32,
+    sci: u64,
+    sa: &MacsecSaConfig,
+) -> Result<()> {
+    request(
+        nl,
+        family,
+        MACSEC_CMD_ADD_RXSA,
+        ifindex,
+        Some((sci, None)),
+        Some(sa),
+    )
+}
+
+pub fn del_rxsa(nl: &mut Socket, family: &Family, ifindex: u32, sci: u64, an: u8) -> Result<()> {
+    let sa = MacsecSaConfig {
+        an,
+        ..Default::default()
+    };
+    request(
+        nl,
+        family,
+        MACSEC_CMD_DEL_RXSA,
+        ifindex,
+        Some((sci, None)),
+        Some(&sa),
+    )
+}
+
+// Only pn and active can be updated.
+pub fn upd_rxsa(
+    nl: &mut Socket,
+    family: &Family,
+    ifindex: u32,
+    sci: u64,
+    sa: &MacsecSaConfig,
+) -> Result<()> {
+    request(
+        nl,
+        family,
+        MACSEC_CMD_UPD_RXSA,
+        ifindex,
+        Some((sci, None)),
+        Some(sa),
+    )
+}
+
+// offload is MacsecOffload.
+pub fn upd_offload(nl: &mut Socket, family: &Family, ifindex: u32, offload: u8) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(
+        &mut nlv,
+        family,
+        MACSEC_CMD_UPD_OFFLOAD,
+        libc::NLM_F_ACK as u16,
+        Some(ifindex),
+    )?;
+    MacsecAttr::offload_start(&mut nlv)?;
+    MacsecOffloadAttr::put_otype(&mut nlv, &offload)?;
+    nlv.nest_end()?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sci() {
+        let sci = macsec_sci(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01], 1);
+        // bytes in memory are as on the wire
+        assert_eq!(
+            sci.to_ne_bytes(),
+            [0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01]
+        );
+        assert_eq!(u64::from_be(sci), 0x0200_0000_0001_0001);
+        assert_eq!(
+            macsec_sci(&[0xff; 6], 0xabcd).to_ne_bytes()[6..],
+            [0xab, 0xcd]
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use errno::Errno;
+use std::{convert::TryFrom, mem};
+
+use mnl::{Attr, AttrTbl, MsgVec, Msghdr, Result, Socket};
+
+use genetlink::{Family, Genlmsghdr};
+use util::{run, seq};
+
+pub const TEAM_STRING_MAX_LEN: usize = 32;
+
+pub const TEAM_GENL_NAME: &str = "team";
+pub const TEAM_GENL_VERSION: u8 = 1;
+pub const TEAM_GENL_CHANGE_EVENT_MC_GRP_NAME: &str = "change_event";
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TeamCmd {
+    // TEAM_CMD_
+    Noop = 0,
+    OptionsSet,
+    OptionsGet,
+    PortListGet,
+    _MAX,
+}
+pub const TEAM_CMD_NOOP: u8 = TeamCmd::Noop as u8;
+pub const TEAM_CMD_OPTIONS_SET: u8 = TeamCmd::OptionsSet as u8;
+pub const TEAM_CMD_OPTIONS_GET: u8 = TeamCmd::OptionsGet as u8;
+pub const TEAM_CMD_PORT_LIST_GET: u8 = TeamCmd::PortListGet as u8;
+pub const __TEAM_CMD_MAX: u8 = TeamCmd::_MAX as u8;
+pub const TEAM_CMD_MAX: u8 = __TEAM_CMD_MAX - 1;
+
+// TEAM_ATTR_LIST_OPTION and TEAM_ATTR_LIST_PORT contain
+// TEAM_ATTR_ITEM_OPTION and TEAM_ATTR_ITEM_PORT nests respectively, each of
+// them can be parsed as an array element.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TeamAttrTbl"]
+pub enum TeamAttr {
+    // TEAM_ATTR_
+    Unspec = 0,
+
+    #[nla_type(u32, team_ifindex)]
+    TeamIfindex,
+
+    #[nla_nest([TeamOptionAttrTbl], list_option)]
+    ListOption,
+
+    #[nla_nest([TeamPortAttrTbl], list_port)]
+    ListPort,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum TeamItemOption {
+    // TEAM_ATTR_ITEM_OPTION_
+    Unspec = 0,
+
+    #[nla_nest(TeamOptionAttrTbl, option)]
+    Option,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TeamOptionAttrTbl"]
+pub enum TeamOptionAttr {
+    // TEAM_ATTR_OPTION_
+    Unspec = 0,
+
+    #[nla_type(cstr, name)]
+    Name,
+
+    #[nla_type(flag, changed)]
+    Changed,
+
+    #[nla_type(u8, otype)]
+    Type, // TeamOptionType
+
+    Data, // dynamic, by Type, see TeamOptionAttrTbl::data()
+
+    #[nla_type(flag, removed)]
+    Removed,
+
+    #[nla_type(u32, port_ifindex)]
+    PortIfindex, // for per-port options
+
+    #[nla_type(u32, array_index)]
+    ArrayIndex, // for array options
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+pub enum TeamItemPort {
+    // TEAM_ATTR_ITEM_PORT_
+    Unspec = 0,
+
+    #[nla_nest(TeamPortAttrTbl, port)]
+    Port,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TeamPortAttrTbl"]
+pub enum TeamPortAttr {
+    // TEAM_ATTR_PORT_
+    Unspec = 0,
+
+    #[nla_type(u32, ifindex)]
+    Ifindex,
+
+    #[nla_type(flag, changed)]
+    Changed,
+
+    #[nla_type(flag, linkup)]
+    Linkup,
+
+    #[nla_type(u32, speed)]
+    Speed,
+
+    #[nla_type(u8, duplex)]
+    Duplex,
+
+    #[nla_type(flag, removed)]
+    Removed,
+
+    _MAX,
+}
+
+// TEAM_ATTR_OPTION_TYPE, NLA_* in include/net/netlink.h, not exported to
+// uapi.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TeamOptionType {
+    U32 = 3,     // NLA_U32
+    String = 5,  // NLA_STRING
+    Bool = 6,    // NLA_FLAG
+    Binary = 11, // NLA_BINARY
+    S32 = 14,    // NLA_S32
+}
+
+impl TryFrom<u8> for TeamOptionType {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            3 => Ok(TeamOptionType::U32),
+            5 => Ok(TeamOptionType::String),
+            6 => Ok(TeamOptionType::Bool),
+            11 => Ok(TeamOptionType::Binary),
+            14 => Ok(TeamOptionType::S32),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TeamOptionValue {
+    U32(u32),
+    String(String),
+    Bool(bool),
+    Binary(Vec<u8>),
+    S32(i32),
+}
+
+impl TeamOptionValue {
+    pub fn otype(&self) -> TeamOptionType {
+        match self {
+            TeamOptionValue::U32(_) => TeamOptionType::U32,
+            TeamOptionValue::String(_) => TeamOptionType::String,
+            TeamOptionValue::Bool(_) => TeamOptionType::Bool,
+            TeamOptionValue::Binary(_) => TeamOptionType::Binary,
+            TeamOptionValue::S32(_) => TeamOptionType::S32,
+        }
+    }
+}
+
+impl<'a> TeamOptionAttrTbl<'a> {
+    // A bool option has no TEAM_ATTR_OPTION_DATA if false.
+    pub fn data(&self) -> Result<Option<TeamOptionValue>> {
+        let otype = match self.otype()? {
+            Some(t) => TeamOptionType::try_from(*t)?,
+            None => return Ok(None),
+        };
+        let attr = match self[TeamOptionAttr::Data] {
+            Some(attr) => attr,
+            None if otype == TeamOptionType::Bool => return Ok(Some(TeamOptionValue::Bool(false))),
+            None => return Ok(None),
+        };
+        Ok(Some(match otype {
+            TeamOptionType::U32 => TeamOptionValue::U32(*attr.value_ref::<u32>()?),
+            TeamOptionType::String => TeamOptionValue::String(attr.cstr()?.to_string()),
+            TeamOptionType::Bool => TeamOptionValue::Bool(true),
+            TeamOptionType::Binary => TeamOptionValue::Binary(attr.bytes_ref().to_vec()),
+            TeamOptionType::S32 => TeamOptionValue::S32(*attr.value_ref::<i32>()?),
+        }))
+    }
+}
+
+impl TeamOptionAttr {
+    // Puts TEAM_ATTR_OPTION_TYPE and TEAM_ATTR_OPTION_DATA.
+    pub fn put_data<'a>(nlv: &'a mut MsgVec, value: &TeamOptionValue) -> Result<&'a mut MsgVec> {
+        TeamOptionAttr::put_otype(nlv, &(value.otype() as u8))?;
+        match value {
+            TeamOptionValue::U32(v) => nlv.put(TeamOptionAttr::Data, v),
+            TeamOptionValue::String(v) => {
+                if v.len() >= TEAM_STRING_MAX_LEN {
+                    return Err(Errno(libc::EINVAL));
+                }
+                nlv.put_cstr(TeamOptionAttr::Data, v)
+            }
+            TeamOptionValue::Bool(true) => nlv.put_flag(TeamOptionAttr::Data),
+            TeamOptionValue::Bool(false) => Ok(nlv),
+            TeamOptionValue::Binary(v) => nlv.put_bytes(TeamOptionAttr::Data, v),
+            TeamOptionValue::S32(v) => nlv.put(TeamOptionAttr::Data, v),
+        }
+    }
+}
+
+// An option of the team device, or its port if port_ifindex is set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TeamOption {
+    pub name: String,
+    pub value: TeamOptionValue,
+    pub port_ifindex: Option<u32>,
+    pub array_index: Option<u32>,
+    pub changed: bool, // notified or got after the change
+    pub removed: bool,
+}
+
+impl TeamOption {
+    pub fn new(name: &str, value: TeamOptionValue) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            port_ifindex: None,
+            array_index: None,
+            changed: false,
+            removed: false,
+        }
+    }
+
+    pub fn from_tbl(tb: &TeamOptionAttrTbl) -> Result<Self> {
+        Ok(Self {
+            name: tb.name()?.ok_or(Errno(libc::EINVAL))?.to_string(),
+            value: tb.data()?.ok_or(Errno(libc::EINVAL))?,
+            port_ifindex: tb.port_ifindex()?.copied(),
+            array_index: tb.array_index()?.copied(),
+            changed: tb.changed()?,
+            removed: tb.removed()?,
+        })
+    }
+
+    // Puts a TEAM_ATTR_ITEM_OPTION nest to TEAM_ATTR_LIST_OPTION.
+    pub fn put<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        TeamItemOption::option_start(nlv)?;
+        TeamOptionAttr::put_name(nlv, &self.name)?;
+        TeamOptionAttr::put_data(nlv, &self.value)?;
+        if let Some(ifindex) = self.port_ifindex {
+            TeamOptionAttr::put_port_ifindex(nlv, &ifindex)?;
+        }
+        if let Some(index) = self.array_index {
+            TeamOptionAttr::put_array_index(nlv, &index)?;
+        }
+        nlv.nest_end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TeamPort {
+    pub ifindex: u32,
+    pub linkup: bool,
+    pub speed: u32, // Mb/s
+    pub duplex: u8, // DUPLEX_HALF or DUPLEX_FULL
+    pub changed: bool,
+    pub removed: bool,
+}
+
+impl TeamPort {
+    pub fn from_tbl(tb: &TeamPortAttrTbl) -> Result<Self> {
+        Ok(Self {
+            ifindex: *tb.ifindex()?.ok_or(Errno(libc::EINVAL))?,
+            linkup: tb.linkup()?,
+            speed: tb.speed()?.copied().unwrap_or(0),
+            duplex: tb.duplex()?.copied().unwrap_or(0),
+            changed: tb.changed()?,
+            removed: tb.removed()?,
+        })
+    }
+}
+
+// A message of TEAM_CMD_OPTIONS_GET or TEAM_CMD_PORT_LIST_GET, both of
+// replies and TEAM_GENL_CHANGE_EVENT_MC_GRP_NAME notifications. A
+// notification has the changed items only.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TeamEvent {
+    Options {
+        ifindex: u32,
+        options: Vec<TeamOption>,
+    },
+    Ports {
+        ifindex: u32,
+        ports: Vec<TeamPort>,
+    },
+}
+
+impl TeamEvent {
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let genlh = nlh.payload::<Genlmsghdr>()?;
+        let tb = TeamAttrTbl::from_nlmsg(mem::size_of::<Genlmsghdr>(), nlh)?;
+        let ifindex = *tb.team_ifindex()?.ok_or(Errno(libc::EINVAL))?;
+        match genlh.cmd {
+            TEAM_CMD_OPTIONS_GET => Ok(TeamEvent::Options {
+                ifindex,
+                options: tb
+                    .list_option()?
+                    .unwrap_or_default()
+                    .iter()
+                    .map(TeamOption::from_tbl)
+                    .collect::<Result<_>>()?,
+            }),
+            TEAM_CMD_PORT_LIST_GET => Ok(TeamEvent::Ports {
+                ifindex,
+                ports: tb
+                    .list_port()?
+                    .unwrap_or_default()
+                    .iter()
+                    .map(TeamPort::from_tbl)
+                    .collect::<Result<_>>()?,
+            }),
+            _ => Err(Errno(libc::EPROTO)),
+        }
+    }
+}
+
+// Joins the TEAM_GENL_CHANGE_EVENT_MC_GRP_NAME group, then receive and
+// parse by TeamEvent::from_nlmsg().
+pub fn subscribe(nl: &Socket, family: &Family) -> Result<()> {
+    let group = family
+        .mcast_group(TEAM_GENL_CHANGE_EVENT_MC_GRP_NAME)
+        .ok_or(Errno(libc::ENOENT))?;
+    nl.add_membership(group)
+}
+
+fn put_request(
+    nlv: &mut MsgVec,
+    family: &Family,
+    cmd: u8,
+    flags: u16,
+    ifindex: u32,
+) -> Result<u32> {
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = family.id;
+    nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let genlh = nlv.put_extra_header::<Genlmsghdr>()?;
+    genlh.cmd = cmd;
+    genlh.version = TEAM_GENL_VERSION;
+    TeamAttr::put_team_ifindex(nlv, &ifindex)?;
+    Ok(seq)
+}
+
+// family is the resolved TEAM_GENL_NAME.
+pub fn options_get(nl: &mut Socket, family: &Family, ifindex: u32) -> Result<Vec<TeamOption>> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(&mut nlv, family, TEAM_CMD_OPTIONS_GET, 0, ifindex)?;
+    let mut options = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        if let TeamEvent::Options { options: mut v, .. } = TeamEvent::from_nlmsg(nlh)? {
+            options.append(&mut v);
+        }
+        Ok(())
+    })?;
+    Ok(options)
+}
+
+pub fn options_set(
+    nl: &mut Socket,
+    family: &Family,
+    ifindex: u32,
+    options: &[TeamOption],
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(
+        &mut nlv,
+        family,
+        TEAM_CMD_OPTIONS_SET,
+        libc::NLM_F_ACK as u16,
+        ifindex,
+    )?;
+    TeamAttr::list_option_start(&mut nlv)?;
+    for option in options {
+        option.put(&mut nlv)?;
+    }
+    nlv.nest_end()?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn port_list_get(nl: &mut Socket, family: &Family, ifindex: u32) -> Result<Vec<TeamPort>> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(&mut nlv, family, TEAM_CMD_PORT_LIST_GET, 0, ifindex)?;
+    let mut ports = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        if let TeamEvent::Ports { ports: mut v, .. } = TeamEvent::from_nlmsg(nlh)? {
+            ports.append(&mut v);
+        }
+        Ok(())
+    })?;
+    Ok(ports)
+}
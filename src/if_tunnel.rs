@@ -0,0 +1,317 @@
+use errno::Errno;
+
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// GRE flags, i_flags and o_flags in struct ip_tunnel_parm,
+// IFLA_GRE_IFLAGS and IFLA_GRE_OFLAGS. These are __be16.
+pub const GRE_CSUM: u16 = 0x8000u16.to_be();
+pub const GRE_ROUTING: u16 = 0x4000u16.to_be();
+pub const GRE_KEY: u16 = 0x2000u16.to_be();
+pub const GRE_SEQ: u16 = 0x1000u16.to_be();
+pub const GRE_STRICT: u16 = 0x0800u16.to_be();
+pub const GRE_REC: u16 = 0x0700u16.to_be();
+pub const GRE_ACK: u16 = 0x0080u16.to_be();
+pub const GRE_FLAGS: u16 = 0x0078u16.to_be();
+pub const GRE_VERSION: u16 = 0x0007u16.to_be();
+
+pub const GRE_VERSION_0: u16 = 0x0000u16.to_be();
+pub const GRE_VERSION_1: u16 = 0x0001u16.to_be();
+pub const GRE_PROTO_PPP: u16 = 0x880bu16.to_be();
+pub const GRE_PPTP_KEY_MASK: u32 = 0xffffu32.to_be();
+
+// SIT-mode i_flags
+pub const SIT_ISATAP: u16 = 0x0001;
+
+// PRL flags
+pub const PRL_DEFAULT: u16 = 0x0001;
+
+// VTI-mode i_flags
+pub const VTI_ISVTI: u16 = 0x0001;
+
+// IFLA_IPTUN_FLAGS of ip6tnl, from ip6_tunnel.h
+pub const IP6_TNL_F_IGN_ENCAP_LIMIT: u32 = 0x1; // don't add encap limit if not in inner packet
+pub const IP6_TNL_F_USE_ORIG_TCLASS: u32 = 0x2; // copy the traffic class from inner packet
+pub const IP6_TNL_F_USE_ORIG_FLOWLABEL: u32 = 0x4; // copy the flowlabel from inner packet
+pub const IP6_TNL_F_MIP6_DEV: u32 = 0x8; // being used for Mobile IPv6
+pub const IP6_TNL_F_RCV_DSCP_COPY: u32 = 0x10; // copy DSCP from the outer packet
+pub const IP6_TNL_F_USE_ORIG_FWMARK: u32 = 0x20; // copy fwmark from inner packet
+pub const IP6_TNL_F_ALLOW_LOCAL_REMOTE: u32 = 0x40; // allow remote endpoint on the local node
+
+// ipip, sit and ip6tnl
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "IptunTbl"]
+pub enum Iptun {
+    // IFLA_IPTUN_
+    Unspec = 0,
+
+    #[nla_type(u32, link)]
+    Link,
+
+    #[nla_type(Ipv4Addr, local4)]
+    #[nla_type(Ipv6Addr, local6)] // ip6tnl
+    Local,
+
+    #[nla_type(Ipv4Addr, remote4)]
+    #[nla_type(Ipv6Addr, remote6)] // ip6tnl
+    Remote,
+
+    #[nla_type(u8, ttl)]
+    Ttl,
+
+    #[nla_type(u8, tos)]
+    Tos,
+
+    #[nla_type(u8, encap_limit)]
+    EncapLimit,
+
+    #[nla_type(u32, flowinfo)]
+    Flowinfo, // __be32
+
+    #[nla_type(u16, sit_flags)] // __be16 i_flags, SIT_ISATAP
+    #[nla_type(u32, flags)] // ip6tnl, IP6_TNL_F_
+    Flags,
+
+    #[nla_type(u8, proto)]
+    Proto, // IPPROTO_
+
+    #[nla_type(u8, pmtudisc)]
+    Pmtudisc,
+
+    #[nla_type(Ipv6Addr, sixrd_prefix)]
+    SixrdPrefix,
+
+    #[nla_type(Ipv4Addr, sixrd_relay_prefix)]
+    SixrdRelayPrefix,
+
+    #[nla_type(u16, sixrd_prefixlen)]
+    SixrdPrefixlen,
+
+    #[nla_type(u16, sixrd_relay_prefixlen)]
+    SixrdRelayPrefixlen,
+
+    #[nla_type(u16, encap_type)]
+    EncapType, // TUNNEL_ENCAP_
+
+    #[nla_type(u16, encap_flags)]
+    EncapFlags, // TUNNEL_ENCAP_FLAG_
+
+    #[nla_type(u16, encap_sport)]
+    EncapSport, // __be16
+
+    #[nla_type(u16, encap_dport)]
+    EncapDport, // __be16
+
+    #[nla_type(flag, collect_metadata)]
+    CollectMetadata,
+
+    #[nla_type(u32, fwmark)]
+    Fwmark,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TunnelEncapTypes {
+    // TUNNEL_ENCAP_
+    None = 0,
+    Fou,
+    Gue,
+    Mpls,
+}
+pub const TUNNEL_ENCAP_NONE: u16 = TunnelEncapTypes::None as u16;
+pub const TUNNEL_ENCAP_FOU: u16 = TunnelEncapTypes::Fou as u16;
+pub const TUNNEL_ENCAP_GUE: u16 = TunnelEncapTypes::Gue as u16;
+pub const TUNNEL_ENCAP_MPLS: u16 = TunnelEncapTypes::Mpls as u16;
+
+pub const TUNNEL_ENCAP_FLAG_CSUM: u16 = 1 << 0;
+pub const TUNNEL_ENCAP_FLAG_CSUM6: u16 = 1 << 1;
+pub const TUNNEL_ENCAP_FLAG_REMCSUM: u16 = 1 << 2;
+
+// gre, gretap, ip6gre, ip6gretap, erspan and ip6erspan
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "GreTbl"]
+pub enum Gre {
+    // IFLA_GRE_
+    Unspec = 0,
+
+    #[nla_type(u32, link)]
+    Link,
+
+    #[nla_type(u16, iflags)]
+    Iflags, // __be16, GRE_
+
+    #[nla_type(u16, oflags)]
+    Oflags, // __be16, GRE_
+
+    #[nla_type(u32, ikey)]
+    Ikey, // __be32
+
+    #[nla_type(u32, okey)]
+    Okey, // __be32
+
+    #[nla_type(Ipv4Addr, local4)]
+    #[nla_type(Ipv6Addr, local6)] // ip6gre, ip6gretap and ip6erspan
+    Local,
+
+    #[nla_type(Ipv4Addr, remote4)]
+    #[nla_type(Ipv6Addr, remote6)] // ip6gre, ip6gretap and ip6erspan
+    Remote,
+
+    #[nla_type(u8, ttl)]
+    Ttl,
+
+    #[nla_type(u8, tos)]
+    Tos,
+
+    #[nla_type(u8, pmtudisc)]
+    Pmtudisc,
+
+    #[nla_type(u8, encap_limit)]
+    EncapLimit,
+
+    #[nla_type(u32, flowinfo)]
+    Flowinfo, // __be32
+
+    #[nla_type(u32, flags)]
+    Flags, // IP6_TNL_F_
+
+    #[nla_type(u16, encap_type)]
+    EncapType, // TUNNEL_ENCAP_
+
+    #[nla_type(u16, encap_flags)]
+    EncapFlags, // TUNNEL_ENCAP_FLAG_
+
+    #[nla_type(u16, encap_sport)]
+    EncapSport, // __be16
+
+    #[nla_type(u16, encap_dport)]
+    EncapDport, // __be16
+
+    #[nla_type(flag, collect_metadata)]
+    CollectMetadata,
+
+    #[nla_type(u8, ignore_df)]
+    IgnoreDf,
+
+    #[nla_type(u32, fwmark)]
+    Fwmark,
+
+    #[nla_type(u32, erspan_index)]
+    ErspanIndex,
+
+    #[nla_type(u8, erspan_ver)]
+    ErspanVer, // 0, 1 or 2
+
+    #[nla_type(u8, erspan_dir)]
+    ErspanDir, // 0: ingress, 1: egress
+
+    #[nla_type(u16, erspan_hwid)]
+    ErspanHwid,
+
+    _MAX,
+}
+
+// vti and vti6
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "VtiTbl"]
+pub enum Vti {
+    // IFLA_VTI_
+    Unspec = 0,
+
+    #[nla_type(u32, link)]
+    Link,
+
+    #[nla_type(u32, ikey)]
+    Ikey, // __be32
+
+    #[nla_type(u32, okey)]
+    Okey, // __be32
+
+    #[nla_type(Ipv4Addr, local4)]
+    #[nla_type(Ipv6Addr, local6)] // vti6
+    Local,
+
+    #[nla_type(Ipv4Addr, remote4)]
+    #[nla_type(Ipv6Addr, remote6)] // vti6
+    Remote,
+
+    #[nla_type(u32, fwmark)]
+    Fwmark,
+
+    _MAX,
+}
+
+impl Iptun {
+    // IFLA_IPTUN_LOCAL by the address family, ip6tnl takes IPv6 only.
+    pub fn put_local<'a>(nlv: &'a mut MsgVec, addr: &IpAddr) -> Result<&'a mut MsgVec> {
+        match addr {
+            IpAddr::V4(addr) => Self::put_local4(nlv, addr),
+            IpAddr::V6(addr) => Self::put_local6(nlv, addr),
+        }
+    }
+
+    pub fn put_remote<'a>(nlv: &'a mut MsgVec, addr: &IpAddr) -> Result<&'a mut MsgVec> {
+        match addr {
+            IpAddr::V4(addr) => Self::put_remote4(nlv, addr),
+            IpAddr::V6(addr) => Self::put_remote6(nlv, addr),
+        }
+    }
+}
+
+impl Gre {
+    pub fn put_local<'a>(nlv: &'a mut MsgVec, addr: &IpAddr) -> Result<&'a mut MsgVec> {
+        match addr {
+            IpAddr::V4(addr) => Self::put_local4(nlv, addr),
+            IpAddr::V6(addr) => Self::put_local6(nlv, addr),
+        }
+    }
+
+    pub fn put_remote<'a>(nlv: &'a mut MsgVec, addr: &IpAddr) -> Result<&'a mut MsgVec> {
+        match addr {
+            IpAddr::V4(addr) => Self::put_remote4(nlv, addr),
+            IpAddr::V6(addr) => Self::put_remote6(nlv, addr),
+        }
+    }
+
+    // Puts IFLA_GRE_IFLAGS and IFLA_GRE_OFLAGS, GRE_KEY is added to the flags
+    // if the key is specified. The keys are in host byte order.
+    pub fn put_keys(
+        nlv: &mut MsgVec,
+        mut iflags: u16,
+        ikey: Option<u32>,
+        mut oflags: u16,
+        okey: Option<u32>,
+    ) -> Result<&mut MsgVec> {
+        if let Some(key) = ikey {
+            iflags |= GRE_KEY;
+            Self::put_ikey(nlv, &key.to_be())?;
+        }
+        if let Some(key) = okey {
+            oflags |= GRE_KEY;
+            Self::put_okey(nlv, &key.to_be())?;
+        }
+        Self::put_iflags(nlv, &iflags)?;
+        Self::put_oflags(nlv, &oflags)
+    }
+}
+
+impl Vti {
+    pub fn put_local<'a>(nlv: &'a mut MsgVec, addr: &IpAddr) -> Result<&'a mut MsgVec> {
+        match addr {
+            IpAddr::V4(addr) => Self::put_local4(nlv, addr),
+            IpAddr::V6(addr) => Self::put_local6(nlv, addr),
+        }
+    }
+
+    pub fn put_remote<'a>(nlv: &'a mut MsgVec, addr: &IpAddr) -> Result<&'a mut MsgVec> {
+        match addr {
+            IpAddr::V4(addr) => Self::put_remote4(nlv, addr),
+            IpAddr::V6(addr) => Self::put_remote6(nlv, addr),
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use errno::Errno;
 use libc::{c_uchar, c_uint, c_ulong, c_ushort};
+use std::convert::TryFrom;
 
 pub const IFNAMSIZ: usize = 16;
 pub const IFALIASZ: usize = 256;
@@ -104,6 +106,23 @@ pub const IF_OPER_TESTING: u8 = IfOper::Testing as u8;
 pub const IF_OPER_DORMANT: u8 = IfOper::Dormant as u8;
 pub const IF_OPER_UP: u8 = IfOper::Up as u8;
 
+impl TryFrom<u8> for IfOper {
+    type Error = Errno;
+
+    fn try_from(v: u8) -> std::result::Result<Self, Errno> {
+        match v {
+            IF_OPER_UNKNOWN => Ok(IfOper::Unknown),
+            IF_OPER_NOTPRESENT => Ok(IfOper::Notpresent),
+            IF_OPER_DOWN => Ok(IfOper::Down),
+            IF_OPER_LOWERLAYERDOWN => Ok(IfOper::Lowerlayerdown),
+            IF_OPER_TESTING => Ok(IfOper::Testing),
+            IF_OPER_DORMANT => Ok(IfOper::Dormant),
+            IF_OPER_UP => Ok(IfOper::Up),
+            _ => Err(Errno(libc::ERANGE)),
+        }
+    }
+}
+
 // link modes */
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -0,0 +1,42 @@
+// index of IFLA_INET_CONF array, the value at IPV4_DEVCONF_* - 1,
+// and attribute type in IFLA_INET_CONF nest on RTM_SETLINK.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ipv4Devconf {
+    // IPV4_DEVCONF_
+    Forwarding = 1,
+    McForwarding,
+    ProxyArp,
+    AcceptRedirects,
+    SecureRedirects,
+    SendRedirects,
+    SharedMedia,
+    RpFilter,
+    AcceptSourceRoute,
+    BootpRelay,
+    LogMartians,
+    Tag,
+    Arpfilter,
+    MediumId,
+    Noxfrm,
+    Nopolicy,
+    ForceIgmpVersion,
+    ArpAnnounce,
+    ArpIgnore,
+    PromoteSecondaries,
+    ArpAccept,
+    ArpNotify,
+    AcceptLocal,
+    SrcVmark,
+    ProxyArpPvlan,
+    RouteLocalnet,
+    Igmpv2UnsolicitedReportInterval,
+    Igmpv3UnsolicitedReportInterval,
+    IgnoreRoutesWithLinkdown,
+    DropUnicastInL2Multicast,
+    DropGratuitousArp,
+    BcForwarding,
+    ArpEvictNocarrier,
+    _MAX,
+}
+pub const IPV4_DEVCONF_MAX: u16 = Ipv4Devconf::_MAX as u16 - 1;
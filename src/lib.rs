@@ -5,12 +5,30 @@ extern crate rsmnl as mnl;
 #[macro_use]
 extern crate rsmnl_derive;
 
+pub mod addr_cache;
+pub mod can;
 pub mod genetlink;
 pub mod if_addr;
+pub mod if_bonding;
+pub mod if_bridge;
 pub mod if_link;
+pub mod if_macsec;
+pub mod if_team;
+pub mod if_tunnel;
 pub mod ifh;
+pub mod ip;
 pub mod ipv6;
+pub mod link_cache;
 pub mod neighbour;
+pub mod net_namespace;
 pub mod netfilter;
 pub mod netlink;
+pub mod netns;
+pub mod pkt_cls;
+pub mod pkt_sched;
 pub mod rtnetlink;
+pub mod snmp;
+pub mod tc_act;
+mod util;
+pub mod veth;
+pub mod wireguard;
@@ -0,0 +1,473 @@
+use errno::Errno;
+use libc::c_uint;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    mem,
+    os::unix::io::{AsRawFd, RawFd},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use mnl::{self, AttrTbl, CbStatus, MsgVec, Msghdr, Result, Socket};
+
+use if_link::{Ifla, IflaTbl};
+use ifh::{IfOper, NetDeviceFlags};
+use netns;
+use rtnetlink::{self, Ifinfomsg};
+use util::{dump_ctl, nlmsgs, run, seq, to_errno};
+
+// A link in LinkCache, built from RTM_NEWLINK
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub index: u32,
+    pub name: String,
+    pub alt_names: Vec<String>,
+    pub link_type: u16, // ARPHRD_
+    pub flags: c_uint,  // IFF_
+    pub operstate: Option<IfOper>,
+    pub mtu: u32,
+    pub address: Vec<u8>,
+    pub master: Option<u32>,
+    pub link: Option<u32>,          // IFLA_LINK, lower device
+    pub link_netnsid: Option<i32>,  // netns of the lower device or the peer
+    pub kind: Option<String>,       // IFLA_INFO_KIND
+    pub slave_kind: Option<String>, // IFLA_INFO_SLAVE_KIND
+}
+
+impl Link {
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let ifm = nlh.payload::<Ifinfomsg>()?;
+        let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
+        let (kind, slave_kind) = match tb.linkinfo()? {
+            Some(info) => (
+                info.kind()?.map(|s| s.trim_end_matches('\0').to_string()),
+                info.slave_kind()?
+                    .map(|s| s.trim_end_matches('\0').to_string()),
+            ),
+            None => (None, None),
+        };
+        let operstate = match tb.operstate()? {
+            Some(v) => Some(IfOper::try_from(*v)?),
+            None => None,
+        };
+
+        Ok(Self {
+            index: ifm.ifi_index as u32,
+            name: tb
+                .ifname()?
+                .map(|s| s.trim_end_matches('\0').to_string())
+                .unwrap_or_default(),
+            alt_names: tb
+                .alt_ifnames()?
+                .unwrap_or_default()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            link_type: ifm.ifi_type,
+            flags: ifm.ifi_flags,
+            operstate,
+            mtu: tb.mtu()?.copied().unwrap_or(0),
+            address: tb.address()?.map(<[u8]>::to_vec).unwrap_or_default(),
+            master: tb.master()?.copied(),
+            link: tb.link()?.copied(),
+            link_netnsid: tb.link_netnsid()?.copied(),
+            kind,
+            slave_kind,
+        })
+    }
+
+    pub fn has_flag(&self, flag: NetDeviceFlags) -> bool {
+        self.flags & flag as c_uint != 0
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.has_flag(NetDeviceFlags::Up)
+    }
+
+    pub fn has_carrier(&self) -> bool {
+        self.has_flag(NetDeviceFlags::LowerUp)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkEvent {
+    Added(u32),
+    Removed(Link),
+    MovedToNetns {
+        link: Link,
+        netnsid: i32,
+    },
+    Renamed {
+        index: u32,
+        old: String,
+        new: String,
+    },
+    AltNamesChanged {
+        index: u32,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    AdminUp(u32),
+    AdminDown(u32),
+    CarrierUp(u32),
+    CarrierDown(u32),
+    OperStateChanged {
+        index: u32,
+        old: Option<IfOper>,
+        new: Option<IfOper>,
+    },
+    MtuChanged {
+        index: u32,
+        old: u32,
+        new: u32,
+    },
+    AddressChanged {
+        index: u32,
+        old: Vec<u8>,
+        new: Vec<u8>,
+    },
+    Enslaved {
+        index: u32,
+        master: u32,
+    },
+    Released {
+        index: u32,
+        master: u32,
+    },
+}
+
+fn link_events(old: &Link, new: &Link) -> Vec<LinkEvent> {
+    let index = new.index;
+    let mut events = Vec::new();
+    if old.name != new.name {
+        events.push(LinkEvent::Renamed {
+            index,
+            old: old.name.clone(),
+            new: new.name.clone(),
+        });
+    }
+    if old.alt_names != new.alt_names {
+        events.push(LinkEvent::AltNamesChanged {
+            index,
+            old: old.alt_names.clone(),
+            new: new.alt_names.clone(),
+        });
+    }
+    match (old.is_up(), new.is_up()) {
+        (false, true) => events.push(LinkEvent::AdminUp(index)),
+        (true, false) => events.push(LinkEvent::AdminDown(index)),
+        _ => {}
+    }
+    match (old.has_carrier(), new.has_carrier()) {
+        (false, true) => events.push(LinkEvent::CarrierUp(index)),
+        (true, false) => events.push(LinkEvent::CarrierDown(index)),
+        _ => {}
+    }
+    if old.operstate != new.operstate {
+        events.push(LinkEvent::OperStateChanged {
+            index,
+            old: old.operstate,
+            new: new.operstate,
+        });
+    }
+    if old.mtu != new.mtu {
+        events.push(LinkEvent::MtuChanged {
+            index,
+            old: old.mtu,
+            new: new.mtu,
+        });
+    }
+    if old.address != new.address {
+        events.push(LinkEvent::AddressChanged {
+            index,
+            old: old.address.clone(),
+            new: new.address.clone(),
+        });
+    }
+    if old.master != new.master {
+        if let Some(master) = old.master {
+            events.push(LinkEvent::Released { index, master });
+        }
+        if let Some(master) = new.master {
+            events.push(LinkEvent::Enslaved { index, master });
+        }
+    }
+    events
+}
+
+// RTM_GETLINK dump without the cache. target_nsid is the netns to dump
+// instead of the socket's one, see netns::nsid(). The strict checking is
+// turned on then and kept, as if_addr::dump().
+pub fn dump(nl: &mut Socket, target_nsid: Option<i32>) -> Result<Vec<Link>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETLINK;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let ifm: &mut Ifinfomsg = nlv.put_extra_header()?;
+    ifm.ifi_family = libc::AF_UNSPEC as u8;
+    if let Some(nsid) = target_nsid {
+        Ifla::put_target_netnsid(&mut nlv, &nsid)?;
+        netns::set_strict_check(nl, true)?;
+    }
+
+    let mut links = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        links.push(Link::from_nlmsg(nlh)?);
+        Ok(())
+    })?;
+    Ok(links)
+}
+
+// Links in the netns of the socket, filled by RTM_GETLINK dump then
+// updated by RTNLGRP_LINK notifications. Call process() when the socket,
+// as_raw_fd(), is readable.
+pub struct LinkCache {
+    nl: Socket,
+    buf: Vec<u8>,
+    links: HashMap<u32, Link>,
+    names: HashMap<String, u32>, // both of name and alt names
+    subscribers: Vec<Sender<LinkEvent>>,
+}
+
+impl LinkCache {
+    pub fn new() -> Result<Self> {
+        let mut nl = Socket::open(libc::NETLINK_ROUTE, 0)?;
+        nl.bind(rtnetlink::RTMGRP_LINK, mnl::SOCKET_AUTOPID)?;
+        let mut cache = Self {
+            nl,
+            buf: vec![0u8; mnl::SOCKET_DUMP_SIZE],
+            links: HashMap::new(),
+            names: HashMap::new(),
+            subscribers: Vec::new(),
+        };
+        cache.refresh()?;
+        Ok(cache)
+    }
+
+    // Receives the change events in addition to process() return value.
+    pub fn subscribe(&mut self) -> Receiver<LinkEvent> {
+        let (tx, rx) = channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    pub fn get(&self, index: u32) -> Option<&Link> {
+        self.links.get(&index)
+    }
+
+    // name may be an alt name
+    pub fn by_name(&self, name: &str) -> Option<&Link> {
+        self.names.get(name).and_then(|i| self.links.get(i))
+    }
+
+    pub fn index(&self, name: &str) -> Option<u32> {
+        self.names.get(name).copied()
+    }
+
+    pub fn name(&self, index: u32) -> Option<&str> {
+        self.links.get(&index).map(|link| link.name.as_str())
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &Link> {
+        self.links.values()
+    }
+
+    pub fn slaves(&self, master: u32) -> Vec<&Link> {
+        self.links
+            .values()
+            .filter(|link| link.master == Some(master))
+            .collect()
+    }
+
+    // Dumps all links and returns the differences from the current.
+    // This is needed after the socket reported ENOBUFS, process() does it.
+    pub fn refresh(&mut self) -> Result<Vec<LinkEvent>> {
+        // the events applied by the interrupted dumps are kept
+        let mut events = Vec::new();
+        let ret = loop {
+            match self.dump(&mut events) {
+                Err(Errno(libc::EINTR)) => continue, // NLM_F_DUMP_INTR
+                ret => break ret,
+            }
+        };
+        self.notify(&events);
+        ret.map(|_| events)
+    }
+
+    fn dump(&mut self, events: &mut Vec<LinkEvent>) -> Result<()> {
+        let mut nlv = MsgVec::new();
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = rtnetlink::RTM_GETLINK;
+        nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+        let seq = seq();
+        nlh.nlmsg_seq = seq;
+        let ifm: &mut Ifinfomsg = nlv.put_extra_header()?;
+        ifm.ifi_family = libc::AF_UNSPEC as u8;
+        self.nl.sendto(&nlv)?;
+
+        let portid = self.nl.portid();
+        let mut buf = mem::take(&mut self.buf);
+        let mut seen = HashSet::new();
+        let mut intr = false;
+        let ret = 'recv: loop {
+            let nrecv = match self.nl.recvfrom(&mut buf) {
+                Ok(n) => n,
+                Err(errno) => break Err(errno),
+            };
+            for nlh in nlmsgs(&buf[..nrecv]) {
+                // notifications may be interleaved, which have the portid and
+                // seq of the request if it was made by the other socket
+                if nlh.nlmsg_pid == portid && nlh.nlmsg_seq == seq {
+                    if let Some(ret) = dump_ctl(nlh, &mut intr) {
+                        break 'recv ret;
+                    }
+                }
+                if nlh.nlmsg_type < libc::NLMSG_MIN_TYPE as u16 {
+                    continue;
+                }
+                if let Err(errno) = self.dumped(nlh, &mut seen, events) {
+                    break 'recv Err(errno);
+                }
+            }
+        };
+        self.buf = buf;
+        ret?;
+
+        let gone: Vec<u32> = self
+            .links
+            .keys()
+            .filter(|i| !seen.contains(i))
+            .copied()
+            .collect();
+        for index in gone {
+            if let Some(link) = self.remove(index) {
+                events.push(LinkEvent::Removed(link));
+            }
+        }
+        Ok(())
+    }
+
+    // Applies a reply of the dump or a notification, seen is the links
+    // which exist at the end of the dump.
+    fn dumped(
+        &mut self,
+        nlh: &Msghdr,
+        seen: &mut HashSet<u32>,
+        events: &mut Vec<LinkEvent>,
+    ) -> Result<()> {
+        let ifm = nlh.payload::<Ifinfomsg>()?;
+        if ifm.ifi_family == libc::AF_UNSPEC as u8 {
+            match nlh.nlmsg_type {
+                rtnetlink::RTM_NEWLINK => {
+                    seen.insert(ifm.ifi_index as u32);
+                }
+                rtnetlink::RTM_DELLINK => {
+                    seen.remove(&(ifm.ifi_index as u32));
+                }
+                _ => {}
+            }
+        }
+        events.append(&mut self.update(nlh)?);
+        Ok(())
+    }
+
+    // Receives once and applies notifications, blocks if the socket is.
+    pub fn process(&mut self) -> Result<Vec<LinkEvent>> {
+        let mut buf = mem::take(&mut self.buf);
+        let nrecv = match self.nl.recvfrom(&mut buf) {
+            Ok(n) => n,
+            Err(Errno(libc::ENOBUFS)) => {
+                self.buf = buf;
+                return self.refresh();
+            }
+            Err(errno) => {
+                self.buf = buf;
+                return Err(errno);
+            }
+        };
+        let mut events = Vec::new();
+        let status = mnl::cb_run(
+            &buf[..nrecv],
+            0,
+            0,
+            Some(|nlh: &Msghdr| {
+                events.append(&mut self.update(nlh)?);
+                Ok(CbStatus::Ok)
+            }),
+        );
+        self.buf = buf;
+        status.map_err(to_errno)?;
+        self.notify(&events);
+        Ok(events)
+    }
+
+    // Applies RTM_NEWLINK or RTM_DELLINK, received by the other socket.
+    // Subscribers are not notified.
+    pub fn update(&mut self, nlh: &Msghdr) -> Result<Vec<LinkEvent>> {
+        let ifm = nlh.payload::<Ifinfomsg>()?;
+        // AF_BRIDGE ones are for bridge ports
+        if ifm.ifi_family != libc::AF_UNSPEC as u8 {
+            return Ok(Vec::new());
+        }
+        match nlh.nlmsg_type {
+            rtnetlink::RTM_NEWLINK => {
+                let link = Link::from_nlmsg(nlh)?;
+                let events = match self.links.get(&link.index) {
+                    Some(old) => link_events(old, &link),
+                    None => vec![LinkEvent::Added(link.index)],
+                };
+                self.insert(link);
+                Ok(events)
+            }
+            rtnetlink::RTM_DELLINK => {
+                let tb = IflaTbl::from_nlmsg(mem::size_of::<Ifinfomsg>(), nlh)?;
+                let link = match self.remove(ifm.ifi_index as u32) {
+                    Some(link) => link,
+                    None => return Ok(Vec::new()),
+                };
+                match tb.new_netnsid()? {
+                    Some(&netnsid) => Ok(vec![LinkEvent::MovedToNetns { link, netnsid }]),
+                    None => Ok(vec![LinkEvent::Removed(link)]),
+                }
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn insert(&mut self, link: Link) {
+        if let Some(old) = self.links.get(&link.index) {
+            for name in old.alt_names.iter().chain(Some(&old.name)) {
+                if self.names.get(name) == Some(&link.index) {
+                    self.names.remove(name);
+                }
+            }
+        }
+        for name in link.alt_names.iter().chain(Some(&link.name)) {
+            self.names.insert(name.clone(), link.index);
+        }
+        self.links.insert(link.index, link);
+    }
+
+    fn remove(&mut self, index: u32) -> Option<Link> {
+        let link = self.links.remove(&index)?;
+        for name in link.alt_names.iter().chain(Some(&link.name)) {
+            if self.names.get(name) == Some(&index) {
+                self.names.remove(name);
+            }
+        }
+        Some(link)
+    }
+
+    fn notify(&mut self, events: &[LinkEvent]) {
+        self.subscribers
+            .retain(|tx| events.iter().all(|ev| tx.send(ev.clone()).is_ok()));
+    }
+}
+
+impl AsRawFd for LinkCache {
+    fn as_raw_fd(&self) -> RawFd {
+        self.nl.as_raw_fd()
+    }
+}
@@ -1,7 +1,16 @@
 use errno::Errno;
 use libc;
-use mnl::{Attr, AttrTbl, MsgVec, Result};
-use std::net::{Ipv4Addr, Ipv6Addr};
+use mnl::{self, Attr, AttrTbl, CbStatus, MsgVec, Msghdr, Result, Socket};
+use std::{
+    collections::HashMap,
+    mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    os::unix::io::AsRawFd,
+    time::{Duration, Instant},
+};
+
+use rtnetlink;
+use util::{put_64bit, run, seq, to_errno, unaligned};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -70,30 +79,138 @@ pub enum Nda {
 /*
  *	Neighbor Cache Entry Flags
  */
-pub const NTF_USE: u8 = 0x01;
-pub const NTF_SELF: u8 = 0x02;
-pub const NTF_MASTER: u8 = 0x04;
-pub const NTF_PROXY: u8		= 0x08	/* == ATF_PUBL */;
-pub const NTF_EXT_LEARNED: u8 = 0x10;
-pub const NTF_OFFLOADED: u8 = 0x20;
-pub const NTF_STICKY: u8 = 0x40;
-pub const NTF_ROUTER: u8 = 0x80;
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ntf {
+    // NTF_
+    Use = 0x01,
+    Self_ = 0x02,
+    Master = 0x04,
+    Proxy = 0x08, // == ATF_PUBL
+    ExtLearned = 0x10,
+    Offloaded = 0x20,
+    Sticky = 0x40,
+    Router = 0x80,
+}
+pub const NTF_USE: u8 = Ntf::Use as u8;
+pub const NTF_SELF: u8 = Ntf::Self_ as u8;
+pub const NTF_MASTER: u8 = Ntf::Master as u8;
+pub const NTF_PROXY: u8 = Ntf::Proxy as u8;
+pub const NTF_EXT_LEARNED: u8 = Ntf::ExtLearned as u8;
+pub const NTF_OFFLOADED: u8 = Ntf::Offloaded as u8;
+pub const NTF_STICKY: u8 = Ntf::Sticky as u8;
+pub const NTF_ROUTER: u8 = Ntf::Router as u8;
 
 /*
  *	Neighbor Cache Entry States.
  */
-pub const NUD_INCOMPLETE: u8 = 0x01;
-pub const NUD_REACHABLE: u8 = 0x02;
-pub const NUD_STALE: u8 = 0x04;
-pub const NUD_DELAY: u8 = 0x08;
-pub const NUD_PROBE: u8 = 0x10;
-pub const NUD_FAILED: u8 = 0x20;
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Nud {
+    // NUD_
+    Incomplete = 0x01,
+    Reachable = 0x02,
+    Stale = 0x04,
+    Delay = 0x08,
+    Probe = 0x10,
+    Failed = 0x20,
+    // Dummy states
+    Noarp = 0x40,
+    Permanent = 0x80,
+}
+pub const NUD_INCOMPLETE: u8 = Nud::Incomplete as u8;
+pub const NUD_REACHABLE: u8 = Nud::Reachable as u8;
+pub const NUD_STALE: u8 = Nud::Stale as u8;
+pub const NUD_DELAY: u8 = Nud::Delay as u8;
+pub const NUD_PROBE: u8 = Nud::Probe as u8;
+pub const NUD_FAILED: u8 = Nud::Failed as u8;
 
 // Dummy states
-pub const NUD_NOARP: u8 = 0x40;
-pub const NUD_PERMANENT: u8 = 0x80;
+pub const NUD_NOARP: u8 = Nud::Noarp as u8;
+pub const NUD_PERMANENT: u8 = Nud::Permanent as u8;
 pub const NUD_NONE: u8 = 0x00;
 
+// include/net/neighbour.h, has a link layer address
+pub const NUD_VALID: u8 =
+    NUD_PERMANENT | NUD_NOARP | NUD_REACHABLE | NUD_PROBE | NUD_STALE | NUD_DELAY;
+
+// A set of NTF_, ndm_flags
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NtfFlags(pub u8);
+
+impl NtfFlags {
+    pub fn contains(&self, flag: Ntf) -> bool {
+        self.0 & flag as u8 != 0
+    }
+
+    pub fn insert(&mut self, flag: Ntf) -> &mut Self {
+        self.0 |= flag as u8;
+        self
+    }
+
+    pub fn remove(&mut self, flag: Ntf) -> &mut Self {
+        self.0 &= !(flag as u8);
+        self
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+}
+
+impl From<u8> for NtfFlags {
+    fn from(v: u8) -> Self {
+        Self(v)
+    }
+}
+
+impl From<Ntf> for NtfFlags {
+    fn from(flag: Ntf) -> Self {
+        Self(flag as u8)
+    }
+}
+
+// A set of NUD_, ndm_state. Entries are in one state, but requests and
+// the kernel test them as a mask.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NudState(pub u16);
+
+impl NudState {
+    pub fn contains(&self, state: Nud) -> bool {
+        self.0 & state as u16 != 0
+    }
+
+    pub fn insert(&mut self, state: Nud) -> &mut Self {
+        self.0 |= state as u16;
+        self
+    }
+
+    pub fn remove(&mut self, state: Nud) -> &mut Self {
+        self.0 &= !(state as u16);
+        self
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.0 & NUD_VALID as u16 != 0
+    }
+}
+
+impl From<u16> for NudState {
+    fn from(v: u16) -> Self {
+        Self(v)
+    }
+}
+
+impl From<Nud> for NudState {
+    fn from(state: Nud) -> Self {
+        Self(state as u16)
+    }
+}
+
 /* NUD_NOARP & NUD_PERMANENT are pseudostates, they never change
  * and make no address resolution or NUD.
  * NUD_PERMANENT also cannot be deleted by garbage collectors.
@@ -178,10 +295,10 @@ pub enum Ndtpa {
     #[nla_type(u64, delay_probe_time)]
     DelayProbeTime, // u64
 
-    #[nla_type(u64, queue_len)]
+    #[nla_type(u32, queue_len)]
     QueueLen, // u32
 
-    #[nla_type(u64, app_probes)]
+    #[nla_type(u32, app_probes)]
     AppProbes, // u32
 
     #[nla_type(u32, ucast_probes)]
@@ -240,7 +357,7 @@ pub struct NdtConfig {
 pub enum Ndta {
     Unspec,
 
-    #[nla_type(str, name)]
+    #[nla_type(cstr, name)]
     Name,
 
     #[nla_type(u32, thresh1)]
@@ -297,3 +414,827 @@ pub enum Nfea {
 
     _MAX,
 }
+
+// A neighbour entry, RTM_NEWNEIGH and RTM_DELNEIGH of ARP (AF_INET) or ND
+// (AF_INET6). Proxy entries have NTF_PROXY and no lladdr.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Neighbour {
+    pub index: u32,
+    pub dst: IpAddr,
+    pub lladdr: Option<Vec<u8>>,
+    pub state: NudState,
+    pub flags: NtfFlags,
+    pub ntype: u8, // ndm_type, RTN_
+    pub probes: Option<u32>,
+    pub vlan: Option<u16>,
+    pub vni: Option<u32>,
+    pub master: Option<u32>,
+    pub protocol: Option<u8>, // RTPROT_
+    // NDA_CACHEINFO, elapsed since
+    pub confirmed: Option<Duration>,
+    pub used: Option<Duration>,
+    pub updated: Option<Duration>,
+    pub refcnt: u32,
+}
+
+impl Neighbour {
+    // A permanent entry, set lladdr for ARP/ND.
+    pub fn new(index: u32, dst: IpAddr) -> Self {
+        Self {
+            index,
+            dst,
+            lladdr: None,
+            state: NudState::from(Nud::Permanent),
+            flags: NtfFlags::default(),
+            ntype: 0,
+            probes: None,
+            vlan: None,
+            vni: None,
+            master: None,
+            protocol: None,
+            confirmed: None,
+            used: None,
+            updated: None,
+            refcnt: 0,
+        }
+    }
+
+    // Answers ARP requests or neighbour solicitations for dst on the link.
+    pub fn proxy(index: u32, dst: IpAddr) -> Self {
+        let mut neigh = Self::new(index, dst);
+        neigh.flags.insert(Ntf::Proxy);
+        neigh
+    }
+
+    pub fn family(&self) -> u8 {
+        match self.dst {
+            IpAddr::V4(_) => libc::AF_INET as u8,
+            IpAddr::V6(_) => libc::AF_INET6 as u8,
+        }
+    }
+
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let ndm = nlh.payload::<Ndmsg>()?;
+        let tb = NdaTbl::from_nlmsg(mem::size_of::<Ndmsg>(), nlh)?;
+        let dst = match ndm.ndm_family as i32 {
+            libc::AF_INET => tb.v4dst()?.map(|a| IpAddr::V4(*a)),
+            libc::AF_INET6 => tb.v6dst()?.map(|a| IpAddr::V6(*a)),
+            _ => return Err(Errno(libc::EAFNOSUPPORT)),
+        }
+        .ok_or(Errno(libc::EINVAL))?;
+        // clock_t, USER_HZ is 100
+        let ticks = |v: u32| Duration::from_millis(v as u64 * 10);
+        let cacheinfo = tb.cacheinfo()?;
+        Ok(Self {
+            index: ndm.ndm_ifindex as u32,
+            dst,
+            lladdr: tb.lladdr()?.map(<[u8]>::to_vec),
+            state: NudState(ndm.ndm_state),
+            flags: NtfFlags(ndm.ndm_flags),
+            ntype: ndm.ndm_type,
+            probes: tb.probes()?.copied(),
+            vlan: tb.vlan()?.copied(),
+            vni: tb.vni()?.copied(),
+            master: tb.master()?.copied(),
+            protocol: tb.protocol()?.copied(),
+            confirmed: cacheinfo.map(|ci| ticks(ci.ndm_confirmed)),
+            used: cacheinfo.map(|ci| ticks(ci.ndm_used)),
+            updated: cacheinfo.map(|ci| ticks(ci.ndm_updated)),
+            refcnt: cacheinfo.map(|ci| ci.ndm_refcnt).unwrap_or(0),
+        })
+    }
+
+    fn put_header<'a>(
+        &self,
+        nlv: &'a mut MsgVec,
+        mtype: u16,
+        flags: u16,
+        seq: u32,
+    ) -> Result<&'a mut MsgVec> {
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = mtype;
+        nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+        nlh.nlmsg_seq = seq;
+        let ndm = nlv.put_extra_header::<Ndmsg>()?;
+        ndm.ndm_family = self.family();
+        ndm.ndm_ifindex = self.index as i32;
+        ndm.ndm_state = self.state.0;
+        ndm.ndm_flags = self.flags.0;
+        ndm.ndm_type = self.ntype;
+        match self.dst {
+            IpAddr::V4(addr) => Nda::put_v4dst(nlv, &addr)?,
+            IpAddr::V6(addr) => Nda::put_v6dst(nlv, &addr)?,
+        };
+        Ok(nlv)
+    }
+
+    fn put_new<'a>(&self, nlv: &'a mut MsgVec, flags: u16, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_header(nlv, rtnetlink::RTM_NEWNEIGH, flags, seq)?;
+        if let Some(lladdr) = &self.lladdr {
+            Nda::put_lladdr(nlv, lladdr)?;
+        }
+        if let Some(vlan) = self.vlan {
+            Nda::put_vlan(nlv, &vlan)?;
+        }
+        if let Some(vni) = self.vni {
+            Nda::put_vni(nlv, &vni)?;
+        }
+        if let Some(master) = self.master {
+            Nda::put_master(nlv, &master)?;
+        }
+        if let Some(protocol) = self.protocol {
+            Nda::put_protocol(nlv, &protocol)?;
+        }
+        Ok(nlv)
+    }
+
+    // RTM_NEWNEIGH with NLM_F_CREATE | NLM_F_EXCL, EEXIST if exists.
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_new(
+            nlv,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+        )
+    }
+
+    // RTM_NEWNEIGH with NLM_F_CREATE | NLM_F_REPLACE.
+    pub fn put_replace<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_new(
+            nlv,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_REPLACE) as u16,
+            seq,
+        )
+    }
+
+    // RTM_DELNEIGH, identified by index and dst, and NTF_PROXY.
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_header(nlv, rtnetlink::RTM_DELNEIGH, libc::NLM_F_ACK as u16, seq)
+    }
+}
+
+// A bridge or VXLAN forwarding database entry, RTM_NEWNEIGH and
+// RTM_DELNEIGH of AF_BRIDGE. NTF_SELF is for the device itself, e.g.
+// vxlan or the bridge, and NTF_MASTER is for the bridge of the port.
+//
+// state is NUD_PERMANENT for local, NUD_NOARP for static and
+// NUD_REACHABLE for dynamic entries, like bridge(8).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FdbEntry {
+    pub index: u32, // the port or the vxlan device
+    pub lladdr: Vec<u8>,
+    pub state: NudState,
+    pub flags: NtfFlags,
+    pub dst: Option<IpAddr>, // vxlan remote
+    pub vlan: Option<u16>,
+    pub vni: Option<u32>,
+    pub src_vni: Option<u32>,
+    pub port: Option<u16>,    // vxlan remote UDP port, host byte order
+    pub ifindex: Option<u32>, // vxlan outgoing device to the remote
+    pub master: Option<u32>,
+    pub nh_id: Option<u32>,
+    // NDA_FDB_EXT_ATTRS
+    pub activity_notify: Option<u8>, // Fdb::NotifyBit | Fdb::NotifyInactiveBit
+    pub dont_refresh: bool,          // on replace, do not refresh activity
+    // NDA_CACHEINFO, elapsed since
+    pub used: Option<Duration>,
+    pub updated: Option<Duration>,
+}
+
+impl FdbEntry {
+    // A static entry for the bridge of the port, NTF_MASTER.
+    pub fn new(index: u32, lladdr: &[u8]) -> Self {
+        Self {
+            index,
+            lladdr: lladdr.to_vec(),
+            state: NudState::from(Nud::Noarp),
+            flags: NtfFlags::from(Ntf::Master),
+            dst: None,
+            vlan: None,
+            vni: None,
+            src_vni: None,
+            port: None,
+            ifindex: None,
+            master: None,
+            nh_id: None,
+            activity_notify: None,
+            dont_refresh: false,
+            used: None,
+            updated: None,
+        }
+    }
+
+    // A static entry of the vxlan device to the remote, NTF_SELF. All zero
+    // lladdr is for the flood list of BUM traffic, see put_append().
+    pub fn vxlan(index: u32, lladdr: &[u8], dst: IpAddr) -> Self {
+        let mut entry = Self::new(index, lladdr);
+        entry.flags = NtfFlags::from(Ntf::Self_);
+        entry.state = NudState::from(Nud::Permanent);
+        entry.dst = Some(dst);
+        entry
+    }
+
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let ndm = nlh.payload::<Ndmsg>()?;
+        if ndm.ndm_family != libc::AF_BRIDGE as u8 {
+            return Err(Errno(libc::EAFNOSUPPORT));
+        }
+        let tb = NdaTbl::from_nlmsg(mem::size_of::<Ndmsg>(), nlh)?;
+        // IPv4 or IPv6 by the length
+        let dst = match tb[Nda::Dst] {
+            Some(attr) if attr.payload_len() == 4 => Some(IpAddr::V4(*tb.v4dst()?.unwrap())),
+            Some(attr) if attr.payload_len() == 16 => Some(IpAddr::V6(*tb.v6dst()?.unwrap())),
+            Some(_) => return Err(Errno(libc::EINVAL)),
+            None => None,
+        };
+        let (activity_notify, dont_refresh) = match tb.fdb_ext_attrs()? {
+            Some(nfea) => (nfea.activity_notify()?.copied(), nfea.dont_refresh()?),
+            None => (None, false),
+        };
+        let ticks = |v: u32| Duration::from_millis(v as u64 * 10);
+        let cacheinfo = tb.cacheinfo()?;
+        Ok(Self {
+            index: ndm.ndm_ifindex as u32,
+            lladdr: tb.lladdr()?.map(<[u8]>::to_vec).unwrap_or_default(),
+            state: NudState(ndm.ndm_state),
+            flags: NtfFlags(ndm.ndm_flags),
+            dst,
+            vlan: tb.vlan()?.copied(),
+            vni: tb.vni()?.copied(),
+            src_vni: tb.src_vni()?.copied(),
+            port: tb.port()?.map(|p| u16::from_be(*p)),
+            ifindex: tb.ifindex()?.copied(),
+            master: tb.master()?.copied(),
+            nh_id: tb.nh_id()?.copied(),
+            activity_notify,
+            dont_refresh,
+            used: cacheinfo.map(|ci| ticks(ci.ndm_used)),
+            updated: cacheinfo.map(|ci| ticks(ci.ndm_updated)),
+        })
+    }
+
+    fn put_request<'a>(
+        &self,
+        nlv: &'a mut MsgVec,
+        mtype: u16,
+        flags: u16,
+        seq: u32,
+    ) -> Result<&'a mut MsgVec> {
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = mtype;
+        nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+        nlh.nlmsg_seq = seq;
+        let ndm = nlv.put_extra_header::<Ndmsg>()?;
+        ndm.ndm_family = libc::AF_BRIDGE as u8;
+        ndm.ndm_ifindex = self.index as i32;
+        ndm.ndm_state = self.state.0;
+        ndm.ndm_flags = self.flags.0;
+        Nda::put_lladdr(nlv, &self.lladdr)?;
+        match self.dst {
+            Some(IpAddr::V4(addr)) => {
+                Nda::put_v4dst(nlv, &addr)?;
+            }
+            Some(IpAddr::V6(addr)) => {
+                Nda::put_v6dst(nlv, &addr)?;
+            }
+            None => {}
+        }
+        if let Some(vlan) = self.vlan {
+            Nda::put_vlan(nlv, &vlan)?;
+        }
+        if let Some(vni) = self.vni {
+            Nda::put_vni(nlv, &vni)?;
+        }
+        if let Some(vni) = self.src_vni {
+            Nda::put_src_vni(nlv, &vni)?;
+        }
+        if let Some(port) = self.port {
+            Nda::put_port(nlv, &port.to_be())?;
+        }
+        if let Some(ifindex) = self.ifindex {
+            Nda::put_ifindex(nlv, &ifindex)?;
+        }
+        if let Some(master) = self.master {
+            Nda::put_master(nlv, &master)?;
+        }
+        if let Some(id) = self.nh_id {
+            Nda::put_nh_id(nlv, &id)?;
+        }
+        if mtype == rtnetlink::RTM_NEWNEIGH && (self.activity_notify.is_some() || self.dont_refresh)
+        {
+            Nda::fdb_ext_attrs_start(nlv)?;
+            if let Some(v) = self.activity_notify {
+                Nfea::put_activity_notify(nlv, &v)?;
+            }
+            if self.dont_refresh {
+                Nfea::put_dont_refresh(nlv)?;
+            }
+            nlv.nest_end()?;
+        }
+        Ok(nlv)
+    }
+
+    // RTM_NEWNEIGH with NLM_F_CREATE | NLM_F_EXCL, EEXIST if exists.
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(
+            nlv,
+            rtnetlink::RTM_NEWNEIGH,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+        )
+    }
+
+    // RTM_NEWNEIGH with NLM_F_CREATE | NLM_F_REPLACE.
+    pub fn put_replace<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(
+            nlv,
+            rtnetlink::RTM_NEWNEIGH,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_REPLACE) as u16,
+            seq,
+        )
+    }
+
+    // RTM_NEWNEIGH with NLM_F_CREATE | NLM_F_APPEND, adds dst to the
+    // remotes of the vxlan entry, head-end replication of the flood list.
+    pub fn put_append<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(
+            nlv,
+            rtnetlink::RTM_NEWNEIGH,
+            (libc::NLM_F_ACK | libc::NLM_F_CREATE | libc::NLM_F_APPEND) as u16,
+            seq,
+        )
+    }
+
+    // RTM_DELNEIGH, a vxlan entry with dst deletes the remote only.
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_DELNEIGH, libc::NLM_F_ACK as u16, seq)
+    }
+}
+
+// The neighbour table names of NDTA_NAME.
+pub const ARP_TABLE: &str = "arp_cache";
+pub const NDISC_TABLE: &str = "ndisc_cache";
+
+// A parameter set in NDTA_PARMS, the default of the table if ifindex is None
+// or the device specific. Times are in msecs. To change, only the Some fields
+// are put except read only ones, refcnt and reachable_time which is computed
+// from base_reachable_time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NeighParms {
+    pub ifindex: Option<u32>,
+    pub refcnt: Option<u32>,
+    pub reachable_time: Option<u64>,
+    pub base_reachable_time: Option<u64>,
+    pub retrans_time: Option<u64>,
+    pub gc_staletime: Option<u64>,
+    pub delay_probe_time: Option<u64>,
+    pub queue_len: Option<u32>,
+    pub queue_lenbytes: Option<u32>,
+    pub app_probes: Option<u32>,
+    pub ucast_probes: Option<u32>,
+    pub mcast_probes: Option<u32>,
+    pub mcast_reprobes: Option<u32>,
+    pub anycast_delay: Option<u64>,
+    pub proxy_delay: Option<u64>,
+    pub proxy_qlen: Option<u32>,
+    pub locktime: Option<u64>,
+}
+
+impl NeighParms {
+    // The device specific set, to change.
+    pub fn dev(ifindex: u32) -> Self {
+        Self {
+            ifindex: Some(ifindex),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_tbl(tb: &NdtpaTbl) -> Result<Self> {
+        Ok(Self {
+            ifindex: tb.ifindex()?.copied(),
+            refcnt: tb.refcnt()?.copied(),
+            reachable_time: unaligned(tb[Ndtpa::ReachableTime])?,
+            base_reachable_time: unaligned(tb[Ndtpa::BaseReachableTime])?,
+            retrans_time: unaligned(tb[Ndtpa::RetransTime])?,
+            gc_staletime: unaligned(tb[Ndtpa::GcStaletime])?,
+            delay_probe_time: unaligned(tb[Ndtpa::DelayProbeTime])?,
+            queue_len: tb.queue_len()?.copied(),
+            queue_lenbytes: tb.queue_lenbytes()?.copied(),
+            app_probes: tb.app_probes()?.copied(),
+            ucast_probes: tb.ucast_probes()?.copied(),
+            mcast_probes: tb.mcast_probes()?.copied(),
+            mcast_reprobes: tb.mcast_reprobes()?.copied(),
+            anycast_delay: unaligned(tb[Ndtpa::AnycastDelay])?,
+            proxy_delay: unaligned(tb[Ndtpa::ProxyDelay])?,
+            proxy_qlen: tb.proxy_qlen()?.copied(),
+            locktime: unaligned(tb[Ndtpa::Locktime])?,
+        })
+    }
+
+    // NDTA_PARMS nest.
+    pub fn put<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        Ndta::parms_start(nlv)?;
+        if let Some(v) = self.ifindex {
+            Ndtpa::put_ifindex(nlv, &v)?;
+        }
+        for (atype, v) in [
+            (Ndtpa::BaseReachableTime, self.base_reachable_time),
+            (Ndtpa::RetransTime, self.retrans_time),
+            (Ndtpa::GcStaletime, self.gc_staletime),
+            (Ndtpa::DelayProbeTime, self.delay_probe_time),
+            (Ndtpa::AnycastDelay, self.anycast_delay),
+            (Ndtpa::ProxyDelay, self.proxy_delay),
+            (Ndtpa::Locktime, self.locktime),
+        ] {
+            if let Some(v) = v {
+                put_64bit(nlv, atype, Ndtpa::PAD, &v)?;
+            }
+        }
+        for (atype, v) in [
+            (Ndtpa::QueueLen, self.queue_len),
+            (Ndtpa::QueueLenbytes, self.queue_lenbytes),
+            (Ndtpa::AppProbes, self.app_probes),
+            (Ndtpa::UcastProbes, self.ucast_probes),
+            (Ndtpa::McastProbes, self.mcast_probes),
+            (Ndtpa::McastReprobes, self.mcast_reprobes),
+            (Ndtpa::ProxyQlen, self.proxy_qlen),
+        ] {
+            if let Some(v) = v {
+                nlv.put(atype, &v)?;
+            }
+        }
+        nlv.nest_end()
+    }
+}
+
+// A neighbour table grouped from the multi-message RTM_NEWNEIGHTBL, see the
+// comment on Neighbour tables specific messages above. gc_interval is in msecs.
+#[derive(Debug, Clone)]
+pub struct NeighTable {
+    pub family: u8,
+    pub name: String,
+    pub thresh1: u32,
+    pub thresh2: u32,
+    pub thresh3: u32,
+    pub gc_interval: u64,
+    pub config: NdtConfig,
+    pub stats: NdtStats,
+    pub default_parms: NeighParms,
+    pub per_dev: HashMap<u32, NeighParms>,
+}
+
+impl NeighTable {
+    // From the first message of the sequence, ENOMSG if it is not, which
+    // does not have NDTA_CONFIG.
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let ndtm = nlh.payload::<Ndtmsg>()?;
+        let tb = NdtaTbl::from_nlmsg(mem::size_of::<Ndtmsg>(), nlh)?;
+        let config = match tb.config()? {
+            Some(config) => *config,
+            None => return Err(Errno(libc::ENOMSG)),
+        };
+        let default_parms = match tb.parms()? {
+            Some(parms) => NeighParms::from_tbl(&parms)?,
+            None => NeighParms::default(),
+        };
+        Ok(Self {
+            family: ndtm.ndtm_family,
+            name: tb.name()?.ok_or(Errno(libc::EINVAL))?.to_string(),
+            thresh1: tb.thresh1()?.copied().unwrap_or(0),
+            thresh2: tb.thresh2()?.copied().unwrap_or(0),
+            thresh3: tb.thresh3()?.copied().unwrap_or(0),
+            gc_interval: unaligned(tb[Ndta::GcInterval])?.unwrap_or(0),
+            config,
+            stats: unaligned(tb[Ndta::Stats])?.ok_or(Errno(libc::EINVAL))?,
+            default_parms,
+            per_dev: HashMap::new(),
+        })
+    }
+
+    // Applies the following device specific message. Returns false if it is
+    // for the other table, then the caller needs to look up by family and name.
+    pub fn update(&mut self, nlh: &Msghdr) -> Result<bool> {
+        let ndtm = nlh.payload::<Ndtmsg>()?;
+        let tb = NdtaTbl::from_nlmsg(mem::size_of::<Ndtmsg>(), nlh)?;
+        if ndtm.ndtm_family != self.family || tb.name()? != Some(self.name.as_str()) {
+            return Ok(false);
+        }
+        let parms = match tb.parms()? {
+            Some(parms) => NeighParms::from_tbl(&parms)?,
+            None => return Err(Errno(libc::EINVAL)),
+        };
+        match parms.ifindex {
+            Some(ifindex) => self.per_dev.insert(ifindex, parms),
+            None => return Err(Errno(libc::EINVAL)),
+        };
+        Ok(true)
+    }
+}
+
+// Changes for RTM_SETNEIGHTBL, the table is identified by name, family may be
+// AF_UNSPEC. The gc thresholds and interval are only for the table, parms may
+// be the default or device specific, NeighParms::dev().
+#[derive(Debug, Clone, Default)]
+pub struct NeighTableConfig {
+    pub family: u8,
+    pub name: String,
+    pub thresh1: Option<u32>,
+    pub thresh2: Option<u32>,
+    pub thresh3: Option<u32>,
+    pub gc_interval: Option<u64>,
+    pub parms: Option<NeighParms>,
+}
+
+impl NeighTableConfig {
+    pub fn new(family: u8, name: &str) -> Self {
+        Self {
+            family,
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn put<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = rtnetlink::RTM_SETNEIGHTBL;
+        nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+        nlh.nlmsg_seq = seq;
+        let ndtm = nlv.put_extra_header::<Ndtmsg>()?;
+        ndtm.ndtm_family = self.family;
+        Ndta::put_name(nlv, &self.name)?;
+        if let Some(v) = self.thresh1 {
+            Ndta::put_thresh1(nlv, &v)?;
+        }
+        if let Some(v) = self.thresh2 {
+            Ndta::put_thresh2(nlv, &v)?;
+        }
+        if let Some(v) = self.thresh3 {
+            Ndta::put_thresh3(nlv, &v)?;
+        }
+        if let Some(v) = self.gc_interval {
+            put_64bit(nlv, Ndta::GcInterval, Ndta::Pad, &v)?;
+        }
+        if let Some(parms) = self.parms {
+            parms.put(nlv)?;
+        }
+        Ok(nlv)
+    }
+}
+
+pub fn add(nl: &mut Socket, neigh: &Neighbour) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    neigh.put_add(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn replace(nl: &mut Socket, neigh: &Neighbour) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    neigh.put_replace(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn delete(nl: &mut Socket, neigh: &Neighbour) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    neigh.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_GETNEIGH dump, family is AF_INET, AF_INET6 or AF_UNSPEC for both.
+// Proxy entries are dumped separately, by NTF_PROXY in flags.
+pub fn dump(nl: &mut Socket, family: u8, flags: u8) -> Result<Vec<Neighbour>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETNEIGH;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let ndm = nlv.put_extra_header::<Ndmsg>()?;
+    ndm.ndm_family = family;
+    ndm.ndm_flags = flags;
+
+    let mut neighs = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        match Neighbour::from_nlmsg(nlh) {
+            Ok(neigh) => neighs.push(neigh),
+            Err(Errno(libc::EAFNOSUPPORT)) => {}
+            Err(errno) => return Err(errno),
+        }
+        Ok(())
+    })?;
+    Ok(neighs)
+}
+
+// RTM_GETNEIGH of the entry, ENOENT if not exists.
+pub fn get(nl: &mut Socket, index: u32, dst: IpAddr) -> Result<Neighbour> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETNEIGH;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let ndm = nlv.put_extra_header::<Ndmsg>()?;
+    ndm.ndm_ifindex = index as i32;
+    match dst {
+        IpAddr::V4(addr) => {
+            ndm.ndm_family = libc::AF_INET as u8;
+            Nda::put_v4dst(&mut nlv, &addr)?;
+        }
+        IpAddr::V6(addr) => {
+            ndm.ndm_family = libc::AF_INET6 as u8;
+            Nda::put_v6dst(&mut nlv, &addr)?;
+        }
+    }
+
+    let mut neigh = None;
+    run(nl, &nlv, seq, |nlh| {
+        neigh = Some(Neighbour::from_nlmsg(nlh)?);
+        Ok(())
+    })?;
+    neigh.ok_or(Errno(libc::ENOENT))
+}
+
+fn resolved(neigh: &Neighbour) -> Result<bool> {
+    let state = neigh.state;
+    if state.contains(Nud::Failed) {
+        return Err(Errno(libc::EHOSTUNREACH));
+    }
+    Ok(state.contains(Nud::Reachable)
+        || state.contains(Nud::Permanent)
+        || state.contains(Nud::Noarp))
+}
+
+// Triggers resolution of dst on the link by NTF_USE, creating the entry if
+// needed, then waits for NUD_REACHABLE, or NUD_PERMANENT and NUD_NOARP
+// which are reachable without resolution. Returns EHOSTUNREACH if it
+// becomes NUD_FAILED, ETIMEDOUT on timeout. A NUD_STALE entry goes to
+// NUD_DELAY first and is probed after delay_first_probe_time, 5 seconds by
+// default.
+pub fn resolve(nl: &mut Socket, index: u32, dst: IpAddr, timeout: Duration) -> Result<Neighbour> {
+    let deadline = Instant::now() + timeout;
+    // subscribe first not to miss the notification
+    let mut mon = Socket::open(libc::NETLINK_ROUTE, 0)?;
+    mon.bind(rtnetlink::RTMGRP_NEIGH, mnl::SOCKET_AUTOPID)?;
+
+    let mut neigh = Neighbour::new(index, dst);
+    neigh.state = NudState::default();
+    neigh.flags.insert(Ntf::Use);
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    neigh.put_new(&mut nlv, (libc::NLM_F_ACK | libc::NLM_F_CREATE) as u16, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))?;
+
+    let mut buf = mnl::default_buffer();
+    let mut current = Some(get(nl, index, dst)?);
+    loop {
+        if let Some(neigh) = current.take() {
+            if resolved(&neigh)? {
+                return Ok(neigh);
+            }
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(Errno(libc::ETIMEDOUT));
+        }
+        let mut pfd = libc::pollfd {
+            fd: mon.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let msecs = (deadline - now).as_millis() as libc::c_int + 1;
+        match unsafe { libc::poll(&mut pfd, 1, msecs) } {
+            n if n < 0 => match errno::errno() {
+                Errno(libc::EINTR) => continue,
+                errno => return Err(errno),
+            },
+            0 => continue,
+            _ => {}
+        }
+        let nrecv = match mon.recvfrom(&mut buf) {
+            Ok(n) => n,
+            Err(Errno(libc::ENOBUFS)) => {
+                current = Some(get(nl, index, dst)?);
+                continue;
+            }
+            Err(errno) => return Err(errno),
+        };
+        mnl::cb_run(
+            &buf[..nrecv],
+            0,
+            0,
+            Some(|nlh: &Msghdr| {
+                let neigh = match Neighbour::from_nlmsg(nlh) {
+                    Ok(neigh) => neigh,
+                    Err(Errno(libc::EAFNOSUPPORT)) => return Ok(CbStatus::Ok),
+                    Err(errno) => return Err(errno.into()),
+                };
+                if neigh.index != index || neigh.dst != dst || neigh.flags.contains(Ntf::Proxy) {
+                    return Ok(CbStatus::Ok);
+                }
+                if nlh.nlmsg_type == rtnetlink::RTM_DELNEIGH {
+                    return Err(Errno(libc::EHOSTUNREACH).into());
+                }
+                current = Some(neigh);
+                Ok(CbStatus::Ok)
+            }),
+        )
+        .map_err(to_errno)?;
+    }
+}
+
+pub fn fdb_add(nl: &mut Socket, entry: &FdbEntry) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    entry.put_add(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn fdb_replace(nl: &mut Socket, entry: &FdbEntry) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    entry.put_replace(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn fdb_append(nl: &mut Socket, entry: &FdbEntry) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    entry.put_append(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn fdb_delete(nl: &mut Socket, entry: &FdbEntry) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    entry.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_GETNEIGH dump of AF_BRIDGE. The kernel filters by the bridge and the
+// port only on the socket with netns::set_strict_check(), they are also
+// applied here.
+pub fn fdb_dump(nl: &mut Socket, master: Option<u32>, port: Option<u32>) -> Result<Vec<FdbEntry>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETNEIGH;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let ndm = nlv.put_extra_header::<Ndmsg>()?;
+    ndm.ndm_family = libc::AF_BRIDGE as u8;
+    ndm.ndm_ifindex = port.unwrap_or(0) as i32;
+    if let Some(master) = master {
+        Nda::put_master(&mut nlv, &master)?;
+    }
+
+    let mut entries = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        let entry = FdbEntry::from_nlmsg(nlh)?;
+        if (port.is_none() || port == Some(entry.index))
+            && (master.is_none() || master == entry.master)
+        {
+            entries.push(entry);
+        }
+        Ok(())
+    })?;
+    Ok(entries)
+}
+
+// RTM_GETNEIGHTBL dump, family is AF_INET, AF_INET6 or AF_UNSPEC for all.
+pub fn table_dump(nl: &mut Socket, family: u8) -> Result<Vec<NeighTable>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETNEIGHTBL;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let ndtm = nlv.put_extra_header::<Ndtmsg>()?;
+    ndtm.ndtm_family = family;
+
+    let mut tables: Vec<NeighTable> = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        match NeighTable::from_nlmsg(nlh) {
+            Ok(table) => tables.push(table),
+            Err(Errno(libc::ENOMSG)) => {
+                // the sequence should follow its table, the last
+                for table in tables.iter_mut().rev() {
+                    if table.update(nlh)? {
+                        return Ok(());
+                    }
+                }
+                return Err(Errno(libc::ENOENT));
+            }
+            Err(errno) => return Err(errno),
+        }
+        Ok(())
+    })?;
+    Ok(tables)
+}
+
+pub fn table_set(nl: &mut Socket, config: &NeighTableConfig) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    config.put(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
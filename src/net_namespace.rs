@@ -0,0 +1,30 @@
+use errno::Errno;
+
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+// Attributes of RTM_NEWNSID/RTM_GETNSID messages
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "NetnsaTbl"]
+pub enum Netnsa {
+    // NETNSA_
+    None = 0,
+
+    #[nla_type(i32, nsid)]
+    Nsid,
+
+    #[nla_type(u32, pid)]
+    Pid,
+
+    #[nla_type(u32, fd)]
+    Fd,
+
+    #[nla_type(i32, target_nsid)]
+    TargetNsid,
+
+    #[nla_type(i32, current_nsid)]
+    CurrentNsid,
+
+    _MAX,
+}
+pub const NETNSA_NSID_NOT_ASSIGNED: i32 = -1;
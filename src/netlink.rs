@@ -226,6 +226,7 @@ pub const NETLINK_LISTEN_ALL_NSID: c_int = 8;
 pub const NETLINK_LIST_MEMBERSHIPS: c_int = 9;
 pub const NETLINK_CAP_ACK: c_int = 10;
 pub const NETLINK_EXT_ACK: c_int = 11;
+pub const NETLINK_GET_STRICT_CHK: c_int = 12;
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
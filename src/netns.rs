@@ -0,0 +1,141 @@
+use errno::Errno;
+use libc::c_int;
+use std::{
+    fs::File,
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+use mnl::{AttrTbl, MsgVec, Result, Socket};
+
+use if_link::Ifla;
+use net_namespace::{Netnsa, NetnsaTbl, NETNSA_NSID_NOT_ASSIGNED};
+use netlink::NETLINK_GET_STRICT_CHK;
+use rtnetlink::{self, Rtgenmsg};
+use util::{run, seq};
+
+// where ip-netns(8) bind mounts named network namespaces
+pub const NETNS_RUN_DIR: &str = "/run/netns";
+
+// Network namespace other than the socket's one, for link operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Netns {
+    Fd(RawFd), // IFLA_NET_NS_FD
+    Pid(u32),  // IFLA_NET_NS_PID
+    Nsid(i32), // IFLA_TARGET_NETNSID, see nsid()
+}
+
+impl Ifla {
+    // Fd and Pid are the destination, RTM_NEWLINK creates the link in it
+    // and RTM_SETLINK moves the link to it. Nsid is the netns in which
+    // RTM_NEWLINK, RTM_SETLINK, RTM_DELLINK and RTM_GETLINK operate instead
+    // of the socket's one, it can not be used for moving.
+    pub fn put_netns(nlv: &mut MsgVec, netns: Netns) -> Result<&mut MsgVec> {
+        match netns {
+            Netns::Fd(fd) => Ifla::put_net_ns_fd(nlv, &(fd as u32)),
+            Netns::Pid(pid) => Ifla::put_net_ns_pid(nlv, &pid),
+            Netns::Nsid(nsid) => Ifla::put_target_netnsid(nlv, &nsid),
+        }
+    }
+}
+
+fn io_errno(err: io::Error) -> Errno {
+    Errno(err.raw_os_error().unwrap_or(libc::EIO))
+}
+
+// Opens the named netns created by `ip netns add`, the returned file can be
+// used as Netns::Fd.
+pub fn open(name: &str) -> Result<File> {
+    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+        return Err(Errno(libc::EINVAL));
+    }
+    File::open(format!("{}/{}", NETNS_RUN_DIR, name)).map_err(io_errno)
+}
+
+fn setns(fd: RawFd) -> Result<()> {
+    if unsafe { libc::setns(fd, libc::CLONE_NEWNET) } < 0 {
+        return Err(errno::errno());
+    }
+    Ok(())
+}
+
+// Opens a netlink socket in the netns referred by fd. setns(2) changes only
+// the calling thread, which is back to the original netns on return. The
+// socket keeps working in the netns it was opened in.
+pub fn socket_at(fd: RawFd, bus: c_int) -> Result<Socket> {
+    let orig = File::open("/proc/thread-self/ns/net").map_err(io_errno)?;
+    setns(fd)?;
+    let ret = Socket::open(bus, 0);
+    setns(orig.as_raw_fd())?;
+    ret
+}
+
+// Opens a netlink socket in the named netns.
+pub fn socket(name: &str, bus: c_int) -> Result<Socket> {
+    let ns = open(name)?;
+    socket_at(ns.as_raw_fd(), bus)
+}
+
+// Kernel takes IFA_TARGET_NETNSID in RTM_GETADDR dump request and some
+// other dump filters only if NETLINK_GET_STRICT_CHK is set on the socket.
+pub fn set_strict_check(nl: &Socket, v: bool) -> Result<()> {
+    let on: c_int = v as c_int;
+    let ret = unsafe {
+        libc::setsockopt(
+            nl.as_raw_fd(),
+            libc::SOL_NETLINK,
+            NETLINK_GET_STRICT_CHK,
+            &on as *const _ as *const libc::c_void,
+            std::mem::size_of::<c_int>() as libc::socklen_t,
+        )
+    };
+    if ret < 0 {
+        return Err(errno::errno());
+    }
+    Ok(())
+}
+
+fn nsid_request(nl: &mut Socket, mtype: u16, fd: RawFd, nsid: Option<i32>) -> Result<Option<i32>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = mtype;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let rt: &mut Rtgenmsg = nlv.put_extra_header()?;
+    rt.rtgen_family = libc::AF_UNSPEC as u8;
+    Netnsa::put_fd(&mut nlv, &(fd as u32))?;
+    if let Some(nsid) = nsid {
+        Netnsa::put_nsid(&mut nlv, &nsid)?;
+    }
+
+    let mut ret = None;
+    run(nl, &nlv, seq, |nlh| {
+        let tb = NetnsaTbl::from_nlmsg(std::mem::size_of::<Rtgenmsg>(), nlh)?;
+        ret = tb.nsid()?.copied();
+        Ok(())
+    })?;
+    Ok(ret)
+}
+
+// The nsid of the netns referred by fd, as seen from the socket's netns.
+// None if it is not assigned yet.
+pub fn nsid(nl: &mut Socket, fd: RawFd) -> Result<Option<i32>> {
+    match nsid_request(nl, rtnetlink::RTM_GETNSID, fd, None)? {
+        Some(NETNSA_NSID_NOT_ASSIGNED) | None => Ok(None),
+        Some(nsid) => Ok(Some(nsid)),
+    }
+}
+
+// Assigns an nsid to the netns referred by fd if not yet, then returns it.
+pub fn ensure_nsid(nl: &mut Socket, fd: RawFd) -> Result<i32> {
+    if let Some(nsid) = nsid(nl, fd)? {
+        return Ok(nsid);
+    }
+    // NETNSA_NSID -1 lets the kernel choose one
+    match nsid_request(nl, rtnetlink::RTM_NEWNSID, fd, Some(-1)) {
+        Ok(_) | Err(Errno(libc::EEXIST)) => {}
+        Err(errno) => return Err(errno),
+    }
+    nsid(nl, fd)?.ok_or(Errno(libc::ENOENT))
+}
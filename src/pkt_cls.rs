@@ -0,0 +1,1770 @@
+use errno::Errno;
+use std::{
+    mem,
+    net::{Ipv4Addr, Ipv6Addr},
+    ptr,
+};
+
+use mnl::{Attr, AttrTbl, CbStatus, MsgVec, Msghdr, Result, Socket};
+
+use netlink::NlaBitfield32;
+use pkt_sched::TcRatespec;
+use rtnetlink::{self, Tca, TcaTbl, Tcmsg};
+use util::{payload_bytes, put_64bit, run, seq, to_errno, unaligned};
+
+pub const TC_COOKIE_MAX_SIZE: usize = 16;
+
+// Action attributes
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "ActTbl"]
+pub enum Act {
+    // TCA_ACT_
+    Unspec,
+
+    #[nla_type(cstr, kind)]
+    Kind,
+
+    Options, // by the kind, see tc_act
+
+    #[nla_type(u32, index)]
+    Index,
+
+    Stats, // nest of TCA_STATS_, gen_stats.h
+
+    Pad,
+
+    #[nla_type(bytes, cookie)]
+    Cookie, // up to TC_COOKIE_MAX_SIZE
+
+    #[nla_type(NlaBitfield32, flags)]
+    Flags, // TCA_ACT_FLAGS_
+
+    #[nla_type(NlaBitfield32, hw_stats)]
+    HwStats, // TCA_ACT_HW_STATS_
+
+    #[nla_type(NlaBitfield32, used_hw_stats)]
+    UsedHwStats,
+
+    _MAX,
+}
+
+pub const TCA_ACT_FLAGS_NO_PERCPU_STATS: u32 = 1 << 0; // Don't use percpu allocator for
+                                                       // actions stats.
+
+// tca HW stats type
+// When user does not pass the attribute, he does not care.
+// It is the same as if he would pass the attribute with
+// all supported bits set.
+// In case no bits are set, user is not interested in getting any HW statistics.
+pub const TCA_ACT_HW_STATS_IMMEDIATE: u32 = 1 << 0; // Means that in dump, user
+                                                    // gets the current HW stats
+                                                    // state from the device
+                                                    // queried at the dump time.
+pub const TCA_ACT_HW_STATS_DELAYED: u32 = 1 << 1; // Means that in dump, user gets
+                                                  // HW stats that might be out of date
+                                                  // for some time, maybe couple of
+                                                  // seconds. This is the case when
+                                                  // driver polls stats updates
+                                                  // periodically or when it gets async
+                                                  // stats update from the device.
+
+pub const TCA_ACT_MAX: u16 = Act::_MAX as u16;
+pub const TCA_OLD_COMPAT: u16 = TCA_ACT_MAX + 1;
+pub const TCA_ACT_MAX_PRIO: u16 = 32;
+pub const TCA_ACT_BIND: i32 = 1;
+pub const TCA_ACT_NOBIND: i32 = 0;
+pub const TCA_ACT_UNBIND: i32 = 1;
+pub const TCA_ACT_NOUNBIND: i32 = 0;
+pub const TCA_ACT_REPLACE: i32 = 1;
+pub const TCA_ACT_NOREPLACE: i32 = 0;
+
+pub const TC_ACT_UNSPEC: i32 = -1;
+pub const TC_ACT_OK: i32 = 0;
+pub const TC_ACT_RECLASSIFY: i32 = 1;
+pub const TC_ACT_SHOT: i32 = 2;
+pub const TC_ACT_PIPE: i32 = 3;
+pub const TC_ACT_STOLEN: i32 = 4;
+pub const TC_ACT_QUEUED: i32 = 5;
+pub const TC_ACT_REPEAT: i32 = 6;
+pub const TC_ACT_REDIRECT: i32 = 7;
+pub const TC_ACT_TRAP: i32 = 8; // For hw path, this means "trap to cpu"
+                                // and don't further process the frame
+                                // in hardware. For sw path, this is
+                                // equivalent of TC_ACT_STOLEN - drop
+                                // the skb and act like everything
+                                // is alright.
+pub const TC_ACT_VALUE_MAX: i32 = TC_ACT_TRAP;
+
+// There is a special kind of actions called "extended actions",
+// which need a value parameter. These have a local opcode located in
+// the highest nibble, starting from 1. The rest of the bits
+// are used to carry the value. These two parts together make
+// a combined opcode.
+pub const __TC_ACT_EXT_SHIFT: i32 = 28;
+pub const fn __tc_act_ext(local: i32) -> i32 {
+    local << __TC_ACT_EXT_SHIFT
+}
+pub const TC_ACT_EXT_VAL_MASK: i32 = (1 << __TC_ACT_EXT_SHIFT) - 1;
+pub const fn tc_act_ext_opcode(combined: i32) -> i32 {
+    combined & !TC_ACT_EXT_VAL_MASK
+}
+pub const fn tc_act_ext_cmp(combined: i32, opcode: i32) -> bool {
+    tc_act_ext_opcode(combined) == opcode
+}
+
+pub const TC_ACT_JUMP: i32 = __tc_act_ext(1);
+pub const TC_ACT_GOTO_CHAIN: i32 = __tc_act_ext(2);
+pub const TC_ACT_EXT_OPCODE_MAX: i32 = TC_ACT_GOTO_CHAIN;
+
+// These macros are put here for binary compatibility with userspace apps that
+// make use of them. For kernel code and new userspace apps, use the TCA_ID_*
+// versions.
+pub const TCA_ACT_GACT: u32 = 5;
+pub const TCA_ACT_IPT: u32 = 6;
+pub const TCA_ACT_PEDIT: u32 = 7;
+pub const TCA_ACT_MIRRED: u32 = 8;
+pub const TCA_ACT_NAT: u32 = 9;
+pub const TCA_ACT_XT: u32 = 10;
+pub const TCA_ACT_SKBEDIT: u32 = 11;
+pub const TCA_ACT_VLAN: u32 = 12;
+pub const TCA_ACT_BPF: u32 = 13;
+pub const TCA_ACT_CONNMARK: u32 = 14;
+pub const TCA_ACT_SKBMOD: u32 = 15;
+pub const TCA_ACT_CSUM: u32 = 16;
+pub const TCA_ACT_TUNNEL_KEY: u32 = 17;
+pub const TCA_ACT_SIMP: u32 = 22;
+pub const TCA_ACT_IFE: u32 = 25;
+pub const TCA_ACT_SAMPLE: u32 = 26;
+
+// Action type identifiers
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcaId {
+    Unspec = 0,
+    Police = 1,
+    Gact = TCA_ACT_GACT,
+    Ipt = TCA_ACT_IPT,
+    Pedit = TCA_ACT_PEDIT,
+    Mirred = TCA_ACT_MIRRED,
+    Nat = TCA_ACT_NAT,
+    Xt = TCA_ACT_XT,
+    Skbedit = TCA_ACT_SKBEDIT,
+    Vlan = TCA_ACT_VLAN,
+    Bpf = TCA_ACT_BPF,
+    Connmark = TCA_ACT_CONNMARK,
+    Skbmod = TCA_ACT_SKBMOD,
+    Csum = TCA_ACT_CSUM,
+    TunnelKey = TCA_ACT_TUNNEL_KEY,
+    Simp = TCA_ACT_SIMP,
+    Ife = TCA_ACT_IFE,
+    Sample = TCA_ACT_SAMPLE,
+    Ctinfo,
+    Mpls,
+    Ct,
+    Gate,
+    // other actions go here
+    _MAX = 255,
+}
+pub const TCA_ID_UNSPEC: u32 = TcaId::Unspec as u32;
+pub const TCA_ID_POLICE: u32 = TcaId::Police as u32;
+pub const TCA_ID_GACT: u32 = TcaId::Gact as u32;
+pub const TCA_ID_IPT: u32 = TcaId::Ipt as u32;
+pub const TCA_ID_PEDIT: u32 = TcaId::Pedit as u32;
+pub const TCA_ID_MIRRED: u32 = TcaId::Mirred as u32;
+pub const TCA_ID_NAT: u32 = TcaId::Nat as u32;
+pub const TCA_ID_XT: u32 = TcaId::Xt as u32;
+pub const TCA_ID_SKBEDIT: u32 = TcaId::Skbedit as u32;
+pub const TCA_ID_VLAN: u32 = TcaId::Vlan as u32;
+pub const TCA_ID_BPF: u32 = TcaId::Bpf as u32;
+pub const TCA_ID_CONNMARK: u32 = TcaId::Connmark as u32;
+pub const TCA_ID_SKBMOD: u32 = TcaId::Skbmod as u32;
+pub const TCA_ID_CSUM: u32 = TcaId::Csum as u32;
+pub const TCA_ID_TUNNEL_KEY: u32 = TcaId::TunnelKey as u32;
+pub const TCA_ID_SIMP: u32 = TcaId::Simp as u32;
+pub const TCA_ID_IFE: u32 = TcaId::Ife as u32;
+pub const TCA_ID_SAMPLE: u32 = TcaId::Sample as u32;
+pub const TCA_ID_CTINFO: u32 = TcaId::Ctinfo as u32;
+pub const TCA_ID_MPLS: u32 = TcaId::Mpls as u32;
+pub const TCA_ID_CT: u32 = TcaId::Ct as u32;
+pub const TCA_ID_GATE: u32 = TcaId::Gate as u32;
+pub const TCA_ID_MAX: u32 = TcaId::_MAX as u32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcPolice {
+    pub index: u32,
+    pub action: i32, // TC_POLICE_
+    pub limit: u32,
+    pub burst: u32,
+    pub mtu: u32,
+    pub rate: TcRatespec,
+    pub peakrate: TcRatespec,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub capab: u32,
+}
+pub const TC_POLICE_UNSPEC: i32 = TC_ACT_UNSPEC;
+pub const TC_POLICE_OK: i32 = TC_ACT_OK;
+pub const TC_POLICE_RECLASSIFY: i32 = TC_ACT_RECLASSIFY;
+pub const TC_POLICE_SHOT: i32 = TC_ACT_SHOT;
+pub const TC_POLICE_PIPE: i32 = TC_ACT_PIPE;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcfT {
+    pub install: u64,
+    pub lastuse: u64,
+    pub expires: u64,
+    pub firstuse: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcCnt {
+    pub refcnt: i32,
+    pub bindcnt: i32,
+}
+
+// #define tc_gen is expanded in each struct of tc_act
+//     __u32                 index;
+//     __u32                 capab;
+//     int                   action;
+//     int                   refcnt;
+//     int                   bindcnt
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "PoliceTbl"]
+pub enum Police {
+    // TCA_POLICE_
+    Unspec,
+
+    #[nla_type(TcPolice, tbf)]
+    Tbf,
+
+    #[nla_type(bytes, rate)]
+    Rate, // u32[256] rate table
+
+    #[nla_type(bytes, peakrate)]
+    Peakrate,
+
+    #[nla_type(u32, avrate)]
+    Avrate,
+
+    #[nla_type(u32, result)]
+    Result,
+
+    Tm, // struct tcf_t, see tm()
+    Pad,
+    Rate64, // u64, see rate64()
+    Peakrate64,
+    _MAX,
+}
+
+// tca flags definitions
+pub const TCA_CLS_FLAGS_SKIP_HW: u32 = 1 << 0; // don't offload filter to HW
+pub const TCA_CLS_FLAGS_SKIP_SW: u32 = 1 << 1; // don't use filter in SW
+pub const TCA_CLS_FLAGS_IN_HW: u32 = 1 << 2; // filter is offloaded to HW
+pub const TCA_CLS_FLAGS_NOT_IN_HW: u32 = 1 << 3; // filter isn't offloaded to HW
+pub const TCA_CLS_FLAGS_VERBOSE: u32 = 1 << 4; // verbose logging
+
+// U32 filters
+pub const fn tc_u32_htid(h: u32) -> u32 {
+    h & 0xFFF00000
+}
+pub const fn tc_u32_userhtid(h: u32) -> u32 {
+    tc_u32_htid(h) >> 20
+}
+pub const fn tc_u32_hash(h: u32) -> u32 {
+    (h >> 12) & 0xFF
+}
+pub const fn tc_u32_node(h: u32) -> u32 {
+    h & 0xFFF
+}
+pub const fn tc_u32_key(h: u32) -> u32 {
+    h & 0xFFFFF
+}
+pub const TC_U32_UNSPEC: u32 = 0;
+pub const TC_U32_ROOT: u32 = 0xFFF00000;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "U32Tbl"]
+pub enum U32 {
+    // TCA_U32_
+    Unspec,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_type(u32, hash)]
+    Hash,
+
+    #[nla_type(u32, link)]
+    Link,
+
+    #[nla_type(u32, divisor)]
+    Divisor,
+
+    #[nla_type(TcU32Sel, sel)]
+    Sel, // followed by nkeys of struct tc_u32_key, see keys()
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    Act, // see actions()
+
+    #[nla_type(cstr, indev)]
+    Indev,
+
+    Pcnt, // struct tc_u32_pcnt, see pcnt()
+
+    #[nla_type(TcU32Mark, mark)]
+    Mark,
+
+    #[nla_type(u32, flags)]
+    Flags, // TCA_CLS_FLAGS_
+
+    Pad,
+    _MAX,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcU32Key {
+    pub mask: u32, // __be32
+    pub val: u32,  // __be32
+    pub off: i32,
+    pub offmask: i32,
+}
+
+// struct tc_u32_key keys[] follows.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcU32Sel {
+    pub flags: u8, // TC_U32_
+    pub offshift: u8,
+    pub nkeys: u8,
+
+    pub offmask: u16, // __be16
+    pub off: u16,
+    pub offoff: i16,
+
+    pub hoff: i16,
+    pub hmask: u32, // __be32
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcU32Mark {
+    pub val: u32,
+    pub mask: u32,
+    pub success: u32,
+}
+
+// u64 kcnts[] follows, for each key.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcU32Pcnt {
+    pub rcnt: u64,
+    pub rhit: u64,
+}
+
+// Flags
+pub const TC_U32_TERMINAL: u8 = 1;
+pub const TC_U32_OFFSET: u8 = 2;
+pub const TC_U32_VAROFFSET: u8 = 4;
+pub const TC_U32_EAT: u8 = 8;
+
+pub const TC_U32_MAXDEPTH: usize = 8;
+
+// RSVP filter
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "RsvpTbl"]
+pub enum Rsvp {
+    // TCA_RSVP_
+    Unspec,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_type(bytes, dst)]
+    Dst, // in_addr or in6_addr
+
+    #[nla_type(bytes, src)]
+    Src,
+
+    #[nla_type(TcRsvpPinfo, pinfo)]
+    Pinfo,
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    Act, // see actions()
+    _MAX,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcRsvpGpi {
+    pub key: u32,
+    pub mask: u32,
+    pub offset: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcRsvpPinfo {
+    pub dpi: TcRsvpGpi,
+    pub spi: TcRsvpGpi,
+    pub protocol: u8,
+    pub tunnelid: u8,
+    pub tunnelhdr: u8,
+    pub pad: u8,
+}
+
+// ROUTE filter
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "Route4Tbl"]
+pub enum Route4 {
+    // TCA_ROUTE4_
+    Unspec,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_type(u32, to)]
+    To, // realm
+
+    #[nla_type(u32, from)]
+    From,
+
+    #[nla_type(u32, iif)]
+    Iif,
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    Act, // see actions()
+    _MAX,
+}
+
+// FW filter
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FwTbl"]
+pub enum Fw {
+    // TCA_FW_
+    Unspec,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    #[nla_type(cstr, indev)]
+    Indev,
+
+    Act, // used by CONFIG_NET_CLS_ACT, see actions()
+
+    #[nla_type(u32, mask)]
+    Mask,
+
+    _MAX,
+}
+
+// TC index filter
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TcindexTbl"]
+pub enum Tcindex {
+    // TCA_TCINDEX_
+    Unspec,
+
+    #[nla_type(u32, hash)]
+    Hash,
+
+    #[nla_type(u16, mask)]
+    Mask,
+
+    #[nla_type(u32, shift)]
+    Shift,
+
+    #[nla_type(u32, fall_through)]
+    FallThrough,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    Act, // see actions()
+    _MAX,
+}
+
+// Flow filter
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlowKey {
+    Src,
+    Dst,
+    Proto,
+    ProtoSrc,
+    ProtoDst,
+    Iif,
+    Priority,
+    Mark,
+    Nfct,
+    NfctSrc,
+    NfctDst,
+    NfctProtoSrc,
+    NfctProtoDst,
+    Rtclassid,
+    Skuid,
+    Skgid,
+    VlanTag,
+    Rxhash,
+    _MAX,
+}
+pub const FLOW_KEY_SRC: u32 = FlowKey::Src as u32;
+pub const FLOW_KEY_DST: u32 = FlowKey::Dst as u32;
+pub const FLOW_KEY_PROTO: u32 = FlowKey::Proto as u32;
+pub const FLOW_KEY_PROTO_SRC: u32 = FlowKey::ProtoSrc as u32;
+pub const FLOW_KEY_PROTO_DST: u32 = FlowKey::ProtoDst as u32;
+pub const FLOW_KEY_IIF: u32 = FlowKey::Iif as u32;
+pub const FLOW_KEY_PRIORITY: u32 = FlowKey::Priority as u32;
+pub const FLOW_KEY_MARK: u32 = FlowKey::Mark as u32;
+pub const FLOW_KEY_NFCT: u32 = FlowKey::Nfct as u32;
+pub const FLOW_KEY_NFCT_SRC: u32 = FlowKey::NfctSrc as u32;
+pub const FLOW_KEY_NFCT_DST: u32 = FlowKey::NfctDst as u32;
+pub const FLOW_KEY_NFCT_PROTO_SRC: u32 = FlowKey::NfctProtoSrc as u32;
+pub const FLOW_KEY_NFCT_PROTO_DST: u32 = FlowKey::NfctProtoDst as u32;
+pub const FLOW_KEY_RTCLASSID: u32 = FlowKey::Rtclassid as u32;
+pub const FLOW_KEY_SKUID: u32 = FlowKey::Skuid as u32;
+pub const FLOW_KEY_SKGID: u32 = FlowKey::Skgid as u32;
+pub const FLOW_KEY_VLAN_TAG: u32 = FlowKey::VlanTag as u32;
+pub const FLOW_KEY_RXHASH: u32 = FlowKey::Rxhash as u32;
+pub const FLOW_KEY_MAX: u32 = FlowKey::_MAX as u32 - 1;
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlowMode {
+    Map,
+    Hash,
+}
+pub const FLOW_MODE_MAP: u32 = FlowMode::Map as u32;
+pub const FLOW_MODE_HASH: u32 = FlowMode::Hash as u32;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowTbl"]
+pub enum Flow {
+    // TCA_FLOW_
+    Unspec,
+
+    #[nla_type(u32, keys)]
+    Keys, // 1 << FLOW_KEY_
+
+    #[nla_type(u32, mode)]
+    Mode, // FLOW_MODE_
+
+    #[nla_type(u32, baseclass)]
+    Baseclass,
+
+    #[nla_type(u32, rshift)]
+    Rshift,
+
+    #[nla_type(u32, addend)]
+    Addend,
+
+    #[nla_type(u32, mask)]
+    Mask,
+
+    #[nla_type(u32, xor)]
+    Xor,
+
+    #[nla_type(u32, divisor)]
+    Divisor,
+
+    Act, // see actions()
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    #[nla_nest(EmatchTreeTbl, ematches)]
+    Ematches,
+
+    #[nla_type(u32, perturb)]
+    Perturb,
+
+    _MAX,
+}
+
+// Basic filter
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcBasicPcnt {
+    pub rcnt: u64,
+    pub rhit: u64,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BasicTbl"]
+pub enum Basic {
+    // TCA_BASIC_
+    Unspec,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_nest(EmatchTreeTbl, ematches)]
+    Ematches,
+
+    Act, // see actions()
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    Pcnt, // struct tc_basic_pcnt, see pcnt()
+    Pad,
+    _MAX,
+}
+
+// Cgroup classifier
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "CgroupTbl"]
+pub enum Cgroup {
+    // TCA_CGROUP_
+    Unspec,
+    Act, // see actions()
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    #[nla_nest(EmatchTreeTbl, ematches)]
+    Ematches,
+
+    _MAX,
+}
+
+// BPF classifier
+pub const TCA_BPF_FLAG_ACT_DIRECT: u32 = 1 << 0;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "BpfTbl"]
+pub enum Bpf {
+    // TCA_BPF_
+    Unspec,
+    Act, // see actions()
+
+    #[nla_nest(PoliceTbl, police)]
+    Police,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_type(u16, ops_len)]
+    OpsLen,
+
+    #[nla_type(bytes, ops)]
+    Ops, // struct sock_filter[ops_len], classic BPF
+
+    #[nla_type(u32, fd)]
+    Fd, // eBPF program fd, BPF_PROG_TYPE_SCHED_CLS
+
+    #[nla_type(cstr, name)]
+    Name,
+
+    #[nla_type(u32, flags)]
+    Flags, // TCA_BPF_FLAG_
+
+    #[nla_type(u32, flags_gen)]
+    FlagsGen, // TCA_CLS_FLAGS_
+
+    #[nla_type(bytes, tag)]
+    Tag, // BPF_TAG_SIZE
+
+    #[nla_type(u32, id)]
+    Id,
+
+    _MAX,
+}
+
+// Flower classifier
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowerTbl"]
+pub enum Flower {
+    // TCA_FLOWER_
+    Unspec,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    #[nla_type(cstr, indev)]
+    Indev,
+
+    Act, // see actions()
+
+    #[nla_type(bytes, key_eth_dst)]
+    KeyEthDst, // ETH_ALEN
+
+    #[nla_type(bytes, key_eth_dst_mask)]
+    KeyEthDstMask, // ETH_ALEN
+
+    #[nla_type(bytes, key_eth_src)]
+    KeyEthSrc, // ETH_ALEN
+
+    #[nla_type(bytes, key_eth_src_mask)]
+    KeyEthSrcMask, // ETH_ALEN
+
+    #[nla_type(u16, key_eth_type)]
+    KeyEthType, // be16
+
+    #[nla_type(u8, key_ip_proto)]
+    KeyIpProto, // u8
+
+    #[nla_type(Ipv4Addr, key_ipv4_src)]
+    KeyIpv4Src, // be32
+
+    #[nla_type(Ipv4Addr, key_ipv4_src_mask)]
+    KeyIpv4SrcMask, // be32
+
+    #[nla_type(Ipv4Addr, key_ipv4_dst)]
+    KeyIpv4Dst, // be32
+
+    #[nla_type(Ipv4Addr, key_ipv4_dst_mask)]
+    KeyIpv4DstMask, // be32
+
+    #[nla_type(Ipv6Addr, key_ipv6_src)]
+    KeyIpv6Src, // struct in6_addr
+
+    #[nla_type(Ipv6Addr, key_ipv6_src_mask)]
+    KeyIpv6SrcMask, // struct in6_addr
+
+    #[nla_type(Ipv6Addr, key_ipv6_dst)]
+    KeyIpv6Dst, // struct in6_addr
+
+    #[nla_type(Ipv6Addr, key_ipv6_dst_mask)]
+    KeyIpv6DstMask, // struct in6_addr
+
+    #[nla_type(u16, key_tcp_src)]
+    KeyTcpSrc, // be16
+
+    #[nla_type(u16, key_tcp_dst)]
+    KeyTcpDst, // be16
+
+    #[nla_type(u16, key_udp_src)]
+    KeyUdpSrc, // be16
+
+    #[nla_type(u16, key_udp_dst)]
+    KeyUdpDst, // be16
+
+    #[nla_type(u32, flags)]
+    Flags, // TCA_CLS_FLAGS_
+
+    #[nla_type(u16, key_vlan_id)]
+    KeyVlanId, // be16
+
+    #[nla_type(u8, key_vlan_prio)]
+    KeyVlanPrio, // u8
+
+    #[nla_type(u16, key_vlan_eth_type)]
+    KeyVlanEthType, // be16
+
+    #[nla_type(u32, key_enc_key_id)]
+    KeyEncKeyId, // be32
+
+    #[nla_type(Ipv4Addr, key_enc_ipv4_src)]
+    KeyEncIpv4Src, // be32
+
+    #[nla_type(Ipv4Addr, key_enc_ipv4_src_mask)]
+    KeyEncIpv4SrcMask, // be32
+
+    #[nla_type(Ipv4Addr, key_enc_ipv4_dst)]
+    KeyEncIpv4Dst, // be32
+
+    #[nla_type(Ipv4Addr, key_enc_ipv4_dst_mask)]
+    KeyEncIpv4DstMask, // be32
+
+    #[nla_type(Ipv6Addr, key_enc_ipv6_src)]
+    KeyEncIpv6Src, // struct in6_addr
+
+    #[nla_type(Ipv6Addr, key_enc_ipv6_src_mask)]
+    KeyEncIpv6SrcMask, // struct in6_addr
+
+    #[nla_type(Ipv6Addr, key_enc_ipv6_dst)]
+    KeyEncIpv6Dst, // struct in6_addr
+
+    #[nla_type(Ipv6Addr, key_enc_ipv6_dst_mask)]
+    KeyEncIpv6DstMask, // struct in6_addr
+
+    #[nla_type(u16, key_tcp_src_mask)]
+    KeyTcpSrcMask, // be16
+
+    #[nla_type(u16, key_tcp_dst_mask)]
+    KeyTcpDstMask, // be16
+
+    #[nla_type(u16, key_udp_src_mask)]
+    KeyUdpSrcMask, // be16
+
+    #[nla_type(u16, key_udp_dst_mask)]
+    KeyUdpDstMask, // be16
+
+    #[nla_type(u16, key_sctp_src_mask)]
+    KeySctpSrcMask, // be16
+
+    #[nla_type(u16, key_sctp_dst_mask)]
+    KeySctpDstMask, // be16
+
+    #[nla_type(u16, key_sctp_src)]
+    KeySctpSrc, // be16
+
+    #[nla_type(u16, key_sctp_dst)]
+    KeySctpDst, // be16
+
+    #[nla_type(u16, key_enc_udp_src_port)]
+    KeyEncUdpSrcPort, // be16
+
+    #[nla_type(u16, key_enc_udp_src_port_mask)]
+    KeyEncUdpSrcPortMask, // be16
+
+    #[nla_type(u16, key_enc_udp_dst_port)]
+    KeyEncUdpDstPort, // be16
+
+    #[nla_type(u16, key_enc_udp_dst_port_mask)]
+    KeyEncUdpDstPortMask, // be16
+
+    #[nla_type(u32, key_flags)]
+    KeyFlags, // be32, TCA_FLOWER_KEY_FLAGS_
+
+    #[nla_type(u32, key_flags_mask)]
+    KeyFlagsMask, // be32
+
+    #[nla_type(u8, key_icmpv4_code)]
+    KeyIcmpv4Code, // u8
+
+    #[nla_type(u8, key_icmpv4_code_mask)]
+    KeyIcmpv4CodeMask, // u8
+
+    #[nla_type(u8, key_icmpv4_type)]
+    KeyIcmpv4Type, // u8
+
+    #[nla_type(u8, key_icmpv4_type_mask)]
+    KeyIcmpv4TypeMask, // u8
+
+    #[nla_type(u8, key_icmpv6_code)]
+    KeyIcmpv6Code, // u8
+
+    #[nla_type(u8, key_icmpv6_code_mask)]
+    KeyIcmpv6CodeMask, // u8
+
+    #[nla_type(u8, key_icmpv6_type)]
+    KeyIcmpv6Type, // u8
+
+    #[nla_type(u8, key_icmpv6_type_mask)]
+    KeyIcmpv6TypeMask, // u8
+
+    #[nla_type(Ipv4Addr, key_arp_sip)]
+    KeyArpSip, // be32
+
+    #[nla_type(Ipv4Addr, key_arp_sip_mask)]
+    KeyArpSipMask, // be32
+
+    #[nla_type(Ipv4Addr, key_arp_tip)]
+    KeyArpTip, // be32
+
+    #[nla_type(Ipv4Addr, key_arp_tip_mask)]
+    KeyArpTipMask, // be32
+
+    #[nla_type(u8, key_arp_op)]
+    KeyArpOp, // u8
+
+    #[nla_type(u8, key_arp_op_mask)]
+    KeyArpOpMask, // u8
+
+    #[nla_type(bytes, key_arp_sha)]
+    KeyArpSha, // ETH_ALEN
+
+    #[nla_type(bytes, key_arp_sha_mask)]
+    KeyArpShaMask, // ETH_ALEN
+
+    #[nla_type(bytes, key_arp_tha)]
+    KeyArpTha, // ETH_ALEN
+
+    #[nla_type(bytes, key_arp_tha_mask)]
+    KeyArpThaMask, // ETH_ALEN
+
+    #[nla_type(u8, key_mpls_ttl)]
+    KeyMplsTtl, // u8 - 8 bits
+
+    #[nla_type(u8, key_mpls_bos)]
+    KeyMplsBos, // u8 - 1 bit
+
+    #[nla_type(u8, key_mpls_tc)]
+    KeyMplsTc, // u8 - 3 bits
+
+    #[nla_type(u32, key_mpls_label)]
+    KeyMplsLabel, // be32 - 20 bits
+
+    #[nla_type(u16, key_tcp_flags)]
+    KeyTcpFlags, // be16
+
+    #[nla_type(u16, key_tcp_flags_mask)]
+    KeyTcpFlagsMask, // be16
+
+    #[nla_type(u8, key_ip_tos)]
+    KeyIpTos, // u8
+
+    #[nla_type(u8, key_ip_tos_mask)]
+    KeyIpTosMask, // u8
+
+    #[nla_type(u8, key_ip_ttl)]
+    KeyIpTtl, // u8
+
+    #[nla_type(u8, key_ip_ttl_mask)]
+    KeyIpTtlMask, // u8
+
+    #[nla_type(u16, key_cvlan_id)]
+    KeyCvlanId, // be16
+
+    #[nla_type(u8, key_cvlan_prio)]
+    KeyCvlanPrio, // u8
+
+    #[nla_type(u16, key_cvlan_eth_type)]
+    KeyCvlanEthType, // be16
+
+    #[nla_type(u8, key_enc_ip_tos)]
+    KeyEncIpTos, // u8
+
+    #[nla_type(u8, key_enc_ip_tos_mask)]
+    KeyEncIpTosMask, // u8
+
+    #[nla_type(u8, key_enc_ip_ttl)]
+    KeyEncIpTtl, // u8
+
+    #[nla_type(u8, key_enc_ip_ttl_mask)]
+    KeyEncIpTtlMask, // u8
+
+    #[nla_nest(FlowerKeyEncOptsTbl, key_enc_opts)]
+    KeyEncOpts,
+
+    #[nla_nest(FlowerKeyEncOptsTbl, key_enc_opts_mask)]
+    KeyEncOptsMask,
+
+    #[nla_type(u32, in_hw_count)]
+    InHwCount,
+
+    #[nla_type(u16, key_port_src_min)]
+    KeyPortSrcMin, // be16
+
+    #[nla_type(u16, key_port_src_max)]
+    KeyPortSrcMax, // be16
+
+    #[nla_type(u16, key_port_dst_min)]
+    KeyPortDstMin, // be16
+
+    #[nla_type(u16, key_port_dst_max)]
+    KeyPortDstMax, // be16
+
+    #[nla_type(u16, key_ct_state)]
+    KeyCtState, // u16, TCA_FLOWER_KEY_CT_FLAGS_
+
+    #[nla_type(u16, key_ct_state_mask)]
+    KeyCtStateMask, // u16
+
+    #[nla_type(u16, key_ct_zone)]
+    KeyCtZone, // u16
+
+    #[nla_type(u16, key_ct_zone_mask)]
+    KeyCtZoneMask, // u16
+
+    #[nla_type(u32, key_ct_mark)]
+    KeyCtMark, // u32
+
+    #[nla_type(u32, key_ct_mark_mask)]
+    KeyCtMarkMask, // u32
+
+    #[nla_type(bytes, key_ct_labels)]
+    KeyCtLabels, // u128
+
+    #[nla_type(bytes, key_ct_labels_mask)]
+    KeyCtLabelsMask, // u128
+
+    #[nla_nest(FlowerKeyMplsOptsTbl, key_mpls_opts)]
+    KeyMplsOpts,
+
+    _MAX,
+}
+
+pub const TCA_FLOWER_KEY_CT_FLAGS_NEW: u16 = 1 << 0; // Beginning of a new connection.
+pub const TCA_FLOWER_KEY_CT_FLAGS_ESTABLISHED: u16 = 1 << 1; // Part of an existing connection.
+pub const TCA_FLOWER_KEY_CT_FLAGS_RELATED: u16 = 1 << 2; // Related to an established connection.
+pub const TCA_FLOWER_KEY_CT_FLAGS_TRACKED: u16 = 1 << 3; // Conntrack has occurred.
+pub const TCA_FLOWER_KEY_CT_FLAGS_INVALID: u16 = 1 << 4; // Conntrack is invalid.
+pub const TCA_FLOWER_KEY_CT_FLAGS_REPLY: u16 = 1 << 5; // Packet is in the reply direction.
+
+// The table keeps the last one of repeated options, e.g. GENEVE.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowerKeyEncOptsTbl"]
+pub enum FlowerKeyEncOpts {
+    // TCA_FLOWER_KEY_ENC_OPTS_
+    Unspec,
+
+    #[nla_nest(FlowerKeyEncOptGeneveTbl, geneve)]
+    Geneve, // Nested TCA_FLOWER_KEY_ENC_OPT_GENEVE_ attributes
+
+    #[nla_nest(FlowerKeyEncOptVxlanTbl, vxlan)]
+    Vxlan, // Nested TCA_FLOWER_KEY_ENC_OPT_VXLAN_ attributes
+
+    #[nla_nest(FlowerKeyEncOptErspanTbl, erspan)]
+    Erspan, // Nested TCA_FLOWER_KEY_ENC_OPT_ERSPAN_ attributes
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowerKeyEncOptGeneveTbl"]
+pub enum FlowerKeyEncOptGeneve {
+    // TCA_FLOWER_KEY_ENC_OPT_GENEVE_
+    Unspec,
+
+    #[nla_type(u16, class)]
+    Class, // u16
+
+    #[nla_type(u8, type_)]
+    Type, // u8
+
+    #[nla_type(bytes, data)]
+    Data, // 4 to 128 bytes
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowerKeyEncOptVxlanTbl"]
+pub enum FlowerKeyEncOptVxlan {
+    // TCA_FLOWER_KEY_ENC_OPT_VXLAN_
+    Unspec,
+
+    #[nla_type(u32, gbp)]
+    Gbp, // u32
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowerKeyEncOptErspanTbl"]
+pub enum FlowerKeyEncOptErspan {
+    // TCA_FLOWER_KEY_ENC_OPT_ERSPAN_
+    Unspec,
+
+    #[nla_type(u8, ver)]
+    Ver, // u8
+
+    #[nla_type(u32, index)]
+    Index, // be32
+
+    #[nla_type(u8, dir)]
+    Dir, // u8
+
+    #[nla_type(u8, hwid)]
+    Hwid, // u8
+
+    _MAX,
+}
+
+// LSE are repeated for each depth, see FlowerTbl::key_mpls_lses().
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowerKeyMplsOptsTbl"]
+pub enum FlowerKeyMplsOpts {
+    // TCA_FLOWER_KEY_MPLS_OPTS_
+    Unspec,
+
+    #[nla_nest(FlowerKeyMplsOptLseTbl, lse)]
+    Lse,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FlowerKeyMplsOptLseTbl"]
+pub enum FlowerKeyMplsOptLse {
+    // TCA_FLOWER_KEY_MPLS_OPT_LSE_
+    Unspec,
+
+    #[nla_type(u8, depth)]
+    Depth, // from 1
+
+    #[nla_type(u8, ttl)]
+    Ttl,
+
+    #[nla_type(u8, bos)]
+    Bos,
+
+    #[nla_type(u8, tc)]
+    Tc,
+
+    #[nla_type(u32, label)]
+    Label,
+
+    _MAX,
+}
+
+pub const TCA_FLOWER_KEY_FLAGS_IS_FRAGMENT: u32 = 1 << 0;
+pub const TCA_FLOWER_KEY_FLAGS_FRAG_IS_FIRST: u32 = 1 << 1;
+
+pub const TCA_FLOWER_MASK_FLAGS_RANGE: u32 = 1 << 0; // Range-based match
+
+// Match-all classifier
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcMatchallPcnt {
+    pub rhit: u64,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MatchallTbl"]
+pub enum Matchall {
+    // TCA_MATCHALL_
+    Unspec,
+
+    #[nla_type(u32, classid)]
+    Classid,
+
+    Act, // see actions()
+
+    #[nla_type(u32, flags)]
+    Flags, // TCA_CLS_FLAGS_
+
+    Pcnt, // struct tc_matchall_pcnt, see pcnt()
+    Pad,
+    _MAX,
+}
+
+// Extended Matches
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcfEmatchTreeHdr {
+    pub nmatches: u16,
+    pub progid: u16, // TCF_EM_PROG_
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "EmatchTreeTbl"]
+pub enum EmatchTree {
+    // TCA_EMATCH_TREE_
+    Unspec,
+
+    #[nla_type(TcfEmatchTreeHdr, hdr)]
+    Hdr,
+
+    List, // nest of struct tcf_ematch_hdr and its data, typed by the order from 1
+    _MAX,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcfEmatchHdr {
+    pub matchid: u16,
+    pub kind: u16, // TCF_EM_
+    pub flags: u16,
+    pub pad: u16, // currently unused
+}
+
+//  0                   1
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5
+// +-----------------------+-+-+---+
+// |         Unused        |S|I| R |
+// +-----------------------+-+-+---+
+//
+// R(2) ::= relation to next ematch
+//          where: 0 0 END (last ematch)
+//                 0 1 AND
+//                 1 0 OR
+//                 1 1 Unused (invalid)
+// I(1) ::= invert result
+// S(1) ::= simple payload
+pub const TCF_EM_REL_END: u16 = 0;
+pub const TCF_EM_REL_AND: u16 = 1 << 0;
+pub const TCF_EM_REL_OR: u16 = 1 << 1;
+pub const TCF_EM_INVERT: u16 = 1 << 2;
+pub const TCF_EM_SIMPLE: u16 = 1 << 3;
+
+pub const TCF_EM_REL_MASK: u16 = 3;
+pub const fn tcf_em_rel_valid(v: u16) -> bool {
+    (v & TCF_EM_REL_MASK) != TCF_EM_REL_MASK
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcfLayer {
+    Link,
+    Network,
+    Transport,
+    _MAX,
+}
+pub const TCF_LAYER_LINK: u8 = TcfLayer::Link as u8;
+pub const TCF_LAYER_NETWORK: u8 = TcfLayer::Network as u8;
+pub const TCF_LAYER_TRANSPORT: u8 = TcfLayer::Transport as u8;
+pub const TCF_LAYER_MAX: u8 = TcfLayer::_MAX as u8 - 1;
+
+// Ematch type assignments
+//   1..32767		Reserved for ematches inside kernel tree
+//   32768..65535	Free to use, not reliable
+pub const TCF_EM_CONTAINER: u16 = 0;
+pub const TCF_EM_CMP: u16 = 1;
+pub const TCF_EM_NBYTE: u16 = 2;
+pub const TCF_EM_U32: u16 = 3;
+pub const TCF_EM_META: u16 = 4;
+pub const TCF_EM_TEXT: u16 = 5;
+pub const TCF_EM_VLAN: u16 = 6;
+pub const TCF_EM_CANID: u16 = 7;
+pub const TCF_EM_IPSET: u16 = 8;
+pub const TCF_EM_IPT: u16 = 9;
+pub const TCF_EM_MAX: u16 = 9;
+
+pub const TCF_EM_PROG_TC: u16 = 0;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcfEmOpnd {
+    Eq,
+    Gt,
+    Lt,
+}
+pub const TCF_EM_OPND_EQ: u8 = TcfEmOpnd::Eq as u8;
+pub const TCF_EM_OPND_GT: u8 = TcfEmOpnd::Gt as u8;
+pub const TCF_EM_OPND_LT: u8 = TcfEmOpnd::Lt as u8;
+
+// Following u64 array, e.g. kcnts of tc_u32_pcnt.
+fn unaligned_u64s(b: &[u8]) -> Vec<u64> {
+    b.chunks_exact(mem::size_of::<u64>())
+        .map(|c| unsafe { ptr::read_unaligned(c.as_ptr() as *const u64) })
+        .collect()
+}
+
+impl Act {
+    // An action in the list of TCA_*_ACT or TCA_ROOT_TAB, order from 1 up
+    // to TCA_ACT_MAX_PRIO. put TCA_ACT_KIND and the others, then nest_end()
+    pub fn start(nlv: &mut MsgVec, order: u16) -> Result<&mut MsgVec> {
+        nlv.nest_start(order)
+    }
+
+    // put attributes of the table for TCA_ACT_KIND, then nest_end()
+    pub fn options_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Act::Options)
+    }
+}
+
+// The actions in TCA_*_ACT of the classifiers or TCA_ROOT_TAB, which are
+// nested by the order.
+pub fn actions<'a>(nest: &'a Attr<'a>) -> Result<Vec<ActTbl<'a>>> {
+    let mut acts = Vec::new();
+    nest.parse_nested(|attr| {
+        acts.push(ActTbl::from_nest(attr)?);
+        Ok(CbStatus::Ok)
+    })
+    .map_err(to_errno)?;
+    Ok(acts)
+}
+
+impl Police {
+    pub fn put_rate64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Police::Rate64, Police::Pad, &rate)
+    }
+
+    pub fn put_peakrate64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Police::Peakrate64, Police::Pad, &rate)
+    }
+}
+
+impl<'a> PoliceTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Police::Tm])
+    }
+
+    pub fn rate64(&self) -> Result<Option<u64>> {
+        unaligned(self[Police::Rate64])
+    }
+
+    pub fn peakrate64(&self) -> Result<Option<u64>> {
+        unaligned(self[Police::Peakrate64])
+    }
+}
+
+impl TcU32Sel {
+    // TCA_U32_SEL, nkeys is set from keys.
+    pub fn put<'a>(&self, nlv: &'a mut MsgVec, keys: &[TcU32Key]) -> Result<&'a mut MsgVec> {
+        if keys.len() > u8::MAX as usize {
+            return Err(Errno(libc::EINVAL));
+        }
+        let mut sel = *self;
+        sel.nkeys = keys.len() as u8;
+        let mut b = Vec::with_capacity(mem::size_of::<TcU32Sel>() + mem::size_of_val(keys));
+        b.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(&sel as *const _ as *const u8, mem::size_of::<TcU32Sel>())
+        });
+        b.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(keys.as_ptr() as *const u8, mem::size_of_val(keys))
+        });
+        nlv.put_bytes(U32::Sel, &b)
+    }
+}
+
+impl<'a> U32Tbl<'a> {
+    // struct tc_u32_key keys[] following TCA_U32_SEL
+    pub fn keys(&self) -> Result<Option<Vec<TcU32Key>>> {
+        let attr = match self[U32::Sel] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let sel = attr.value_ref::<TcU32Sel>()?;
+        let b = &attr.bytes_ref()[mem::size_of::<TcU32Sel>()..];
+        let size = mem::size_of::<TcU32Key>();
+        if b.len() < size * sel.nkeys as usize {
+            return Err(Errno(libc::ERANGE));
+        }
+        Ok(Some(
+            b.chunks_exact(size)
+                .take(sel.nkeys as usize)
+                .map(|c| unsafe { ptr::read_unaligned(c.as_ptr() as *const TcU32Key) })
+                .collect(),
+        ))
+    }
+
+    // and kcnts[] for each key
+    pub fn pcnt(&self) -> Result<Option<(TcU32Pcnt, Vec<u64>)>> {
+        let pcnt = match unaligned::<TcU32Pcnt>(self[U32::Pcnt])? {
+            Some(pcnt) => pcnt,
+            None => return Ok(None),
+        };
+        let b = self[U32::Pcnt].unwrap().bytes_ref();
+        Ok(Some((
+            pcnt,
+            unaligned_u64s(&b[mem::size_of::<TcU32Pcnt>()..]),
+        )))
+    }
+
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[U32::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> RsvpTbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Rsvp::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> Route4Tbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Route4::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> FwTbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Fw::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> TcindexTbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Tcindex::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> FlowTbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Flow::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> BasicTbl<'a> {
+    pub fn pcnt(&self) -> Result<Option<TcBasicPcnt>> {
+        unaligned(self[Basic::Pcnt])
+    }
+
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Basic::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> CgroupTbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Cgroup::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> BpfTbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Bpf::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+impl<'a> FlowerTbl<'a> {
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Flower::Act].map(|nest| actions(nest)).transpose()
+    }
+
+    // All of the repeated LSE in TCA_FLOWER_KEY_MPLS_OPTS, of which table
+    // keeps the last one only.
+    pub fn key_mpls_lses(&self) -> Result<Option<Vec<FlowerKeyMplsOptLseTbl<'a>>>> {
+        let nest = match self[Flower::KeyMplsOpts] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let mut lses = Vec::new();
+        nest.parse_nested(|attr| {
+            if attr.atype() == FlowerKeyMplsOpts::Lse as u16 {
+                lses.push(FlowerKeyMplsOptLseTbl::from_nest(attr)?);
+            }
+            Ok(CbStatus::Ok)
+        })
+        .map_err(to_errno)?;
+        Ok(Some(lses))
+    }
+}
+
+impl<'a> MatchallTbl<'a> {
+    pub fn pcnt(&self) -> Result<Option<TcMatchallPcnt>> {
+        unaligned(self[Matchall::Pcnt])
+    }
+
+    pub fn actions(&self) -> Result<Option<Vec<ActTbl<'a>>>> {
+        self[Matchall::Act].map(|nest| actions(nest)).transpose()
+    }
+}
+
+// TCA_OPTIONS of filters by TCA_KIND.
+pub enum FilterOptions<'a> {
+    U32(U32Tbl<'a>),
+    Rsvp(RsvpTbl<'a>),
+    Rsvp6(RsvpTbl<'a>),
+    Route4(Route4Tbl<'a>),
+    Fw(FwTbl<'a>),
+    Tcindex(TcindexTbl<'a>),
+    Flow(FlowTbl<'a>),
+    Basic(BasicTbl<'a>),
+    Cgroup(CgroupTbl<'a>),
+    Bpf(BpfTbl<'a>),
+    Flower(Box<FlowerTbl<'a>>), // much larger than the others
+    Matchall(MatchallTbl<'a>),
+    Unknown(&'a str, &'a [u8]), // kind, raw TCA_OPTIONS payload
+}
+
+impl<'a> TcaTbl<'a> {
+    pub fn filter_options(&self) -> Result<Option<FilterOptions<'a>>> {
+        let options = match self[Tca::Options] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let kind = self[Tca::Kind].ok_or(Errno(libc::EINVAL))?.cstr()?;
+        Ok(Some(match kind {
+            "u32" => FilterOptions::U32(U32Tbl::from_nest(options)?),
+            "rsvp" => FilterOptions::Rsvp(RsvpTbl::from_nest(options)?),
+            "rsvp6" => FilterOptions::Rsvp6(RsvpTbl::from_nest(options)?),
+            "route" => FilterOptions::Route4(Route4Tbl::from_nest(options)?),
+            "fw" => FilterOptions::Fw(FwTbl::from_nest(options)?),
+            "tcindex" => FilterOptions::Tcindex(TcindexTbl::from_nest(options)?),
+            "flow" => FilterOptions::Flow(FlowTbl::from_nest(options)?),
+            "basic" => FilterOptions::Basic(BasicTbl::from_nest(options)?),
+            "cgroup" => FilterOptions::Cgroup(CgroupTbl::from_nest(options)?),
+            "bpf" => FilterOptions::Bpf(BpfTbl::from_nest(options)?),
+            "flower" => FilterOptions::Flower(Box::new(FlowerTbl::from_nest(options)?)),
+            "matchall" => FilterOptions::Matchall(MatchallTbl::from_nest(options)?),
+            _ => FilterOptions::Unknown(kind, payload_bytes(options)),
+        }))
+    }
+}
+
+// A filter, RTM_NEWTFILTER. parent is the qdisc handle or the class, e.g.
+// tc_h_make(TC_H_CLSACT, TC_H_MIN_INGRESS) for clsact ingress. priority and
+// protocol, ETH_P_ in host byte order, are in tcm_info. handle 0 on adding
+// lets the classifier allocate.
+//
+// The builders put the header, TCA_KIND and TCA_CHAIN, the options by the
+// kind can be put after that by Tca::options_start().
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub index: u32,
+    pub parent: u32,
+    pub handle: u32,
+    pub priority: u16,
+    pub protocol: u16,
+    pub chain: Option<u32>,
+    pub kind: String,
+}
+
+// tcm_info of filters
+pub fn tc_info(priority: u16, protocol: u16) -> u32 {
+    ((priority as u32) << 16) | protocol.to_be() as u32
+}
+
+impl Filter {
+    pub fn new(index: u32, parent: u32, priority: u16, protocol: u16, kind: &str) -> Self {
+        Self {
+            index,
+            parent,
+            handle: 0,
+            priority,
+            protocol,
+            chain: None,
+            kind: kind.to_string(),
+        }
+    }
+
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let tcm = nlh.payload::<Tcmsg>()?;
+        let tb = TcaTbl::from_nlmsg(mem::size_of::<Tcmsg>(), nlh)?;
+        Ok(Self {
+            index: tcm.tcm_ifindex,
+            parent: tcm.tcm_parent,
+            handle: tcm.tcm_handle,
+            priority: (tcm.tcm_info >> 16) as u16,
+            protocol: u16::from_be(tcm.tcm_info as u16),
+            chain: tb.chain()?.copied(),
+            kind: tb.kind()?.ok_or(Errno(libc::EINVAL))?.to_string(),
+        })
+    }
+
+    fn put_request<'a>(
+        &self,
+        nlv: &'a mut MsgVec,
+        mtype: u16,
+        flags: u16,
+        seq: u32,
+    ) -> Result<&'a mut MsgVec> {
+        let nlh = nlv.put_header();
+        nlh.nlmsg_type = mtype;
+        nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16 | flags;
+        nlh.nlmsg_seq = seq;
+        let tcm = nlv.put_extra_header::<Tcmsg>()?;
+        tcm.tcm_family = libc::AF_UNSPEC as u8;
+        tcm.tcm_ifindex = self.index;
+        tcm.tcm_parent = self.parent;
+        tcm.tcm_handle = self.handle;
+        tcm.tcm_info = tc_info(self.priority, self.protocol);
+        // deletion does not require
+        if !self.kind.is_empty() {
+            nlv.put_cstr(Tca::Kind, &self.kind)?;
+        }
+        if let Some(chain) = self.chain {
+            nlv.put(Tca::Chain, &chain)?;
+        }
+        Ok(nlv)
+    }
+
+    // RTM_NEWTFILTER, EEXIST if the handle exists.
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(
+            nlv,
+            rtnetlink::RTM_NEWTFILTER,
+            (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+        )
+    }
+
+    // RTM_NEWTFILTER without NLM_F_CREATE, changes the existing by handle.
+    pub fn put_change<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_NEWTFILTER, 0, seq)
+    }
+
+    pub fn put_replace<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(
+            nlv,
+            rtnetlink::RTM_NEWTFILTER,
+            (libc::NLM_F_CREATE | libc::NLM_F_REPLACE) as u16,
+            seq,
+        )
+    }
+
+    // RTM_DELTFILTER, all of the priority and the protocol if the handle is
+    // 0, or all of the parent if the priority is 0 too.
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        self.put_request(nlv, rtnetlink::RTM_DELTFILTER, 0, seq)
+    }
+}
+
+// options puts TCA_OPTIONS by the kind to the message built, e.g.
+//   |nlv| {
+//       Tca::options_start(nlv)?;
+//       nlv.put(Flower::KeyIpProto, &(libc::IPPROTO_TCP as u8))?;
+//       nlv.nest_end()?;
+//       Ok(())
+//   }
+pub fn add<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    filter: &Filter,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(filter.put_add(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn change<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    filter: &Filter,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(filter.put_change(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn replace<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    filter: &Filter,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(filter.put_replace(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn delete(nl: &mut Socket, filter: &Filter) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    filter.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_GETTFILTER dump of the parent on the link, the root qdisc if parent
+// is 0. The kernel dumps the chain only if specified.
+pub fn dump(nl: &mut Socket, index: u32, parent: u32, chain: Option<u32>) -> Result<Vec<Filter>> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = rtnetlink::RTM_GETTFILTER;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let tcm = nlv.put_extra_header::<Tcmsg>()?;
+    tcm.tcm_family = libc::AF_UNSPEC as u8;
+    tcm.tcm_ifindex = index;
+    tcm.tcm_parent = parent;
+    if let Some(chain) = chain {
+        nlv.put(Tca::Chain, &chain)?;
+    }
+
+    let mut filters = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        filters.push(Filter::from_nlmsg(nlh)?);
+        Ok(())
+    })?;
+    Ok(filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tc_info_encoding() {
+        // priority in the upper 16 bits, protocol in network byte order
+        // in the lower, TC_H_MAJ() and TC_H_MIN() of the kernel
+        let info = tc_info(10, libc::ETH_P_IP as u16);
+        assert_eq!(info >> 16, 10);
+        assert_eq!((info as u16).to_ne_bytes(), [0x08, 0x00]);
+        assert_eq!(tc_info(0xffff, 0) & 0xffff, 0);
+    }
+
+    fn key(mask: u32, val: u32, off: i32) -> TcU32Key {
+        TcU32Key {
+            mask: mask.to_be(),
+            val: val.to_be(),
+            off,
+            offmask: 0,
+        }
+    }
+
+    // TCA_OPTIONS like nest which has TCA_U32_SEL
+    fn options(sel: &TcU32Sel, keys: &[TcU32Key]) -> Result<MsgVec> {
+        let mut nlv = MsgVec::new();
+        nlv.put_header();
+        nlv.nest_start(Tca::Options)?;
+        sel.put(&mut nlv, keys)?;
+        nlv.nest_end()?;
+        Ok(nlv)
+    }
+
+    fn sel() -> TcU32Sel {
+        TcU32Sel {
+            flags: TC_U32_TERMINAL,
+            offshift: 0,
+            nkeys: 0xff, // overwritten by put()
+            offmask: 0,
+            off: 0,
+            offoff: 0,
+            hoff: 0,
+            hmask: 0,
+        }
+    }
+
+    #[test]
+    fn u32_sel_keys() {
+        let keys = [
+            key(0xffff_ffff, 0xc000_0201, 16),
+            key(0x00ff_0000, 0x0006_0000, 8),
+        ];
+        let nlv = options(&sel(), &keys).unwrap();
+        nlv.msghdr()
+            .unwrap()
+            .parse(0, |nest| {
+                let tb = U32Tbl::from_nest(nest)?;
+                let got = tb.sel()?.unwrap();
+                assert_eq!(got.flags, TC_U32_TERMINAL);
+                assert_eq!(got.nkeys, 2);
+                let got = tb.keys()?.unwrap();
+                assert_eq!(got.len(), keys.len());
+                for (a, b) in got.iter().zip(keys.iter()) {
+                    assert_eq!(
+                        (a.mask, a.val, a.off, a.offmask),
+                        (b.mask, b.val, b.off, b.offmask)
+                    );
+                }
+                Ok(CbStatus::Ok)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn u32_sel_no_keys() {
+        let nlv = options(&sel(), &[]).unwrap();
+        nlv.msghdr()
+            .unwrap()
+            .parse(0, |nest| {
+                let tb = U32Tbl::from_nest(nest)?;
+                assert_eq!(tb.sel()?.unwrap().nkeys, 0);
+                assert!(tb.keys()?.unwrap().is_empty());
+                Ok(CbStatus::Ok)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn u32_sel_too_many_keys() {
+        let keys = vec![key(0, 0, 0); u8::MAX as usize + 1];
+        assert_eq!(options(&sel(), &keys).err(), Some(Errno(libc::EINVAL)));
+    }
+}
@@ -0,0 +1,1428 @@
+use errno::Errno;
+use std::mem;
+
+use mnl::{Attr, AttrTbl, CbStatus, MsgVec, Msghdr, Result, Socket};
+
+use netlink::NlaBitfield32;
+use rtnetlink::{self, Tca, TcaTbl, Tcmsg};
+use util::{payload_bytes, put_64bit, run, seq, to_errno, unaligned};
+
+// Logical priority bands not depending on specific packet scheduler.
+// Every scheduler will map them to real traffic classes, if it has
+// no more precise mechanism to classify packets.
+//
+// These numbers have no special meaning, though their coincidence
+// with obsolete IPv6 values is not occasional :-). New IPv6 drafts
+// preferred full anarchy inspired by diffserv group.
+//
+// Note: TC_PRIO_BESTEFFORT does not mean that it is the most unhappy
+// class, actually, as rule it will be handled with more care than
+// filler or even bulk.
+pub const TC_PRIO_BESTEFFORT: u8 = 0;
+pub const TC_PRIO_FILLER: u8 = 1;
+pub const TC_PRIO_BULK: u8 = 2;
+pub const TC_PRIO_INTERACTIVE_BULK: u8 = 4;
+pub const TC_PRIO_INTERACTIVE: u8 = 6;
+pub const TC_PRIO_CONTROL: u8 = 7;
+
+pub const TC_PRIO_MAX: usize = 15;
+
+// Generic queue statistics, available for all the elements.
+// Particular schedulers may have also their private records.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcStats {
+    pub bytes: u64,      // Number of enqueued bytes
+    pub packets: u32,    // Number of enqueued packets
+    pub drops: u32,      // Packets dropped because of lack of resources
+    pub overlimits: u32, // Number of throttle events when this
+    // flow goes out of allocated bandwidth
+    pub bps: u32, // Current flow byte rate
+    pub pps: u32, // Current flow packet rate
+    pub qlen: u32,
+    pub backlog: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcEstimator {
+    pub interval: i8,
+    pub ewma_log: u8,
+}
+
+// "Handles"
+// ---------
+//
+// All the traffic control objects have 32bit identifiers, or "handles".
+//
+// They can be considered as opaque numbers from user API viewpoint,
+// but actually they always consist of two fields: major and
+// minor numbers, which are interpreted by kernel specially,
+// that may be used by applications, though not recommended.
+//
+// F.e. qdisc handles always have minor number equal to zero,
+// classes (or flows) have major equal to parent qdisc major, and
+// minor uniquely identifying class inside qdisc.
+//
+// Macros to manipulate handles:
+pub const TC_H_MAJ_MASK: u32 = 0xFFFF0000;
+pub const TC_H_MIN_MASK: u32 = 0x0000FFFF;
+
+pub fn tc_h_maj(h: u32) -> u32 {
+    h & TC_H_MAJ_MASK
+}
+
+pub fn tc_h_min(h: u32) -> u32 {
+    h & TC_H_MIN_MASK
+}
+
+pub fn tc_h_make(maj: u32, min: u32) -> u32 {
+    (maj & TC_H_MAJ_MASK) | (min & TC_H_MIN_MASK)
+}
+
+pub const TC_H_UNSPEC: u32 = 0;
+pub const TC_H_ROOT: u32 = 0xFFFFFFFF;
+pub const TC_H_INGRESS: u32 = 0xFFFFFFF1;
+pub const TC_H_CLSACT: u32 = TC_H_INGRESS;
+
+pub const TC_H_MIN_PRIORITY: u32 = 0xFFE0;
+pub const TC_H_MIN_INGRESS: u32 = 0xFFF2;
+pub const TC_H_MIN_EGRESS: u32 = 0xFFF3;
+
+// Need to corrospond to iproute2 tc/tc_core.h "enum link_layer"
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcLinkLayer {
+    Unaware, // Indicate unaware old iproute2 util
+    Ethernet,
+    Atm,
+}
+pub const TC_LINKLAYER_UNAWARE: u8 = TcLinkLayer::Unaware as u8;
+pub const TC_LINKLAYER_ETHERNET: u8 = TcLinkLayer::Ethernet as u8;
+pub const TC_LINKLAYER_ATM: u8 = TcLinkLayer::Atm as u8;
+pub const TC_LINKLAYER_MASK: u8 = 0x0F; // limit use to lower 4 bits
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcRatespec {
+    pub cell_log: u8,
+    pub linklayer: u8, // lower 4 bits
+    pub overhead: u16,
+    pub cell_align: i16,
+    pub mpu: u16,
+    pub rate: u32,
+}
+
+impl TcRatespec {
+    // rate in bytes/s. The kernel does not require the rate table on
+    // TC_LINKLAYER_ETHERNET. rate is saturated to u32, put _RATE64 of the
+    // qdisc too if it exceeds.
+    pub fn new(rate: u64) -> Self {
+        Self {
+            cell_log: 0,
+            linklayer: TC_LINKLAYER_ETHERNET,
+            overhead: 0,
+            cell_align: 0,
+            mpu: 0,
+            rate: if rate >= u32::MAX as u64 {
+                u32::MAX
+            } else {
+                rate as u32
+            },
+        }
+    }
+}
+
+pub const TC_RTAB_SIZE: usize = 1024;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcSizespec {
+    pub cell_log: u8,
+    pub size_log: u8,
+    pub cell_align: i16,
+    pub overhead: i32,
+    pub linklayer: u32,
+    pub mpu: u32,
+    pub mtu: u32,
+    pub tsize: u32,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "StabTbl"]
+pub enum Stab {
+    // TCA_STAB_
+    Unspec,
+
+    #[nla_type(TcSizespec, base)]
+    Base,
+
+    #[nla_type(bytes, data)]
+    Data, // u16 array of tsize
+
+    _MAX,
+}
+
+// FIFO section
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcFifoQopt {
+    pub limit: u32, // Queue length: bytes for bfifo, packets for pfifo
+}
+
+// PRIO section
+pub const TCQ_PRIO_BANDS: usize = 16;
+pub const TCQ_MIN_PRIO_BANDS: usize = 2;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcPrioQopt {
+    pub bands: i32,                     // Number of bands
+    pub priomap: [u8; TC_PRIO_MAX + 1], // Map: logical priority -> PRIO band
+}
+
+// TBF section
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcTbfQopt {
+    pub rate: TcRatespec,
+    pub peakrate: TcRatespec,
+    pub limit: u32,
+    pub buffer: u32, // psched ticks, see tc_calc_xmittime()
+    pub mtu: u32,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TbfTbl"]
+pub enum Tbf {
+    // TCA_TBF_
+    Unspec,
+
+    #[nla_type(TcTbfQopt, parms)]
+    Parms,
+
+    #[nla_type(bytes, rtab)]
+    Rtab, // u32[256] for TC_LINKLAYER_UNAWARE
+
+    #[nla_type(bytes, ptab)]
+    Ptab,
+
+    Rate64, // u64, see rate64()
+    Prate64,
+
+    #[nla_type(u32, burst)]
+    Burst, // bytes, instead of parms.buffer
+
+    #[nla_type(u32, pburst)]
+    Pburst,
+
+    Pad,
+    _MAX,
+}
+
+// RED section
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "RedTbl"]
+pub enum Red {
+    // TCA_RED_
+    Unspec,
+
+    #[nla_type(TcRedQopt, parms)]
+    Parms,
+
+    #[nla_type(bytes, stab)]
+    Stab, // u8[256]
+
+    #[nla_type(u32, max_p)]
+    MaxP,
+
+    #[nla_type(NlaBitfield32, flags)]
+    Flags, // TC_RED_
+
+    #[nla_type(u32, early_drop_block)]
+    EarlyDropBlock,
+
+    #[nla_type(u32, mark_block)]
+    MarkBlock,
+
+    _MAX,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcRedQopt {
+    pub limit: u32,    // HARD maximal queue length (bytes)
+    pub qth_min: u32,  // Min average length threshold (bytes)
+    pub qth_max: u32,  // Max average length threshold (bytes)
+    pub Wlog: u8,      // log(W)
+    pub Plog: u8,      // log(P_max/(qth_max-qth_min))
+    pub Scell_log: u8, // cell size for idle damping
+
+    // This field can be used for flags that a RED-like qdisc has
+    // historically supported. E.g. when configuring RED, it can be used for
+    // ECN, HARDDROP and ADAPTATIVE. For SFQ it can be used for ECN,
+    // HARDDROP. Etc. Because this field has not been validated, and is
+    // copied back on dump, any bits besides those to which a given qdisc
+    // has assigned a historical meaning need to be considered for free use
+    // by userspace tools.
+    //
+    // Any further flags need to be passed differently, e.g. through an
+    // attribute (such as TCA_RED_FLAGS above). Such attribute should allow
+    // passing both recent and historic flags in one value.
+    pub flags: u8,
+}
+
+pub const TC_RED_ECN: u32 = 1;
+pub const TC_RED_HARDDROP: u32 = 2;
+pub const TC_RED_ADAPTATIVE: u32 = 4;
+pub const TC_RED_NODROP: u32 = 8;
+
+pub const TC_RED_HISTORIC_FLAGS: u32 = TC_RED_ECN | TC_RED_HARDDROP | TC_RED_ADAPTATIVE;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcRedXstats {
+    pub early: u32,  // Early drops
+    pub pdrop: u32,  // Drops due to queue limits
+    pub other: u32,  // Drops due to drop() calls
+    pub marked: u32, // Marked packets
+}
+
+// HTB section
+pub const TC_HTB_NUMPRIO: usize = 8;
+pub const TC_HTB_MAXDEPTH: usize = 8;
+pub const TC_HTB_PROTOVER: u32 = 3; // the same as HTB and TC's major
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcHtbOpt {
+    pub rate: TcRatespec,
+    pub ceil: TcRatespec,
+    pub buffer: u32,  // psched ticks, see tc_calc_xmittime()
+    pub cbuffer: u32, // psched ticks
+    pub quantum: u32,
+    pub level: u32, // out only
+    pub prio: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcHtbGlob {
+    pub version: u32,      // to match HTB/TC
+    pub rate2quantum: u32, // bps->quantum divisor
+    pub defcls: u32,       // default class number
+    pub debug: u32,        // debug flags
+
+    // stats
+    pub direct_pkts: u32, // count of non shaped packets
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "HtbTbl"]
+pub enum Htb {
+    // TCA_HTB_
+    Unspec,
+
+    #[nla_type(TcHtbOpt, parms)]
+    Parms, // class
+
+    #[nla_type(TcHtbGlob, init)]
+    Init, // qdisc
+
+    #[nla_type(bytes, ctab)]
+    Ctab,
+
+    #[nla_type(bytes, rtab)]
+    Rtab,
+
+    #[nla_type(u32, direct_qlen)]
+    DirectQlen,
+
+    Rate64, // u64, see rate64()
+    Ceil64,
+    Pad,
+
+    #[nla_type(flag, offload)]
+    Offload,
+
+    _MAX,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcHtbXstats {
+    pub lends: u32,
+    pub borrows: u32,
+    pub giants: u32, // unused since 'Make HTB scheduler work with TSO.'
+    pub tokens: i32,
+    pub ctokens: i32,
+}
+
+// Not in uapi, the kernel psched tick is 64 nsec, PSCHED_TICKS2NS().
+pub const PSCHED_SHIFT: u32 = 6;
+
+// Time to send size bytes at rate bytes/s in psched ticks, for
+// tc_tbf_qopt.buffer and tc_htb_opt.buffer, like iproute2
+// tc_calc_xmittime().
+pub fn tc_calc_xmittime(rate: u64, size: u32) -> u32 {
+    if rate == 0 {
+        return 0;
+    }
+    let ticks = (size as u64 * 1_000_000_000 / rate) >> PSCHED_SHIFT;
+    if ticks > u32::MAX as u64 {
+        u32::MAX
+    } else {
+        ticks as u32
+    }
+}
+
+// NETEM
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "NetemTbl"]
+pub enum Netem {
+    // TCA_NETEM_
+    Unspec,
+
+    #[nla_type(TcNetemCorr, corr)]
+    Corr,
+
+    #[nla_type(bytes, delay_dist)]
+    DelayDist, // i16 array, NETEM_DIST_SCALE
+
+    #[nla_type(TcNetemReorder, reorder)]
+    Reorder,
+
+    #[nla_type(TcNetemCorrupt, corrupt)]
+    Corrupt,
+
+    #[nla_nest(NetemLossTbl, loss)]
+    Loss,
+
+    #[nla_type(TcNetemRate, rate)]
+    Rate,
+
+    #[nla_type(u32, ecn)]
+    Ecn,
+
+    Rate64,    // u64, see rate64()
+    Pad,       //
+    Latency64, // i64 nsec, see latency64()
+    Jitter64,  // i64 nsec
+    Slot,      // struct tc_netem_slot, see slot()
+
+    #[nla_type(bytes, slot_dist)]
+    SlotDist,
+
+    _MAX,
+}
+
+// TCA_OPTIONS of netem is this struct followed by TCA_NETEM_ attributes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemQopt {
+    pub latency: u32,   // added delay (us)
+    pub limit: u32,     // fifo limit (packets)
+    pub loss: u32,      // random packet loss (0=none ~0=100%)
+    pub gap: u32,       // re-ordering gap (0 for none)
+    pub duplicate: u32, // random packet dup  (0=none ~0=100%)
+    pub jitter: u32,    // random jitter in latency (us)
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemCorr {
+    pub delay_corr: u32, // delay correlation
+    pub loss_corr: u32,  // packet loss correlation
+    pub dup_corr: u32,   // duplicate correlation
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemReorder {
+    pub probability: u32,
+    pub correlation: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemCorrupt {
+    pub probability: u32,
+    pub correlation: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemRate {
+    pub rate: u32, // byte/s
+    pub packet_overhead: i32,
+    pub cell_size: u32,
+    pub cell_overhead: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemSlot {
+    pub min_delay: i64, // nsec
+    pub max_delay: i64,
+    pub max_packets: i32,
+    pub max_bytes: i32,
+    pub dist_delay: i64,  // nsec
+    pub dist_jitter: i64, // nsec
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "NetemLossTbl"]
+pub enum NetemLoss {
+    // NETEM_LOSS_
+    Unspec,
+
+    #[nla_type(TcNetemGimodel, gi)]
+    Gi, // General Intuitive - 4 state model
+
+    #[nla_type(TcNetemGemodel, ge)]
+    Ge, // Gilbert Elliot models
+
+    _MAX,
+}
+
+// State transition probabilities for 4 state model
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemGimodel {
+    pub p13: u32,
+    pub p31: u32,
+    pub p32: u32,
+    pub p14: u32,
+    pub p23: u32,
+}
+
+// Gilbert-Elliot models
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcNetemGemodel {
+    pub p: u32,
+    pub r: u32,
+    pub h: u32,
+    pub k1: u32,
+}
+
+pub const NETEM_DIST_SCALE: i32 = 8192;
+pub const NETEM_DIST_MAX: usize = 16384;
+
+// MQPRIO
+pub const TC_QOPT_BITMASK: usize = 15;
+pub const TC_QOPT_MAX_QUEUE: usize = 16;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcMqprioHwOffload {
+    None, // no offload requested
+    Tcs,  // offload TCs, no queue counts
+    _MAX,
+}
+pub const TC_MQPRIO_HW_OFFLOAD_NONE: u8 = TcMqprioHwOffload::None as u8;
+pub const TC_MQPRIO_HW_OFFLOAD_TCS: u8 = TcMqprioHwOffload::Tcs as u8;
+pub const TC_MQPRIO_HW_OFFLOAD_MAX: u8 = TcMqprioHwOffload::_MAX as u8 - 1;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcMqprioMode {
+    Dcb,
+    Channel,
+    _MAX,
+}
+pub const TC_MQPRIO_MODE_DCB: u16 = TcMqprioMode::Dcb as u16;
+pub const TC_MQPRIO_MODE_CHANNEL: u16 = TcMqprioMode::Channel as u16;
+pub const TC_MQPRIO_MODE_MAX: u16 = TcMqprioMode::_MAX as u16 - 1;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcMqprioShaper {
+    Dcb,
+    BwRate, // Add new shapers below
+    _MAX,
+}
+pub const TC_MQPRIO_SHAPER_DCB: u16 = TcMqprioShaper::Dcb as u16;
+pub const TC_MQPRIO_SHAPER_BW_RATE: u16 = TcMqprioShaper::BwRate as u16;
+pub const TC_MQPRIO_SHAPER_MAX: u16 = TcMqprioShaper::_MAX as u16 - 1;
+
+// TCA_OPTIONS of mqprio is this struct followed by TCA_MQPRIO_ attributes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcMqprioQopt {
+    pub num_tc: u8,
+    pub prio_tc_map: [u8; TC_QOPT_BITMASK + 1],
+    pub hw: u8,
+    pub count: [u16; TC_QOPT_MAX_QUEUE],
+    pub offset: [u16; TC_QOPT_MAX_QUEUE],
+}
+
+pub const TC_MQPRIO_F_MODE: u32 = 0x1;
+pub const TC_MQPRIO_F_SHAPER: u32 = 0x2;
+pub const TC_MQPRIO_F_MIN_RATE: u32 = 0x4;
+pub const TC_MQPRIO_F_MAX_RATE: u32 = 0x8;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MqprioTbl"]
+pub enum Mqprio {
+    // TCA_MQPRIO_
+    Unspec,
+
+    #[nla_type(u16, mode)]
+    Mode, // TC_MQPRIO_MODE_
+
+    #[nla_type(u16, shaper)]
+    Shaper, // TC_MQPRIO_SHAPER_
+
+    MinRate64, // nest of u64 for each tc, see min_rate64()
+    MaxRate64,
+    _MAX,
+}
+
+// FQ_CODEL
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FqCodelTbl"]
+pub enum FqCodel {
+    // TCA_FQ_CODEL_
+    Unspec,
+
+    #[nla_type(u32, target)]
+    Target, // us
+
+    #[nla_type(u32, limit)]
+    Limit,
+
+    #[nla_type(u32, interval)]
+    Interval, // us
+
+    #[nla_type(u32, ecn)]
+    Ecn,
+
+    #[nla_type(u32, flows)]
+    Flows,
+
+    #[nla_type(u32, quantum)]
+    Quantum,
+
+    #[nla_type(u32, ce_threshold)]
+    CeThreshold, // us
+
+    #[nla_type(u32, drop_batch_size)]
+    DropBatchSize,
+
+    #[nla_type(u32, memory_limit)]
+    MemoryLimit,
+
+    _MAX,
+}
+
+pub const TCA_FQ_CODEL_XSTATS_QDISC: u32 = 0;
+pub const TCA_FQ_CODEL_XSTATS_CLASS: u32 = 1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcFqCodelQdStats {
+    pub maxpacket: u32,      // largest packet we've seen so far
+    pub drop_overlimit: u32, // number of time max qdisc packet limit was hit
+    pub ecn_mark: u32,       // number of packets we ECN marked instead of being dropped
+    pub new_flow_count: u32, // number of time packets created a 'new flow'
+    pub new_flows_len: u32,  // count of flows in new list
+    pub old_flows_len: u32,  // count of flows in old list
+    pub ce_mark: u32,        // packets above ce_threshold
+    pub memory_usage: u32,   // in bytes
+    pub drop_overmemory: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcFqCodelClStats {
+    pub deficit: i32,
+    pub ldelay: u32, // in-queue delay seen by most recently dequeued packet
+    pub count: u32,
+    pub lastcount: u32,
+    pub dropping: u32,
+    pub drop_next: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TcFqCodelXstats {
+    pub type_: u32, // TCA_FQ_CODEL_XSTATS_
+    pub u: _TcFqCodelXstatsUnion,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union _TcFqCodelXstatsUnion {
+    pub qdisc_stats: TcFqCodelQdStats,
+    pub class_stats: TcFqCodelClStats,
+}
+
+// FQ
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "FqTbl"]
+pub enum Fq {
+    // TCA_FQ_
+    Unspec,
+
+    #[nla_type(u32, plimit)]
+    Plimit, // limit of total number of packets in queue
+
+    #[nla_type(u32, flow_plimit)]
+    FlowPlimit, // limit of packets per flow
+
+    #[nla_type(u32, quantum)]
+    Quantum, // RR quantum
+
+    #[nla_type(u32, initial_quantum)]
+    InitialQuantum, // RR quantum for new flow
+
+    #[nla_type(u32, rate_enable)]
+    RateEnable, // enable/disable rate limiting
+
+    #[nla_type(u32, flow_default_rate)]
+    FlowDefaultRate, // obsolete, do not use
+
+    #[nla_type(u32, flow_max_rate)]
+    FlowMaxRate, // per flow max rate
+
+    #[nla_type(u32, buckets_log)]
+    BucketsLog, // log2(number of buckets)
+
+    #[nla_type(u32, flow_refill_delay)]
+    FlowRefillDelay, // flow credit refill delay in usec
+
+    #[nla_type(u32, orphan_mask)]
+    OrphanMask, // mask applied to orphaned skb hashes
+
+    #[nla_type(u32, low_rate_threshold)]
+    LowRateThreshold, // per packet delay under this rate
+
+    #[nla_type(u32, ce_threshold)]
+    CeThreshold, // DCTCP-like CE-marking threshold
+
+    #[nla_type(u32, timer_slack)]
+    TimerSlack, // timer slack
+
+    #[nla_type(u32, horizon)]
+    Horizon, // time horizon in us
+
+    #[nla_type(u8, horizon_drop)]
+    HorizonDrop, // drop packets beyond horizon, or cap their EDT
+
+    _MAX,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TcFqQdStats {
+    pub gc_flows: u64,
+    pub highprio_packets: u64,
+    pub tcp_retrans: u64,
+    pub throttled: u64,
+    pub flows_plimit: u64,
+    pub pkts_too_long: u64,
+    pub allocation_errors: u64,
+    pub time_next_delayed_flow: i64,
+    pub flows: u32,
+    pub inactive_flows: u32,
+    pub throttled_flows: u32,
+    pub unthrottle_latency_ns: u32,
+    pub ce_mark: u64, // packets above ce_threshold
+    pub horizon_drops: u64,
+    pub horizon_caps: u64,
+}
+
+// CAKE
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "CakeTbl"]
+pub enum Cake {
+    // TCA_CAKE_
+    Unspec,
+    Pad,
+    BaseRate64, // u64 bytes/s, see base_rate64()
+
+    #[nla_type(u32, diffserv_mode)]
+    DiffservMode, // CAKE_DIFFSERV_
+
+    #[nla_type(u32, atm)]
+    Atm, // CAKE_ATM_
+
+    #[nla_type(u32, flow_mode)]
+    FlowMode, // CAKE_FLOW_
+
+    #[nla_type(i32, overhead)]
+    Overhead,
+
+    #[nla_type(u32, rtt)]
+    Rtt, // us
+
+    #[nla_type(u32, target)]
+    Target, // us
+
+    #[nla_type(u32, autorate)]
+    Autorate,
+
+    #[nla_type(u32, memory)]
+    Memory,
+
+    #[nla_type(u32, nat)]
+    Nat,
+
+    #[nla_type(u32, raw)]
+    Raw,
+
+    #[nla_type(u32, wash)]
+    Wash,
+
+    #[nla_type(u32, mpu)]
+    Mpu,
+
+    #[nla_type(u32, ingress)]
+    Ingress,
+
+    #[nla_type(u32, ack_filter)]
+    AckFilter, // CAKE_ACK_
+
+    #[nla_type(u32, split_gso)]
+    SplitGso,
+
+    #[nla_type(u32, fwmark)]
+    Fwmark,
+
+    _MAX,
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CakeFlow {
+    None = 0,
+    SrcIp,
+    DstIp,
+    Hosts, // = CAKE_FLOW_SRC_IP | CAKE_FLOW_DST_IP
+    Flows,
+    DualSrc, // = CAKE_FLOW_SRC_IP | CAKE_FLOW_FLOWS
+    DualDst, // = CAKE_FLOW_DST_IP | CAKE_FLOW_FLOWS
+    Triple,  // = CAKE_FLOW_HOSTS  | CAKE_FLOW_FLOWS
+    _MAX,
+}
+pub const CAKE_FLOW_NONE: u32 = CakeFlow::None as u32;
+pub const CAKE_FLOW_SRC_IP: u32 = CakeFlow::SrcIp as u32;
+pub const CAKE_FLOW_DST_IP: u32 = CakeFlow::DstIp as u32;
+pub const CAKE_FLOW_HOSTS: u32 = CakeFlow::Hosts as u32;
+pub const CAKE_FLOW_FLOWS: u32 = CakeFlow::Flows as u32;
+pub const CAKE_FLOW_DUAL_SRC: u32 = CakeFlow::DualSrc as u32;
+pub const CAKE_FLOW_DUAL_DST: u32 = CakeFlow::DualDst as u32;
+pub const CAKE_FLOW_TRIPLE: u32 = CakeFlow::Triple as u32;
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CakeDiffserv {
+    Diffserv3 = 0,
+    Diffserv4,
+    Diffserv8,
+    Besteffort,
+    Precedence,
+    _MAX,
+}
+pub const CAKE_DIFFSERV_DIFFSERV3: u32 = CakeDiffserv::Diffserv3 as u32;
+pub const CAKE_DIFFSERV_DIFFSERV4: u32 = CakeDiffserv::Diffserv4 as u32;
+pub const CAKE_DIFFSERV_DIFFSERV8: u32 = CakeDiffserv::Diffserv8 as u32;
+pub const CAKE_DIFFSERV_BESTEFFORT: u32 = CakeDiffserv::Besteffort as u32;
+pub const CAKE_DIFFSERV_PRECEDENCE: u32 = CakeDiffserv::Precedence as u32;
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CakeAck {
+    None = 0,
+    Filter,
+    Aggressive,
+    _MAX,
+}
+pub const CAKE_ACK_NONE: u32 = CakeAck::None as u32;
+pub const CAKE_ACK_FILTER: u32 = CakeAck::Filter as u32;
+pub const CAKE_ACK_AGGRESSIVE: u32 = CakeAck::Aggressive as u32;
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CakeAtm {
+    None = 0,
+    Atm,
+    Ptm,
+    _MAX,
+}
+pub const CAKE_ATM_NONE: u32 = CakeAtm::None as u32;
+pub const CAKE_ATM_ATM: u32 = CakeAtm::Atm as u32;
+pub const CAKE_ATM_PTM: u32 = CakeAtm::Ptm as u32;
+
+impl Tbf {
+    pub fn put_rate64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Tbf::Rate64, Tbf::Pad, &rate)
+    }
+
+    pub fn put_prate64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Tbf::Prate64, Tbf::Pad, &rate)
+    }
+}
+
+impl<'a> TbfTbl<'a> {
+    pub fn rate64(&self) -> Result<Option<u64>> {
+        unaligned(self[Tbf::Rate64])
+    }
+
+    pub fn prate64(&self) -> Result<Option<u64>> {
+        unaligned(self[Tbf::Prate64])
+    }
+}
+
+impl Htb {
+    pub fn put_rate64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Htb::Rate64, Htb::Pad, &rate)
+    }
+
+    pub fn put_ceil64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Htb::Ceil64, Htb::Pad, &rate)
+    }
+}
+
+impl<'a> HtbTbl<'a> {
+    pub fn rate64(&self) -> Result<Option<u64>> {
+        unaligned(self[Htb::Rate64])
+    }
+
+    pub fn ceil64(&self) -> Result<Option<u64>> {
+        unaligned(self[Htb::Ceil64])
+    }
+}
+
+impl Netem {
+    pub fn put_rate64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Netem::Rate64, Netem::Pad, &rate)
+    }
+
+    pub fn put_latency64(nlv: &mut MsgVec, nsec: i64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Netem::Latency64, Netem::Pad, &nsec)
+    }
+
+    pub fn put_jitter64(nlv: &mut MsgVec, nsec: i64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Netem::Jitter64, Netem::Pad, &nsec)
+    }
+
+    pub fn put_slot<'a>(nlv: &'a mut MsgVec, slot: &TcNetemSlot) -> Result<&'a mut MsgVec> {
+        put_64bit(nlv, Netem::Slot, Netem::Pad, slot)
+    }
+}
+
+impl<'a> NetemTbl<'a> {
+    pub fn rate64(&self) -> Result<Option<u64>> {
+        unaligned(self[Netem::Rate64])
+    }
+
+    pub fn latency64(&self) -> Result<Option<i64>> {
+        unaligned(self[Netem::Latency64])
+    }
+
+    pub fn jitter64(&self) -> Result<Option<i64>> {
+        unaligned(self[Netem::Jitter64])
+    }
+
+    pub fn slot(&self) -> Result<Option<TcNetemSlot>> {
+        unaligned(self[Netem::Slot])
+    }
+}
+
+impl Mqprio {
+    fn put_rates<'a>(nlv: &'a mut MsgVec, atype: Mqprio, rates: &[u64]) -> Result<&'a mut MsgVec> {
+        nlv.nest_start(atype)?;
+        for rate in rates {
+            // no pad attribute in mqprio
+            nlv.put_bytes(atype, &rate.to_ne_bytes())?;
+        }
+        nlv.nest_end()
+    }
+
+    // for each tc, requires TC_MQPRIO_SHAPER_BW_RATE
+    pub fn put_min_rate64<'a>(nlv: &'a mut MsgVec, rates: &[u64]) -> Result<&'a mut MsgVec> {
+        Self::put_rates(nlv, Mqprio::MinRate64, rates)
+    }
+
+    pub fn put_max_rate64<'a>(nlv: &'a mut MsgVec, rates: &[u64]) -> Result<&'a mut MsgVec> {
+        Self::put_rates(nlv, Mqprio::MaxRate64, rates)
+    }
+}
+
+impl<'a> MqprioTbl<'a> {
+    fn rates(&self, atype: Mqprio) -> Result<Option<Vec<u64>>> {
+        let nest = match self[atype] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let mut rates = Vec::new();
+        nest.parse_nested(|attr| {
+            if let Some(rate) = unaligned(Some(attr))? {
+                rates.push(rate);
+            }
+            Ok(CbStatus::Ok)
+        })
+        .map_err(to_errno)?;
+        Ok(Some(rates))
+    }
+
+    pub fn min_rate64(&self) -> Result<Option<Vec<u64>>> {
+        self.rates(Mqprio::MinRate64)
+    }
+
+    pub fn max_rate64(&self) -> Result<Option<Vec<u64>>> {
+        self.rates(Mqprio::MaxRate64)
+    }
+}
+
+impl Cake {
+    pub fn put_base_rate64(nlv: &mut MsgVec, rate: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Cake::BaseRate64, Cake::Pad, &rate)
+    }
+}
+
+impl<'a> CakeTbl<'a> {
+    pub fn base_rate64(&self) -> Result<Option<u64>> {
+        unaligned(self[Cake::BaseRate64])
+    }
+}
+
+// TCA_OPTIONS of qdiscs and their classes depends on TCA_KIND, which is a
+// struct, a nest, or the struct followed by attributes. Classes of htb put
+// parms, the qdisc init.
+pub enum QdiscOptions<'a> {
+    Pfifo(&'a TcFifoQopt),
+    Bfifo(&'a TcFifoQopt),
+    PfifoHeadDrop(&'a TcFifoQopt),
+    Prio(&'a TcPrioQopt),
+    PfifoFast(&'a TcPrioQopt), // the default, not configurable
+    Tbf(TbfTbl<'a>),
+    Red(RedTbl<'a>),
+    Htb(HtbTbl<'a>),
+    Netem(&'a TcNetemQopt, NetemTbl<'a>),
+    Mqprio(&'a TcMqprioQopt, MqprioTbl<'a>),
+    FqCodel(FqCodelTbl<'a>),
+    Fq(FqTbl<'a>),
+    Cake(CakeTbl<'a>),
+    Ingress,                    // an empty nest
+    Clsact,                     // an empty nest
+    Unknown(&'a str, &'a [u8]), // kind, raw TCA_OPTIONS payload
+}
+
+impl<'a> TcaTbl<'a> {
+    pub fn qdisc_options(&self) -> Result<Option<QdiscOptions<'a>>> {
+        let options = match self[Tca::Options] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let kind = self[Tca::Kind].ok_or(Errno(libc::EINVAL))?.cstr()?;
+        Ok(Some(match kind {
+            "pfifo" => QdiscOptions::Pfifo(options.value_ref()?),
+            "bfifo" => QdiscOptions::Bfifo(options.value_ref()?),
+            "pfifo_head_drop" => QdiscOptions::PfifoHeadDrop(options.value_ref()?),
+            "prio" => QdiscOptions::Prio(options.value_ref()?),
+            "pfifo_fast" => QdiscOptions::PfifoFast(options.value_ref()?),
+            "tbf" => QdiscOptions::Tbf(TbfTbl::from_nest(options)?),
+            "red" => QdiscOptions::Red(RedTbl::from_nest(options)?),
+            "htb" => QdiscOptions::Htb(HtbTbl::from_nest(options)?),
+            "netem" => {
+                let (qopt, tb) = rtnetlink::parse_nest_with_header(options)?;
+                QdiscOptions::Netem(qopt, tb)
+            }
+            "mqprio" => {
+                let (qopt, tb) = rtnetlink::parse_nest_with_header(options)?;
+                QdiscOptions::Mqprio(qopt, tb)
+            }
+            "fq_codel" => QdiscOptions::FqCodel(FqCodelTbl::from_nest(options)?),
+            "fq" => QdiscOptions::Fq(FqTbl::from_nest(options)?),
+            "cake" => QdiscOptions::Cake(CakeTbl::from_nest(options)?),
+            "ingress" => QdiscOptions::Ingress,
+            "clsact" => QdiscOptions::Clsact,
+            _ => QdiscOptions::Unknown(kind, payload_bytes(options)),
+        }))
+    }
+}
+
+impl TcFifoQopt {
+    // TCA_OPTIONS of pfifo, bfifo and pfifo_head_drop.
+    pub fn put<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        nlv.put(Tca::Options, self)
+    }
+}
+
+impl TcPrioQopt {
+    pub fn put<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        nlv.put(Tca::Options, self)
+    }
+}
+
+impl TcNetemQopt {
+    // Starts TCA_OPTIONS with this, put TCA_NETEM_ attributes then nest_end().
+    pub fn put_start<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        Tca::options_start(nlv)?;
+        *nlv.put_extra_header::<Self>()? = *self;
+        Ok(nlv)
+    }
+}
+
+impl TcMqprioQopt {
+    // Starts TCA_OPTIONS with this, put TCA_MQPRIO_ attributes then nest_end().
+    pub fn put_start<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        Tca::options_start(nlv)?;
+        *nlv.put_extra_header::<Self>()? = *self;
+        Ok(nlv)
+    }
+}
+
+// A qdisc, RTM_NEWQDISC. handle is major:0, 0 on adding lets the kernel
+// allocate. parent is TC_H_ROOT, TC_H_INGRESS, TC_H_CLSACT or the class of
+// the classful parent.
+//
+// The builders put the header and TCA_KIND, the options by the kind can be
+// put after that, e.g. TcNetemQopt::put_start() or Tca::options_start().
+#[derive(Debug, Clone)]
+pub struct Qdisc {
+    pub index: u32,
+    pub handle: u32,
+    pub parent: u32,
+    pub kind: String,
+    pub stats: Option<TcStats>, // TCA_STATS, not on building
+}
+
+// A class of the classful qdisc, RTM_NEWTCLASS. handle is the classid, of
+// which major is the qdisc's. parent is the qdisc handle or the parent class.
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub index: u32,
+    pub handle: u32,
+    pub parent: u32,
+    pub kind: String, // of the qdisc
+    pub stats: Option<TcStats>,
+}
+
+fn from_nlmsg(nlh: &Msghdr) -> Result<(Tcmsg, String, Option<TcStats>)> {
+    let tcm = *nlh.payload::<Tcmsg>()?;
+    let tb = TcaTbl::from_nlmsg(mem::size_of::<Tcmsg>(), nlh)?;
+    let kind = tb.kind()?.ok_or(Errno(libc::EINVAL))?.to_string();
+    Ok((tcm, kind, unaligned(tb[Tca::Stats])?))
+}
+
+fn put_request<'a>(
+    nlv: &'a mut MsgVec,
+    mtype: u16,
+    flags: u16,
+    seq: u32,
+    tcm: Tcmsg,
+    kind: &str,
+) -> Result<&'a mut MsgVec> {
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = mtype;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16 | flags;
+    nlh.nlmsg_seq = seq;
+    *nlv.put_extra_header::<Tcmsg>()? = tcm;
+    // deletion does not require
+    if !kind.is_empty() {
+        Tca::put_kind(nlv, kind)?;
+    }
+    Ok(nlv)
+}
+
+impl Qdisc {
+    // The root qdisc, the handle is allocated by the kernel.
+    pub fn new(index: u32, kind: &str) -> Self {
+        Self {
+            index,
+            handle: 0,
+            parent: TC_H_ROOT,
+            kind: kind.to_string(),
+            stats: None,
+        }
+    }
+
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let (tcm, kind, stats) = from_nlmsg(nlh)?;
+        Ok(Self {
+            index: tcm.tcm_ifindex,
+            handle: tcm.tcm_handle,
+            parent: tcm.tcm_parent,
+            kind,
+            stats,
+        })
+    }
+
+    fn tcmsg(&self) -> Tcmsg {
+        Tcmsg {
+            tcm_family: libc::AF_UNSPEC as u8,
+            tcm__pad1: 0,
+            tcm__pad2: 0,
+            tcm_ifindex: self.index,
+            tcm_handle: self.handle,
+            tcm_parent: self.parent,
+            tcm_info: 0,
+        }
+    }
+
+    // RTM_NEWQDISC, EEXIST if exists at the parent.
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_NEWQDISC,
+            (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+
+    // RTM_NEWQDISC without NLM_F_CREATE, changes the options of the existing.
+    pub fn put_change<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_NEWQDISC,
+            0,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+
+    // RTM_NEWQDISC, adds or replaces the qdisc at the parent.
+    pub fn put_replace<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_NEWQDISC,
+            (libc::NLM_F_CREATE | libc::NLM_F_REPLACE) as u16,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+
+    // RTM_DELQDISC, identified by the parent and the handle if not 0.
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_DELQDISC,
+            0,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+}
+
+impl Class {
+    // A class at the root of the qdisc, tc_h_maj(handle).
+    pub fn new(index: u32, handle: u32, kind: &str) -> Self {
+        Self {
+            index,
+            handle,
+            parent: tc_h_maj(handle),
+            kind: kind.to_string(),
+            stats: None,
+        }
+    }
+
+    pub fn from_nlmsg(nlh: &Msghdr) -> Result<Self> {
+        let (tcm, kind, stats) = from_nlmsg(nlh)?;
+        Ok(Self {
+            index: tcm.tcm_ifindex,
+            handle: tcm.tcm_handle,
+            parent: tcm.tcm_parent,
+            kind,
+            stats,
+        })
+    }
+
+    fn tcmsg(&self) -> Tcmsg {
+        Tcmsg {
+            tcm_family: libc::AF_UNSPEC as u8,
+            tcm__pad1: 0,
+            tcm__pad2: 0,
+            tcm_ifindex: self.index,
+            tcm_handle: self.handle,
+            tcm_parent: self.parent,
+            tcm_info: 0,
+        }
+    }
+
+    pub fn put_add<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_NEWTCLASS,
+            (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+
+    pub fn put_change<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_NEWTCLASS,
+            0,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+
+    pub fn put_replace<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_NEWTCLASS,
+            (libc::NLM_F_CREATE | libc::NLM_F_REPLACE) as u16,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+
+    // RTM_DELTCLASS, EBUSY if it has children or filters refer to.
+    pub fn put_delete<'a>(&self, nlv: &'a mut MsgVec, seq: u32) -> Result<&'a mut MsgVec> {
+        put_request(
+            nlv,
+            rtnetlink::RTM_DELTCLASS,
+            0,
+            seq,
+            self.tcmsg(),
+            &self.kind,
+        )
+    }
+}
+
+// options puts TCA_OPTIONS by the kind to the message built, e.g.
+//   |nlv| { TcNetemQopt { .. }.put_start(nlv)?.nest_end()?; Ok(()) }
+pub fn qdisc_add<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    qdisc: &Qdisc,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(qdisc.put_add(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn qdisc_change<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    qdisc: &Qdisc,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(qdisc.put_change(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn qdisc_replace<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    qdisc: &Qdisc,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(qdisc.put_replace(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn qdisc_delete(nl: &mut Socket, qdisc: &Qdisc) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    qdisc.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn class_add<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    class: &Class,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(class.put_add(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn class_change<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    class: &Class,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(class.put_change(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn class_replace<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    class: &Class,
+    options: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    options(class.put_replace(&mut nlv, seq)?)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+pub fn class_delete(nl: &mut Socket, class: &Class) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    class.put_delete(&mut nlv, seq)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+fn dump_request(mtype: u16, index: u32, seq: u32) -> Result<MsgVec> {
+    let mut nlv = MsgVec::new();
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = mtype;
+    nlh.nlmsg_flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    nlh.nlmsg_seq = seq;
+    let tcm = nlv.put_extra_header::<Tcmsg>()?;
+    tcm.tcm_family = libc::AF_UNSPEC as u8;
+    tcm.tcm_ifindex = index;
+    Ok(nlv)
+}
+
+// RTM_GETQDISC dump, of all links if index is None. The kernel does not
+// filter by the index, it is applied here.
+pub fn qdisc_dump(nl: &mut Socket, index: Option<u32>) -> Result<Vec<Qdisc>> {
+    let seq = seq();
+    let nlv = dump_request(rtnetlink::RTM_GETQDISC, index.unwrap_or(0), seq)?;
+    let mut qdiscs = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        let qdisc = Qdisc::from_nlmsg(nlh)?;
+        if index.is_none() || index == Some(qdisc.index) {
+            qdiscs.push(qdisc);
+        }
+        Ok(())
+    })?;
+    Ok(qdiscs)
+}
+
+// RTM_GETTCLASS dump of the link, which is required.
+pub fn class_dump(nl: &mut Socket, index: u32) -> Result<Vec<Class>> {
+    let seq = seq();
+    let nlv = dump_request(rtnetlink::RTM_GETTCLASS, index, seq)?;
+    let mut classes = Vec::new();
+    run(nl, &nlv, seq, |nlh| {
+        classes.push(Class::from_nlmsg(nlh)?);
+        Ok(())
+    })?;
+    Ok(classes)
+}
@@ -1,12 +1,14 @@
 use errno::Errno;
 use libc::{c_int, c_uchar, c_uint, c_ushort, sa_family_t};
 use std::{
+    convert::TryFrom,
     mem,
     net::{Ipv4Addr, Ipv6Addr},
 };
 
-use mnl::{Attr, AttrTbl, MsgVec, Result};
-use netlink::{self, Nlmsghdr};
+use mnl::{self, Attr, AttrTbl, MsgVec, Result};
+use netlink::{self, NlaBitfield32, Nlmsghdr};
+use pkt_sched::{StabTbl, TcEstimator};
 
 // rtnetlink families. Values up to 127 are reserved for real address
 // families, values above 128 may be used arbitrarily.
@@ -147,7 +149,9 @@ pub const RTM_GETNEXTHOP: u16 = Rtm::Getnexthop as u16;
 pub const RTM_NEWLINKPROP: u16 = Rtm::Newlinkprop as u16;
 pub const RTM_DELLINKPROP: u16 = Rtm::Dellinkprop as u16;
 pub const RTM_GETLINKPROP: u16 = Rtm::Getlinkprop as u16;
-pub const RTM_NEWNVLAN: u16 = Rtm::Newvlan as u16;
+pub const RTM_NEWVLAN: u16 = Rtm::Newvlan as u16;
+#[deprecated(note = "misspelled, use RTM_NEWVLAN")]
+pub const RTM_NEWNVLAN: u16 = RTM_NEWVLAN;
 pub const RTM_DELVLAN: u16 = Rtm::Delvlan as u16;
 pub const RTM_GETVLAN: u16 = Rtm::Getvlan as u16;
 pub const __RTM_MAX: u16 = Rtm::_MAX as u16;
@@ -682,24 +686,58 @@ pub const TCM_IFINDEX_MAGIC_BLOCK: c_int = -1;
 #[tbname = "TcaTbl"]
 pub enum Tca {
     Unspec = 0,
+
+    #[nla_type(cstr, kind)]
     Kind,
+
+    // by the kind, see pkt_sched::QdiscOptions
     Options,
+
+    // struct tc_stats, may not be aligned, see pkt_sched::Qdisc
     Stats,
-    Xstats,
+
+    #[nla_type(bytes, xstats)]
+    Xstats, // by the kind, e.g. struct tc_htb_xstats
+
+    #[nla_type(TcEstimator, rate)]
     Rate,
+
     Fcnt,
     Stats2,
+
+    #[nla_nest(StabTbl, stab)]
     Stab,
+
     Pad,
+
+    #[nla_type(flag, dump_invisible)]
     DumpInvisible,
+
+    #[nla_type(u32, chain)]
     Chain,
+
+    #[nla_type(u8, hw_offload)]
     HwOffload,
+
+    #[nla_type(u32, ingress_block)]
     IngressBlock,
+
+    #[nla_type(u32, egress_block)]
     EgressBlock,
-    DumpFlags,
+
+    #[nla_type(NlaBitfield32, dump_flags)]
+    DumpFlags, // TCA_DUMP_FLAGS_
+
     _MAX,
 }
 
+impl Tca {
+    // put attributes of the table for TCA_KIND, then nest_end()
+    pub fn options_start(nlv: &mut MsgVec) -> Result<&mut MsgVec> {
+        nlv.nest_start(Tca::Options)
+    }
+}
+
 pub const TCA_DUMP_FLAGS_TERSE: u32 = 1 << 0; // Means that in dump user gets only basic
                                               // data necessary to identify the objects
                                               // (handle, cookie, etc.) and stats.
@@ -849,14 +887,24 @@ pub struct Tcamsg {
 
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TcaRootTbl"]
 pub enum TcaRoot {
     Unspec = 0,
-    Tab,
-    Flags,
+    Tab, // actions, see pkt_cls::actions()
+
+    #[nla_type(NlaBitfield32, flags)]
+    Flags, // TCA_FLAG_, TCA_ACT_FLAG_
+
+    #[nla_type(u32, count)]
     Count,
-    TimeDelta,
+
+    #[nla_type(u32, time_delta)]
+    TimeDelta, // in msecs
+
     _MAX,
 }
+pub const TCA_ACT_TAB: u16 = TcaRoot::Tab as u16; // attr type must be >=1
+pub const TCAA_MAX: u16 = TcaRoot::_MAX as u16 - 1;
 
 pub unsafe fn ta_rta(r: &mut Tcamsg) -> &mut Rtattr {
     &mut *((r as *mut _ as *mut u8)
@@ -889,4 +937,25 @@ pub const RTEXT_FILTER_MRP: u32 = 1 << 4;
 pub const RTEXT_FILTER_CFM_CONFIG: u32 = 1 << 5;
 pub const RTEXT_FILTER_CFM_STATUS: u32 = 1 << 6;
 
+// Some attributes, e.g. VETH_INFO_PEER, carry a fixed size header followed
+// by attributes, which Attr::parse_nested() can not handle. Returns the
+// header and a table of the following attributes.
+pub fn parse_nest_with_header<'a, H, T: AttrTbl<'a>>(nest: &'a Attr<'a>) -> Result<(&'a H, T)> {
+    let hdr = nest.value_ref::<H>()?;
+    let hdrlen = mnl::align(mem::size_of::<H>());
+    let mut tb = T::new();
+    let mut len = nest.payload_len() as isize - hdrlen as isize;
+    let mut attr =
+        unsafe { &*((nest as *const _ as *const u8).add(Attr::HDRLEN + hdrlen) as *const Attr) };
+    while attr.ok(len) {
+        // skip unsupported attribute in user-space
+        if let Ok(atype) = T::Index::try_from(attr.atype()) {
+            tb._set(atype, attr);
+        }
+        len -= mnl::align(attr.nla_len as usize) as isize;
+        attr = unsafe { attr.next() };
+    }
+    Ok((hdr, tb))
+}
+
 // End of information exported to user level
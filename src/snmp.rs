@@ -0,0 +1,62 @@
+// ipstats mib definitions, index of IFLA_INET6_STATS
+#[repr(usize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IpstatsMib {
+    // IPSTATS_MIB_
+    Num = 0,
+    // frequently written fields in fast path, kept in same cache line
+    Inpkts,           // InReceives
+    Inoctets,         // InOctets
+    Indelivers,       // InDelivers
+    Outforwdatagrams, // OutForwDatagrams
+    Outrequests,      // OutRequests
+    Outoctets,        // OutOctets
+    // other fields
+    Inhdrerrors,     // InHdrErrors
+    Intoobigerrors,  // InTooBigErrors
+    Innoroutes,      // InNoRoutes
+    Inaddrerrors,    // InAddrErrors
+    Inunknownprotos, // InUnknownProtos
+    Intruncatedpkts, // InTruncatedPkts
+    Indiscards,      // InDiscards
+    Outdiscards,     // OutDiscards
+    Outnoroutes,     // OutNoRoutes
+    Reasmtimeout,    // ReasmTimeout
+    Reasmreqds,      // ReasmReqds
+    Reasmoks,        // ReasmOKs
+    Reasmfails,      // ReasmFails
+    Fragoks,         // FragOKs
+    Fragfails,       // FragFails
+    Fragcreates,     // FragCreates
+    Inmcastpkts,     // InMcastPkts
+    Outmcastpkts,    // OutMcastPkts
+    Inbcastpkts,     // InBcastPkts
+    Outbcastpkts,    // OutBcastPkts
+    Inmcastoctets,   // InMcastOctets
+    Outmcastoctets,  // OutMcastOctets
+    Inbcastoctets,   // InBcastOctets
+    Outbcastoctets,  // OutBcastOctets
+    Csumerrors,      // InCsumErrors
+    Noectpkts,       // InNoECTPkts
+    Ect1pkts,        // InECT1Pkts
+    Ect0pkts,        // InECT0Pkts
+    Cepkts,          // InCEPkts
+    ReasmOverlaps,   // ReasmOverlaps
+    Outpkts,         // OutTransmits
+    _MAX,
+}
+
+// ICMP6 (IPv6-ICMP) mib definitions, index of IFLA_INET6_ICMP6STATS
+#[repr(usize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Icmp6Mib {
+    // ICMP6_MIB_
+    Num = 0,
+    Inmsgs,        // InMsgs
+    Inerrors,      // InErrors
+    Outmsgs,       // OutMsgs
+    Outerrors,     // OutErrors
+    Csumerrors,    // InCsumErrors
+    Ratelimithost, // OutRateLimitHost
+    _MAX,
+}
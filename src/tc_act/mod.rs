@@ -0,0 +1,331 @@
+use errno::Errno;
+use std::mem;
+
+use mnl::{AttrTbl, MsgVec, Msghdr, Result, Socket};
+
+use netlink::NlaBitfield32;
+use pkt_cls::{self, Act, ActTbl, PoliceTbl};
+use rtnetlink::{self, TcaRoot, TcaRootTbl, Tcamsg};
+use util::{payload_bytes, run, seq};
+
+pub mod tc_connmark;
+pub mod tc_csum;
+pub mod tc_ct;
+pub mod tc_gact;
+pub mod tc_mirred;
+pub mod tc_nat;
+pub mod tc_pedit;
+pub mod tc_sample;
+pub mod tc_skbedit;
+pub mod tc_tunnel_key;
+pub mod tc_vlan;
+
+use self::tc_connmark::ConnmarkTbl;
+use self::tc_csum::CsumTbl;
+use self::tc_ct::CtTbl;
+use self::tc_gact::GactTbl;
+use self::tc_mirred::MirredTbl;
+use self::tc_nat::NatTbl;
+use self::tc_pedit::PeditTbl;
+use self::tc_sample::SampleTbl;
+use self::tc_skbedit::SkbeditTbl;
+use self::tc_tunnel_key::TunnelKeyTbl;
+use self::tc_vlan::VlanTbl;
+
+// TCA_ACT_OPTIONS by TCA_ACT_KIND.
+pub enum ActionOptions<'a> {
+    Gact(GactTbl<'a>),
+    Mirred(MirredTbl<'a>),
+    Police(PoliceTbl<'a>),
+    Skbedit(SkbeditTbl<'a>),
+    Vlan(VlanTbl<'a>),
+    TunnelKey(TunnelKeyTbl<'a>),
+    Pedit(PeditTbl<'a>),
+    Nat(NatTbl<'a>),
+    Csum(CsumTbl<'a>),
+    Ct(CtTbl<'a>),
+    Connmark(ConnmarkTbl<'a>),
+    Sample(SampleTbl<'a>),
+    Unknown(&'a str, &'a [u8]), // kind, raw TCA_ACT_OPTIONS payload
+}
+
+impl<'a> ActionOptions<'a> {
+    // index in tc_gen of the parms, TCA_ACT_INDEX is put in terse dump only.
+    pub fn index(&self) -> Result<Option<u32>> {
+        Ok(match self {
+            ActionOptions::Gact(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Mirred(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Police(tb) => tb.tbf()?.map(|p| p.index),
+            ActionOptions::Skbedit(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Vlan(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::TunnelKey(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Pedit(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Nat(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Csum(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Ct(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Connmark(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Sample(tb) => tb.parms()?.map(|p| p.index),
+            ActionOptions::Unknown(..) => None,
+        })
+    }
+}
+
+impl<'a> ActTbl<'a> {
+    pub fn options(&self) -> Result<Option<ActionOptions<'a>>> {
+        let options = match self[Act::Options] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let kind = self[Act::Kind].ok_or(Errno(libc::EINVAL))?.cstr()?;
+        Ok(Some(match kind {
+            "gact" => ActionOptions::Gact(GactTbl::from_nest(options)?),
+            "mirred" => ActionOptions::Mirred(MirredTbl::from_nest(options)?),
+            "police" => ActionOptions::Police(PoliceTbl::from_nest(options)?),
+            "skbedit" => ActionOptions::Skbedit(SkbeditTbl::from_nest(options)?),
+            "vlan" => ActionOptions::Vlan(VlanTbl::from_nest(options)?),
+            "tunnel_key" => ActionOptions::TunnelKey(TunnelKeyTbl::from_nest(options)?),
+            "pedit" => ActionOptions::Pedit(PeditTbl::from_nest(options)?),
+            "nat" => ActionOptions::Nat(NatTbl::from_nest(options)?),
+            "csum" => ActionOptions::Csum(CsumTbl::from_nest(options)?),
+            "ct" => ActionOptions::Ct(CtTbl::from_nest(options)?),
+            "connmark" => ActionOptions::Connmark(ConnmarkTbl::from_nest(options)?),
+            "sample" => ActionOptions::Sample(SampleTbl::from_nest(options)?),
+            _ => ActionOptions::Unknown(kind, payload_bytes(options)),
+        }))
+    }
+}
+
+// An action in TCA_ROOT_TAB of RTM_NEWACTION or in TCA_*_ACT of the
+// classifiers. index 0 on adding lets the kernel allocate, or the index in
+// tc_gen of the parms is used.
+#[derive(Debug, Clone, Default)]
+pub struct Action {
+    pub kind: String,
+    pub index: u32,
+    pub cookie: Option<Vec<u8>>,
+}
+
+impl Action {
+    pub fn new(kind: &str) -> Self {
+        Self {
+            kind: kind.to_string(),
+            index: 0,
+            cookie: None,
+        }
+    }
+
+    pub fn from_tbl(tb: &ActTbl) -> Result<Self> {
+        let index = match tb.index()? {
+            Some(index) => *index,
+            None => match tb.options()? {
+                Some(options) => options.index()?.unwrap_or(0),
+                None => 0,
+            },
+        };
+        Ok(Self {
+            kind: tb.kind()?.ok_or(Errno(libc::EINVAL))?.to_string(),
+            index,
+            cookie: tb.cookie()?.map(|b| b.to_vec()),
+        })
+    }
+
+    // Puts the action as the order-th, from 1, in the list. options puts
+    // TCA_ACT_OPTIONS by Act::options_start() as filter's.
+    pub fn put<'a, F: FnOnce(&mut MsgVec) -> Result<()>>(
+        &self,
+        nlv: &'a mut MsgVec,
+        order: u16,
+        options: F,
+    ) -> Result<&'a mut MsgVec> {
+        Act::start(nlv, order)?;
+        nlv.put_cstr(Act::Kind, &self.kind)?;
+        if self.index != 0 {
+            nlv.put(Act::Index, &self.index)?;
+        }
+        if let Some(cookie) = &self.cookie {
+            if cookie.len() > pkt_cls::TC_COOKIE_MAX_SIZE {
+                return Err(Errno(libc::EINVAL));
+            }
+            nlv.put_bytes(Act::Cookie, cookie)?;
+        }
+        options(nlv)?;
+        nlv.nest_end()
+    }
+}
+
+// The header and tcamsg of RTM_*ACTION, TCA_ROOT_TAB follows.
+fn put_request(nlv: &mut MsgVec, mtype: u16, flags: u16, seq: u32) -> Result<&mut MsgVec> {
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = mtype;
+    nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+    nlh.nlmsg_seq = seq;
+    let tca = nlv.put_extra_header::<Tcamsg>()?;
+    tca.tca_family = libc::AF_UNSPEC as u8;
+    Ok(nlv)
+}
+
+// TCA_ROOT_TAB of the only action, which has kind and index only.
+fn put_tab<'a>(nlv: &'a mut MsgVec, kind: &str, index: u32) -> Result<&'a mut MsgVec> {
+    nlv.nest_start(TcaRoot::Tab)?;
+    let act = Action {
+        kind: kind.to_string(),
+        index,
+        cookie: None,
+    };
+    act.put(nlv, 1, |_| Ok(()))?;
+    nlv.nest_end()
+}
+
+fn new_action<F: FnOnce(&mut MsgVec) -> Result<()>>(
+    nl: &mut Socket,
+    flags: u16,
+    acts: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    put_request(
+        &mut nlv,
+        rtnetlink::RTM_NEWACTION,
+        libc::NLM_F_ACK as u16 | flags,
+        seq,
+    )?;
+    nlv.nest_start(TcaRoot::Tab)?;
+    acts(&mut nlv)?;
+    nlv.nest_end()?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_NEWACTION, acts puts the actions into TCA_ROOT_TAB, e.g.
+//   |nlv| {
+//       Action::new("gact").put(nlv, 1, |nlv| {
+//           Act::options_start(nlv)?;
+//           let parms = TcGact {
+//               action: pkt_cls::TC_ACT_SHOT,
+//               ..Default::default()
+//           };
+//           nlv.put(Gact::Parms, &parms)?;
+//           nlv.nest_end()?;
+//           Ok(())
+//       })?;
+//       Ok(())
+//   }
+// EEXIST if the index in the parms exists.
+pub fn add<F: FnOnce(&mut MsgVec) -> Result<()>>(nl: &mut Socket, acts: F) -> Result<()> {
+    new_action(nl, (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16, acts)
+}
+
+pub fn replace<F: FnOnce(&mut MsgVec) -> Result<()>>(nl: &mut Socket, acts: F) -> Result<()> {
+    new_action(nl, (libc::NLM_F_CREATE | libc::NLM_F_REPLACE) as u16, acts)
+}
+
+// RTM_DELACTION, EPERM if it is bound to a filter.
+pub fn delete(nl: &mut Socket, kind: &str, index: u32) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    put_request(
+        &mut nlv,
+        rtnetlink::RTM_DELACTION,
+        libc::NLM_F_ACK as u16,
+        seq,
+    )?;
+    put_tab(&mut nlv, kind, index)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_DELACTION with NLM_F_ROOT, all of the kind not bound to a filter.
+pub fn flush(nl: &mut Socket, kind: &str) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    put_request(
+        &mut nlv,
+        rtnetlink::RTM_DELACTION,
+        (libc::NLM_F_ACK | libc::NLM_F_ROOT) as u16,
+        seq,
+    )?;
+    put_tab(&mut nlv, kind, 0)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}
+
+// RTM_GETACTION of the index, f is called with the action replied.
+pub fn get<F: FnMut(&ActTbl) -> Result<()>>(
+    nl: &mut Socket,
+    kind: &str,
+    index: u32,
+    mut f: F,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    // the reply is not terminated by NLMSG_DONE but the ack
+    put_request(
+        &mut nlv,
+        rtnetlink::RTM_GETACTION,
+        libc::NLM_F_ACK as u16,
+        seq,
+    )?;
+    put_tab(&mut nlv, kind, index)?;
+    run(nl, &nlv, seq, |nlh| each_action(nlh, &mut f).map(|_| ()))
+}
+
+// Calls f with the actions in TCA_ROOT_TAB, returns TCA_ROOT_COUNT.
+fn each_action<F: FnMut(&ActTbl) -> Result<()>>(nlh: &Msghdr, f: &mut F) -> Result<u32> {
+    let tb = TcaRootTbl::from_nlmsg(mem::size_of::<Tcamsg>(), nlh)?;
+    if let Some(tab) = tb[TcaRoot::Tab] {
+        for act in pkt_cls::actions(tab)? {
+            f(&act)?;
+        }
+    }
+    Ok(tb.count()?.copied().unwrap_or(0))
+}
+
+// RTM_GETACTION dump of the kind. flags are TCA_FLAG_LARGE_DUMP_ON, without
+// which the kernel puts up to TCA_ACT_MAX_PRIO actions in a message, and
+// TCA_ACT_FLAG_TERSE_DUMP, with which the actions have kind, index, cookie
+// and stats only. time_delta dumps the actions used within the msecs only.
+// f is called with each action and returns the sum of TCA_ROOT_COUNT. The
+// kernel puts it in every dump message, but counts the actions in it only
+// with TCA_FLAG_LARGE_DUMP_ON, so the sum is 0 without the flag.
+pub fn dump_with<F: FnMut(&ActTbl) -> Result<()>>(
+    nl: &mut Socket,
+    kind: &str,
+    flags: u32,
+    time_delta: Option<u32>,
+    mut f: F,
+) -> Result<u32> {
+    let mut nlv = MsgVec::new();
+    let seq = seq();
+    put_request(
+        &mut nlv,
+        rtnetlink::RTM_GETACTION,
+        libc::NLM_F_DUMP as u16,
+        seq,
+    )?;
+    put_tab(&mut nlv, kind, 0)?;
+    if flags != 0 {
+        nlv.put(
+            TcaRoot::Flags,
+            &NlaBitfield32 {
+                value: flags,
+                selector: flags,
+            },
+        )?;
+    }
+    if let Some(msecs) = time_delta {
+        nlv.put(TcaRoot::TimeDelta, &msecs)?;
+    }
+
+    let mut count = 0;
+    run(nl, &nlv, seq, |nlh| {
+        count += each_action(nlh, &mut f)?;
+        Ok(())
+    })?;
+    Ok(count)
+}
+
+pub fn dump(nl: &mut Socket, kind: &str, flags: u32) -> Result<Vec<Action>> {
+    let mut acts = Vec::new();
+    dump_with(nl, kind, flags, None, |tb| {
+        acts.push(Action::from_tbl(tb)?);
+        Ok(())
+    })?;
+    Ok(acts)
+}
@@ -0,0 +1,38 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcConnmark {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub zone: u16,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "ConnmarkTbl"]
+pub enum Connmark {
+    // TCA_CONNMARK_
+    Unspec,
+
+    #[nla_type(TcConnmark, parms)]
+    Parms,
+
+    Tm,
+    Pad,
+    _MAX,
+}
+
+impl<'a> ConnmarkTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Connmark::Tm])
+    }
+}
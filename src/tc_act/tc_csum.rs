@@ -0,0 +1,46 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "CsumTbl"]
+pub enum Csum {
+    // TCA_CSUM_
+    Unspec,
+
+    #[nla_type(TcCsum, parms)]
+    Parms,
+
+    Tm,
+    Pad,
+    _MAX,
+}
+
+pub const TCA_CSUM_UPDATE_FLAG_IPV4HDR: u32 = 1;
+pub const TCA_CSUM_UPDATE_FLAG_ICMP: u32 = 2;
+pub const TCA_CSUM_UPDATE_FLAG_IGMP: u32 = 4;
+pub const TCA_CSUM_UPDATE_FLAG_TCP: u32 = 8;
+pub const TCA_CSUM_UPDATE_FLAG_UDP: u32 = 16;
+pub const TCA_CSUM_UPDATE_FLAG_UDPLITE: u32 = 32;
+pub const TCA_CSUM_UPDATE_FLAG_SCTP: u32 = 64;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcCsum {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub update_flags: u32, // TCA_CSUM_UPDATE_FLAG_
+}
+
+impl<'a> CsumTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Csum::Tm])
+    }
+}
@@ -0,0 +1,83 @@
+use errno::Errno;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "CtTbl"]
+pub enum Ct {
+    // TCA_CT_
+    Unspec,
+
+    #[nla_type(TcCt, parms)]
+    Parms,
+
+    Tm,
+
+    #[nla_type(u16, action)]
+    Action, // TCA_CT_ACT_
+
+    #[nla_type(u16, zone)]
+    Zone,
+
+    #[nla_type(u32, mark)]
+    Mark,
+
+    #[nla_type(u32, mark_mask)]
+    MarkMask,
+
+    #[nla_type(bytes, labels)]
+    Labels, // u128
+
+    #[nla_type(bytes, labels_mask)]
+    LabelsMask,
+
+    #[nla_type(Ipv4Addr, nat_ipv4_min)]
+    NatIpv4Min,
+
+    #[nla_type(Ipv4Addr, nat_ipv4_max)]
+    NatIpv4Max,
+
+    #[nla_type(Ipv6Addr, nat_ipv6_min)]
+    NatIpv6Min,
+
+    #[nla_type(Ipv6Addr, nat_ipv6_max)]
+    NatIpv6Max,
+
+    #[nla_type(u16, nat_port_min)]
+    NatPortMin, // be16
+
+    #[nla_type(u16, nat_port_max)]
+    NatPortMax,
+
+    Pad,
+    _MAX,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcCt {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+}
+
+pub const TCA_CT_ACT_COMMIT: u16 = 1 << 0;
+pub const TCA_CT_ACT_FORCE: u16 = 1 << 1;
+pub const TCA_CT_ACT_CLEAR: u16 = 1 << 2;
+pub const TCA_CT_ACT_NAT: u16 = 1 << 3;
+pub const TCA_CT_ACT_NAT_SRC: u16 = 1 << 4;
+pub const TCA_CT_ACT_NAT_DST: u16 = 1 << 5;
+
+impl<'a> CtTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Ct::Tm])
+    }
+}
@@ -0,0 +1,53 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcGact {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32, // TC_ACT_
+    pub refcnt: i32,
+    pub bindcnt: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcGactP {
+    pub ptype: u16, // PGACT_
+    pub pval: u16,
+    pub paction: i32,
+}
+
+pub const PGACT_NONE: u16 = 0;
+pub const PGACT_NETRAND: u16 = 1;
+pub const PGACT_DETERM: u16 = 2;
+pub const MAX_RAND: u16 = PGACT_DETERM + 1;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "GactTbl"]
+pub enum Gact {
+    // TCA_GACT_
+    Unspec,
+    Tm, // struct tcf_t, see tm()
+
+    #[nla_type(TcGact, parms)]
+    Parms,
+
+    #[nla_type(TcGactP, prob)]
+    Prob,
+
+    Pad,
+    _MAX,
+}
+
+impl<'a> GactTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Gact::Tm])
+    }
+}
@@ -0,0 +1,44 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+pub const TCA_EGRESS_REDIR: i32 = 1; // packet redirect to EGRESS
+pub const TCA_EGRESS_MIRROR: i32 = 2; // mirror packet to EGRESS
+pub const TCA_INGRESS_REDIR: i32 = 3; // packet redirect to INGRESS
+pub const TCA_INGRESS_MIRROR: i32 = 4; // mirror packet to INGRESS
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcMirred {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub eaction: i32, // one of IN/EGRESS_MIRROR/REDIR
+    pub ifindex: u32, // ifindex of egress port
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "MirredTbl"]
+pub enum Mirred {
+    // TCA_MIRRED_
+    Unspec,
+    Tm,
+
+    #[nla_type(TcMirred, parms)]
+    Parms,
+
+    Pad,
+    _MAX,
+}
+
+impl<'a> MirredTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Mirred::Tm])
+    }
+}
@@ -0,0 +1,43 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "NatTbl"]
+pub enum Nat {
+    // TCA_NAT_
+    Unspec,
+
+    #[nla_type(TcNat, parms)]
+    Parms,
+
+    Tm,
+    Pad,
+    _MAX,
+}
+
+pub const TCA_NAT_FLAG_EGRESS: u32 = 1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcNat {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub old_addr: u32, // be32
+    pub new_addr: u32,
+    pub mask: u32,
+    pub flags: u32, // TCA_NAT_FLAG_
+}
+
+impl<'a> NatTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Nat::Tm])
+    }
+}
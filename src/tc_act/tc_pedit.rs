@@ -0,0 +1,186 @@
+use errno::Errno;
+use std::{mem, ptr};
+
+use mnl::{Attr, AttrTbl, CbStatus, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::{to_errno, unaligned};
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "PeditTbl"]
+pub enum Pedit {
+    // TCA_PEDIT_
+    Unspec,
+    Tm,
+    Parms, // struct tc_pedit_sel and keys, see parms() and keys()
+    Pad,
+    ParmsEx, // same as Parms, with KeysEx
+
+    KeysEx, // nest of TCA_PEDIT_KEY_EX for each key, see keys_ex()
+
+    KeyEx,
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "PeditKeyExTbl"]
+pub enum PeditKeyEx {
+    // TCA_PEDIT_KEY_EX_
+    Unspec = 0,
+
+    #[nla_type(u16, htype)]
+    Htype = 1, // TCA_PEDIT_KEY_EX_HDR_TYPE_
+
+    #[nla_type(u16, cmd)]
+    Cmd = 2, // TCA_PEDIT_KEY_EX_CMD_
+
+    _MAX,
+}
+
+// TCA_PEDIT_KEY_EX_HDR_TYPE_NETWROK is a special case for legacy users. It
+// means no specific header type - offset is relative to the network layer
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeditHeaderType {
+    Network = 0,
+    Eth = 1,
+    Ip4 = 2,
+    Ip6 = 3,
+    Tcp = 4,
+    Udp = 5,
+    _MAX,
+}
+pub const TCA_PEDIT_KEY_EX_HDR_TYPE_NETWORK: u16 = PeditHeaderType::Network as u16;
+pub const TCA_PEDIT_KEY_EX_HDR_TYPE_ETH: u16 = PeditHeaderType::Eth as u16;
+pub const TCA_PEDIT_KEY_EX_HDR_TYPE_IP4: u16 = PeditHeaderType::Ip4 as u16;
+pub const TCA_PEDIT_KEY_EX_HDR_TYPE_IP6: u16 = PeditHeaderType::Ip6 as u16;
+pub const TCA_PEDIT_KEY_EX_HDR_TYPE_TCP: u16 = PeditHeaderType::Tcp as u16;
+pub const TCA_PEDIT_KEY_EX_HDR_TYPE_UDP: u16 = PeditHeaderType::Udp as u16;
+pub const TCA_PEDIT_HDR_TYPE_MAX: u16 = PeditHeaderType::_MAX as u16 - 1;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeditCmd {
+    Set = 0,
+    Add = 1,
+    _MAX,
+}
+pub const TCA_PEDIT_KEY_EX_CMD_SET: u16 = PeditCmd::Set as u16;
+pub const TCA_PEDIT_KEY_EX_CMD_ADD: u16 = PeditCmd::Add as u16;
+pub const TCA_PEDIT_CMD_MAX: u16 = PeditCmd::_MAX as u16 - 1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcPeditKey {
+    pub mask: u32, // AND
+    pub val: u32,  // XOR
+    pub off: u32,  // offset
+    pub at: u32,
+    pub offmask: u32,
+    pub shift: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcPeditSel {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub nkeys: u8,
+    pub flags: u8,
+    // struct tc_pedit_key keys[0];
+}
+
+impl TcPeditSel {
+    // TCA_PEDIT_PARMS or TCA_PEDIT_PARMS_EX, nkeys is set from keys.
+    pub fn put<'a>(
+        &self,
+        nlv: &'a mut MsgVec,
+        ex: bool,
+        keys: &[TcPeditKey],
+    ) -> Result<&'a mut MsgVec> {
+        if keys.len() > u8::MAX as usize {
+            return Err(Errno(libc::EINVAL));
+        }
+        let mut sel = *self;
+        sel.nkeys = keys.len() as u8;
+        let mut b = Vec::with_capacity(mem::size_of::<TcPeditSel>() + mem::size_of_val(keys));
+        b.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(&sel as *const _ as *const u8, mem::size_of::<TcPeditSel>())
+        });
+        b.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(keys.as_ptr() as *const u8, mem::size_of_val(keys))
+        });
+        nlv.put_bytes(if ex { Pedit::ParmsEx } else { Pedit::Parms }, &b)
+    }
+}
+
+impl Pedit {
+    // TCA_PEDIT_KEYS_EX, in the order of the keys of TCA_PEDIT_PARMS_EX.
+    pub fn put_keys_ex<'a>(nlv: &'a mut MsgVec, keys: &[(u16, u16)]) -> Result<&'a mut MsgVec> {
+        nlv.nest_start(Pedit::KeysEx)?;
+        for (htype, cmd) in keys {
+            nlv.nest_start(Pedit::KeyEx)?;
+            nlv.put(PeditKeyEx::Htype, htype)?;
+            nlv.put(PeditKeyEx::Cmd, cmd)?;
+            nlv.nest_end()?;
+        }
+        nlv.nest_end()
+    }
+}
+
+impl<'a> PeditTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Pedit::Tm])
+    }
+
+    // TCA_PEDIT_PARMS_EX, or TCA_PEDIT_PARMS if not
+    pub fn parms(&self) -> Result<Option<TcPeditSel>> {
+        match self[Pedit::ParmsEx] {
+            Some(attr) => unaligned(Some(attr)),
+            None => unaligned(self[Pedit::Parms]),
+        }
+    }
+
+    // struct tc_pedit_key keys[] following the parms
+    pub fn keys(&self) -> Result<Option<Vec<TcPeditKey>>> {
+        let sel = match self.parms()? {
+            Some(sel) => sel,
+            None => return Ok(None),
+        };
+        let attr = self[Pedit::ParmsEx].or(self[Pedit::Parms]).unwrap();
+        let b = &attr.bytes_ref()[mem::size_of::<TcPeditSel>()..];
+        let size = mem::size_of::<TcPeditKey>();
+        if b.len() < size * sel.nkeys as usize {
+            return Err(Errno(libc::ERANGE));
+        }
+        Ok(Some(
+            b.chunks_exact(size)
+                .take(sel.nkeys as usize)
+                .map(|c| unsafe { ptr::read_unaligned(c.as_ptr() as *const TcPeditKey) })
+                .collect(),
+        ))
+    }
+
+    // All of the TCA_PEDIT_KEY_EX, of which table keeps the last one only.
+    pub fn keys_ex(&self) -> Result<Option<Vec<PeditKeyExTbl<'a>>>> {
+        let nest = match self[Pedit::KeysEx] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let mut keys = Vec::new();
+        nest.parse_nested(|attr| {
+            if attr.atype() == Pedit::KeyEx as u16 {
+                keys.push(PeditKeyExTbl::from_nest(attr)?);
+            }
+            Ok(CbStatus::Ok)
+        })
+        .map_err(to_errno)?;
+        Ok(Some(keys))
+    }
+}
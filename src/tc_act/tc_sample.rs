@@ -0,0 +1,46 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcSample {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "SampleTbl"]
+pub enum Sample {
+    // TCA_SAMPLE_
+    Unspec,
+    Tm,
+
+    #[nla_type(TcSample, parms)]
+    Parms,
+
+    #[nla_type(u32, rate)]
+    Rate,
+
+    #[nla_type(u32, trunc_size)]
+    TruncSize,
+
+    #[nla_type(u32, psample_group)]
+    PsampleGroup,
+
+    Pad,
+    _MAX,
+}
+
+impl<'a> SampleTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Sample::Tm])
+    }
+}
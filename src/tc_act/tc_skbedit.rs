@@ -0,0 +1,71 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::{put_64bit, unaligned};
+
+pub const SKBEDIT_F_PRIORITY: u64 = 0x1;
+pub const SKBEDIT_F_QUEUE_MAPPING: u64 = 0x2;
+pub const SKBEDIT_F_MARK: u64 = 0x4;
+pub const SKBEDIT_F_PTYPE: u64 = 0x8;
+pub const SKBEDIT_F_MASK: u64 = 0x10;
+pub const SKBEDIT_F_INHERITDSFIELD: u64 = 0x20;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcSkbedit {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "SkbeditTbl"]
+pub enum Skbedit {
+    // TCA_SKBEDIT_
+    Unspec,
+    Tm,
+
+    #[nla_type(TcSkbedit, parms)]
+    Parms,
+
+    #[nla_type(u32, priority)]
+    Priority,
+
+    #[nla_type(u16, queue_mapping)]
+    QueueMapping,
+
+    #[nla_type(u32, mark)]
+    Mark,
+
+    Pad,
+
+    #[nla_type(u16, ptype)]
+    Ptype, // PACKET_ of if_packet.h
+
+    #[nla_type(u32, mask)]
+    Mask, // of mark
+
+    Flags, // u64, SKBEDIT_F_INHERITDSFIELD only, see flags()
+    _MAX,
+}
+
+impl Skbedit {
+    pub fn put_flags(nlv: &mut MsgVec, flags: u64) -> Result<&mut MsgVec> {
+        put_64bit(nlv, Skbedit::Flags, Skbedit::Pad, &flags)
+    }
+}
+
+impl<'a> SkbeditTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Skbedit::Tm])
+    }
+
+    pub fn flags(&self) -> Result<Option<u64>> {
+        unaligned(self[Skbedit::Flags])
+    }
+}
@@ -0,0 +1,163 @@
+use errno::Errno;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use mnl::{Attr, AttrTbl, CbStatus, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::{to_errno, unaligned};
+
+pub const TCA_TUNNEL_KEY_ACT_SET: i32 = 1;
+pub const TCA_TUNNEL_KEY_ACT_RELEASE: i32 = 2;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcTunnelKey {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub t_action: i32, // TCA_TUNNEL_KEY_ACT_
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TunnelKeyTbl"]
+pub enum TunnelKey {
+    // TCA_TUNNEL_KEY_
+    Unspec,
+    Tm,
+
+    #[nla_type(TcTunnelKey, parms)]
+    Parms,
+
+    #[nla_type(Ipv4Addr, enc_ipv4_src)]
+    EncIpv4Src,
+
+    #[nla_type(Ipv4Addr, enc_ipv4_dst)]
+    EncIpv4Dst,
+
+    #[nla_type(Ipv6Addr, enc_ipv6_src)]
+    EncIpv6Src,
+
+    #[nla_type(Ipv6Addr, enc_ipv6_dst)]
+    EncIpv6Dst,
+
+    #[nla_type(u32, enc_key_id)]
+    EncKeyId, // be64 in the header, but the kernel puts be32
+
+    Pad,
+
+    #[nla_type(u16, enc_dst_port)]
+    EncDstPort, // be16
+
+    #[nla_type(u8, no_csum)]
+    NoCsum,
+
+    #[nla_nest(TunnelKeyEncOptsTbl, enc_opts)]
+    EncOpts,
+
+    #[nla_type(u8, enc_tos)]
+    EncTos,
+
+    #[nla_type(u8, enc_ttl)]
+    EncTtl,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TunnelKeyEncOptsTbl"]
+pub enum TunnelKeyEncOpts {
+    // TCA_TUNNEL_KEY_ENC_OPTS_
+    Unspec,
+    Geneve, // nested TCA_TUNNEL_KEY_ENC_OPT_GENEVE_, see enc_opt_geneves()
+
+    #[nla_nest(TunnelKeyEncOptVxlanTbl, vxlan)]
+    Vxlan,
+
+    #[nla_nest(TunnelKeyEncOptErspanTbl, erspan)]
+    Erspan,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TunnelKeyEncOptGeneveTbl"]
+pub enum TunnelKeyEncOptGeneve {
+    // TCA_TUNNEL_KEY_ENC_OPT_GENEVE_
+    Unspec,
+
+    #[nla_type(u16, class)]
+    Class, // be16
+
+    #[nla_type(u8, opt_type)]
+    Type,
+
+    #[nla_type(bytes, data)]
+    Data, // 4 to 128 bytes
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TunnelKeyEncOptVxlanTbl"]
+pub enum TunnelKeyEncOptVxlan {
+    // TCA_TUNNEL_KEY_ENC_OPT_VXLAN_
+    Unspec,
+
+    #[nla_type(u32, gbp)]
+    Gbp,
+
+    _MAX,
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "TunnelKeyEncOptErspanTbl"]
+pub enum TunnelKeyEncOptErspan {
+    // TCA_TUNNEL_KEY_ENC_OPT_ERSPAN_
+    Unspec,
+
+    #[nla_type(u8, ver)]
+    Ver,
+
+    #[nla_type(u32, index)]
+    Index, // be32
+
+    #[nla_type(u8, dir)]
+    Dir,
+
+    #[nla_type(u8, hwid)]
+    Hwid,
+
+    _MAX,
+}
+
+impl<'a> TunnelKeyTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[TunnelKey::Tm])
+    }
+
+    // All of the repeated TCA_TUNNEL_KEY_ENC_OPTS_GENEVE, of which table
+    // keeps the last one only.
+    pub fn enc_opt_geneves(&self) -> Result<Option<Vec<TunnelKeyEncOptGeneveTbl<'a>>>> {
+        let nest = match self[TunnelKey::EncOpts] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let mut opts = Vec::new();
+        nest.parse_nested(|attr| {
+            if attr.atype() == TunnelKeyEncOpts::Geneve as u16 {
+                opts.push(TunnelKeyEncOptGeneveTbl::from_nest(attr)?);
+            }
+            Ok(CbStatus::Ok)
+        })
+        .map_err(to_errno)?;
+        Ok(Some(opts))
+    }
+}
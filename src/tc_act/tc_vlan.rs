@@ -0,0 +1,60 @@
+use errno::Errno;
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use pkt_cls::TcfT;
+use util::unaligned;
+
+pub const TCA_VLAN_ACT_POP: i32 = 1;
+pub const TCA_VLAN_ACT_PUSH: i32 = 2;
+pub const TCA_VLAN_ACT_MODIFY: i32 = 3;
+pub const TCA_VLAN_ACT_POP_ETH: i32 = 4;
+pub const TCA_VLAN_ACT_PUSH_ETH: i32 = 5;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcVlan {
+    // tc_gen
+    pub index: u32,
+    pub capab: u32,
+    pub action: i32,
+    pub refcnt: i32,
+    pub bindcnt: i32,
+    pub v_action: i32, // TCA_VLAN_ACT_
+}
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "VlanTbl"]
+pub enum Vlan {
+    // TCA_VLAN_
+    Unspec,
+    Tm,
+
+    #[nla_type(TcVlan, parms)]
+    Parms,
+
+    #[nla_type(u16, push_vlan_id)]
+    PushVlanId,
+
+    #[nla_type(u16, push_vlan_protocol)]
+    PushVlanProtocol, // be16, ETH_P_8021Q or ETH_P_8021AD
+
+    Pad,
+
+    #[nla_type(u8, push_vlan_priority)]
+    PushVlanPriority,
+
+    #[nla_type(bytes, push_eth_dst)]
+    PushEthDst, // ETH_ALEN
+
+    #[nla_type(bytes, push_eth_src)]
+    PushEthSrc,
+
+    _MAX,
+}
+
+impl<'a> VlanTbl<'a> {
+    pub fn tm(&self) -> Result<Option<TcfT>> {
+        unaligned(self[Vlan::Tm])
+    }
+}
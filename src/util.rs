@@ -0,0 +1,140 @@
+use errno::Errno;
+use libc::c_int;
+use mnl::{self, Attr, CbStatus, GenError, MsgVec, Msghdr, Result, Socket};
+use std::{
+    iter, mem, ptr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use netlink::Nlmsgerr;
+
+// Helpers shared by the modules, to send a request and handle the replies,
+// and to get and put the u64 attributes.
+
+pub(crate) fn to_errno(err: GenError) -> Errno {
+    match err.downcast_ref::<Errno>() {
+        Some(errno) => *errno,
+        None => Errno(libc::EINVAL),
+    }
+}
+
+static SEQ: AtomicU32 = AtomicU32::new(0);
+
+// A sequence number unique in the process. 0 is skipped since cb_run() does
+// not check the sequence number of 0.
+pub(crate) fn seq() -> u32 {
+    loop {
+        let seq = SEQ.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        if seq != 0 {
+            return seq;
+        }
+    }
+}
+
+// Sends the request and calls f for each reply until NLMSG_DONE, or the ack
+// if NLM_F_ACK is set.
+pub(crate) fn run<F: FnMut(&Msghdr) -> Result<()>>(
+    nl: &mut Socket,
+    nlv: &MsgVec,
+    seq: u32,
+    mut f: F,
+) -> Result<()> {
+    nl.sendto(nlv)?;
+    let portid = nl.portid();
+    let mut buf = mnl::dump_buffer();
+    loop {
+        let nrecv = nl.recvfrom(&mut buf)?;
+        let status = mnl::cb_run(
+            &buf[..nrecv],
+            seq,
+            portid,
+            Some(|nlh: &Msghdr| {
+                f(nlh)?;
+                Ok(CbStatus::Ok)
+            }),
+        );
+        match status {
+            Ok(CbStatus::Ok) => continue,
+            Ok(CbStatus::Stop) => return Ok(()),
+            Err(err) => return Err(to_errno(err)),
+        }
+    }
+}
+
+// Messages in the received buf, without the checks of cb_run().
+pub(crate) fn nlmsgs<'a>(buf: &'a [u8]) -> impl Iterator<Item = &'a Msghdr<'a>> {
+    let mut len = buf.len() as isize;
+    let mut next = buf.as_ptr() as *const Msghdr;
+    iter::from_fn(move || {
+        if len < Msghdr::HDRLEN as isize {
+            return None;
+        }
+        let nlh = unsafe { &*next };
+        if !nlh.ok(len) {
+            return None;
+        }
+        next = unsafe { nlh.next(&mut len) };
+        Some(nlh)
+    })
+}
+
+// Handles a reply nlh of the dump, returns Some at the end, NLMSG_DONE or
+// NLMSG_ERROR. cb_run() returns EINTR at the first reply which has
+// NLM_F_DUMP_INTR and the rest is left for the next request. Instead, intr
+// is set and EINTR is returned at the end.
+pub(crate) fn dump_ctl(nlh: &Msghdr, intr: &mut bool) -> Option<Result<()>> {
+    if nlh.nlmsg_flags & libc::NLM_F_DUMP_INTR as u16 != 0 {
+        *intr = true;
+    }
+    let ret = match nlh.nlmsg_type as c_int {
+        libc::NLMSG_DONE => Ok(()),
+        libc::NLMSG_ERROR => match nlh.payload::<Nlmsgerr>() {
+            Ok(err) if err.error != 0 => Err(Errno(-err.error)),
+            Ok(_) => Ok(()),
+            Err(errno) => Err(errno),
+        },
+        _ => return None,
+    };
+    match ret {
+        Ok(_) if *intr => Some(Err(Errno(libc::EINTR))),
+        ret => Some(ret),
+    }
+}
+
+// The u64 attributes and the structs which have, e.g. TCA_STATS, are aligned
+// by the pad attribute only on the arch without efficient unaligned access,
+// nla_put_64bit().
+pub(crate) fn unaligned<T: Copy>(attr: Option<&Attr>) -> Result<Option<T>> {
+    let attr = match attr {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+    if (attr.payload_len() as usize) < mem::size_of::<T>() {
+        return Err(Errno(libc::ERANGE));
+    }
+    let b = attr.bytes_ref();
+    Ok(Some(unsafe { ptr::read_unaligned(b.as_ptr() as *const T) }))
+}
+
+// Attr::bytes_ref() panics on 0 length payload, e.g. an empty nest which
+// the kernel puts for the kind without parameters.
+pub(crate) fn payload_bytes<'a>(attr: &'a Attr) -> &'a [u8] {
+    if attr.payload_len() == 0 {
+        return &[];
+    }
+    attr.bytes_ref()
+}
+
+// MsgVec::put() u64 requires 8 bytes aligned payload, put the pad attribute
+// ahead if not like nla_put_64bit().
+pub(crate) fn put_64bit<'a, T: Into<u16>, P: Into<u16>, U: Copy>(
+    nlv: &'a mut MsgVec,
+    atype: T,
+    pad: P,
+    v: &U,
+) -> Result<&'a mut MsgVec> {
+    if (nlv.len() + Attr::HDRLEN) & 7 != 0 {
+        nlv.put_flag(pad)?;
+    }
+    nlv.put(atype, v)
+}
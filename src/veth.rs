@@ -0,0 +1,49 @@
+use errno::Errno;
+
+use mnl::{Attr, AttrTbl, MsgVec, Result};
+
+use if_link::IflaTbl;
+use rtnetlink::{self, Ifinfomsg};
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "VethInfoTbl"]
+pub enum VethInfo {
+    // VETH_INFO_
+    Unspec = 0,
+
+    // struct ifinfomsg followed by IFLA_* attributes,
+    // see VethInfo::peer_start() and VethInfoTbl::peer()
+    Peer,
+
+    _MAX,
+}
+
+// The peer nest of veth and vxcan, shared by VethInfo and VxcanInfo.
+pub(crate) fn peer_start<T: Into<u16>>(nlv: &mut MsgVec, atype: T) -> Result<&mut Ifinfomsg> {
+    nlv.nest_start(atype)?;
+    nlv.put_extra_header::<Ifinfomsg>()
+}
+
+pub(crate) fn peer<'a>(nest: Option<&'a Attr>) -> Result<Option<(&'a Ifinfomsg, IflaTbl<'a>)>> {
+    match nest {
+        Some(nest) => Ok(Some(rtnetlink::parse_nest_with_header(nest)?)),
+        None => Ok(None),
+    }
+}
+
+impl VethInfo {
+    // Fill the returned ifinfomsg and put Ifla attributes of the peer, e.g.
+    // Ifla::put_ifname() or Ifla::put_net_ns_fd(), then nest_end()
+    pub fn peer_start(nlv: &mut MsgVec) -> Result<&mut Ifinfomsg> {
+        peer_start(nlv, VethInfo::Peer)
+    }
+}
+
+impl<'a> VethInfoTbl<'a> {
+    // Kernel does not dump VETH_INFO_PEER, this is for RTM_NEWLINK
+    // requests built by VethInfo::peer_start().
+    pub fn peer(&self) -> Result<Option<(&'a Ifinfomsg, IflaTbl<'a>)>> {
+        peer(self[VethInfo::Peer])
+    }
+}
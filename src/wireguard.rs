@@ -0,0 +1,526 @@
+// The wireguard generic netlink family, resolve WG_GENL_NAME by
+// genetlink::Family::resolve().
+//
+// WG_CMD_GET_DEVICE may only be called via NLM_F_REQUEST | NLM_F_DUMP with
+// one but not both of WGDEVICE_A_IFINDEX or WGDEVICE_A_IFNAME. The kernel
+// returns several messages (NLM_F_MULTI), peers and their allowed IPs may be
+// split across them, Device::update() merges them.
+//
+// WG_CMD_SET_DEVICE may only be called via NLM_F_REQUEST with one of
+// WGDEVICE_A_IFINDEX or WGDEVICE_A_IFNAME, and the attributes to change,
+// see DeviceConfig.
+use errno::Errno;
+use std::{
+    mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use mnl::{Attr, AttrTbl, MsgVec, Msghdr, Result, Socket};
+
+use genetlink::{Family, Genlmsghdr};
+use ifh::IFNAMSIZ;
+use util::{run, seq};
+
+pub const WG_GENL_NAME: &str = "wireguard";
+pub const WG_GENL_VERSION: u8 = 1;
+
+pub const WG_KEY_LEN: usize = 32;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WgCmd {
+    // WG_CMD_
+    GetDevice = 0,
+    SetDevice,
+    _MAX,
+}
+pub const WG_CMD_GET_DEVICE: u8 = WgCmd::GetDevice as u8;
+pub const WG_CMD_SET_DEVICE: u8 = WgCmd::SetDevice as u8;
+pub const __WG_CMD_MAX: u8 = WgCmd::_MAX as u8;
+pub const WG_CMD_MAX: u8 = __WG_CMD_MAX - 1;
+
+// wgdevice_flag
+pub const WGDEVICE_F_REPLACE_PEERS: u32 = 1 << 0;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "WgdeviceAttrTbl"]
+pub enum WgdeviceAttr {
+    // WGDEVICE_A_
+    Unspec = 0,
+
+    #[nla_type(u32, ifindex)]
+    Ifindex,
+
+    #[nla_type(cstr, ifname)]
+    Ifname,
+
+    #[nla_type([u8; 32], private_key)]
+    PrivateKey,
+
+    #[nla_type([u8; 32], public_key)]
+    PublicKey,
+
+    #[nla_type(u32, flags)]
+    Flags, // WGDEVICE_F_
+
+    #[nla_type(u16, listen_port)]
+    ListenPort,
+
+    #[nla_type(u32, fwmark)]
+    Fwmark,
+
+    #[nla_nest([WgpeerAttrTbl], peers)]
+    Peers,
+
+    _MAX,
+}
+
+// wgpeer_flag
+pub const WGPEER_F_REMOVE_ME: u32 = 1 << 0;
+pub const WGPEER_F_REPLACE_ALLOWEDIPS: u32 = 1 << 1;
+pub const WGPEER_F_UPDATE_ONLY: u32 = 1 << 2;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "WgpeerAttrTbl"]
+pub enum WgpeerAttr {
+    // WGPEER_A_
+    Unspec = 0,
+
+    #[nla_type([u8; 32], public_key)]
+    PublicKey,
+
+    #[nla_type([u8; 32], preshared_key)]
+    PresharedKey,
+
+    #[nla_type(u32, flags)]
+    Flags, // WGPEER_F_
+
+    Endpoint, // struct sockaddr_in or sockaddr_in6, see WgpeerAttrTbl::endpoint()
+
+    #[nla_type(u16, persistent_keepalive_interval)]
+    PersistentKeepaliveInterval,
+
+    LastHandshakeTime, // struct __kernel_timespec, see WgpeerAttrTbl::last_handshake_time()
+
+    #[nla_type(u64, rx_bytes)]
+    RxBytes,
+
+    #[nla_type(u64, tx_bytes)]
+    TxBytes,
+
+    #[nla_nest([WgallowedipAttrTbl], allowedips)]
+    Allowedips,
+
+    #[nla_type(u32, protocol_version)]
+    ProtocolVersion,
+
+    _MAX,
+}
+
+// wgallowedip_flag
+pub const WGALLOWEDIP_F_REMOVE_ME: u32 = 1 << 0;
+
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NlaType)]
+#[tbname = "WgallowedipAttrTbl"]
+pub enum WgallowedipAttr {
+    // WGALLOWEDIP_A_
+    Unspec = 0,
+
+    #[nla_type(u16, family)]
+    Family,
+
+    Ipaddr, // struct in_addr or in6_addr, see WgallowedipAttrTbl::ipaddr()
+
+    #[nla_type(u8, cidr_mask)]
+    CidrMask,
+
+    #[nla_type(u32, flags)]
+    Flags, // WGALLOWEDIP_F_
+
+    _MAX,
+}
+
+impl<'a> WgpeerAttrTbl<'a> {
+    pub fn endpoint(&self) -> Result<Option<SocketAddr>> {
+        let attr = match self[WgpeerAttr::Endpoint] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        match attr.payload_len() as usize {
+            n if n == mem::size_of::<libc::sockaddr_in>() => {
+                let sin = attr.value_ref::<libc::sockaddr_in>()?;
+                Ok(Some(SocketAddr::V4(SocketAddrV4::new(
+                    Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr)),
+                    u16::from_be(sin.sin_port),
+                ))))
+            }
+            n if n == mem::size_of::<libc::sockaddr_in6>() => {
+                let sin6 = attr.value_ref::<libc::sockaddr_in6>()?;
+                Ok(Some(SocketAddr::V6(SocketAddrV6::new(
+                    Ipv6Addr::from(sin6.sin6_addr.s6_addr),
+                    u16::from_be(sin6.sin6_port),
+                    sin6.sin6_flowinfo,
+                    sin6.sin6_scope_id,
+                ))))
+            }
+            _ => Err(Errno(libc::EINVAL)),
+        }
+    }
+
+    // Since the epoch, zero if no handshake has been made.
+    pub fn last_handshake_time(&self) -> Result<Option<Duration>> {
+        let attr = match self[WgpeerAttr::LastHandshakeTime] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        // struct __kernel_timespec { __s64 tv_sec; __s64 tv_nsec; },
+        // may not be 8 bytes aligned
+        let b = attr.bytes_ref();
+        if b.len() != 16 {
+            return Err(Errno(libc::EINVAL));
+        }
+        let mut sec = [0u8; 8];
+        let mut nsec = [0u8; 8];
+        sec.copy_from_slice(&b[..8]);
+        nsec.copy_from_slice(&b[8..]);
+        Ok(Some(Duration::new(
+            i64::from_ne_bytes(sec) as u64,
+            i64::from_ne_bytes(nsec) as u32,
+        )))
+    }
+}
+
+impl WgpeerAttr {
+    pub fn put_endpoint<'a>(nlv: &'a mut MsgVec, addr: &SocketAddr) -> Result<&'a mut MsgVec> {
+        match addr {
+            SocketAddr::V4(addr) => {
+                let mut sin: libc::sockaddr_in = unsafe { mem::zeroed() };
+                sin.sin_family = libc::AF_INET as libc::sa_family_t;
+                sin.sin_port = addr.port().to_be();
+                sin.sin_addr.s_addr = u32::from(*addr.ip()).to_be();
+                nlv.put(WgpeerAttr::Endpoint, &sin)
+            }
+            SocketAddr::V6(addr) => {
+                let mut sin6: libc::sockaddr_in6 = unsafe { mem::zeroed() };
+                sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+                sin6.sin6_port = addr.port().to_be();
+                sin6.sin6_flowinfo = addr.flowinfo();
+                sin6.sin6_addr.s6_addr = addr.ip().octets();
+                sin6.sin6_scope_id = addr.scope_id();
+                nlv.put(WgpeerAttr::Endpoint, &sin6)
+            }
+        }
+    }
+}
+
+impl<'a> WgallowedipAttrTbl<'a> {
+    pub fn ipaddr(&self) -> Result<Option<IpAddr>> {
+        let attr = match self[WgallowedipAttr::Ipaddr] {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        match attr.payload_len() {
+            4 => Ok(Some(IpAddr::V4(*attr.value_ref::<Ipv4Addr>()?))),
+            16 => Ok(Some(IpAddr::V6(*attr.value_ref::<Ipv6Addr>()?))),
+            _ => Err(Errno(libc::EINVAL)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AllowedIp {
+    pub addr: IpAddr,
+    pub cidr: u8,
+}
+
+impl AllowedIp {
+    pub fn new(addr: IpAddr, cidr: u8) -> Self {
+        Self { addr, cidr }
+    }
+
+    fn from_tbl(tb: &WgallowedipAttrTbl) -> Result<Self> {
+        Ok(Self {
+            addr: tb.ipaddr()?.ok_or(Errno(libc::EINVAL))?,
+            cidr: *tb.cidr_mask()?.ok_or(Errno(libc::EINVAL))?,
+        })
+    }
+
+    // remove is for WGPEER_F_UPDATE_ONLY peers, by WGALLOWEDIP_F_REMOVE_ME
+    fn put<'a>(&self, nlv: &'a mut MsgVec, remove: bool) -> Result<&'a mut MsgVec> {
+        let max = match self.addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if self.cidr > max {
+            return Err(Errno(libc::EINVAL));
+        }
+        nlv.nest_start(0u16)?;
+        match self.addr {
+            IpAddr::V4(addr) => {
+                WgallowedipAttr::put_family(nlv, &(libc::AF_INET as u16))?;
+                nlv.put(WgallowedipAttr::Ipaddr, &addr.octets())?;
+            }
+            IpAddr::V6(addr) => {
+                WgallowedipAttr::put_family(nlv, &(libc::AF_INET6 as u16))?;
+                nlv.put(WgallowedipAttr::Ipaddr, &addr.octets())?;
+            }
+        }
+        WgallowedipAttr::put_cidr_mask(nlv, &self.cidr)?;
+        if remove {
+            WgallowedipAttr::put_flags(nlv, &WGALLOWEDIP_F_REMOVE_ME)?;
+        }
+        nlv.nest_end()
+    }
+}
+
+// A peer in WG_CMD_GET_DEVICE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    pub public_key: [u8; WG_KEY_LEN],
+    pub preshared_key: Option<[u8; WG_KEY_LEN]>,
+    pub endpoint: Option<SocketAddr>,
+    pub persistent_keepalive_interval: u16, // seconds, 0 is off
+    pub last_handshake_time: Option<SystemTime>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub allowed_ips: Vec<AllowedIp>,
+    pub protocol_version: u32,
+}
+
+fn allowed_ips(tb: &WgpeerAttrTbl) -> Result<Vec<AllowedIp>> {
+    tb.allowedips()?
+        .unwrap_or_default()
+        .iter()
+        .map(AllowedIp::from_tbl)
+        .collect()
+}
+
+impl Peer {
+    fn from_tbl(tb: &WgpeerAttrTbl) -> Result<Self> {
+        Ok(Self {
+            public_key: *tb.public_key()?.ok_or(Errno(libc::EINVAL))?,
+            // kernel puts zeros if not set
+            preshared_key: tb
+                .preshared_key()?
+                .filter(|key| key.iter().any(|&b| b != 0))
+                .copied(),
+            endpoint: tb.endpoint()?,
+            persistent_keepalive_interval: tb
+                .persistent_keepalive_interval()?
+                .copied()
+                .unwrap_or(0),
+            last_handshake_time: tb
+                .last_handshake_time()?
+                .filter(|d| *d != Duration::new(0, 0))
+                .map(|d| UNIX_EPOCH + d),
+            rx_bytes: tb.rx_bytes()?.copied().unwrap_or(0),
+            tx_bytes: tb.tx_bytes()?.copied().unwrap_or(0),
+            allowed_ips: allowed_ips(tb)?,
+            protocol_version: tb.protocol_version()?.copied().unwrap_or(0),
+        })
+    }
+}
+
+// WG_CMD_GET_DEVICE result
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Device {
+    pub ifindex: u32,
+    pub ifname: String,
+    pub private_key: Option<[u8; WG_KEY_LEN]>, // CAP_NET_ADMIN only
+    pub public_key: Option<[u8; WG_KEY_LEN]>,
+    pub listen_port: u16,
+    pub fwmark: u32,
+    pub peers: Vec<Peer>,
+}
+
+impl Device {
+    // Applies a message of the WG_CMD_GET_DEVICE dump. A peer continued from
+    // the previous message has only the public key and the rest of allowed
+    // IPs.
+    pub fn update(&mut self, nlh: &Msghdr) -> Result<()> {
+        let tb = WgdeviceAttrTbl::from_nlmsg(mem::size_of::<Genlmsghdr>(), nlh)?;
+        if let Some(ifindex) = tb.ifindex()? {
+            self.ifindex = *ifindex;
+        }
+        if let Some(ifname) = tb.ifname()? {
+            self.ifname = ifname.to_string();
+        }
+        if let Some(key) = tb.private_key()? {
+            self.private_key = Some(*key);
+        }
+        if let Some(key) = tb.public_key()? {
+            self.public_key = Some(*key);
+        }
+        if let Some(port) = tb.listen_port()? {
+            self.listen_port = *port;
+        }
+        if let Some(fwmark) = tb.fwmark()? {
+            self.fwmark = *fwmark;
+        }
+        for peer in tb.peers()?.unwrap_or_default() {
+            match self.peers.last_mut() {
+                Some(last) if Some(&last.public_key) == peer.public_key()? => {
+                    last.allowed_ips.append(&mut allowed_ips(&peer)?);
+                }
+                _ => self.peers.push(Peer::from_tbl(&peer)?),
+            }
+        }
+        Ok(())
+    }
+}
+
+// A peer in WG_CMD_SET_DEVICE, identified by public_key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerConfig {
+    pub public_key: [u8; WG_KEY_LEN],
+    pub remove: bool,                            // WGPEER_F_REMOVE_ME
+    pub update_only: bool,                       // WGPEER_F_UPDATE_ONLY, do not create
+    pub replace_allowed_ips: bool,               // WGPEER_F_REPLACE_ALLOWEDIPS
+    pub preshared_key: Option<[u8; WG_KEY_LEN]>, // all zero removes
+    pub endpoint: Option<SocketAddr>,
+    pub persistent_keepalive_interval: Option<u16>, // 0 disables
+    pub allowed_ips: Vec<AllowedIp>,
+    pub remove_allowed_ips: Vec<AllowedIp>, // requires newer kernel
+}
+
+impl PeerConfig {
+    pub fn new(public_key: [u8; WG_KEY_LEN]) -> Self {
+        Self {
+            public_key,
+            ..Default::default()
+        }
+    }
+
+    fn put<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        nlv.nest_start(0u16)?;
+        WgpeerAttr::put_public_key(nlv, &self.public_key)?;
+        let mut flags = 0;
+        if self.remove {
+            flags |= WGPEER_F_REMOVE_ME;
+        }
+        if self.update_only {
+            flags |= WGPEER_F_UPDATE_ONLY;
+        }
+        if self.replace_allowed_ips {
+            flags |= WGPEER_F_REPLACE_ALLOWEDIPS;
+        }
+        if flags != 0 {
+            WgpeerAttr::put_flags(nlv, &flags)?;
+        }
+        if let Some(key) = &self.preshared_key {
+            WgpeerAttr::put_preshared_key(nlv, key)?;
+        }
+        if let Some(addr) = &self.endpoint {
+            WgpeerAttr::put_endpoint(nlv, addr)?;
+        }
+        if let Some(interval) = self.persistent_keepalive_interval {
+            WgpeerAttr::put_persistent_keepalive_interval(nlv, &interval)?;
+        }
+        if !self.allowed_ips.is_empty() || !self.remove_allowed_ips.is_empty() {
+            WgpeerAttr::allowedips_start(nlv)?;
+            for ip in &self.allowed_ips {
+                ip.put(nlv, false)?;
+            }
+            for ip in &self.remove_allowed_ips {
+                ip.put(nlv, true)?;
+            }
+            nlv.nest_end()?;
+        }
+        nlv.nest_end()
+    }
+}
+
+// WG_CMD_SET_DEVICE attributes, None leaves it as is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceConfig {
+    pub private_key: Option<[u8; WG_KEY_LEN]>, // all zero removes
+    pub listen_port: Option<u16>,              // 0 chooses randomly
+    pub fwmark: Option<u32>,                   // 0 disables
+    pub replace_peers: bool,                   // WGDEVICE_F_REPLACE_PEERS
+    pub peers: Vec<PeerConfig>,
+}
+
+impl DeviceConfig {
+    // Puts the attributes following WGDEVICE_A_IFINDEX or WGDEVICE_A_IFNAME.
+    pub fn put<'a>(&self, nlv: &'a mut MsgVec) -> Result<&'a mut MsgVec> {
+        if let Some(key) = &self.private_key {
+            WgdeviceAttr::put_private_key(nlv, key)?;
+        }
+        if let Some(port) = self.listen_port {
+            WgdeviceAttr::put_listen_port(nlv, &port)?;
+        }
+        if let Some(fwmark) = self.fwmark {
+            WgdeviceAttr::put_fwmark(nlv, &fwmark)?;
+        }
+        if self.replace_peers {
+            WgdeviceAttr::put_flags(nlv, &WGDEVICE_F_REPLACE_PEERS)?;
+        }
+        if !self.peers.is_empty() {
+            WgdeviceAttr::peers_start(nlv)?;
+            for peer in &self.peers {
+                peer.put(nlv)?;
+            }
+            nlv.nest_end()?;
+        }
+        Ok(nlv)
+    }
+}
+
+fn put_request(
+    nlv: &mut MsgVec,
+    family: &Family,
+    cmd: u8,
+    flags: u16,
+    ifname: &str,
+) -> Result<u32> {
+    if ifname.is_empty() || ifname.len() >= IFNAMSIZ {
+        return Err(Errno(libc::EINVAL));
+    }
+    let nlh = nlv.put_header();
+    nlh.nlmsg_type = family.id;
+    nlh.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
+    let seq = seq();
+    nlh.nlmsg_seq = seq;
+    let genlh = nlv.put_extra_header::<Genlmsghdr>()?;
+    genlh.cmd = cmd;
+    genlh.version = WG_GENL_VERSION;
+    WgdeviceAttr::put_ifname(nlv, ifname)?;
+    Ok(seq)
+}
+
+// family is the resolved WG_GENL_NAME.
+pub fn get_device(nl: &mut Socket, family: &Family, ifname: &str) -> Result<Device> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(
+        &mut nlv,
+        family,
+        WG_CMD_GET_DEVICE,
+        libc::NLM_F_DUMP as u16,
+        ifname,
+    )?;
+    let mut device = Device::default();
+    run(nl, &nlv, seq, |nlh| device.update(nlh))?;
+    Ok(device)
+}
+
+pub fn set_device(
+    nl: &mut Socket,
+    family: &Family,
+    ifname: &str,
+    config: &DeviceConfig,
+) -> Result<()> {
+    let mut nlv = MsgVec::new();
+    let seq = put_request(
+        &mut nlv,
+        family,
+        WG_CMD_SET_DEVICE,
+        libc::NLM_F_ACK as u16,
+        ifname,
+    )?;
+    config.put(&mut nlv)?;
+    run(nl, &nlv, seq, |_| Ok(()))
+}